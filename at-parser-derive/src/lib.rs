@@ -0,0 +1,428 @@
+/***************************************************************************
+ *
+ * AT Command Parser
+ * Copyright (C) 2026 Antonio Salsi <passy.linux@zresa.it>
+ *
+ * This library is free software; you can redistribute it and/or
+ * modify it under the terms of the GNU Lesser General Public
+ * License as published by the Free Software Foundation; either
+ * version 2.1 of the License, or (at your option) any later version.
+ *
+ * This library is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the GNU
+ * Lesser General Public License for more details.
+ *
+ * You should have received a copy of the GNU Lesser General Public
+ * License along with this library; if not, see <https://www.gnu.org/licenses/>.
+ *
+ ***************************************************************************/
+
+ 
+//! Macros generating the `AtContext` boilerplate, the command registrations
+//! and the typed responses of [`at-parser-rs`](https://docs.rs/at-parser-rs)
+//!
+//! Re-exported as `at_parser_rs::at_context`, `at_parser_rs::at_command` and
+//! `at_parser_rs::AtResponse` with the `derive` feature; see there for the
+//! documentation.
+
+use proc_macro::{Delimiter, Spacing, TokenStream, TokenTree};
+
+/// Implement `AtContext` for the type of the annotated `impl` block by
+/// forwarding each form to the method marked `#[exec]`, `#[query]`, `#[test]`
+/// or `#[set]`.
+#[proc_macro_attribute]
+pub fn at_context(attr: TokenStream, item: TokenStream) -> TokenStream {
+    match expand(attr, item) {
+        Ok(tokens) => tokens,
+        Err(message) => format!("::core::compile_error!({message:?});").parse().unwrap(),
+    }
+}
+
+/// Register the annotated handler type for a command, to be gathered by
+/// `collect_commands!`.
+#[proc_macro_attribute]
+pub fn at_command(attr: TokenStream, item: TokenStream) -> TokenStream {
+    match expand_command(attr, item) {
+        Ok(tokens) => tokens,
+        Err(message) => format!("::core::compile_error!({message:?});").parse().unwrap(),
+    }
+}
+
+/// Implement `FromArgs` for a struct whose fields are the arguments of an
+/// information response, in order, and `client::FromResponse` when the
+/// response is named with `#[at_response("+CSQ")]`.
+#[proc_macro_derive(AtResponse, attributes(at_response))]
+pub fn at_response(item: TokenStream) -> TokenStream {
+    match expand_response(item) {
+        Ok(tokens) => tokens,
+        Err(message) => format!("::core::compile_error!({message:?});").parse().unwrap(),
+    }
+}
+
+/// Command form a method is marked for.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Form {
+    Exec,
+    Query,
+    Test,
+    Set,
+}
+
+impl Form {
+    fn from_marker(name: &str) -> Option<Self> {
+        match name {
+            "exec" => Some(Form::Exec),
+            "query" => Some(Form::Query),
+            "test" => Some(Form::Test),
+            "set" => Some(Form::Set),
+            _ => None,
+        }
+    }
+
+    /// Name of the trait method, and number of parameters (including `self`)
+    /// of its plain variant.
+    fn method(self) -> (&'static str, usize) {
+        match self {
+            Form::Exec => ("exec", 2),
+            Form::Query => ("query", 2),
+            Form::Test => ("test", 2),
+            Form::Set => ("set", 3),
+        }
+    }
+}
+
+/// Method of the `impl` block marked for a form.
+struct Marked {
+    form: Form,
+    name: String,
+    params: usize,
+}
+
+fn expand(attr: TokenStream, item: TokenStream) -> Result<TokenStream, String> {
+    let params = split_commas(attr);
+    let (size, state, error) = match params.as_slice() {
+        [size] => (size.clone(), "()".to_string(), "::core::convert::Infallible".to_string()),
+        [size, state] => (size.clone(), state.clone(), "::core::convert::Infallible".to_string()),
+        [size, state, error] => (size.clone(), state.clone(), error.clone()),
+        _ => return Err("expected `#[at_context(SIZE)]`, `#[at_context(SIZE, State)]` or `#[at_context(SIZE, State, Error)]`".into()),
+    };
+
+    let mut tokens: Vec<TokenTree> = item.into_iter().collect();
+    let body = match tokens.pop() {
+        Some(TokenTree::Group(group)) if group.delimiter() == Delimiter::Brace => group,
+        _ => return Err("#[at_context] must be placed on an `impl` block".into()),
+    };
+    let position = tokens
+        .iter()
+        .position(|token| matches!(token, TokenTree::Ident(ident) if ident.to_string() == "impl"))
+        .ok_or("#[at_context] must be placed on an `impl` block")?;
+    let (generics, self_ty) = split_generics(&tokens[position + 1..]);
+
+    let (body, marked) = strip_markers(body.stream())?;
+    let mut methods = String::new();
+    for Marked { form, name, params } in &marked {
+        let (method, plain) = form.method();
+        let (with, responder) = if *params == plain + 1 {
+            ("_with", format!(", responder: &mut ::at_parser_rs::response::Responder<'_, {state}>"))
+        } else if *params == plain {
+            ("", String::new())
+        } else {
+            return Err(format!("the #[{method}] method `{name}` must take {} or {} parameters including `self`", plain, plain + 1));
+        };
+        let (args, forward) = match form {
+            Form::Set => (", args: ::at_parser_rs::Args<'_>", "at_response, args"),
+            _ => ("", "at_response"),
+        };
+        let forward = if responder.is_empty() { forward.to_string() } else { format!("{forward}, responder") };
+        methods.push_str(&format!(
+            "fn {method}{with}(&mut self, at_response: &'static str{args}{responder}) -> ::at_parser_rs::AtResult<'_, {{ {size} }}, {error}> {{ self.{name}({forward}) }}\n"
+        ));
+    }
+
+    let mut output: TokenStream = tokens[..=position].iter().cloned().collect();
+    output.extend(generics.iter().cloned());
+    output.extend(self_ty.iter().cloned());
+    output.extend([TokenTree::Group(proc_macro::Group::new(Delimiter::Brace, body))]);
+
+    let generics: TokenStream = generics.iter().cloned().collect();
+    let self_ty: TokenStream = self_ty.iter().cloned().collect();
+    let context = format!(
+        "impl {generics} ::at_parser_rs::context::AtContext<{{ {size} }}, {state}, {error}> for {self_ty} {{ {methods} }}"
+    );
+    output.extend(context.parse::<TokenStream>().map_err(|e| e.to_string())?);
+    Ok(output)
+}
+
+fn expand_command(attr: TokenStream, item: TokenStream) -> Result<TokenStream, String> {
+    let params = split_commas(attr);
+    let (size, command, response) = match params.as_slice() {
+        [size, command] => (size, literal(command)?, None),
+        [size, command, response] => (size, literal(command)?, Some(literal(response)?)),
+        _ => return Err("expected `#[at_command(SIZE, \"+CMD\")]` or `#[at_command(SIZE, \"+CMD\", \"+CMD: \")]`".into()),
+    };
+    let name = command.strip_prefix("AT").unwrap_or(&command).to_string();
+    let command = format!("AT{name}");
+    let response = response.unwrap_or_else(|| format!("{name}: "));
+
+    let tokens: Vec<TokenTree> = item.clone().into_iter().collect();
+    let position = tokens
+        .iter()
+        .position(|token| matches!(token, TokenTree::Ident(ident) if matches!(ident.to_string().as_str(), "struct" | "enum")))
+        .ok_or("#[at_command] must be placed on a `struct` or `enum`")?;
+    let ty = match tokens.get(position + 1) {
+        Some(TokenTree::Ident(ident)) => ident.to_string(),
+        _ => return Err("#[at_command] must be placed on a `struct` or `enum`".into()),
+    };
+    if matches!(tokens.get(position + 2), Some(TokenTree::Punct(punct)) if punct.as_char() == '<') {
+        return Err("#[at_command] cannot register a generic type".into());
+    }
+
+    let registration = format!(
+        "const _: () = {{
+            #[used]
+            #[cfg_attr(not(target_vendor = \"apple\"), unsafe(link_section = \"at_parser_commands\"))]
+            #[cfg_attr(target_vendor = \"apple\", unsafe(link_section = \"__DATA,__atparsercmds,regular,no_dead_strip\"))]
+            static REGISTRATION: ::at_parser_rs::registry::Registration =
+                ::at_parser_rs::registry::Registration::of::<{{ {size} }}, {ty}>({command:?}, {response:?});
+        }};"
+    );
+    let mut output = item;
+    output.extend(registration.parse::<TokenStream>().map_err(|e| e.to_string())?);
+    Ok(output)
+}
+
+fn expand_response(item: TokenStream) -> Result<TokenStream, String> {
+    let tokens: Vec<TokenTree> = item.into_iter().collect();
+    let mut name = None;
+    for pair in tokens.windows(2) {
+        if let [TokenTree::Punct(hash), TokenTree::Group(group)] = pair
+            && hash.as_char() == '#'
+            && group.delimiter() == Delimiter::Bracket
+            && let Some(TokenTree::Ident(ident)) = group.stream().into_iter().next()
+            && ident.to_string() == "at_response"
+        {
+            let args = group.stream().into_iter().nth(1);
+            let Some(TokenTree::Group(args)) = args else {
+                return Err("expected `#[at_response(\"+NAME\")]`".into());
+            };
+            let response = literal(&args.stream().to_string())?;
+            name = Some(response.trim().trim_end_matches(':').trim_end().to_string());
+        }
+    }
+
+    let position = tokens
+        .iter()
+        .position(|token| matches!(token, TokenTree::Ident(ident) if ident.to_string() == "struct"))
+        .ok_or("#[derive(AtResponse)] only supports structs")?;
+    let ty = match tokens.get(position + 1) {
+        Some(TokenTree::Ident(ident)) => ident.to_string(),
+        _ => return Err("#[derive(AtResponse)] only supports structs".into()),
+    };
+    let (generics, rest) = split_generics(&tokens[position + 2..]);
+    let lifetimes = generics.iter().filter(|token| matches!(token, TokenTree::Punct(punct) if punct.as_char() == '\'')).count();
+    let generics: TokenStream = generics.iter().cloned().collect();
+    let generics = generics.to_string();
+    let (lifetime, self_ty) = match lifetimes {
+        0 if generics.is_empty() => ("'a".to_string(), ty),
+        1 if !generics.contains(',') => {
+            let lifetime = generics.trim_start_matches('<').trim_end_matches('>').trim().replace(' ', "");
+            (lifetime.clone(), format!("{ty}<{lifetime}>"))
+        }
+        _ => return Err("#[derive(AtResponse)] supports at most one lifetime parameter".into()),
+    };
+
+    let fields = match rest.first() {
+        Some(TokenTree::Group(group)) if group.delimiter() == Delimiter::Brace => {
+            let names = field_names(group.stream());
+            let fields = names
+                .iter()
+                .enumerate()
+                .map(|(index, name)| format!("{name}: ::at_parser_rs::FromArg::from_arg(args, {index})?"))
+                .collect::<Vec<_>>();
+            (names.len(), format!("Self {{ {} }}", fields.join(", ")))
+        }
+        Some(TokenTree::Group(group)) if group.delimiter() == Delimiter::Parenthesis => {
+            let count = count_params(group.stream());
+            let fields = (0..count).map(|index| format!("::at_parser_rs::FromArg::from_arg(args, {index})?")).collect::<Vec<_>>();
+            (count, format!("Self({})", fields.join(", ")))
+        }
+        _ => return Err("#[derive(AtResponse)] needs a struct with fields".into()),
+    };
+    let (count, construct) = fields;
+    if count == 0 {
+        return Err("#[derive(AtResponse)] needs a struct with fields".into());
+    }
+
+    let mut output = format!(
+        "impl<{lifetime}> ::at_parser_rs::FromArgs<{lifetime}> for {self_ty} {{
+            fn from_args(args: &::at_parser_rs::Args<{lifetime}>) -> ::core::result::Result<Self, ::at_parser_rs::ArgError> {{
+                args.expect(0, {count})?;
+                ::core::result::Result::Ok({construct})
+            }}
+        }}"
+    );
+    if let Some(name) = name {
+        output.push_str(&format!(
+            "impl<{lifetime}> ::at_parser_rs::client::FromResponse<{lifetime}> for {self_ty} {{ const NAME: &'static str = {name:?}; }}"
+        ));
+    }
+    output.parse().map_err(|e: proc_macro::LexError| e.to_string())
+}
+
+/// Names of the fields of a struct body, skipping their attributes and
+/// visibility.
+fn field_names(body: TokenStream) -> Vec<String> {
+    let mut names = Vec::new();
+    let mut field = true;
+    let mut depth = 0usize;
+    let mut previous: Option<TokenTree> = None;
+    for token in body {
+        match &token {
+            TokenTree::Punct(punct) if punct.as_char() == '<' => depth += 1,
+            TokenTree::Punct(punct) if punct.as_char() == '>' => depth = depth.saturating_sub(1),
+            TokenTree::Punct(punct) if punct.as_char() == ',' && depth == 0 => field = true,
+            TokenTree::Punct(punct) if punct.as_char() == ':' && punct.spacing() == Spacing::Alone && field => {
+                if let Some(TokenTree::Ident(name)) = &previous {
+                    names.push(name.to_string());
+                    field = false;
+                }
+            }
+            _ => {}
+        }
+        previous = Some(token);
+    }
+    names
+}
+
+/// Content of a plain string literal argument.
+fn literal(param: &str) -> Result<String, String> {
+    param
+        .trim()
+        .strip_prefix('"')
+        .and_then(|param| param.strip_suffix('"'))
+        .map(str::to_string)
+        .ok_or_else(|| format!("expected a string literal, found `{}`", param.trim()))
+}
+
+/// Split the `impl` header after the keyword into its generic parameters
+/// (`<'a, T>`, possibly empty) and the self type with its where clause.
+fn split_generics(header: &[TokenTree]) -> (&[TokenTree], &[TokenTree]) {
+    if !matches!(header.first(), Some(TokenTree::Punct(punct)) if punct.as_char() == '<') {
+        return (&[], header);
+    }
+
+    let mut depth = 0;
+    let mut arrow = false;
+    for (i, token) in header.iter().enumerate() {
+        if let TokenTree::Punct(punct) = token {
+            match punct.as_char() {
+                '<' => depth += 1,
+                '>' if !arrow => depth -= 1,
+                _ => {}
+            }
+            if depth == 0 {
+                return header.split_at(i + 1);
+            }
+            arrow = punct.as_char() == '-' && punct.spacing() == Spacing::Joint;
+        } else {
+            arrow = false;
+        }
+    }
+    (header, &[])
+}
+
+/// Remove the `#[exec]`, `#[query]`, `#[test]` and `#[set]` markers from the
+/// `impl` body, returning the methods they mark.
+fn strip_markers(body: TokenStream) -> Result<(TokenStream, Vec<Marked>), String> {
+    let tokens: Vec<TokenTree> = body.into_iter().collect();
+    let mut output = Vec::with_capacity(tokens.len());
+    let mut marked: Vec<Marked> = Vec::new();
+    let mut pending = None;
+    let mut i = 0;
+
+    while i < tokens.len() {
+        if let (TokenTree::Punct(hash), Some(TokenTree::Group(group))) = (&tokens[i], tokens.get(i + 1))
+            && hash.as_char() == '#'
+            && group.delimiter() == Delimiter::Bracket
+            && let Some(form) = Form::from_marker(group.stream().to_string().trim()) {
+            if marked.iter().any(|m| m.form == form) {
+                return Err(format!("more than one method is marked #[{}]", form.method().0));
+            }
+            pending = Some(form);
+            i += 2;
+            continue;
+        }
+
+        if let (Some(form), TokenTree::Ident(keyword)) = (pending, &tokens[i])
+            && keyword.to_string() == "fn"
+            && let Some(TokenTree::Ident(name)) = tokens.get(i + 1) {
+            let params = tokens[i + 2..]
+                .iter()
+                .find_map(|token| match token {
+                    TokenTree::Group(group) if group.delimiter() == Delimiter::Parenthesis => Some(count_params(group.stream())),
+                    _ => None,
+                })
+                .unwrap_or(0);
+            marked.push(Marked { form, name: name.to_string(), params });
+            pending = None;
+        }
+
+        output.push(tokens[i].clone());
+        i += 1;
+    }
+
+    Ok((output.into_iter().collect(), marked))
+}
+
+/// Number of parameters in a parameter list, ignoring commas nested in
+/// generic arguments.
+fn count_params(params: TokenStream) -> usize {
+    let mut count = 0;
+    let mut depth = 0usize;
+    let mut empty = true;
+    let mut arrow = false;
+    for token in params {
+        if let TokenTree::Punct(punct) = &token {
+            match punct.as_char() {
+                '<' => depth += 1,
+                '>' if !arrow => depth = depth.saturating_sub(1),
+                ',' if depth == 0 => {
+                    count += 1;
+                    empty = true;
+                    continue;
+                }
+                _ => {}
+            }
+            arrow = punct.as_char() == '-' && punct.spacing() == Spacing::Joint;
+        } else {
+            arrow = false;
+        }
+        empty = false;
+    }
+    if empty { count } else { count + 1 }
+}
+
+/// Split the attribute arguments on top-level commas.
+fn split_commas(attr: TokenStream) -> Vec<String> {
+    let mut params = vec![String::new()];
+    let mut depth = 0usize;
+    for token in attr {
+        if let TokenTree::Punct(punct) = &token {
+            match punct.as_char() {
+                '<' => depth += 1,
+                '>' => depth = depth.saturating_sub(1),
+                ',' if depth == 0 => {
+                    params.push(String::new());
+                    continue;
+                }
+                _ => {}
+            }
+        }
+        let param = params.last_mut().unwrap();
+        param.push_str(&token.to_string());
+        param.push(' ');
+    }
+    params.retain(|param| !param.trim().is_empty());
+    params
+}
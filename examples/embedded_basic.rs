@@ -21,7 +21,7 @@
 //! Basic usage example demonstrating no_std compatible code
 //!
 //! Shows Args parsing and error handling patterns with the updated AtResult
-//! tuple type `Result<(&'static str, Bytes<SIZE>), (&'static str, AtError)>`.
+//! tuple type `Result<(&'static str, AtResponse<'_, SIZE>), (&'static str, AtError)>`.
 
 #![allow(dead_code)]
 #![no_std]
@@ -36,7 +36,7 @@ const AT_RESP: &str = "+DEMO: ";
 
 // Parse the second argument and echo it back in the response
 fn parse_args_example() -> AtResult<'static, SIZE> {
-    let args = Args { raw: "foo,bar,baz" };
+    let args = Args::new("foo,bar,baz");
     match args.get(1) {
         Some(val) => Ok(at_response!(SIZE, AT_RESP; val.as_ref())),
         None => Err((AT_RESP, AtError::InvalidArgs)),
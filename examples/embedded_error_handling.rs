@@ -72,10 +72,17 @@ fn check_result(result: AtResult<SIZE>) -> u8 {
     match result {
         Ok((_, _))                           => 0,  // success
         Err((_, AtError::InvalidArgs))       => 1,
+        Err((_, AtError::Argument(_)))       => 1,
         Err((_, AtError::NotSupported))      => 2,
         Err((_, AtError::UnknownCommand))    => 3,
         Err((_, AtError::Unhandled(_)))      => 4,
         Err((_, AtError::UnhandledOwned(_))) => 5,
+        Err((_, AtError::Cme(_)))            => 6,
+        Err((_, AtError::Cms(_)))            => 7,
+        Err((_, AtError::Final(_)))          => 8,
+        Err((_, AtError::Aborted))           => 9,
+        Err((_, AtError::Vendor(_, _)))      => 10,
+        Err((_, AtError::LineTooLong))       => 11,
     }
 }
 
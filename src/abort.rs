@@ -0,0 +1,131 @@
+/***************************************************************************
+ *
+ * AT Command Parser
+ * Copyright (C) 2026 Antonio Salsi <passy.linux@zresa.it>
+ *
+ * This library is free software; you can redistribute it and/or
+ * modify it under the terms of the GNU Lesser General Public
+ * License as published by the Free Software Foundation; either
+ * version 2.1 of the License, or (at your option) any later version.
+ *
+ * This library is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the GNU
+ * Lesser General Public License for more details.
+ *
+ * You should have received a copy of the GNU Lesser General Public
+ * License along with this library; if not, see <https://www.gnu.org/licenses/>.
+ *
+ ***************************************************************************/
+
+ 
+//! Command abort protocol
+//!
+//! V.250 lets the DTE abort a command that takes a long time to complete
+//! (dialling, a network scan) by sending any character while it executes. The
+//! character is discarded and the command ends with a result code of its own
+//! choosing, [`AtError::Aborted`](crate::AtError::Aborted) when nothing more
+//! specific applies.
+//!
+//! An [`Abort`] is shared between the parser, which marks when a handler
+//! executes, and the receive path, which reports every incoming character.
+//! Handlers of abortable commands poll it through
+//! [`Responder::is_aborted`](crate::response::Responder::is_aborted):
+//!
+//! ```text
+//! UART interrupt             parser / handler
+//!                            AT+COPS=?  → handler starts scanning
+//! 'x' → abort.input()        responder.is_aborted() == true
+//!                            → Err(AtError::Aborted)  → ABORTED
+//! ```
+
+use core::sync::atomic::{AtomicBool, Ordering};
+
+/// Abort flag shared by the receive path and the executing handler.
+///
+/// Only uses atomic loads and stores, so it can be a `static` shared with an
+/// interrupt handler on any target.
+///
+/// # Example
+///
+/// ```rust
+/// use at_parser_rs::abort::Abort;
+///
+/// static ABORT: Abort = Abort::new();
+///
+/// // nothing executing: the character belongs to the next command line
+/// assert!(!ABORT.input());
+/// ```
+#[derive(Debug, Default)]
+pub struct Abort {
+    /// Whether a handler is executing
+    executing: AtomicBool,
+    /// Whether an abort has been requested since the handler started
+    requested: AtomicBool,
+}
+
+impl Abort {
+    /// Create a flag with no command executing.
+    pub const fn new() -> Self {
+        Self { executing: AtomicBool::new(false), requested: AtomicBool::new(false) }
+    }
+
+    /// Report a character received from the DTE.
+    ///
+    /// Returns `true` when a command is executing: the abort is requested and
+    /// the character must be discarded instead of being fed to the parser.
+    pub fn input(&self) -> bool {
+        let executing = self.executing.load(Ordering::Acquire);
+        if executing {
+            self.requested.store(true, Ordering::Release);
+        }
+        executing
+    }
+
+    /// Request the executing command to abort, whatever the cause.
+    pub fn request(&self) {
+        self.requested.store(true, Ordering::Release);
+    }
+
+    /// Whether an abort has been requested since the current command started.
+    pub fn is_requested(&self) -> bool {
+        self.requested.load(Ordering::Acquire)
+    }
+
+    /// Whether a handler is executing.
+    pub fn is_executing(&self) -> bool {
+        self.executing.load(Ordering::Acquire)
+    }
+
+    /// Mark the start of a handler, discarding any earlier request.
+    pub(crate) fn start(&self) {
+        self.requested.store(false, Ordering::Release);
+        self.executing.store(true, Ordering::Release);
+    }
+
+    /// Mark the end of a handler.
+    pub(crate) fn finish(&self) {
+        self.executing.store(false, Ordering::Release);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Abort;
+
+    #[test]
+    fn input_aborts_only_while_executing() {
+        let abort = Abort::new();
+
+        assert!(!abort.input());
+        assert!(!abort.is_requested());
+
+        abort.start();
+        assert!(abort.input());
+        assert!(abort.is_requested());
+        abort.finish();
+
+        abort.start();
+        assert!(!abort.is_requested());
+    }
+}
@@ -0,0 +1,273 @@
+/***************************************************************************
+ *
+ * AT Command Parser
+ * Copyright (C) 2026 Antonio Salsi <passy.linux@zresa.it>
+ *
+ * This library is free software; you can redistribute it and/or
+ * modify it under the terms of the GNU Lesser General Public
+ * License as published by the Free Software Foundation; either
+ * version 2.1 of the License, or (at your option) any later version.
+ *
+ * This library is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the GNU
+ * Lesser General Public License for more details.
+ *
+ * You should have received a copy of the GNU Lesser General Public
+ * License along with this library; if not, see <https://www.gnu.org/licenses/>.
+ *
+ ***************************************************************************/
+
+ 
+//! Client side: driving a modem
+//!
+//! When the firmware is the DTE, [`AtCommandBuilder`] writes command lines
+//! into a fixed buffer, with strings quoted and escaped:
+//!
+//! ```rust
+//! use at_parser_rs::client::AtCommandBuilder;
+//!
+//! let mut buf = [0u8; 32];
+//! let line = AtCommandBuilder::new(&mut buf, "AT+CPIN").string("1234").finish().unwrap();
+//! assert_eq!(line, r#"AT+CPIN="1234""#);
+//! ```
+//!
+//! [`ResponseParser`] classifies the lines sent back by the modem into
+//! echo, information responses, final result codes and unsolicited result
+//! codes, with their fields exposed as [`Args`](crate::Args); a
+//! [`UrcRouter`] hands the unsolicited ones to subscribed callbacks, and
+//! [`Ingress`] buffers output received in arbitrary chunks. [`round_trip`]
+//! waits for the response of a command and returns its information lines,
+//! within the [`Timeouts`] of the command through a [`deadline`], while a
+//! [`CommandQueue`] pipelines several commands for non-blocking drivers and
+//! a [`Retry`] policy attempts flaky ones again.
+//!
+//! With the `heapless` feature, [`at_builders!`](crate::at_builders) turns
+//! the same parameter declarations as [`at_params!`](crate::at_params) into
+//! strongly-typed functions writing the command line, with every value
+//! checked against the schema:
+//!
+//! ```rust
+//! # #[cfg(feature = "heapless")] {
+//! use at_parser_rs::at_builders;
+//!
+//! at_builders! {
+//!     /// Join an access point: `AT+CWJAP=<ssid>,<password>[,<mode>]`
+//!     pub CWJAP = "AT+CWJAP" => fn cwjap(ssid: string max 32, password: string max 64, mode: optional int 0..=1);
+//! }
+//!
+//! let line = cwjap::<64>("home", "se\"cret", None).unwrap();
+//! assert_eq!(line.as_str(), r#"AT+CWJAP="home","se\"cret""#);
+//! assert!(cwjap::<64>("home", "secret", Some(2)).is_err());
+//! # }
+//! ```
+//!
+//! The generated `CWJAP` [`Schema`](crate::schema::Schema) validates the same
+//! command on the DCE side.
+
+use core::fmt::Write;
+
+#[cfg(feature = "heapless")]
+use crate::ArgError;
+#[cfg(feature = "heapless")]
+use crate::schema::Schema;
+
+mod builder;
+mod exchange;
+mod ingress;
+mod queue;
+mod response;
+mod retry;
+mod timeout;
+mod urc;
+
+pub use builder::AtCommandBuilder;
+pub use exchange::{Exchange, Failure, Payload, Step, round_trip, round_trip_with_data};
+pub use ingress::Ingress;
+pub use queue::{CommandQueue, Completion, Event, Ticket};
+pub use response::{CTRL_Z, ESC, Final, FromResponse, Response, ResponseParser};
+pub use retry::Retry;
+pub use timeout::{Clock, Timeouts, deadline};
+pub use urc::{UrcHandler, UrcRouter};
+
+/// Command line written by a builder
+#[cfg(feature = "heapless")]
+pub type Line<const N: usize> = heapless::String<N>;
+
+/// Argument handed to [`build`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Arg<'a> {
+    /// Number, written as is
+    Int(i64),
+    /// String, written quoted
+    Str(&'a str),
+    /// Omitted optional parameter
+    Omitted,
+}
+
+impl From<i64> for Arg<'_> {
+    fn from(value: i64) -> Self {
+        Arg::Int(value)
+    }
+}
+
+impl<'a> From<&'a str> for Arg<'a> {
+    fn from(value: &'a str) -> Self {
+        Arg::Str(value)
+    }
+}
+
+impl<'a, T: Into<Arg<'a>>> From<Option<T>> for Arg<'a> {
+    fn from(value: Option<T>) -> Self {
+        value.map_or(Arg::Omitted, Into::into)
+    }
+}
+
+/// Write `command` with `args`, checked against `schema`, through an
+/// [`AtCommandBuilder`].
+///
+/// Trailing omitted parameters are left out; a command without arguments is
+/// written without `=`.
+///
+/// # Errors
+///
+/// * [`ArgError::Missing`] — a required parameter is omitted
+/// * [`ArgError::Unexpected`] — more arguments than parameters
+/// * [`ArgError::Invalid`] — an argument is outside its accepted values
+/// * [`ArgError::Overflow`] — the line does not fit in `N` bytes, at the
+///   given argument
+#[cfg(feature = "heapless")]
+pub fn build<const N: usize>(command: &str, schema: &Schema, args: &[Arg]) -> Result<Line<N>, ArgError> {
+    let params = schema.params();
+    if args.len() > params.len() {
+        return Err(ArgError::Unexpected(params.len()));
+    }
+
+    let mut buf = [0u8; N];
+    let mut builder = AtCommandBuilder::new(&mut buf, command);
+    for (index, (param, arg)) in params.iter().zip(args).enumerate() {
+        builder = match *arg {
+            Arg::Int(value) if param.accepts_int(value) => builder.int(value),
+            Arg::Str(value) if param.accepts_str(value) => builder.string(value),
+            Arg::Omitted if param.optional => builder.omitted(),
+            Arg::Omitted => return Err(ArgError::Missing(index)),
+            _ => return Err(ArgError::Invalid(index)),
+        };
+    }
+    if let Some((index, _)) = params.iter().enumerate().skip(args.len()).find(|(_, param)| !param.optional) {
+        return Err(ArgError::Missing(index));
+    }
+
+    let mut line = Line::<N>::new();
+    line.push_str(builder.finish()?).map_err(|_| ArgError::Overflow(0))?;
+    Ok(line)
+}
+
+/// Separator written before the argument at `index`.
+fn separator(index: usize) -> &'static str {
+    if index == 0 { "=" } else { "," }
+}
+
+/// Write `value` between double quotes, escaping `"` and `\` as
+/// [`Args::get`](crate::Args::get) decodes them.
+fn write_quoted(out: &mut impl Write, value: &str) -> core::fmt::Result {
+    out.write_char('"')?;
+    for ch in value.chars() {
+        if matches!(ch, '"' | '\\') {
+            out.write_char('\\')?;
+        }
+        out.write_char(ch)?;
+    }
+    out.write_char('"')
+}
+
+/// Declare commands with their parameters, generating for each one its
+/// [`Schema`](crate::schema::Schema) and a typed builder of the command line.
+///
+/// Each declaration reads `SCHEMA = "AT+CMD" => fn builder(name: param, ...);`
+/// where every `param` is written as in [`at_params!`](crate::at_params).
+/// Builders take `i64` for `int` parameters and `&str` for `string` ones,
+/// wrapped in `Option` when `optional`, and return the line as a
+/// [`Line<N>`](crate::client::Line) through [`build`](crate::client::build).
+///
+/// # Example
+///
+/// ```rust
+/// use at_parser_rs::at_builders;
+///
+/// at_builders! {
+///     pub CMGF = "AT+CMGF" => fn cmgf(mode: int 0..=1);
+///     pub CSCS = "AT+CSCS" => fn cscs(charset: string ["GSM", "UCS2"]);
+/// }
+///
+/// assert_eq!(cmgf::<16>(1).unwrap().as_str(), "AT+CMGF=1");
+/// assert_eq!(cscs::<16>("GSM").unwrap().as_str(), r#"AT+CSCS="GSM""#);
+/// assert_eq!(format!("{CSCS}"), r#"("GSM","UCS2")"#);
+/// ```
+#[cfg(feature = "heapless")]
+#[macro_export]
+macro_rules! at_builders {
+    () => {};
+    ($(#[$meta:meta])* $vis:vis $schema:ident = $command:literal => fn $builder:ident ($($params:tt)*); $($rest:tt)*) => {
+        $crate::at_builders!(@split [$(#[$meta])* $vis $schema $command $builder] [] [] $($params)*);
+        $crate::at_builders!($($rest)*);
+    };
+    (@split $head:tt [$($done:tt)*] [$($current:tt)+] , $($rest:tt)*) => {
+        $crate::at_builders!(@split $head [$($done)* ($($current)+)] [] $($rest)*);
+    };
+    (@split $head:tt [$($done:tt)*] [$($current:tt)*] $next:tt $($rest:tt)*) => {
+        $crate::at_builders!(@split $head [$($done)*] [$($current)* $next] $($rest)*);
+    };
+    (@split $head:tt [$($done:tt)*] [$($current:tt)+]) => {
+        $crate::at_builders!(@split $head [$($done)* ($($current)+)] []);
+    };
+    (@split [$(#[$meta:meta])* $vis:vis $schema:ident $command:literal $builder:ident] [$(($name:ident : $($param:tt)+))*] []) => {
+        $(#[$meta])*
+        $vis const $schema: $crate::schema::Schema =
+            $crate::schema::Schema::new(&[$($crate::at_params!(@param $($param)+)),*]);
+
+        $(#[$meta])*
+        $vis fn $builder<const N: usize>($($name: $crate::at_builders!(@type $($param)+)),*) -> ::core::result::Result<$crate::client::Line<N>, $crate::ArgError> {
+            $crate::client::build($command, &$schema, &[$($crate::client::Arg::from($name)),*])
+        }
+    };
+    (@type optional $($param:tt)+) => { ::core::option::Option<$crate::at_builders!(@type $($param)+)> };
+    (@type int $($values:tt)+) => { i64 };
+    (@type string $($values:tt)+) => { &str };
+}
+
+#[cfg(all(test, feature = "heapless"))]
+mod tests {
+    use crate::{ArgError, Args};
+
+    at_builders! {
+        CWJAP = "AT+CWJAP" => fn cwjap(ssid: string max 8, password: string max 16, mode: optional int 0..=1);
+        CIPSTART = "AT+CIPSTART" => fn cipstart(link: optional int [0, 1, 2, 3, 4], kind: string ["TCP", "UDP"], port: int 1..=65535);
+        GMR = "AT+GMR" => fn gmr();
+    }
+
+    #[test]
+    fn builders_quote_and_escape_strings() {
+        assert_eq!(cwjap::<64>("home", "secret", Some(1)).unwrap().as_str(), r#"AT+CWJAP="home","secret",1"#);
+        assert_eq!(cwjap::<64>("home", r#"a"b\c"#, None).unwrap().as_str(), r#"AT+CWJAP="home","a\"b\\c""#);
+        assert_eq!(cipstart::<64>(None, "TCP", 80).unwrap().as_str(), r#"AT+CIPSTART=,"TCP",80"#);
+        assert_eq!(gmr::<16>().unwrap().as_str(), "AT+GMR");
+    }
+
+    #[test]
+    fn builders_check_the_schema() {
+        assert_eq!(cwjap::<64>("too long ssid", "secret", None).err(), Some(ArgError::Invalid(0)));
+        assert_eq!(cwjap::<64>("home", "secret", Some(2)).err(), Some(ArgError::Invalid(2)));
+        assert_eq!(cipstart::<64>(Some(5), "TCP", 80).err(), Some(ArgError::Invalid(0)));
+        assert_eq!(cipstart::<64>(Some(0), "SCTP", 80).err(), Some(ArgError::Invalid(1)));
+        assert_eq!(cwjap::<16>("home", "secret", None).err(), Some(ArgError::Overflow(1)));
+    }
+
+    #[test]
+    fn built_lines_pass_the_generated_schema() {
+        let line = cwjap::<64>("home", r#"a"b"#, Some(0)).unwrap();
+        let (_, raw) = line.split_once('=').unwrap();
+        assert_eq!(CWJAP.validate(&Args::new(raw)), Ok(()));
+        assert_eq!(Args::new(raw).get(1).as_deref(), Some(r#"a"b"#));
+    }
+}
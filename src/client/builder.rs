@@ -0,0 +1,221 @@
+/***************************************************************************
+ *
+ * AT Command Parser
+ * Copyright (C) 2026 Antonio Salsi <passy.linux@zresa.it>
+ *
+ * This library is free software; you can redistribute it and/or
+ * modify it under the terms of the GNU Lesser General Public
+ * License as published by the Free Software Foundation; either
+ * version 2.1 of the License, or (at your option) any later version.
+ *
+ * This library is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the GNU
+ * Lesser General Public License for more details.
+ *
+ * You should have received a copy of the GNU Lesser General Public
+ * License along with this library; if not, see <https://www.gnu.org/licenses/>.
+ *
+ ***************************************************************************/
+
+ 
+//! Command line serializer
+
+use core::fmt::{self, Write};
+
+use crate::ArgError;
+
+use super::{separator, write_quoted};
+
+/// Writes a command line (`AT+CMD=...`) into a fixed buffer
+///
+/// Arguments are appended in order: numbers in decimal, strings quoted with
+/// `"` and `\` escaped as [`Args::get`](crate::Args::get) decodes them, and
+/// omitted parameters as empty positions. Trailing omitted parameters are
+/// left out, and a command without arguments is written without `=`.
+///
+/// Errors are reported once, by [`finish`](AtCommandBuilder::finish), so
+/// arguments are chained without checking each one.
+///
+/// # Example
+///
+/// ```rust
+/// use at_parser_rs::client::AtCommandBuilder;
+///
+/// let mut buf = [0u8; 64];
+/// let line = AtCommandBuilder::new(&mut buf, "AT+CIPSTART")
+///     .omitted()
+///     .string("TCP")
+///     .string("example.com")
+///     .int(80)
+///     .finish()
+///     .unwrap();
+/// assert_eq!(line, r#"AT+CIPSTART=,"TCP","example.com",80"#);
+///
+/// let mut buf = [0u8; 16];
+/// assert_eq!(AtCommandBuilder::new(&mut buf, "AT+CREG").query(), Ok("AT+CREG?"));
+/// ```
+pub struct AtCommandBuilder<'b> {
+    /// Line written so far
+    out: Cursor<'b>,
+    /// Arguments appended so far, omitted ones included
+    args: usize,
+    /// Omitted arguments not written yet, in case they are trailing
+    omitted: usize,
+    /// Index of the argument that did not fit, `0` for the command itself
+    overflow: Option<usize>,
+}
+
+impl<'b> AtCommandBuilder<'b> {
+    /// Start the line of `command` (`"AT+CMD"`) in `buf`.
+    pub fn new(buf: &'b mut [u8], command: &str) -> Self {
+        let mut out = Cursor { buf, len: 0 };
+        let overflow = out.write_str(command).err().map(|_| 0);
+        Self { out, args: 0, omitted: 0, overflow }
+    }
+
+    /// Append a number, written in decimal.
+    pub fn int(self, value: impl Into<i64>) -> Self {
+        let value = value.into();
+        self.push(|out| write!(out, "{value}"))
+    }
+
+    /// Append a string, written quoted and escaped.
+    pub fn string(self, value: &str) -> Self {
+        self.push(|out| write_quoted(out, value))
+    }
+
+    /// Append `value` as is, for arguments such as hex data written without
+    /// quotes.
+    pub fn raw(self, value: &str) -> Self {
+        self.push(|out| out.write_str(value))
+    }
+
+    /// Skip an optional parameter, leaving its position empty.
+    pub fn omitted(mut self) -> Self {
+        self.args += 1;
+        self.omitted += 1;
+        self
+    }
+
+    /// Number of arguments appended, omitted ones included.
+    pub fn len(&self) -> usize {
+        self.args
+    }
+
+    /// Whether no argument has been appended.
+    pub fn is_empty(&self) -> bool {
+        self.args == 0
+    }
+
+    /// The command line, without terminator.
+    ///
+    /// # Errors
+    ///
+    /// [`ArgError::Overflow`] when the line does not fit in the buffer, at
+    /// the first argument that did not fit.
+    pub fn finish(self) -> Result<&'b str, ArgError> {
+        match self.overflow {
+            Some(index) => Err(ArgError::Overflow(index)),
+            None => Ok(self.out.into_str()),
+        }
+    }
+
+    /// The read command line (`AT+CMD?`).
+    ///
+    /// # Errors
+    ///
+    /// [`ArgError::Unexpected`] when arguments were appended, and
+    /// [`ArgError::Overflow`] when the line does not fit in the buffer.
+    pub fn query(self) -> Result<&'b str, ArgError> {
+        self.finish_with("?")
+    }
+
+    /// The test command line (`AT+CMD=?`), failing as [`query`](AtCommandBuilder::query).
+    pub fn test(self) -> Result<&'b str, ArgError> {
+        self.finish_with("=?")
+    }
+
+    /// Append `value` through `write`, after the separators of the omitted
+    /// arguments before it.
+    fn push(mut self, write: impl FnOnce(&mut Cursor<'b>) -> fmt::Result) -> Self {
+        let index = self.args;
+        self.args += 1;
+        if self.overflow.is_none() {
+            let first = index - self.omitted;
+            let written = (first..=index).try_for_each(|i| self.out.write_str(separator(i))).and_then(|_| write(&mut self.out));
+            self.overflow = written.err().map(|_| index);
+            self.omitted = 0;
+        }
+        self
+    }
+
+    /// Append the `suffix` of a command form without arguments.
+    fn finish_with(mut self, suffix: &str) -> Result<&'b str, ArgError> {
+        if self.args > 0 {
+            return Err(ArgError::Unexpected(0));
+        }
+        if self.overflow.is_none() && self.out.write_str(suffix).is_err() {
+            self.overflow = Some(0);
+        }
+        self.finish()
+    }
+}
+
+/// Byte buffer written as text, failing without writing anything when a
+/// string does not fit
+struct Cursor<'b> {
+    buf: &'b mut [u8],
+    len: usize,
+}
+
+impl<'b> Cursor<'b> {
+    /// Text written so far.
+    fn into_str(self) -> &'b str {
+        let len = self.len;
+        // only whole `str`s are ever written
+        core::str::from_utf8(&self.buf[..len]).unwrap_or("")
+    }
+}
+
+impl Write for Cursor<'_> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        let end = self.len + s.len();
+        if end > self.buf.len() {
+            return Err(fmt::Error);
+        }
+        self.buf[self.len..end].copy_from_slice(s.as_bytes());
+        self.len = end;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::AtCommandBuilder;
+    use crate::ArgError;
+
+    #[test]
+    fn arguments_are_separated_and_trailing_omitted_ones_dropped() {
+        let mut buf = [0u8; 64];
+        let line = AtCommandBuilder::new(&mut buf, "AT+CMGS").string(r#"+1"2\3"#).omitted().int(-129).raw("0A1F").omitted().finish();
+        assert_eq!(line, Ok(r#"AT+CMGS="+1\"2\\3",,-129,0A1F"#));
+
+        let mut buf = [0u8; 16];
+        assert_eq!(AtCommandBuilder::new(&mut buf, "AT+GMR").omitted().finish(), Ok("AT+GMR"));
+        let mut buf = [0u8; 16];
+        assert_eq!(AtCommandBuilder::new(&mut buf, "AT+CSQ").test(), Ok("AT+CSQ=?"));
+        let mut buf = [0u8; 16];
+        assert_eq!(AtCommandBuilder::new(&mut buf, "AT+CMGF").int(1u8).query(), Err(ArgError::Unexpected(0)));
+    }
+
+    #[test]
+    fn overflow_reports_the_first_argument_not_written() {
+        let mut buf = [0u8; 12];
+        assert_eq!(AtCommandBuilder::new(&mut buf, "AT+CWJAP").string("ab").string("cd").finish(), Err(ArgError::Overflow(0)));
+        let mut buf = [0u8; 12];
+        assert_eq!(AtCommandBuilder::new(&mut buf, "AT+CWJAP").int(1).omitted().int(2).finish(), Err(ArgError::Overflow(2)));
+        let mut buf = [0u8; 4];
+        assert_eq!(AtCommandBuilder::new(&mut buf, "AT+CSQ").finish(), Err(ArgError::Overflow(0)));
+    }
+}
@@ -0,0 +1,311 @@
+/***************************************************************************
+ *
+ * AT Command Parser
+ * Copyright (C) 2026 Antonio Salsi <passy.linux@zresa.it>
+ *
+ * This library is free software; you can redistribute it and/or
+ * modify it under the terms of the GNU Lesser General Public
+ * License as published by the Free Software Foundation; either
+ * version 2.1 of the License, or (at your option) any later version.
+ *
+ * This library is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the GNU
+ * Lesser General Public License for more details.
+ *
+ * You should have received a copy of the GNU Lesser General Public
+ * License along with this library; if not, see <https://www.gnu.org/licenses/>.
+ *
+ ***************************************************************************/
+
+ 
+//! Command round-trips
+
+use crate::Args;
+use crate::response::ResultCode;
+
+use super::{CTRL_Z, Final, Response, ResponseParser};
+
+/// Reason a command round-trip failed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Failure {
+    /// Final result code other than `OK` (`ERROR`, `NO CARRIER`, ...)
+    Code(ResultCode),
+    /// `+CME ERROR: <err>`, `None` when reported verbosely (`AT+CMEE=2`)
+    Cme(Option<u16>),
+    /// `+CMS ERROR: <err>`, `None` when reported verbosely
+    Cms(Option<u16>),
+    /// No more bytes before the final result code
+    Timeout,
+    /// The information lines did not fit in the payload buffer
+    Overflow,
+}
+
+/// Information lines of a command, one per line
+///
+/// Named lines are stored without their name (`23,0` for `+CSQ: 23,0`),
+/// text lines as they are.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Payload<'p> {
+    text: &'p str,
+}
+
+impl<'p> Payload<'p> {
+    /// Lines stored in `buf` by an [`Exchange`].
+    pub(super) fn new(buf: &'p [u8]) -> Self {
+        Self { text: core::str::from_utf8(buf).unwrap_or("") }
+    }
+
+    /// Lines joined by `\n`.
+    pub fn as_str(&self) -> &'p str {
+        self.text
+    }
+
+    /// Number of information lines.
+    pub fn len(&self) -> usize {
+        if self.is_empty() { 0 } else { self.text.split('\n').count() }
+    }
+
+    /// Whether the command returned no information line.
+    pub fn is_empty(&self) -> bool {
+        self.text.is_empty()
+    }
+
+    /// Fields of the line at `index`.
+    pub fn line(&self, index: usize) -> Option<Args<'p>> {
+        self.lines().nth(index)
+    }
+
+    /// Fields of every line, in order.
+    pub fn lines(&self) -> impl Iterator<Item = Args<'p>> + use<'p> {
+        let text = self.text;
+        text.split('\n').filter(move |_| !text.is_empty()).map(Args::new)
+    }
+}
+
+/// Collects the response of one command from classified lines
+///
+/// Echoes are skipped, information lines are appended to the payload buffer
+/// and URCs and prompts are handed back, until the final result code. Used by
+/// [`round_trip`] over a [`ResponseParser`], or fed directly with the frames
+/// of an [`Ingress`](super::Ingress).
+pub struct Exchange<'p> {
+    /// Payload buffer
+    buf: &'p mut [u8],
+    /// Number of valid bytes in `buf`
+    len: usize,
+    /// Whether a line did not fit in `buf`
+    overflow: bool,
+}
+
+/// Progress of an [`Exchange`] after a line
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Step<'r> {
+    /// The response goes on
+    Pending,
+    /// Unsolicited result code received meanwhile, not part of the response
+    Urc(&'r str, Args<'r>),
+    /// `> ` prompt: the data of the command is to be sent, ended by
+    /// [`CTRL_Z`](super::CTRL_Z) or cancelled by [`ESC`](super::ESC)
+    Prompt,
+    /// Final result code received
+    Done(Result<(), Failure>),
+}
+
+impl<'p> Exchange<'p> {
+    /// Collect a response into `buf`.
+    pub fn new(buf: &'p mut [u8]) -> Self {
+        Self { buf, len: 0, overflow: false }
+    }
+
+    /// Take the next line of the response.
+    pub fn push<'r>(&mut self, response: Response<'r>) -> Step<'r> {
+        match response {
+            Response::Echo(_) => Step::Pending,
+            Response::Prompt => Step::Prompt,
+            Response::Info(args) => {
+                self.append(args.raw());
+                Step::Pending
+            }
+            Response::Urc(name, args) => Step::Urc(name, args),
+            Response::Final(Final::Code(ResultCode::Ok)) if self.overflow => Step::Done(Err(Failure::Overflow)),
+            Response::Final(Final::Code(ResultCode::Ok)) => Step::Done(Ok(())),
+            Response::Final(Final::Code(code)) => Step::Done(Err(Failure::Code(code))),
+            Response::Final(Final::Cme(args)) => Step::Done(Err(Failure::Cme(args.get_u16(0).ok()))),
+            Response::Final(Final::Cms(args)) => Step::Done(Err(Failure::Cms(args.get_u16(0).ok()))),
+        }
+    }
+
+    /// The information lines collected.
+    pub fn finish(self) -> Payload<'p> {
+        let len = self.len;
+        // only whole `str`s are ever appended
+        Payload { text: core::str::from_utf8(&self.buf[..len]).unwrap_or("") }
+    }
+
+    /// The information lines collected so far.
+    pub fn payload(&self) -> Payload<'_> {
+        Payload { text: core::str::from_utf8(&self.buf[..self.len]).unwrap_or("") }
+    }
+
+    /// Discard the lines collected, to collect the response of another
+    /// command into the same buffer.
+    pub fn reset(&mut self) {
+        self.len = 0;
+        self.overflow = false;
+    }
+
+    /// Append a line, after a `\n` unless it is the first.
+    fn append(&mut self, line: &str) {
+        let separator = usize::from(self.len > 0);
+        let end = self.len + separator + line.len();
+        if self.overflow || end > self.buf.len() {
+            self.overflow = true;
+            return;
+        }
+        if separator > 0 {
+            self.buf[self.len] = b'\n';
+        }
+        self.buf[self.len + separator..end].copy_from_slice(line.as_bytes());
+        self.len = end;
+    }
+}
+
+/// Wait for the response of the command line just sent (`AT+COPS?`) and
+/// return its information lines.
+///
+/// Bytes are taken from `read` until the final result code; `None` from
+/// `read` (a timeout) gives up the command. The echo is skipped, and URCs
+/// received meanwhile are handed to `urc`. Commands sending data after a
+/// `> ` prompt go through [`round_trip_with_data`].
+///
+/// # Errors
+///
+/// Every final result code other than `OK`, a [`Failure::Timeout`], and a
+/// [`Failure::Overflow`] when the information lines do not fit in
+/// `payload`.
+///
+/// # Example
+///
+/// ```rust
+/// use at_parser_rs::client::{ResponseParser, round_trip};
+///
+/// let mut rx = b"AT+COPS?\r\r\n+CREG: 1\r\n\r\n+COPS: 0,0,\"Vodafone\"\r\n\r\nOK\r\n".iter().copied();
+/// let mut modem: ResponseParser<64> = ResponseParser::new();
+/// let mut payload = [0u8; 64];
+/// let mut urcs = 0;
+///
+/// let cops = round_trip(&mut modem, "AT+COPS?", &mut payload, || rx.next(), |_, _| urcs += 1).unwrap();
+/// assert_eq!(cops.line(0).unwrap().get(2).as_deref(), Some("Vodafone"));
+/// assert_eq!((cops.len(), urcs), (1, 1));
+/// ```
+pub fn round_trip<'p, const LINE: usize>(
+    parser: &mut ResponseParser<LINE>,
+    command: &str,
+    payload: &'p mut [u8],
+    read: impl FnMut() -> Option<u8>,
+    urc: impl FnMut(&str, Args<'_>),
+) -> Result<Payload<'p>, Failure> {
+    exchange(parser, command, payload, read, urc, || {})
+}
+
+/// [`round_trip`] of a two-stage command (`AT+CMGS`, `AT+CIPSEND`): on the
+/// `> ` prompt, `data` is handed to `write` followed by [`CTRL_Z`], then the
+/// response is collected as usual.
+///
+/// # Errors
+///
+/// As [`round_trip`]; a command refused before the prompt ends with its
+/// final result code without sending `data`.
+///
+/// # Example
+///
+/// ```rust
+/// use at_parser_rs::client::{CTRL_Z, ResponseParser, round_trip_with_data};
+///
+/// let mut rx = b"AT+CMGS=\"+3912345\"\r\r\n> hello\x1a\r\n+CMGS: 7\r\n\r\nOK\r\n".iter().copied();
+/// let mut tx = Vec::new();
+/// let mut modem: ResponseParser<64> = ResponseParser::new();
+/// let mut payload = [0u8; 64];
+///
+/// let sent = round_trip_with_data(&mut modem, "AT+CMGS=\"+3912345\"", b"hello", |bytes| tx.extend_from_slice(bytes), &mut payload, || rx.next(), |_, _| {});
+/// assert_eq!(sent.unwrap().as_str(), "7");
+/// assert_eq!(tx, [b'h', b'e', b'l', b'l', b'o', CTRL_Z]);
+/// ```
+pub fn round_trip_with_data<'p, const LINE: usize>(
+    parser: &mut ResponseParser<LINE>,
+    command: &str,
+    data: &[u8],
+    mut write: impl FnMut(&[u8]),
+    payload: &'p mut [u8],
+    read: impl FnMut() -> Option<u8>,
+    urc: impl FnMut(&str, Args<'_>),
+) -> Result<Payload<'p>, Failure> {
+    exchange(parser, command, payload, read, urc, || {
+        write(data);
+        write(&[CTRL_Z]);
+    })
+}
+
+/// Loop of [`round_trip`], calling `prompt` on a `> ` prompt.
+fn exchange<'p, const LINE: usize>(
+    parser: &mut ResponseParser<LINE>,
+    command: &str,
+    payload: &'p mut [u8],
+    mut read: impl FnMut() -> Option<u8>,
+    mut urc: impl FnMut(&str, Args<'_>),
+    mut prompt: impl FnMut(),
+) -> Result<Payload<'p>, Failure> {
+    parser.send(command);
+    let mut exchange = Exchange::new(payload);
+    loop {
+        let Some(byte) = read() else {
+            parser.cancel();
+            return Err(Failure::Timeout);
+        };
+        let Some(response) = parser.push(byte) else {
+            continue;
+        };
+        match exchange.push(response) {
+            Step::Pending => {}
+            Step::Urc(name, args) => urc(name, args),
+            Step::Prompt => prompt(),
+            Step::Done(result) => return result.map(|()| exchange.finish()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Failure, round_trip};
+    use crate::client::ResponseParser;
+    use crate::response::ResultCode;
+
+    fn run(rx: &[u8], command: &str, payload: &mut [u8]) -> Result<usize, Failure> {
+        let mut modem: ResponseParser<64> = ResponseParser::new();
+        let mut rx = rx.iter().copied();
+        round_trip(&mut modem, command, payload, || rx.next(), |_, _| {}).map(|payload| payload.len())
+    }
+
+    #[test]
+    fn information_lines_are_collected_without_echo() {
+        let mut modem: ResponseParser<64> = ResponseParser::new();
+        let mut rx = b"\r\nQuectel\r\nEC25\r\n\r\nRevision: EC25EFAR06A06M4G\r\n\r\nOK\r\n".iter().copied();
+        let mut payload = [0u8; 64];
+        let info = round_trip(&mut modem, "ATI", &mut payload, || rx.next(), |_, _| {}).unwrap();
+        assert_eq!(info.as_str(), "Quectel\nEC25\nRevision: EC25EFAR06A06M4G");
+        assert_eq!(info.lines().count(), 3);
+
+        assert_eq!(run(b"AT+CMGF=1\r\r\nOK\r\n", "AT+CMGF=1", &mut payload), Ok(0));
+    }
+
+    #[test]
+    fn failures_end_the_round_trip() {
+        let mut payload = [0u8; 8];
+        assert_eq!(run(b"\r\n+CME ERROR: 10\r\n", "AT+CPIN?", &mut payload), Err(Failure::Cme(Some(10))));
+        assert_eq!(run(b"\r\n+CMS ERROR: unknown error\r\n", "AT+CMGS", &mut payload), Err(Failure::Cms(None)));
+        assert_eq!(run(b"\r\nNO CARRIER\r\n", "ATD123;", &mut payload), Err(Failure::Code(ResultCode::NoCarrier)));
+        assert_eq!(run(b"\r\n+CSQ: 23", "AT+CSQ", &mut payload), Err(Failure::Timeout));
+        assert_eq!(run(b"\r\n+CGSN: 490154203237518\r\n\r\nOK\r\n", "AT+CGSN", &mut payload), Err(Failure::Overflow));
+    }
+}
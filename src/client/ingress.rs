@@ -0,0 +1,190 @@
+/***************************************************************************
+ *
+ * AT Command Parser
+ * Copyright (C) 2026 Antonio Salsi <passy.linux@zresa.it>
+ *
+ * This library is free software; you can redistribute it and/or
+ * modify it under the terms of the GNU Lesser General Public
+ * License as published by the Free Software Foundation; either
+ * version 2.1 of the License, or (at your option) any later version.
+ *
+ * This library is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the GNU
+ * Lesser General Public License for more details.
+ *
+ * You should have received a copy of the GNU Lesser General Public
+ * License along with this library; if not, see <https://www.gnu.org/licenses/>.
+ *
+ ***************************************************************************/
+
+ 
+//! Ingress buffering of fragmented modem output
+
+use super::{Response, ResponseParser};
+
+/// Buffers the bytes received from a modem and digests them into classified
+/// frames
+///
+/// Modem output arrives split arbitrarily across UART reads: each chunk is
+/// handed to [`ingest`](Ingress::ingest), then [`digest`](Ingress::digest)
+/// is called until it returns `None`. Partial lines stay buffered across chunks, the echo of the
+/// pending command is stripped, and URCs interleaved with its response come
+/// out as frames of their own, in order.
+///
+/// `BUF` bytes not digested yet are buffered; lines are up to `LINE` bytes.
+///
+/// # Example
+///
+/// ```rust
+/// use at_parser_rs::client::{Final, Ingress, Response};
+///
+/// let mut modem: Ingress<64, 128> = Ingress::new();
+/// modem.ingest(b"AT+CSQ\r\r\n+CS");
+/// assert_eq!(modem.digest(), None);
+///
+/// modem.ingest(b"Q: 23,0\r\n\r\nRING\r\n\r\nOK\r\n");
+/// assert!(matches!(modem.digest(), Some(Response::Info(args)) if args.raw() == "23,0"));
+/// assert!(matches!(modem.digest(), Some(Response::Urc("RING", _))));
+/// assert!(matches!(modem.digest(), Some(Response::Final(result)) if result.is_ok()));
+/// assert_eq!(modem.digest(), None);
+/// ```
+pub struct Ingress<const LINE: usize, const BUF: usize> {
+    /// Line classifier
+    parser: ResponseParser<LINE>,
+    /// Bytes received and not digested yet
+    buf: [u8; BUF],
+    /// Number of valid bytes in `buf`
+    len: usize,
+    /// Number of bytes of `buf` already digested
+    pos: usize,
+}
+
+impl<const LINE: usize, const BUF: usize> Ingress<LINE, BUF> {
+    /// Create an empty ingress buffer with no pending command.
+    pub const fn new() -> Self {
+        Self { parser: ResponseParser::new(), buf: [0; BUF], len: 0, pos: 0 }
+    }
+
+    /// Buffer a chunk of received bytes, returning how many fit.
+    ///
+    /// Bytes that do not fit are dropped: digest more often or enlarge
+    /// `BUF`.
+    pub fn ingest(&mut self, bytes: &[u8]) -> usize {
+        if self.len + bytes.len() > BUF && self.pos > 0 {
+            self.buf.copy_within(self.pos..self.len, 0);
+            self.len -= self.pos;
+            self.pos = 0;
+        }
+        let count = bytes.len().min(BUF - self.len);
+        self.buf[self.len..self.len + count].copy_from_slice(&bytes[..count]);
+        self.len += count;
+        count
+    }
+
+    /// Next complete frame, or `None` once the buffered bytes are exhausted.
+    ///
+    /// Echoed command lines are not returned, but still mark their command
+    /// pending.
+    pub fn digest(&mut self) -> Option<Response<'_>> {
+        while self.pos < self.len {
+            let byte = self.buf[self.pos];
+            self.pos += 1;
+            if self.parser.feed(byte) && !matches!(self.parser.response(), Some(Response::Echo(_))) {
+                return self.parser.response();
+            }
+        }
+        self.len = 0;
+        self.pos = 0;
+        None
+    }
+
+    /// Number of bytes buffered and not digested yet.
+    pub fn len(&self) -> usize {
+        self.len - self.pos
+    }
+
+    /// Whether every buffered byte has been digested.
+    pub fn is_empty(&self) -> bool {
+        self.len == self.pos
+    }
+
+    /// Mark the command line just sent as pending, see
+    /// [`ResponseParser::send`].
+    pub fn send(&mut self, command: &str) {
+        self.parser.send(command);
+    }
+
+    /// Whether a command waits for its final result code.
+    pub fn is_pending(&self) -> bool {
+        self.parser.is_pending()
+    }
+
+    /// Give up the pending command, see [`ResponseParser::cancel`].
+    pub fn cancel(&mut self) {
+        self.parser.cancel();
+    }
+
+    /// Drop the buffered bytes and the partially received line.
+    pub fn clear(&mut self) {
+        self.len = 0;
+        self.pos = 0;
+        self.parser.clear();
+    }
+}
+
+impl<const LINE: usize, const BUF: usize> Default for Ingress<LINE, BUF> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::format;
+    use alloc::string::String;
+    use alloc::vec::Vec;
+
+    use super::Ingress;
+    use crate::Args;
+    use crate::client::Response;
+
+    fn digest_all<const LINE: usize, const BUF: usize>(modem: &mut Ingress<LINE, BUF>) -> Vec<String> {
+        let mut frames = Vec::new();
+        while let Some(frame) = modem.digest() {
+            frames.push(format!("{frame:?}"));
+        }
+        frames
+    }
+
+    #[test]
+    fn frames_do_not_depend_on_how_output_is_split() {
+        let output = b"AT+CPIN?\r\r\n+CPIN: READY\r\n\r\n+CMTI: \"SM\",1\r\n\r\nOK\r\n";
+        let mut whole: Ingress<32, 64> = Ingress::new();
+        whole.ingest(output);
+        let expected = digest_all(&mut whole);
+        assert_eq!(expected.len(), 3);
+
+        for size in 1..8 {
+            let mut modem: Ingress<32, 16> = Ingress::new();
+            let mut frames = Vec::new();
+            for chunk in output.chunks(size) {
+                assert_eq!(modem.ingest(chunk), chunk.len());
+                frames.extend(digest_all(&mut modem));
+            }
+            assert_eq!(frames, expected);
+            assert!(modem.is_empty() && !modem.is_pending());
+        }
+    }
+
+    #[test]
+    fn full_buffers_keep_undigested_bytes() {
+        let mut modem: Ingress<16, 8> = Ingress::new();
+        assert_eq!(modem.ingest(b"RING\r\n+C"), 8);
+        assert_eq!(modem.ingest(b"GEV"), 0);
+        assert!(modem.digest().is_some());
+        assert_eq!(modem.len(), 3);
+        assert_eq!(modem.ingest(b"GEV\r\n"), 5);
+        assert_eq!(digest_all(&mut modem), [format!("{:?}", Response::Urc("+CGEV", Args::new("")))]);
+    }
+}
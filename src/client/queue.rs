@@ -0,0 +1,305 @@
+/***************************************************************************
+ *
+ * AT Command Parser
+ * Copyright (C) 2026 Antonio Salsi <passy.linux@zresa.it>
+ *
+ * This library is free software; you can redistribute it and/or
+ * modify it under the terms of the GNU Lesser General Public
+ * License as published by the Free Software Foundation; either
+ * version 2.1 of the License, or (at your option) any later version.
+ *
+ * This library is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the GNU
+ * Lesser General Public License for more details.
+ *
+ * You should have received a copy of the GNU Lesser General Public
+ * License along with this library; if not, see <https://www.gnu.org/licenses/>.
+ *
+ ***************************************************************************/
+
+ 
+//! Pipelined command queue
+
+use core::time::Duration;
+
+use crate::Args;
+
+use super::{Clock, Exchange, Failure, Payload, Response, ResponseParser, Step, Timeouts};
+
+/// Callback receiving the result of a queued command
+pub type Completion<'q> = &'q mut dyn FnMut(Result<Payload<'_>, Failure>);
+
+/// Handle of a queued command, identifying its result
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Ticket(u32);
+
+/// What a [`CommandQueue`] reports while its output is pushed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Event<'e> {
+    /// Unsolicited result code
+    Urc(&'e str, Args<'e>),
+    /// `> ` prompt of the command in flight: its data is to be written,
+    /// ended by [`CTRL_Z`](super::CTRL_Z)
+    Prompt(Ticket),
+    /// Result of a command submitted without a [`Completion`]
+    Done(Ticket, Result<Payload<'e>, Failure>),
+}
+
+/// Queued command
+struct Slot<'q> {
+    ticket: Ticket,
+    command: &'q str,
+    /// Callback of the result, `None` to report it as an [`Event::Done`]
+    completion: Option<Completion<'q>>,
+}
+
+/// Queued commands, apart from the line classifier
+struct Slots<'q, const DEPTH: usize> {
+    /// Collects the response of the command in flight
+    exchange: Exchange<'q>,
+    /// Ring buffer of the commands, the oldest at `head`
+    slots: [Option<Slot<'q>>; DEPTH],
+    head: usize,
+    len: usize,
+    /// Start of the command at `head`, once written
+    started: Option<Duration>,
+    /// Ticket of the next command
+    next: u32,
+}
+
+impl<'q, const DEPTH: usize> Slots<'q, DEPTH> {
+    /// Dequeue the command in flight with its result, handing it to its
+    /// completion or returning it.
+    fn complete(&mut self, result: Result<(), Failure>) -> Option<Event<'_>> {
+        let slot = self.slots[self.head].take()?;
+        self.head = (self.head + 1) % DEPTH;
+        self.len -= 1;
+        self.started = None;
+        let result = result.map(|()| self.exchange.payload());
+        match slot.completion {
+            Some(completion) => {
+                completion(result);
+                None
+            }
+            None => Some(Event::Done(slot.ticket, result)),
+        }
+    }
+}
+
+/// Fixed-capacity queue of client commands
+///
+/// Up to `DEPTH` commands are accepted at once and written one at a time:
+/// [`next_command`](CommandQueue::next_command) hands out the command line to
+/// write once the previous one has completed, and the modem output pushed
+/// byte by byte is matched to it. Each result goes to the
+/// [`Completion`] given at submission, or else is returned as an
+/// [`Event::Done`] carrying the [`Ticket`] of the command;
+/// [`expire`](CommandQueue::expire) fails the command in flight after its
+/// [`Timeouts`].
+///
+/// # Example
+///
+/// ```rust
+/// use core::time::Duration;
+/// use at_parser_rs::client::{CommandQueue, Event, Timeouts};
+///
+/// let clock = || Duration::ZERO;
+/// let mut payload = [0u8; 64];
+/// let mut modem: CommandQueue<4, 64> = CommandQueue::new(&mut payload, Timeouts::STANDARD);
+///
+/// let mut fw = None;
+/// let mut on_fw = |result: Result<at_parser_rs::client::Payload, _>| fw = result.ok().map(|p| p.as_str().len());
+/// modem.submit_with("AT+CGMR", &mut on_fw).unwrap();
+/// let csq = modem.submit("AT+CSQ").unwrap();
+///
+/// let mut rx: &[u8] = b"\r\nEC25EFAR06A06M4G\r\n\r\nOK\r\n\r\n+CSQ: 23,99\r\n\r\nOK\r\n";
+/// let mut rssi = None;
+/// while !modem.is_empty() {
+///     if let Some(line) = modem.next_command(&clock) {
+///         assert!(line.starts_with("AT+"));             // written to the modem
+///     }
+///     let (&byte, rest) = rx.split_first().unwrap();
+///     rx = rest;
+///     if let Some(Event::Done(ticket, Ok(lines))) = modem.push(byte) {
+///         assert_eq!(ticket, csq);
+///         rssi = lines.line(0).and_then(|args| args.get_u8(0).ok());
+///     }
+/// }
+/// assert_eq!(rssi, Some(23));
+/// assert_eq!(fw, Some(16));
+/// ```
+pub struct CommandQueue<'q, const DEPTH: usize, const LINE: usize> {
+    /// Line classifier
+    parser: ResponseParser<LINE>,
+    /// Queued commands
+    slots: Slots<'q, DEPTH>,
+    /// Timeout of each command
+    timeouts: Timeouts,
+}
+
+impl<'q, const DEPTH: usize, const LINE: usize> CommandQueue<'q, DEPTH, LINE> {
+    /// Create an empty queue collecting responses into `payload`, failing
+    /// commands after their `timeouts`.
+    pub fn new(payload: &'q mut [u8], timeouts: Timeouts) -> Self {
+        Self {
+            parser: ResponseParser::new(),
+            slots: Slots {
+                exchange: Exchange::new(payload),
+                slots: [const { None }; DEPTH],
+                head: 0,
+                len: 0,
+                started: None,
+                next: 0,
+            },
+            timeouts,
+        }
+    }
+
+    /// Queue `command`, its result to be returned as an [`Event::Done`];
+    /// `None` when the queue is full.
+    pub fn submit(&mut self, command: &'q str) -> Option<Ticket> {
+        self.enqueue(command, None)
+    }
+
+    /// Queue `command`, its result to be handed to `completion`; `None` when
+    /// the queue is full.
+    pub fn submit_with(&mut self, command: &'q str, completion: Completion<'q>) -> Option<Ticket> {
+        self.enqueue(command, Some(completion))
+    }
+
+    /// Number of queued commands, including the one in flight.
+    pub fn len(&self) -> usize {
+        self.slots.len
+    }
+
+    /// Whether no command is queued.
+    pub fn is_empty(&self) -> bool {
+        self.slots.len == 0
+    }
+
+    /// Whether no more command can be queued.
+    pub fn is_full(&self) -> bool {
+        self.slots.len == DEPTH
+    }
+
+    /// Ticket of the command written and waiting for its final result code.
+    pub fn in_flight(&self) -> Option<Ticket> {
+        self.slots.started?;
+        self.slots.slots[self.slots.head].as_ref().map(|slot| slot.ticket)
+    }
+
+    /// Command line to write to the modem, when the previous command has
+    /// completed and another one is queued; it is in flight from now, as
+    /// measured by `clock`.
+    pub fn next_command<C: Clock + ?Sized>(&mut self, clock: &C) -> Option<&'q str> {
+        if self.slots.started.is_some() {
+            return None;
+        }
+        let command = self.slots.slots[self.slots.head].as_ref()?.command;
+        self.parser.send(command);
+        self.slots.exchange.reset();
+        self.slots.started = Some(clock.now());
+        Some(command)
+    }
+
+    /// Fail the command in flight with [`Failure::Timeout`] once its timeout
+    /// has elapsed, returning the result unless it went to a completion.
+    pub fn expire<C: Clock + ?Sized>(&mut self, clock: &C) -> Option<Event<'_>> {
+        let started = self.slots.started?;
+        let command = self.slots.slots[self.slots.head].as_ref()?.command;
+        if clock.now().saturating_sub(started) < self.timeouts.get(command) {
+            return None;
+        }
+        self.parser.cancel();
+        self.slots.complete(Err(Failure::Timeout))
+    }
+
+    /// Drop every queued command, without result, and give up the one in
+    /// flight.
+    pub fn clear(&mut self) {
+        self.slots.slots.iter_mut().for_each(|slot| *slot = None);
+        self.slots.head = 0;
+        self.slots.len = 0;
+        self.slots.started = None;
+        self.parser.cancel();
+        self.parser.clear();
+    }
+
+    /// Append a byte of modem output, returning what the line it completes
+    /// reports, if anything.
+    ///
+    /// Lines are part of the response of the command in flight, or else
+    /// unsolicited; a result handed to its completion is not returned.
+    pub fn push(&mut self, byte: u8) -> Option<Event<'_>> {
+        let response = self.parser.push(byte)?;
+        if self.slots.started.is_none() {
+            return match response {
+                Response::Urc(name, args) => Some(Event::Urc(name, args)),
+                _ => None,
+            };
+        }
+        match self.slots.exchange.push(response) {
+            Step::Pending => None,
+            Step::Urc(name, args) => Some(Event::Urc(name, args)),
+            Step::Prompt => self.slots.slots[self.slots.head].as_ref().map(|slot| Event::Prompt(slot.ticket)),
+            Step::Done(result) => self.slots.complete(result),
+        }
+    }
+
+    /// Append `command` to the ring buffer.
+    fn enqueue(&mut self, command: &'q str, completion: Option<Completion<'q>>) -> Option<Ticket> {
+        if self.is_full() {
+            return None;
+        }
+        let ticket = Ticket(self.slots.next);
+        self.slots.next = self.slots.next.wrapping_add(1);
+        let tail = (self.slots.head + self.slots.len) % DEPTH;
+        self.slots.slots[tail] = Some(Slot { ticket, command, completion });
+        self.slots.len += 1;
+        Some(ticket)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::format;
+    use alloc::vec::Vec;
+    use core::cell::Cell;
+    use core::time::Duration;
+
+    use super::{CommandQueue, Event};
+    use crate::client::{Failure, Timeouts};
+
+    #[test]
+    fn commands_are_written_one_at_a_time_and_matched_to_their_results() {
+        let ticks = Cell::new(0u64);
+        let clock = || Duration::from_millis(ticks.get());
+        let mut payload = [0u8; 32];
+        let mut modem: CommandQueue<2, 64> = CommandQueue::new(&mut payload, Timeouts::STANDARD);
+
+        let creg = modem.submit("AT+CREG?").unwrap();
+        let cops = modem.submit("AT+COPS=?").unwrap();
+        assert!(modem.is_full() && modem.submit("AT").is_none());
+        assert_eq!(modem.next_command(&clock), Some("AT+CREG?"));
+        assert_eq!(modem.next_command(&clock), None);
+        assert_eq!(modem.in_flight(), Some(creg));
+
+        let mut events = Vec::new();
+        for &byte in b"\r\n+CREG: 0,1\r\n\r\nRING\r\n\r\nOK\r\n" {
+            if let Some(event) = modem.push(byte) {
+                events.push(format!("{event:?}"));
+            }
+        }
+        assert_eq!(events.len(), 2);
+        assert!(events[0].starts_with("Urc(\"RING\""));
+        assert!(events[1].starts_with(&format!("Done({creg:?}, Ok(Payload {{ text: \"0,1\" }}))")));
+
+        assert_eq!(modem.next_command(&clock), Some("AT+COPS=?"));
+        ticks.set(179_999);
+        assert_eq!(modem.expire(&clock), None);
+        ticks.set(180_000);
+        assert_eq!(modem.expire(&clock), Some(Event::Done(cops, Err(Failure::Timeout))));
+        assert!(modem.is_empty() && modem.in_flight().is_none());
+    }
+}
@@ -0,0 +1,389 @@
+/***************************************************************************
+ *
+ * AT Command Parser
+ * Copyright (C) 2026 Antonio Salsi <passy.linux@zresa.it>
+ *
+ * This library is free software; you can redistribute it and/or
+ * modify it under the terms of the GNU Lesser General Public
+ * License as published by the Free Software Foundation; either
+ * version 2.1 of the License, or (at your option) any later version.
+ *
+ * This library is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the GNU
+ * Lesser General Public License for more details.
+ *
+ * You should have received a copy of the GNU Lesser General Public
+ * License along with this library; if not, see <https://www.gnu.org/licenses/>.
+ *
+ ***************************************************************************/
+
+ 
+//! Response line classifier
+
+use core::ops::Range;
+
+use crate::response::ResultCode;
+use crate::{ArgError, Args, FromArgs};
+
+/// Line received from the modem, as classified by [`ResponseParser`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Response<'a> {
+    /// Command line, or data sent after a [`Prompt`](Response::Prompt),
+    /// echoed back by the modem (`ATE1`)
+    Echo(&'a str),
+    /// Information response of the pending command: the fields after its
+    /// prefix (`23,0` for `+CSQ: 23,0`), or a whole text line (`ATI`)
+    Info(Args<'a>),
+    /// `> ` prompt asking for the data of the pending command (`AT+CMGS`),
+    /// to be ended by [`CTRL_Z`] or cancelled by [`ESC`]
+    Prompt,
+    /// Final result code, ending the pending command
+    Final(Final<'a>),
+    /// Unsolicited result code: its name (`+CMTI`, `RING`) and its fields
+    Urc(&'a str, Args<'a>),
+}
+
+/// Final result code of a command
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Final<'a> {
+    /// V.250 result code (`OK`, `ERROR`, `NO CARRIER`, ...)
+    Code(ResultCode),
+    /// `+CME ERROR: <err>`, numeric or verbose as selected by `AT+CMEE`
+    Cme(Args<'a>),
+    /// `+CMS ERROR: <err>`
+    Cms(Args<'a>),
+}
+
+impl Final<'_> {
+    /// Whether the command succeeded (`OK`).
+    pub fn is_ok(&self) -> bool {
+        matches!(self, Final::Code(ResultCode::Ok))
+    }
+}
+
+/// Information response parsed into a type, as implemented by
+/// [`#[derive(AtResponse)]`](macro@crate::AtResponse)
+pub trait FromResponse<'a>: FromArgs<'a> {
+    /// Name of the response (`+CSQ`)
+    const NAME: &'static str;
+
+    /// Parse an information line (`+CSQ: 23,99`).
+    ///
+    /// Returns `None` when the line is not named [`NAME`](FromResponse::NAME).
+    fn from_line(line: &'a str) -> Option<Result<Self, ArgError>> {
+        let fields = line.trim().strip_prefix(Self::NAME)?.strip_prefix(':')?;
+        Some(Args::new(fields.trim()).parse())
+    }
+}
+
+/// Character ending the data sent after a [`Response::Prompt`]
+pub const CTRL_Z: u8 = 0x1a;
+
+/// Character cancelling the data sent after a [`Response::Prompt`]
+pub const ESC: u8 = 0x1b;
+
+/// Splits the bytes received from a modem into lines and classifies them
+///
+/// A command is pending from its echo, or from [`send`](ResponseParser::send)
+/// when echo is disabled (`ATE0`), until its final result code. Meanwhile,
+/// lines starting with the command name (`+CSQ:` for `AT+CSQ`) and text lines
+/// are its information response; any other named line, `RING`, and every
+/// line received while no command is pending are unsolicited result codes.
+///
+/// The `> ` prompt of two-stage commands (`AT+CMGS`, `AT+CIPSEND`) has no
+/// line terminator and is reported as soon as it is received. When the modem
+/// echoes, the data sent next is reported as an echo, up to the [`CTRL_Z`]
+/// or [`ESC`] ending it or the next line terminator.
+///
+/// Lines are terminated by `\r` or `\n`, empty lines are skipped and bytes
+/// beyond `SIZE` are discarded. One command per command line is tracked.
+///
+/// # Example
+///
+/// ```rust
+/// use at_parser_rs::client::{Final, Response, ResponseParser};
+///
+/// let mut parser: ResponseParser<64> = ResponseParser::new();
+/// let mut rssi = None;
+/// for &byte in b"AT+CSQ\r\r\n+CSQ: 23,0\r\n\r\nOK\r\n" {
+///     match parser.push(byte) {
+///         Some(Response::Info(args)) => rssi = args.get_u8(0).ok(),
+///         Some(Response::Final(result)) => assert!(result.is_ok()),
+///         _ => {}
+///     }
+/// }
+/// assert_eq!(rssi, Some(23));
+/// assert!(!parser.is_pending());
+/// ```
+pub struct ResponseParser<const SIZE: usize> {
+    /// Line being received
+    buf: [u8; SIZE],
+    /// Number of valid bytes in `buf`
+    len: usize,
+    /// Name of the pending command (`+CSQ`), empty for basic commands
+    name: [u8; SIZE],
+    /// Number of valid bytes in `name`
+    name_len: usize,
+    /// Whether a command waits for its final result code
+    pending: bool,
+    /// Whether the pending command was echoed
+    echo: bool,
+    /// Whether the data sent after a prompt is being echoed
+    data: bool,
+    /// Line completed by the last byte
+    ready: Option<Ready>,
+}
+
+impl<const SIZE: usize> ResponseParser<SIZE> {
+    /// Create a parser with no pending command.
+    pub const fn new() -> Self {
+        Self { buf: [0; SIZE], len: 0, name: [0; SIZE], name_len: 0, pending: false, echo: false, data: false, ready: None }
+    }
+
+    /// Mark the command line just sent (`AT+CSQ`) as pending.
+    ///
+    /// Only needed when the modem does not echo commands: an echoed line
+    /// marks its command pending by itself.
+    pub fn send(&mut self, command: &str) {
+        let name = command_name(command.trim());
+        let len = name.len().min(SIZE);
+        self.name[..len].copy_from_slice(&name.as_bytes()[..len]);
+        self.name_len = len;
+        self.pending = true;
+        self.echo = false;
+    }
+
+    /// Whether a command waits for its final result code.
+    pub fn is_pending(&self) -> bool {
+        self.pending
+    }
+
+    /// Give up the pending command, e.g. after a timeout: the following
+    /// lines are unsolicited.
+    pub fn cancel(&mut self) {
+        self.pending = false;
+    }
+
+    /// Discard the partially received line.
+    pub fn clear(&mut self) {
+        self.len = 0;
+    }
+
+    /// Append `byte`, returning the line it completes, if any.
+    ///
+    /// Lines that are not valid UTF-8 are dropped.
+    pub fn push(&mut self, byte: u8) -> Option<Response<'_>> {
+        if self.feed(byte) { self.response() } else { None }
+    }
+
+    /// Append `byte`, returning whether it completes a line, then available
+    /// from [`response`](ResponseParser::response) until the next byte.
+    pub(super) fn feed(&mut self, byte: u8) -> bool {
+        self.ready = None;
+        if self.data && matches!(byte, CTRL_Z | ESC | b'\r' | b'\n') {
+            self.data = false;
+            let len = core::mem::take(&mut self.len);
+            self.ready = (len > 0).then_some(Ready { kind: Kind::Echo, name: 0..len, fields: 0..0 });
+            return self.ready.is_some();
+        }
+
+        if !matches!(byte, b'\r' | b'\n') {
+            if self.len < SIZE {
+                self.buf[self.len] = byte;
+                self.len += 1;
+            }
+            if self.pending && &self.buf[..self.len] == b"> " {
+                self.len = 0;
+                self.data = self.echo;
+                self.ready = Some(Ready { kind: Kind::Prompt, name: 0..0, fields: 0..0 });
+            }
+            return self.ready.is_some();
+        }
+
+        let len = core::mem::take(&mut self.len);
+        self.ready = self.classify(len);
+        self.ready.is_some()
+    }
+
+    /// Line completed by the last byte fed.
+    pub(super) fn response(&self) -> Option<Response<'_>> {
+        let Ready { kind, name, fields } = self.ready.clone()?;
+        // both ranges fall on character boundaries of a line checked as UTF-8
+        let text = |range| core::str::from_utf8(&self.buf[range]).unwrap_or("");
+        let args = Args::new(text(fields));
+        let response = match kind {
+            Kind::Echo => Response::Echo(text(name)),
+            Kind::Info => Response::Info(args),
+            Kind::Prompt => Response::Prompt,
+            Kind::Code(code) => Response::Final(Final::Code(code)),
+            Kind::Cme => Response::Final(Final::Cme(args)),
+            Kind::Cms => Response::Final(Final::Cms(args)),
+            Kind::Urc => Response::Urc(text(name), args),
+        };
+        Some(response)
+    }
+
+    /// Classify the first `len` bytes of the buffer, following the pending
+    /// command.
+    fn classify(&mut self, len: usize) -> Option<Ready> {
+        let line = core::str::from_utf8(&self.buf[..len]).ok()?.trim();
+        if line.is_empty() {
+            return None;
+        }
+        // position of a part of `line` in the buffer
+        let range = |part: &str| {
+            if part.is_empty() {
+                return 0..0;
+            }
+            let start = part.as_ptr() as usize - self.buf.as_ptr() as usize;
+            start..start + part.len()
+        };
+        let ready = |kind, name: &str, fields: &str| Some(Ready { kind, name: range(name), fields: range(fields) });
+
+        if line.get(..2).is_some_and(|at| at.eq_ignore_ascii_case("AT")) {
+            let name = command_name(line);
+            self.name[..name.len()].copy_from_slice(name.as_bytes());
+            self.name_len = name.len();
+            self.pending = true;
+            self.echo = true;
+            return ready(Kind::Echo, line, "");
+        }
+
+        if let Some(code) = ResultCode::from_verbose(line) {
+            if code == ResultCode::Ring || !self.pending {
+                return ready(Kind::Urc, line, "");
+            }
+            self.pending = false;
+            return ready(Kind::Code(code), "", "");
+        }
+
+        let (name, fields) = split_name(line).unwrap_or(("", line));
+        let fields = fields.trim();
+        match name {
+            "+CME ERROR" | "+CMS ERROR" => {
+                self.pending = false;
+                ready(if name == "+CME ERROR" { Kind::Cme } else { Kind::Cms }, name, fields)
+            }
+            "" if self.pending => ready(Kind::Info, name, fields),
+            "" => ready(Kind::Urc, line, ""),
+            name if self.pending && name.as_bytes().eq_ignore_ascii_case(&self.name[..self.name_len]) => {
+                ready(Kind::Info, name, fields)
+            }
+            name => ready(Kind::Urc, name, fields),
+        }
+    }
+}
+
+/// Classification of a completed line
+#[derive(Clone, Copy)]
+enum Kind {
+    Echo,
+    Info,
+    Prompt,
+    Code(ResultCode),
+    Cme,
+    Cms,
+    Urc,
+}
+
+/// Completed line, as ranges of the line buffer
+#[derive(Clone)]
+struct Ready {
+    kind: Kind,
+    /// Echoed line or URC name
+    name: Range<usize>,
+    /// Fields of an information response, error or URC
+    fields: Range<usize>,
+}
+
+impl<const SIZE: usize> Default for ResponseParser<SIZE> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Name of the command of a command line (`+CSQ` for `AT+CSQ?`), whose
+/// information responses start with it; empty for basic commands (`ATI`).
+pub(super) fn command_name(line: &str) -> &str {
+    let command = line.get(2..).unwrap_or("");
+    if !command.starts_with(|ch: char| ch.is_ascii_punctuation()) {
+        return "";
+    }
+    let end = command.find(['=', '?', ';']).unwrap_or(command.len());
+    &command[..end]
+}
+
+/// Split a named line (`+CMTI: "SM",3`, `+PBREADY`) into its name and fields.
+fn split_name(line: &str) -> Option<(&str, &str)> {
+    if !line.starts_with(|ch: char| ch.is_ascii_punctuation() && ch != '"' && ch != '(') {
+        return None;
+    }
+    let (name, fields) = line.split_once(':').unwrap_or((line, ""));
+    (!name.contains(char::is_whitespace) || name.ends_with(" ERROR")).then_some((name, fields))
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::format;
+    use alloc::string::String;
+    use alloc::vec::Vec;
+
+    use super::{Final, Response, ResponseParser};
+    use crate::Args;
+    use crate::response::ResultCode;
+
+    fn feed<const SIZE: usize>(parser: &mut ResponseParser<SIZE>, bytes: &[u8]) -> Vec<String> {
+        bytes.iter().filter_map(|&byte| parser.push(byte).map(|response| format!("{response:?}"))).collect()
+    }
+
+    fn expected(responses: &[Response]) -> Vec<String> {
+        responses.iter().map(|response| format!("{response:?}")).collect()
+    }
+
+    const NONE: Args = Args::new("");
+
+    #[test]
+    fn echoed_commands_are_followed_to_their_final_code() {
+        let mut parser: ResponseParser<64> = ResponseParser::new();
+        let lines = feed(&mut parser, b"AT+CSQ\r\r\n+CSQ: 23,0\r\n\r\n+CMTI: \"SM\",3\r\n\r\nOK\r\n\r\nRING\r\n");
+        assert_eq!(lines, expected(&[
+            Response::Echo("AT+CSQ"),
+            Response::Info(Args::new("23,0")),
+            Response::Urc("+CMTI", Args::new("\"SM\",3")),
+            Response::Final(Final::Code(ResultCode::Ok)),
+            Response::Urc("RING", NONE),
+        ]));
+    }
+
+    #[test]
+    fn commands_sent_without_echo_end_with_errors() {
+        let mut parser: ResponseParser<64> = ResponseParser::new();
+        parser.send("ATI");
+        let lines = feed(&mut parser, b"\r\nQuectel\r\n\r\n+CME ERROR: 10\r\n\r\nNO CARRIER\r\n");
+        assert_eq!(lines, expected(&[
+            Response::Info(Args::new("Quectel")),
+            Response::Final(Final::Cme(Args::new("10"))),
+            Response::Urc("NO CARRIER", NONE),
+        ]));
+
+        parser.send("AT+CMGS=\"+3912345\"");
+        assert_eq!(feed(&mut parser, b"\r\n> "), expected(&[Response::Prompt]));
+        let lines = feed(&mut parser, b"\r\n+CMGS: 7\r\n\r\n+CMS ERROR: 500\r\n");
+        assert_eq!(lines, expected(&[Response::Info(Args::new("7")), Response::Final(Final::Cms(Args::new("500")))]));
+        assert!(!parser.is_pending());
+    }
+
+    #[test]
+    fn echoed_data_ends_at_ctrl_z() {
+        let mut parser: ResponseParser<64> = ResponseParser::new();
+        let lines = feed(&mut parser, b"AT+CMGS=\"+3912345\"\r\r\n> hello\x1a\r\n+CMGS: 7\r\n\r\nOK\r\n");
+        assert_eq!(lines, expected(&[
+            Response::Echo("AT+CMGS=\"+3912345\""),
+            Response::Prompt,
+            Response::Echo("hello"),
+            Response::Info(Args::new("7")),
+            Response::Final(Final::Code(ResultCode::Ok)),
+        ]));
+    }
+}
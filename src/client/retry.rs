@@ -0,0 +1,220 @@
+/***************************************************************************
+ *
+ * AT Command Parser
+ * Copyright (C) 2026 Antonio Salsi <passy.linux@zresa.it>
+ *
+ * This library is free software; you can redistribute it and/or
+ * modify it under the terms of the GNU Lesser General Public
+ * License as published by the Free Software Foundation; either
+ * version 2.1 of the License, or (at your option) any later version.
+ *
+ * This library is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the GNU
+ * Lesser General Public License for more details.
+ *
+ * You should have received a copy of the GNU Lesser General Public
+ * License along with this library; if not, see <https://www.gnu.org/licenses/>.
+ *
+ ***************************************************************************/
+
+ 
+//! Command retries
+
+use core::time::Duration;
+
+use crate::Args;
+use crate::response::ResultCode;
+
+use super::{Clock, Failure, Payload, ResponseParser, deadline, round_trip};
+
+/// Retry policy of client commands
+///
+/// A command is attempted up to `attempts` times while it fails with a
+/// retryable [`Failure`], waiting `delay` before the second attempt and
+/// `backoff` times longer before each following one.
+///
+/// # Example
+///
+/// ```rust
+/// use core::cell::Cell;
+/// use core::time::Duration;
+/// use at_parser_rs::client::{Failure, Retry};
+///
+/// const WAKE_UP: Retry = Retry::new(3).with_delay(Duration::from_millis(100)).with_backoff(2);
+///
+/// let ms = Cell::new(0);
+/// let clock = || {
+///     ms.set(ms.get() + 1);
+///     Duration::from_millis(ms.get())
+/// };
+/// let mut answers = [Err(Failure::Timeout), Err(Failure::Timeout), Ok("OK")].into_iter();
+/// assert_eq!(WAKE_UP.run(&clock, |_| answers.next().unwrap()), Ok("OK"));
+/// assert_eq!(WAKE_UP.delay(2), Duration::from_millis(200));
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct Retry {
+    /// Maximum number of attempts, at least one
+    attempts: u8,
+    /// Wait before the second attempt
+    delay: Duration,
+    /// Factor of the wait before each following attempt
+    backoff: u32,
+    /// Whether a failure is worth another attempt
+    retryable: fn(&Failure) -> bool,
+}
+
+impl Retry {
+    /// Up to `attempts` attempts of the [`transient`](Retry::transient)
+    /// failures, without waiting.
+    pub const fn new(attempts: u8) -> Self {
+        Self { attempts, delay: Duration::ZERO, backoff: 1, retryable: Self::transient }
+    }
+
+    /// Wait `delay` before the second attempt.
+    pub const fn with_delay(mut self, delay: Duration) -> Self {
+        self.delay = delay;
+        self
+    }
+
+    /// Multiply the wait by `backoff` before each following attempt.
+    pub const fn with_backoff(mut self, backoff: u32) -> Self {
+        self.backoff = backoff;
+        self
+    }
+
+    /// Retry the failures selected by `retryable`.
+    pub const fn with_retryable(mut self, retryable: fn(&Failure) -> bool) -> Self {
+        self.retryable = retryable;
+        self
+    }
+
+    /// Failures a flaky modem may not repeat: no final result code, a plain
+    /// `ERROR` and `+CME ERROR: 14` (SIM busy).
+    pub fn transient(failure: &Failure) -> bool {
+        matches!(failure, Failure::Timeout | Failure::Code(ResultCode::Error) | Failure::Cme(Some(14)))
+    }
+
+    /// Whether `failure` is worth another attempt.
+    pub fn is_retryable(&self, failure: &Failure) -> bool {
+        (self.retryable)(failure)
+    }
+
+    /// Wait after the failed attempt `attempt`, counted from one.
+    pub fn delay(&self, attempt: u8) -> Duration {
+        let factor = self.backoff.saturating_pow(u32::from(attempt.saturating_sub(1)));
+        self.delay.saturating_mul(factor)
+    }
+
+    /// Call `attempt` with the attempt number, from one, until it succeeds,
+    /// fails for good or runs out of attempts, sleeping on `clock` in
+    /// between.
+    ///
+    /// # Errors
+    ///
+    /// The failure of the last attempt.
+    pub fn run<T, C: Clock + ?Sized>(&self, clock: &C, mut attempt: impl FnMut(u8) -> Result<T, Failure>) -> Result<T, Failure> {
+        let mut number = 1;
+        loop {
+            match attempt(number) {
+                Err(failure) if number < self.attempts && self.is_retryable(&failure) => {
+                    clock.sleep(self.delay(number));
+                    number += 1;
+                }
+                result => return result,
+            }
+        }
+    }
+
+    /// [`round_trip`] of `command`, written by `write` on each attempt, the
+    /// response being polled from `poll` for up to `timeout` as with a
+    /// [`deadline`].
+    ///
+    /// # Errors
+    ///
+    /// As [`round_trip`], for the last attempt.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use core::time::Duration;
+    /// use at_parser_rs::client::{ResponseParser, Retry};
+    ///
+    /// let mut rx = b"\r\nERROR\r\nAT\r\r\nOK\r\n".iter().copied();
+    /// let clock = || Duration::ZERO;
+    /// let timeout = Duration::from_millis(300);
+    /// let mut modem: ResponseParser<64> = ResponseParser::new();
+    /// let mut payload = [0u8; 16];
+    /// let mut writes = 0;
+    ///
+    /// let ok = Retry::new(3).round_trip(&clock, &mut modem, "AT", timeout, &mut payload, |_| writes += 1, || rx.next(), |_, _| {});
+    /// assert!(ok.is_ok());
+    /// assert_eq!(writes, 2);
+    /// ```
+    #[allow(clippy::too_many_arguments)]
+    pub fn round_trip<'p, C: Clock + ?Sized, const LINE: usize>(
+        &self,
+        clock: &C,
+        parser: &mut ResponseParser<LINE>,
+        command: &str,
+        timeout: Duration,
+        payload: &'p mut [u8],
+        mut write: impl FnMut(&str),
+        mut poll: impl FnMut() -> Option<u8>,
+        mut urc: impl FnMut(&str, Args<'_>),
+    ) -> Result<Payload<'p>, Failure> {
+        let len = self.run(clock, |_| {
+            parser.clear();
+            write(command);
+            let read = deadline(clock, timeout, &mut poll);
+            round_trip(parser, command, payload, read, &mut urc).map(|lines| lines.as_str().len())
+        })?;
+        Ok(Payload::new(&payload[..len]))
+    }
+}
+
+impl Default for Retry {
+    /// Three attempts, 100 ms apart.
+    fn default() -> Self {
+        Retry::new(3).with_delay(Duration::from_millis(100))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use core::cell::Cell;
+    use core::time::Duration;
+
+    use super::Retry;
+    use crate::client::Failure;
+    use crate::response::ResultCode;
+
+    #[test]
+    fn only_retryable_failures_are_attempted_again() {
+        let ticks = Cell::new(0u64);
+        let clock = || {
+            ticks.set(ticks.get() + 1);
+            Duration::from_millis(ticks.get())
+        };
+        let retry = Retry::new(3).with_delay(Duration::from_millis(10)).with_backoff(3);
+
+        let mut attempts = 0;
+        let result: Result<(), _> = retry.run(&clock, |number| {
+            attempts = number;
+            Err(Failure::Timeout)
+        });
+        assert_eq!((result, attempts), (Err(Failure::Timeout), 3));
+        assert!(ticks.get() >= 10 + 30);
+
+        let result: Result<(), _> = retry.run(&clock, |number| {
+            attempts = number;
+            Err(Failure::Cme(Some(10)))
+        });
+        assert_eq!((result, attempts), (Err(Failure::Cme(Some(10))), 1));
+
+        let stubborn = retry.with_retryable(|failure| matches!(failure, Failure::Code(ResultCode::NoCarrier)));
+        assert!(stubborn.is_retryable(&Failure::Code(ResultCode::NoCarrier)));
+        assert!(!stubborn.is_retryable(&Failure::Timeout));
+        assert_eq!(Retry::new(0).run(&clock, |number| Err::<(), _>(Failure::Cms(Some(number.into())))), Err(Failure::Cms(Some(1))));
+    }
+}
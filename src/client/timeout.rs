@@ -0,0 +1,181 @@
+/***************************************************************************
+ *
+ * AT Command Parser
+ * Copyright (C) 2026 Antonio Salsi <passy.linux@zresa.it>
+ *
+ * This library is free software; you can redistribute it and/or
+ * modify it under the terms of the GNU Lesser General Public
+ * License as published by the Free Software Foundation; either
+ * version 2.1 of the License, or (at your option) any later version.
+ *
+ * This library is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the GNU
+ * Lesser General Public License for more details.
+ *
+ * You should have received a copy of the GNU Lesser General Public
+ * License along with this library; if not, see <https://www.gnu.org/licenses/>.
+ *
+ ***************************************************************************/
+
+ 
+//! Command timeouts
+
+use core::time::Duration;
+
+use super::response::command_name;
+
+/// Monotonic time source of the client layer
+///
+/// Implemented for closures returning the time elapsed since any fixed
+/// instant, such as a tick counter.
+pub trait Clock {
+    /// Time elapsed since a fixed instant.
+    fn now(&self) -> Duration;
+
+    /// Wait for `duration`, spinning on [`now`](Clock::now) unless
+    /// overridden, e.g. with the delay of an RTOS task.
+    fn sleep(&self, duration: Duration) {
+        let start = self.now();
+        while self.now().saturating_sub(start) < duration {}
+    }
+}
+
+impl<F: Fn() -> Duration> Clock for F {
+    fn now(&self) -> Duration {
+        self()
+    }
+}
+
+/// Maximum response time of each command
+///
+/// Commands are looked up by name, ignoring case and the form
+/// (`AT+COPS?` and `AT+COPS=?` are both `+COPS`); basic commands and
+/// unlisted ones get the default.
+///
+/// # Example
+///
+/// ```rust
+/// use core::time::Duration;
+/// use at_parser_rs::client::Timeouts;
+///
+/// assert_eq!(Timeouts::STANDARD.get("AT+COPS=?"), Duration::from_secs(180));
+/// assert_eq!(Timeouts::STANDARD.get("AT+CSQ"), Duration::from_secs(1));
+///
+/// const MODEM: Timeouts = Timeouts::new(&[("+QIOPEN", Duration::from_secs(150))], Duration::from_millis(300));
+/// assert_eq!(MODEM.get("at+qiopen=1,0"), Duration::from_secs(150));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Timeouts {
+    /// Command names (`+COPS`) with their timeouts
+    commands: &'static [(&'static str, Duration)],
+    /// Timeout of the other commands
+    default: Duration,
+}
+
+impl Timeouts {
+    /// Usual maximum response times of 3GPP TS 27.005 and 27.007 commands,
+    /// one second for the others.
+    pub const STANDARD: Timeouts = Timeouts::new(
+        &[
+            ("+CFUN", Duration::from_secs(15)),
+            ("+CGACT", Duration::from_secs(150)),
+            ("+CGATT", Duration::from_secs(140)),
+            ("+CLCK", Duration::from_secs(5)),
+            ("+CMGS", Duration::from_secs(120)),
+            ("+COPS", Duration::from_secs(180)),
+            ("+CPIN", Duration::from_secs(5)),
+            ("+CPWD", Duration::from_secs(5)),
+        ],
+        Duration::from_secs(1),
+    );
+
+    /// Timeouts of the listed `commands`, `default` for the others.
+    pub const fn new(commands: &'static [(&'static str, Duration)], default: Duration) -> Self {
+        Self { commands, default }
+    }
+
+    /// Timeout of the command line `command`.
+    pub fn get(&self, command: &str) -> Duration {
+        let name = command_name(command.trim());
+        self.commands
+            .iter()
+            .find(|(listed, _)| !name.is_empty() && listed.eq_ignore_ascii_case(name))
+            .map_or(self.default, |&(_, timeout)| timeout)
+    }
+
+    /// Timeout of the other commands.
+    pub fn default_timeout(&self) -> Duration {
+        self.default
+    }
+}
+
+impl Default for Timeouts {
+    fn default() -> Self {
+        Timeouts::STANDARD
+    }
+}
+
+/// Bound `poll` by a deadline `timeout` from now.
+///
+/// `poll` returns the next received byte, or `None` when none is available
+/// yet; it is called again until the deadline, after which the returned
+/// reader gives `None`, as [`round_trip`](super::round_trip) expects to
+/// report a [`Failure::Timeout`](super::Failure::Timeout).
+///
+/// # Example
+///
+/// ```rust
+/// use core::cell::Cell;
+/// use core::time::Duration;
+/// use at_parser_rs::client::{Failure, ResponseParser, Timeouts, deadline, round_trip};
+///
+/// let ticks = Cell::new(0);
+/// let clock = || Duration::from_millis(ticks.get());
+/// let silent_modem = || {
+///     ticks.set(ticks.get() + 10);
+///     None
+/// };
+///
+/// let mut modem: ResponseParser<64> = ResponseParser::new();
+/// let mut payload = [0u8; 64];
+/// let read = deadline(&clock, Timeouts::STANDARD.get("AT+CSQ"), silent_modem);
+/// assert_eq!(round_trip(&mut modem, "AT+CSQ", &mut payload, read, |_, _| {}), Err(Failure::Timeout));
+/// assert_eq!(ticks.get(), 1000);
+/// ```
+pub fn deadline<C: Clock + ?Sized>(clock: &C, timeout: Duration, mut poll: impl FnMut() -> Option<u8>) -> impl FnMut() -> Option<u8> {
+    let start = clock.now();
+    move || {
+        while clock.now().saturating_sub(start) < timeout {
+            if let Some(byte) = poll() {
+                return Some(byte);
+            }
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use core::cell::Cell;
+    use core::time::Duration;
+
+    use super::{Timeouts, deadline};
+
+    #[test]
+    fn commands_are_bounded_by_their_timeout() {
+        assert_eq!(Timeouts::STANDARD.get("AT+cgatt=1"), Duration::from_secs(140));
+        assert_eq!(Timeouts::STANDARD.get("ATD+3912345;"), Duration::from_secs(1));
+
+        let ticks = Cell::new(0u64);
+        let clock = || Duration::from_millis(ticks.get());
+        let mut rx = b"OK".iter().copied();
+        let mut read = deadline(&clock, Duration::from_millis(50), || {
+            ticks.set(ticks.get() + 20);
+            rx.next()
+        });
+        assert_eq!((read(), read()), (Some(b'O'), Some(b'K')));
+        assert_eq!(read(), None);
+        assert_eq!(ticks.get(), 60);
+    }
+}
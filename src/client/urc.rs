@@ -0,0 +1,143 @@
+/***************************************************************************
+ *
+ * AT Command Parser
+ * Copyright (C) 2026 Antonio Salsi <passy.linux@zresa.it>
+ *
+ * This library is free software; you can redistribute it and/or
+ * modify it under the terms of the GNU Lesser General Public
+ * License as published by the Free Software Foundation; either
+ * version 2.1 of the License, or (at your option) any later version.
+ *
+ * This library is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the GNU
+ * Lesser General Public License for more details.
+ *
+ * You should have received a copy of the GNU Lesser General Public
+ * License along with this library; if not, see <https://www.gnu.org/licenses/>.
+ *
+ ***************************************************************************/
+
+ 
+//! Unsolicited result code routing
+
+use crate::Args;
+
+use super::{Response, ResponseParser};
+
+/// Callback receiving the fields of an unsolicited result code
+pub type UrcHandler<'a> = &'a mut dyn FnMut(Args<'_>);
+
+/// [`ResponseParser`] routing unsolicited result codes to subscribed
+/// callbacks
+///
+/// Subscriptions pair a URC name, with or without its colon (`"+CMTI:"`,
+/// `"RING"`), with the callback receiving its fields. Subscribed URCs are
+/// consumed by [`push`](UrcRouter::push); every other line, and in particular
+/// the response of the pending command, is returned to the caller.
+///
+/// # Example
+///
+/// ```rust
+/// use core::cell::Cell;
+/// use at_parser_rs::client::{Response, UrcRouter};
+///
+/// let (sms, rings) = (Cell::new(None), Cell::new(0));
+/// let mut on_sms = |args: at_parser_rs::Args| sms.set(args.get_u16(1).ok());
+/// let mut on_ring = |_: at_parser_rs::Args| rings.set(rings.get() + 1);
+/// let urcs: &mut [(&str, at_parser_rs::client::UrcHandler)] = &mut [("+CMTI:", &mut on_sms), ("RING", &mut on_ring)];
+///
+/// let mut modem: UrcRouter<64> = UrcRouter::new(urcs);
+/// let mut ok = false;
+/// for &byte in b"AT\r\r\n+CMTI: \"SM\",3\r\n\r\nRING\r\n\r\nOK\r\n" {
+///     if let Some(Response::Final(result)) = modem.push(byte) {
+///         ok = result.is_ok();
+///     }
+/// }
+/// assert!(ok);
+/// assert_eq!((sms.get(), rings.get()), (Some(3), 1));
+/// ```
+pub struct UrcRouter<'a, const SIZE: usize> {
+    /// Line classifier
+    parser: ResponseParser<SIZE>,
+    /// Subscribed URC names with their callbacks
+    urcs: &'a mut [(&'static str, UrcHandler<'a>)],
+}
+
+impl<'a, const SIZE: usize> UrcRouter<'a, SIZE> {
+    /// Create a router delivering the URCs named in `urcs` to their
+    /// callbacks.
+    pub fn new(urcs: &'a mut [(&'static str, UrcHandler<'a>)]) -> Self {
+        Self { parser: ResponseParser::new(), urcs }
+    }
+
+    /// Replace the subscriptions.
+    pub fn set_urcs(&mut self, urcs: &'a mut [(&'static str, UrcHandler<'a>)]) {
+        self.urcs = urcs;
+    }
+
+    /// Mark the command line just sent as pending, see
+    /// [`ResponseParser::send`].
+    pub fn send(&mut self, command: &str) {
+        self.parser.send(command);
+    }
+
+    /// Whether a command waits for its final result code.
+    pub fn is_pending(&self) -> bool {
+        self.parser.is_pending()
+    }
+
+    /// Give up the pending command, see [`ResponseParser::cancel`].
+    pub fn cancel(&mut self) {
+        self.parser.cancel();
+    }
+
+    /// Append `byte`, returning the line it completes unless it is a
+    /// subscribed URC, handed to its callback instead.
+    pub fn push(&mut self, byte: u8) -> Option<Response<'_>> {
+        let response = self.parser.push(byte)?;
+        if let Response::Urc(name, args) = response
+            && let Some((_, handler)) = self.urcs.iter_mut().find(|(urc, _)| urc.trim_end_matches(':').trim() == name)
+        {
+            handler(args);
+            return None;
+        }
+        Some(response)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::format;
+    use alloc::string::String;
+    use alloc::vec::Vec;
+
+    use super::{UrcHandler, UrcRouter};
+    use crate::Args;
+    use crate::client::{Final, Response};
+    use crate::response::ResultCode;
+
+    #[test]
+    fn subscribed_urcs_are_routed_and_responses_returned() {
+        let mut received = Vec::new();
+        let mut on_urc = |args: Args| received.push(String::from(args.raw()));
+        let urcs: &mut [(&str, UrcHandler)] = &mut [("+QIURC:", &mut on_urc)];
+        let mut modem: UrcRouter<64> = UrcRouter::new(urcs);
+
+        modem.send("AT+QISEND=0,5");
+        let mut returned = Vec::new();
+        for &byte in b"\r\n+QIURC: \"recv\",0\r\n\r\n+CREG: 1\r\n\r\nSEND OK\r\n\r\nOK\r\n" {
+            if let Some(response) = modem.push(byte) {
+                returned.push(format!("{response:?}"));
+            }
+        }
+        assert!(!modem.is_pending());
+
+        assert_eq!(received, ["\"recv\",0"]);
+        assert_eq!(returned, [
+            format!("{:?}", Response::Urc("+CREG", Args::new("1"))),
+            format!("{:?}", Response::Info(Args::new("SEND OK"))),
+            format!("{:?}", Response::Final(Final::Code(ResultCode::Ok))),
+        ]);
+    }
+}
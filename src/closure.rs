@@ -0,0 +1,290 @@
+/***************************************************************************
+ *
+ * AT Command Parser
+ * Copyright (C) 2026 Antonio Salsi <passy.linux@zresa.it>
+ *
+ * This library is free software; you can redistribute it and/or
+ * modify it under the terms of the GNU Lesser General Public
+ * License as published by the Free Software Foundation; either
+ * version 2.1 of the License, or (at your option) any later version.
+ *
+ * This library is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the GNU
+ * Lesser General Public License for more details.
+ *
+ * You should have received a copy of the GNU Lesser General Public
+ * License along with this library; if not, see <https://www.gnu.org/licenses/>.
+ *
+ ***************************************************************************/
+
+ 
+//! Closure-based handlers
+//!
+//! Small commands do not need a dedicated type: [`Closures`] implements
+//! [`AtContext`] by calling one closure per form, and
+//! [`at_commands!`](crate::at_commands) builds a whole command table from
+//! them. A closure returns the [`Reply`] of the command; the registered
+//! response prefix is added by the handler.
+
+use crate::context::AtContext;
+use crate::{Args, AtError, AtResponse, AtResult};
+
+/// Result of a closure handling one form of a command
+pub type Reply<const SIZE: usize> = Result<AtResponse<'static, SIZE>, AtError<'static>>;
+
+/// Placeholder for a form without closure, answered with
+/// [`AtError::NotSupported`]
+pub struct Unset;
+
+/// Closure called for the execution, read or test form
+pub trait Action<const SIZE: usize> {
+    /// Run the closure, `None` when the form has none.
+    fn call(&mut self) -> Option<Reply<SIZE>>;
+}
+
+impl<const SIZE: usize> Action<SIZE> for Unset {
+    fn call(&mut self) -> Option<Reply<SIZE>> {
+        None
+    }
+}
+
+impl<const SIZE: usize, F: FnMut() -> Reply<SIZE>> Action<SIZE> for F {
+    fn call(&mut self) -> Option<Reply<SIZE>> {
+        Some(self())
+    }
+}
+
+/// Closure called for the set form, with the command arguments
+pub trait SetAction<const SIZE: usize> {
+    /// Run the closure, `None` when the form has none.
+    fn call(&mut self, args: Args) -> Option<Reply<SIZE>>;
+}
+
+impl<const SIZE: usize> SetAction<SIZE> for Unset {
+    fn call(&mut self, _args: Args) -> Option<Reply<SIZE>> {
+        None
+    }
+}
+
+impl<const SIZE: usize, F: FnMut(Args) -> Reply<SIZE>> SetAction<SIZE> for F {
+    fn call(&mut self, args: Args) -> Option<Reply<SIZE>> {
+        Some(self(args))
+    }
+}
+
+/// Handler calling one closure per form
+///
+/// Built with [`Closures::new`] and one `on_*` call per handled form:
+///
+/// ```rust
+/// use at_parser_rs::closure::Closures;
+/// use at_parser_rs::context::AtContext;
+/// use at_parser_rs::{AtError, AtResponse};
+///
+/// const SIZE: usize = 32;
+///
+/// let mut on = false;
+/// let mut led = Closures::<SIZE>::new()
+///     .on_set(|args| {
+///         on = args.get_bool(0).map_err(|_| AtError::InvalidArgs)?;
+///         Ok(AtResponse::Ok)
+///     });
+/// assert!(led.exec("+LED: ").is_err());
+/// ```
+pub struct Closures<const SIZE: usize, X = Unset, Q = Unset, T = Unset, S = Unset> {
+    exec: X,
+    query: Q,
+    test: T,
+    set: S,
+}
+
+impl<const SIZE: usize> Closures<SIZE> {
+    /// Handler answering every form with [`AtError::NotSupported`].
+    pub const fn new() -> Self {
+        Self { exec: Unset, query: Unset, test: Unset, set: Unset }
+    }
+}
+
+impl<const SIZE: usize> Default for Closures<SIZE> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const SIZE: usize, X, Q, T, S> Closures<SIZE, X, Q, T, S> {
+    /// Handle the execution form (`AT+CMD`) with `f`.
+    pub fn on_exec<F: FnMut() -> Reply<SIZE>>(self, f: F) -> Closures<SIZE, F, Q, T, S> {
+        Closures { exec: f, query: self.query, test: self.test, set: self.set }
+    }
+
+    /// Handle the read form (`AT+CMD?`) with `f`.
+    pub fn on_query<F: FnMut() -> Reply<SIZE>>(self, f: F) -> Closures<SIZE, X, F, T, S> {
+        Closures { exec: self.exec, query: f, test: self.test, set: self.set }
+    }
+
+    /// Handle the test form (`AT+CMD=?`) with `f`.
+    pub fn on_test<F: FnMut() -> Reply<SIZE>>(self, f: F) -> Closures<SIZE, X, Q, F, S> {
+        Closures { exec: self.exec, query: self.query, test: f, set: self.set }
+    }
+
+    /// Handle the set form (`AT+CMD=<args>`) with `f`.
+    pub fn on_set<F: FnMut(Args) -> Reply<SIZE>>(self, f: F) -> Closures<SIZE, X, Q, T, F> {
+        Closures { exec: self.exec, query: self.query, test: self.test, set: f }
+    }
+}
+
+/// Attach the response prefix to the reply of a form, if it has a closure.
+fn respond<const SIZE: usize>(at_response: &'static str, reply: Option<Reply<SIZE>>) -> AtResult<'static, SIZE> {
+    match reply {
+        Some(Ok(response)) => Ok((at_response, response)),
+        Some(Err(error)) => Err((at_response, error)),
+        None => Err((at_response, AtError::NotSupported)),
+    }
+}
+
+impl<const SIZE: usize, X, Q, T, S> AtContext<SIZE> for Closures<SIZE, X, Q, T, S>
+where
+    X: Action<SIZE>,
+    Q: Action<SIZE>,
+    T: Action<SIZE>,
+    S: SetAction<SIZE> {
+
+    fn exec(&mut self, at_response: &'static str) -> AtResult<'_, SIZE> {
+        respond(at_response, self.exec.call())
+    }
+
+    fn query(&mut self, at_response: &'static str) -> AtResult<'_, SIZE> {
+        respond(at_response, self.query.call())
+    }
+
+    fn test(&mut self, at_response: &'static str) -> AtResult<'_, SIZE> {
+        respond(at_response, self.test.call())
+    }
+
+    fn set(&mut self, at_response: &'static str, args: Args) -> AtResult<'_, SIZE> {
+        respond(at_response, self.set.call(args))
+    }
+}
+
+/// Build a command table from closures, one per handled form.
+///
+/// Each entry reads `"+CMD" => { form: closure, ... }`, registering `AT+CMD`
+/// with the `+CMD: ` response prefix; `("AT+CMD", "prefix") => { ... }` sets
+/// both explicitly. The forms are `exec: || ...`, `query: || ...`,
+/// `test: || ...` and `set: |args| ...`, each returning a
+/// [`Reply`](crate::closure::Reply); the others answer
+/// [`AtError::NotSupported`](crate::AtError::NotSupported). The table is used
+/// like the one of [`at_modules!`](crate::at_modules).
+///
+/// # Example
+///
+/// ```rust
+/// use core::cell::Cell;
+/// use at_parser_rs::at_commands;
+/// use at_parser_rs::parser::DynAtParser;
+/// use at_parser_rs::{AtError, AtResponse};
+/// # use osal_rs::utils::Bytes;
+///
+/// const SIZE: usize = 32;
+///
+/// let led = Cell::new(false);
+/// let commands = at_commands! {
+///     SIZE;
+///     "+LED" => {
+///         set: |args| {
+///             led.set(args.get_bool(0).map_err(|_| AtError::InvalidArgs)?);
+///             Ok(AtResponse::Ok)
+///         },
+///         query: || Ok(AtResponse::InfoText(Bytes::from_str(if led.get() { "1" } else { "0" }))),
+///     },
+///     ("AT+RST", "") => { exec: || Ok(AtResponse::Ok) },
+/// };
+///
+/// let mut parser: DynAtParser<SIZE> = DynAtParser::new();
+/// parser.set_commands(commands);
+/// assert!(parser.execute("AT+LED=1").is_ok());
+/// assert!(led.get());
+/// assert!(parser.execute("AT+LED=?").is_err());
+/// ```
+#[macro_export]
+macro_rules! at_commands {
+    (@entry $size:expr; $command:expr, $at_resp:expr; $($forms:tt)*) => {
+        (
+            $command,
+            $at_resp,
+            &mut $crate::at_commands!(@forms $crate::closure::Closures::<{ $size }>::new(); $($forms)*)
+                as &mut dyn $crate::context::AtContext<{ $size }>,
+        )
+    };
+    (@forms $handler:expr; exec: $closure:expr $(, $($rest:tt)*)?) => {
+        $crate::at_commands!(@forms $handler.on_exec($closure); $($($rest)*)?)
+    };
+    (@forms $handler:expr; query: $closure:expr $(, $($rest:tt)*)?) => {
+        $crate::at_commands!(@forms $handler.on_query($closure); $($($rest)*)?)
+    };
+    (@forms $handler:expr; test: $closure:expr $(, $($rest:tt)*)?) => {
+        $crate::at_commands!(@forms $handler.on_test($closure); $($($rest)*)?)
+    };
+    (@forms $handler:expr; set: $closure:expr $(, $($rest:tt)*)?) => {
+        $crate::at_commands!(@forms $handler.on_set($closure); $($($rest)*)?)
+    };
+    (@forms $handler:expr;) => {
+        $handler
+    };
+    (@table $size:expr; [$($done:expr,)*] $name:literal => { $($forms:tt)* } $(, $($rest:tt)*)?) => {
+        $crate::at_commands!(@table $size; [$($done,)* $crate::at_commands!(@entry $size; ::core::concat!("AT", $name), ::core::concat!($name, ": "); $($forms)*),] $($($rest)*)?)
+    };
+    (@table $size:expr; [$($done:expr,)*] ($command:expr, $at_resp:expr) => { $($forms:tt)* } $(, $($rest:tt)*)?) => {
+        $crate::at_commands!(@table $size; [$($done,)* $crate::at_commands!(@entry $size; $command, $at_resp; $($forms)*),] $($($rest)*)?)
+    };
+    (@table $size:expr; [$($done:expr,)*]) => {
+        &mut [$($done),*]
+    };
+    ($size:expr; $($entries:tt)*) => {
+        $crate::at_commands!(@table $size; [] $($entries)*)
+    };
+}
+
+#[cfg(test)]
+// Some fixtures serve only the tests left out of the reduced builds
+#[cfg_attr(feature = "exec-set-only", allow(unused_imports, dead_code))]
+mod tests {
+    use core::cell::Cell;
+
+    use crate::parser::DynAtParser;
+    use crate::{AtError, AtResponse};
+
+    const SIZE: usize = 32;
+
+    #[cfg(not(feature = "exec-set-only"))]
+    #[test]
+    fn closures_handle_their_forms() {
+        let (level, resets) = (Cell::new(0u8), Cell::new(0));
+        let commands = at_commands! {
+            SIZE;
+            "+LVL" => {
+                query: || Ok(crate::at_response!(SIZE, ""; level.get()).1),
+                set: |args| {
+                    level.set(args.get_u8(0).map_err(|_| AtError::InvalidArgs)?);
+                    Ok(AtResponse::Ok)
+                },
+            },
+            ("ATZ", "") => {
+                exec: || {
+                    resets.set(resets.get() + 1);
+                    Ok(AtResponse::Ok)
+                },
+            },
+        };
+        let mut parser: DynAtParser<SIZE> = DynAtParser::new();
+        parser.set_commands(commands);
+
+        assert!(parser.execute("AT+LVL=4").is_ok());
+        assert!(matches!(parser.execute("AT+LVL?"), Ok(("+LVL: ", AtResponse::InfoText(text))) if alloc::format!("{text}") == "4"));
+        assert!(matches!(parser.execute("AT+LVL=x"), Err(("+LVL: ", AtError::InvalidArgs))));
+        assert!(matches!(parser.execute("AT+LVL"), Err((_, AtError::NotSupported))));
+        assert!(parser.execute("ATZ").is_ok());
+        assert_eq!((level.get(), resets.get()), (4, 1));
+    }
+}
@@ -0,0 +1,156 @@
+/***************************************************************************
+ *
+ * AT Command Parser
+ * Copyright (C) 2026 Antonio Salsi <passy.linux@zresa.it>
+ *
+ * This library is free software; you can redistribute it and/or
+ * modify it under the terms of the GNU Lesser General Public
+ * License as published by the Free Software Foundation; either
+ * version 2.1 of the License, or (at your option) any later version.
+ *
+ * This library is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the GNU
+ * Lesser General Public License for more details.
+ *
+ * You should have received a copy of the GNU Lesser General Public
+ * License along with this library; if not, see <https://www.gnu.org/licenses/>.
+ *
+ ***************************************************************************/
+
+ 
+//! Mobile equipment error codes (3GPP TS 27.007, `+CME ERROR`)
+//!
+//! Handlers report them with [`AtError::Cme`](crate::AtError::Cme). The
+//! response framing layer renders them according to the `AT+CMEE` mode:
+//!
+//! | `AT+CMEE` | Final result code |
+//! |---|---|
+//! | `0` (default) | `ERROR` |
+//! | `1` | `+CME ERROR: 10` |
+//! | `2` | `+CME ERROR: SIM not inserted` |
+
+/// Reporting mode selected with `AT+CMEE=<n>`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CmeMode {
+    /// `+CME ERROR` disabled, plain `ERROR` is used (`0`)
+    Disabled,
+    /// Numeric error codes (`1`)
+    Numeric,
+    /// Verbose error descriptions (`2`)
+    Verbose,
+}
+
+impl CmeMode {
+    /// Mode selected by the `AT+CMEE` parameter value.
+    pub const fn from_value(value: u8) -> Option<Self> {
+        match value {
+            0 => Some(CmeMode::Disabled),
+            1 => Some(CmeMode::Numeric),
+            2 => Some(CmeMode::Verbose),
+            _ => None,
+        }
+    }
+
+    /// `AT+CMEE` parameter value of the mode.
+    pub const fn value(self) -> u8 {
+        match self {
+            CmeMode::Disabled => 0,
+            CmeMode::Numeric => 1,
+            CmeMode::Verbose => 2,
+        }
+    }
+}
+
+/// Phone failure
+pub const PHONE_FAILURE: u16 = 0;
+/// No connection to phone
+pub const NO_CONNECTION: u16 = 1;
+/// Operation not allowed
+pub const OPERATION_NOT_ALLOWED: u16 = 3;
+/// Operation not supported
+pub const OPERATION_NOT_SUPPORTED: u16 = 4;
+/// SIM not inserted
+pub const SIM_NOT_INSERTED: u16 = 10;
+/// SIM PIN required
+pub const SIM_PIN_REQUIRED: u16 = 11;
+/// SIM PUK required
+pub const SIM_PUK_REQUIRED: u16 = 12;
+/// SIM failure
+pub const SIM_FAILURE: u16 = 13;
+/// SIM busy
+pub const SIM_BUSY: u16 = 14;
+/// SIM wrong
+pub const SIM_WRONG: u16 = 15;
+/// Incorrect password
+pub const INCORRECT_PASSWORD: u16 = 16;
+/// Memory full
+pub const MEMORY_FULL: u16 = 20;
+/// Invalid index
+pub const INVALID_INDEX: u16 = 21;
+/// Not found
+pub const NOT_FOUND: u16 = 22;
+/// Memory failure
+pub const MEMORY_FAILURE: u16 = 23;
+/// Text string too long
+pub const TEXT_TOO_LONG: u16 = 24;
+/// Invalid characters in text string
+pub const INVALID_TEXT_CHARACTERS: u16 = 25;
+/// Dial string too long
+pub const DIAL_STRING_TOO_LONG: u16 = 26;
+/// Invalid characters in dial string
+pub const INVALID_DIAL_CHARACTERS: u16 = 27;
+/// No network service
+pub const NO_NETWORK_SERVICE: u16 = 30;
+/// Network timeout
+pub const NETWORK_TIMEOUT: u16 = 31;
+/// Network not allowed - emergency calls only
+pub const EMERGENCY_CALLS_ONLY: u16 = 32;
+/// Incorrect parameters
+pub const INCORRECT_PARAMETERS: u16 = 50;
+/// Unknown
+pub const UNKNOWN: u16 = 100;
+
+/// Verbose description of a `+CME ERROR` code, as listed by 27.007.
+///
+/// Returns `None` for codes without a standard description; the framing
+/// layer then falls back to the number.
+///
+/// # Example
+///
+/// ```rust
+/// use at_parser_rs::cme;
+///
+/// assert_eq!(cme::text(cme::SIM_NOT_INSERTED), Some("SIM not inserted"));
+/// assert_eq!(cme::text(999), None);
+/// ```
+pub const fn text(code: u16) -> Option<&'static str> {
+    let text = match code {
+        PHONE_FAILURE => "phone failure",
+        NO_CONNECTION => "no connection to phone",
+        OPERATION_NOT_ALLOWED => "operation not allowed",
+        OPERATION_NOT_SUPPORTED => "operation not supported",
+        SIM_NOT_INSERTED => "SIM not inserted",
+        SIM_PIN_REQUIRED => "SIM PIN required",
+        SIM_PUK_REQUIRED => "SIM PUK required",
+        SIM_FAILURE => "SIM failure",
+        SIM_BUSY => "SIM busy",
+        SIM_WRONG => "SIM wrong",
+        INCORRECT_PASSWORD => "incorrect password",
+        MEMORY_FULL => "memory full",
+        INVALID_INDEX => "invalid index",
+        NOT_FOUND => "not found",
+        MEMORY_FAILURE => "memory failure",
+        TEXT_TOO_LONG => "text string too long",
+        INVALID_TEXT_CHARACTERS => "invalid characters in text string",
+        DIAL_STRING_TOO_LONG => "dial string too long",
+        INVALID_DIAL_CHARACTERS => "invalid characters in dial string",
+        NO_NETWORK_SERVICE => "no network service",
+        NETWORK_TIMEOUT => "network timeout",
+        EMERGENCY_CALLS_ONLY => "network not allowed - emergency calls only",
+        INCORRECT_PARAMETERS => "Incorrect parameters",
+        UNKNOWN => "unknown",
+        _ => return None,
+    };
+    Some(text)
+}
@@ -0,0 +1,114 @@
+/***************************************************************************
+ *
+ * AT Command Parser
+ * Copyright (C) 2026 Antonio Salsi <passy.linux@zresa.it>
+ *
+ * This library is free software; you can redistribute it and/or
+ * modify it under the terms of the GNU Lesser General Public
+ * License as published by the Free Software Foundation; either
+ * version 2.1 of the License, or (at your option) any later version.
+ *
+ * This library is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the GNU
+ * Lesser General Public License for more details.
+ *
+ * You should have received a copy of the GNU Lesser General Public
+ * License along with this library; if not, see <https://www.gnu.org/licenses/>.
+ *
+ ***************************************************************************/
+
+ 
+//! Message service error codes (3GPP TS 27.005, `+CMS ERROR`)
+//!
+//! SMS handlers report them with [`AtError::Cms`](crate::AtError::Cms). Unlike
+//! `+CME ERROR`, the final result code is `+CMS ERROR: <code>` whatever the
+//! `AT+CMEE` mode; with `AT+CMEE=2` the code is replaced by its description
+//! when one is known.
+
+/// ME failure
+pub const ME_FAILURE: u16 = 300;
+/// SMS service of ME reserved
+pub const SMS_SERVICE_RESERVED: u16 = 301;
+/// Operation not allowed
+pub const OPERATION_NOT_ALLOWED: u16 = 302;
+/// Operation not supported
+pub const OPERATION_NOT_SUPPORTED: u16 = 303;
+/// Invalid PDU mode parameter
+pub const INVALID_PDU_PARAMETER: u16 = 304;
+/// Invalid text mode parameter
+pub const INVALID_TEXT_PARAMETER: u16 = 305;
+/// SIM not inserted
+pub const SIM_NOT_INSERTED: u16 = 310;
+/// SIM PIN required
+pub const SIM_PIN_REQUIRED: u16 = 311;
+/// PH-SIM PIN required
+pub const PH_SIM_PIN_REQUIRED: u16 = 312;
+/// SIM failure
+pub const SIM_FAILURE: u16 = 313;
+/// SIM busy
+pub const SIM_BUSY: u16 = 314;
+/// SIM wrong
+pub const SIM_WRONG: u16 = 315;
+/// SIM PUK required
+pub const SIM_PUK_REQUIRED: u16 = 316;
+/// SIM PIN2 required
+pub const SIM_PIN2_REQUIRED: u16 = 317;
+/// SIM PUK2 required
+pub const SIM_PUK2_REQUIRED: u16 = 318;
+/// Memory failure
+pub const MEMORY_FAILURE: u16 = 320;
+/// Invalid memory index
+pub const INVALID_MEMORY_INDEX: u16 = 321;
+/// Memory full
+pub const MEMORY_FULL: u16 = 322;
+/// SMSC address unknown
+pub const SMSC_ADDRESS_UNKNOWN: u16 = 330;
+/// No network service
+pub const NO_NETWORK_SERVICE: u16 = 331;
+/// Network timeout
+pub const NETWORK_TIMEOUT: u16 = 332;
+/// No `+CNMA` acknowledgement expected
+pub const NO_CNMA_EXPECTED: u16 = 340;
+/// Unknown error
+pub const UNKNOWN: u16 = 500;
+
+/// Verbose description of a `+CMS ERROR` code, as listed by 27.005.
+///
+/// # Example
+///
+/// ```rust
+/// use at_parser_rs::cms;
+///
+/// assert_eq!(cms::text(cms::MEMORY_FULL), Some("memory full"));
+/// assert_eq!(cms::text(1), None);
+/// ```
+pub const fn text(code: u16) -> Option<&'static str> {
+    let text = match code {
+        ME_FAILURE => "ME failure",
+        SMS_SERVICE_RESERVED => "SMS service of ME reserved",
+        OPERATION_NOT_ALLOWED => "operation not allowed",
+        OPERATION_NOT_SUPPORTED => "operation not supported",
+        INVALID_PDU_PARAMETER => "invalid PDU mode parameter",
+        INVALID_TEXT_PARAMETER => "invalid text mode parameter",
+        SIM_NOT_INSERTED => "SIM not inserted",
+        SIM_PIN_REQUIRED => "SIM PIN required",
+        PH_SIM_PIN_REQUIRED => "PH-SIM PIN required",
+        SIM_FAILURE => "SIM failure",
+        SIM_BUSY => "SIM busy",
+        SIM_WRONG => "SIM wrong",
+        SIM_PUK_REQUIRED => "SIM PUK required",
+        SIM_PIN2_REQUIRED => "SIM PIN2 required",
+        SIM_PUK2_REQUIRED => "SIM PUK2 required",
+        MEMORY_FAILURE => "memory failure",
+        INVALID_MEMORY_INDEX => "invalid memory index",
+        MEMORY_FULL => "memory full",
+        SMSC_ADDRESS_UNKNOWN => "SMSC address unknown",
+        NO_NETWORK_SERVICE => "no network service",
+        NETWORK_TIMEOUT => "network timeout",
+        NO_CNMA_EXPECTED => "no +CNMA acknowledgement expected",
+        UNKNOWN => "unknown error",
+        _ => return None,
+    };
+    Some(text)
+}
@@ -0,0 +1,634 @@
+/***************************************************************************
+ *
+ * AT Command Parser
+ * Copyright (C) 2026 Antonio Salsi <passy.linux@zresa.it>
+ *
+ * This library is free software; you can redistribute it and/or
+ * modify it under the terms of the GNU Lesser General Public
+ * License as published by the Free Software Foundation; either
+ * version 2.1 of the License, or (at your option) any later version.
+ *
+ * This library is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the GNU
+ * Lesser General Public License for more details.
+ *
+ * You should have received a copy of the GNU Lesser General Public
+ * License along with this library; if not, see <https://www.gnu.org/licenses/>.
+ *
+ ***************************************************************************/
+
+ 
+//! 3GPP TS 27.010 multiplexer
+//!
+//! After `AT+CMUX=0` a modem stops parsing command lines on its UART and
+//! speaks the basic option of the 27.010 multiplexer protocol instead: the
+//! byte stream is cut into frames, each addressed to a data link connection
+//! (DLCI) that behaves as a serial port of its own. DLCI 0 is the control
+//! channel; the others usually carry a parser each, so that commands, URCs
+//! and data flow side by side over one UART.
+//!
+//! [`Cmux`] decodes the frames fed to it, answers the control frames and
+//! hands the information received on each open DLCI to its [`Channel`], an
+//! [`AtParser`] for instance. Each parser writes to a [`ChannelWriter`] of
+//! the shared [`Mux`], which wraps the output into frames of its DLCI:
+//!
+//! ```text
+//! UART rx ─► Cmux::feed ─┬─► DLCI 1 ─► AtParser ─► ChannelWriter(1) ─┐
+//!                        └─► DLCI 2 ─► AtParser ─► ChannelWriter(2) ─┴─► Mux ─► Link (UART tx)
+//! ```
+//!
+//! [`CmuxCommand`] handles `AT+CMUX` itself: once its `OK` is sent, feed the
+//! UART to a [`Cmux`] until [`is_open`](Cmux::is_open) turns `false`.
+//!
+//! Frames carry at most `N1` bytes of information, 31 by default; longer
+//! frames received are discarded.
+
+use core::cell::RefCell;
+use core::fmt;
+use core::sync::atomic::{AtomicBool, Ordering};
+
+use crate::context::AtContext;
+use crate::parser::AtParser;
+use crate::{Args, AtError, AtResponse, AtResult, UserError, at_response};
+
+/// Default maximum information length of a frame (N1)
+pub const DEFAULT_N1: usize = 31;
+
+/// Opening and closing flag of a frame
+const FLAG: u8 = 0xf9;
+/// Extension bit of the address, length and type fields
+const EA: u8 = 0x01;
+/// Command/response bit of the address and type fields
+const CR: u8 = 0x02;
+/// Poll/final bit of the control field
+const PF: u8 = 0x10;
+
+/// Set asynchronous balanced mode: open a DLCI
+const SABM: u8 = 0x2f;
+/// Unnumbered acknowledgement
+const UA: u8 = 0x63;
+/// Disconnected mode: the DLCI is not open
+const DM: u8 = 0x0f;
+/// Disconnect: close a DLCI
+const DISC: u8 = 0x43;
+/// Unnumbered information with header check
+const UIH: u8 = 0xef;
+/// Unnumbered information
+const UI: u8 = 0x03;
+
+/// Multiplexer close down message of the control channel
+const CLD: u8 = 0xc1;
+/// Modem status message of the control channel
+const MSC: u8 = 0xe1;
+/// Not supported command response of the control channel
+const NSC: u8 = 0x11;
+
+/// Value of the running FCS over a frame with a valid FCS
+const FCS_GOOD: u8 = 0xcf;
+
+/// Transmitter of the frames, usually the UART
+pub trait Link {
+    /// Send `bytes` as they are.
+    fn send(&mut self, bytes: &[u8]);
+}
+
+/// Receiver of the information sent on a DLCI
+pub trait Channel {
+    /// Information received on the DLCI.
+    fn receive(&mut self, bytes: &[u8]);
+}
+
+impl<T, const SIZE: usize, S, E> Channel for AtParser<'_, T, SIZE, S, E>
+where
+    T: AtContext<SIZE, S, E> + ?Sized,
+    E: UserError {
+
+    /// Feed the bytes to the parser; the responses go to its output, the
+    /// [`ChannelWriter`] of the DLCI with response framing enabled.
+    fn receive(&mut self, bytes: &[u8]) {
+        for &byte in bytes {
+            let _ = self.feed(byte);
+        }
+    }
+}
+
+/// Frame encoder, shared by the [`ChannelWriter`]s and the [`Cmux`] of a
+/// link.
+pub struct Mux<L, const N1: usize = DEFAULT_N1> {
+    /// Transmitter of the frames
+    link: RefCell<L>,
+}
+
+impl<L: Link, const N1: usize> Mux<L, N1> {
+    /// Create an encoder sending its frames to `link`.
+    pub const fn new(link: L) -> Self {
+        Self { link: RefCell::new(link) }
+    }
+
+    /// Writer sending what is written to it on `dlci`.
+    pub fn channel(&self, dlci: u8) -> ChannelWriter<'_, L, N1> {
+        ChannelWriter { mux: self, dlci }
+    }
+
+    /// Release the link.
+    pub fn into_link(self) -> L {
+        self.link.into_inner()
+    }
+
+    /// Send a frame of type `control` on `dlci`, `cr` being the
+    /// command/response bit of its address.
+    fn frame(&self, dlci: u8, cr: bool, control: u8, info: &[u8]) {
+        debug_assert!(info.len() <= N1, "information longer than N1");
+        let address = dlci << 2 | if cr { CR } else { 0 } | EA;
+        let len = info.len();
+        let mut header = [FLAG, address, control, (len << 1) as u8 | EA, 0];
+        let end = if len < 0x80 {
+            4
+        } else {
+            header[3] = (len << 1) as u8;
+            header[4] = (len >> 7) as u8;
+            5
+        };
+
+        let mut fcs = header[1..end].iter().fold(0xff, |fcs, &byte| crc(fcs, byte));
+        // UIH frames only check their header
+        if control & !PF != UIH {
+            fcs = info.iter().fold(fcs, |fcs, &byte| crc(fcs, byte));
+        }
+
+        let mut link = self.link.borrow_mut();
+        link.send(&header[..end]);
+        link.send(info);
+        link.send(&[0xff - fcs, FLAG]);
+    }
+}
+
+/// Output of a DLCI, sending everything written to it in UIH frames
+pub struct ChannelWriter<'m, L, const N1: usize = DEFAULT_N1> {
+    /// Encoder of the link
+    mux: &'m Mux<L, N1>,
+    /// DLCI of the channel
+    dlci: u8,
+}
+
+impl<L: Link, const N1: usize> ChannelWriter<'_, L, N1> {
+    /// Send raw bytes on the channel, e.g. PPP data, in frames of up to `N1`
+    /// bytes.
+    pub fn send(&mut self, bytes: &[u8]) {
+        for chunk in bytes.chunks(N1) {
+            self.mux.frame(self.dlci, false, UIH, chunk);
+        }
+    }
+}
+
+impl<L: Link, const N1: usize> fmt::Write for ChannelWriter<'_, L, N1> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        self.send(s.as_bytes());
+        Ok(())
+    }
+}
+
+/// Field of the frame being decoded
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Field {
+    /// Waiting for an opening flag
+    Flag,
+    Address,
+    Control,
+    Length,
+    /// Second byte of a length above 127
+    LengthHigh,
+    Info,
+    Fcs,
+    /// Waiting for the closing flag
+    Close,
+}
+
+/// Frame decoder
+struct Decoder<const N1: usize> {
+    /// Field of the next byte
+    field: Field,
+    /// Address field of the frame
+    address: u8,
+    /// Control field of the frame
+    control: u8,
+    /// Length of the information
+    len: usize,
+    /// Information received so far
+    info: [u8; N1],
+    /// Number of information bytes received
+    received: usize,
+    /// Running FCS
+    fcs: u8,
+}
+
+impl<const N1: usize> Decoder<N1> {
+    const fn new() -> Self {
+        Self { field: Field::Flag, address: 0, control: 0, len: 0, info: [0; N1], received: 0, fcs: 0xff }
+    }
+
+    /// DLCI of the frame.
+    fn dlci(&self) -> u8 {
+        self.address >> 2
+    }
+
+    /// Decode `byte`, returning `true` when it closes a valid frame.
+    fn feed(&mut self, byte: u8) -> bool {
+        match self.field {
+            Field::Flag if byte == FLAG => self.field = Field::Address,
+            Field::Flag => {}
+            // Repeated flags between frames
+            Field::Address if byte == FLAG => {}
+            Field::Address if byte & EA == 0 => self.field = Field::Flag,
+            Field::Address => {
+                self.address = byte;
+                self.fcs = crc(0xff, byte);
+                self.field = Field::Control;
+            }
+            Field::Control => {
+                self.control = byte;
+                self.fcs = crc(self.fcs, byte);
+                self.field = Field::Length;
+            }
+            Field::Length => {
+                self.fcs = crc(self.fcs, byte);
+                self.len = usize::from(byte >> 1);
+                if byte & EA == 0 {
+                    self.field = Field::LengthHigh;
+                } else {
+                    self.start_info();
+                }
+            }
+            Field::LengthHigh => {
+                self.fcs = crc(self.fcs, byte);
+                self.len |= usize::from(byte) << 7;
+                self.start_info();
+            }
+            Field::Info => {
+                self.info[self.received] = byte;
+                self.received += 1;
+                if self.control & !PF != UIH {
+                    self.fcs = crc(self.fcs, byte);
+                }
+                if self.received == self.len {
+                    self.field = Field::Fcs;
+                }
+            }
+            Field::Fcs => {
+                self.fcs = crc(self.fcs, byte);
+                self.field = Field::Close;
+            }
+            Field::Close if byte == FLAG => {
+                // The closing flag may open the next frame
+                self.field = Field::Address;
+                return self.fcs == FCS_GOOD;
+            }
+            Field::Close => self.field = Field::Flag,
+        }
+        false
+    }
+
+    /// Move on to the information of the frame, dropping frames longer than
+    /// N1.
+    fn start_info(&mut self) {
+        self.received = 0;
+        self.field = match self.len {
+            0 => Field::Fcs,
+            len if len > N1 => Field::Flag,
+            _ => Field::Info,
+        };
+    }
+}
+
+/// Demultiplexer serving the DLCIs `1..=CHANNELS` of a link
+///
+/// # Example
+///
+/// ```rust
+/// use at_parser_rs::cmux::{Cmux, Link, Mux};
+/// # use at_parser_rs::context::AtContext;
+/// # use at_parser_rs::parser::AtParser;
+/// # const SIZE: usize = 64;
+/// # struct MyHandler; impl AtContext<SIZE> for MyHandler {}
+///
+/// struct Uart;
+/// impl Link for Uart {
+///     fn send(&mut self, bytes: &[u8]) { /* write to the UART */ }
+/// }
+///
+/// let mux: Mux<Uart> = Mux::new(Uart);
+/// let mut commands = mux.channel(1);
+/// let mut parser: AtParser<MyHandler, SIZE> = AtParser::new();
+/// parser.set_output(&mut commands);
+/// parser.set_response_framing(true);
+///
+/// let mut cmux: Cmux<Uart, 2> = Cmux::new(&mux);
+/// cmux.set_channel(1, &mut parser);
+///
+/// // SABM on DLCI 0 opens the multiplexer
+/// cmux.feed_slice(&[0xf9, 0x03, 0x3f, 0x01, 0x1c, 0xf9]);
+/// assert!(cmux.is_channel_open(0));
+/// ```
+pub struct Cmux<'a, L, const CHANNELS: usize, const N1: usize = DEFAULT_N1> {
+    /// Encoder of the link
+    mux: &'a Mux<L, N1>,
+    /// Receivers of the DLCIs `1..=CHANNELS`
+    channels: [Option<&'a mut dyn Channel>; CHANNELS],
+    /// Whether the control channel is open
+    control: bool,
+    /// Whether each of the DLCIs `1..=CHANNELS` is open
+    open: [bool; CHANNELS],
+    /// Whether the multiplexer closed down
+    closed: bool,
+    /// Decoder of the received frames
+    decoder: Decoder<N1>,
+}
+
+impl<'a, L: Link, const CHANNELS: usize, const N1: usize> Cmux<'a, L, CHANNELS, N1> {
+    /// Create a demultiplexer answering through `mux`, with every DLCI
+    /// closed.
+    pub fn new(mux: &'a Mux<L, N1>) -> Self {
+        Self {
+            mux,
+            channels: [const { None }; CHANNELS],
+            control: false,
+            open: [false; CHANNELS],
+            closed: false,
+            decoder: Decoder::new(),
+        }
+    }
+
+    /// Hand the information received on `dlci` to `channel`.
+    ///
+    /// # Panics
+    ///
+    /// If `dlci` is not in `1..=CHANNELS`.
+    pub fn set_channel(&mut self, dlci: u8, channel: &'a mut dyn Channel) {
+        assert!((1..=CHANNELS).contains(&usize::from(dlci)), "no such DLCI");
+        self.channels[usize::from(dlci) - 1] = Some(channel);
+    }
+
+    /// Whether the multiplexer is still running; `false` once the DTE closed
+    /// it down, the UART going back to command lines.
+    pub fn is_open(&self) -> bool {
+        !self.closed
+    }
+
+    /// Whether `dlci` has been opened by the DTE.
+    pub fn is_channel_open(&self, dlci: u8) -> bool {
+        match usize::from(dlci) {
+            0 => self.control,
+            dlci => self.open.get(dlci - 1).copied().unwrap_or(false),
+        }
+    }
+
+    /// Feed a byte received from the DTE; ignored once closed.
+    pub fn feed(&mut self, byte: u8) {
+        if !self.closed && self.decoder.feed(byte) {
+            self.frame();
+        }
+    }
+
+    /// Feed a chunk of received bytes, up to the close down of the
+    /// multiplexer.
+    ///
+    /// # Returns
+    ///
+    /// The number of bytes consumed; the remainder belongs to the command
+    /// line parser.
+    pub fn feed_slice(&mut self, bytes: &[u8]) -> usize {
+        for (i, &byte) in bytes.iter().enumerate() {
+            self.feed(byte);
+            if self.closed {
+                return i + 1;
+            }
+        }
+        bytes.len()
+    }
+
+    /// Serve the frame just decoded.
+    fn frame(&mut self) {
+        let dlci = self.decoder.dlci();
+        let pf = self.decoder.control & PF;
+        let index = usize::from(dlci).checked_sub(1).filter(|&index| index < CHANNELS);
+        match (self.decoder.control & !PF, index) {
+            (SABM, _) if dlci == 0 => {
+                self.control = true;
+                self.mux.frame(0, true, UA | pf, &[]);
+            }
+            (SABM, Some(index)) if self.control => {
+                self.open[index] = true;
+                self.mux.frame(dlci, true, UA | pf, &[]);
+            }
+            (DISC, _) if dlci == 0 && self.control => {
+                self.mux.frame(0, true, UA | pf, &[]);
+                self.close();
+            }
+            (DISC, Some(index)) if self.open[index] => {
+                self.open[index] = false;
+                self.mux.frame(dlci, true, UA | pf, &[]);
+            }
+            (SABM | DISC, _) => self.mux.frame(dlci, true, DM | pf, &[]),
+            (UIH | UI, _) if dlci == 0 && self.control => self.control_message(),
+            (UIH | UI, Some(index)) if self.open[index] => {
+                if let Some(channel) = self.channels[index].as_deref_mut() {
+                    channel.receive(&self.decoder.info[..self.decoder.len]);
+                }
+            }
+            // Responses of the DTE, frames of closed DLCIs
+            _ => {}
+        }
+    }
+
+    /// Answer a message of the control channel.
+    fn control_message(&mut self) {
+        let info = &mut self.decoder.info[..self.decoder.len];
+        // Only commands are answered
+        let Some(kind) = info.first().copied().filter(|kind| kind & CR != 0) else {
+            return;
+        };
+
+        match kind & !CR {
+            CLD => {
+                self.mux.frame(0, false, UIH, &[CLD, EA]);
+                self.close();
+            }
+            MSC => {
+                // Echoed back as the response
+                info[0] = MSC;
+                self.mux.frame(0, false, UIH, info);
+            }
+            _ => self.mux.frame(0, false, UIH, &[NSC, 1 << 1 | EA, kind]),
+        }
+    }
+
+    /// Close every DLCI and the multiplexer.
+    fn close(&mut self) {
+        self.control = false;
+        self.open = [false; CHANNELS];
+        self.closed = true;
+    }
+}
+
+/// Handler of `AT+CMUX`, accepting the basic option (`AT+CMUX=0`)
+///
+/// Register it like any handler; `AT+CMUX=0` raises the flag it was created
+/// with, telling to switch the UART to a [`Cmux`] once the `OK` has been
+/// sent. Further parameters (subset, port speed, N1) are accepted and
+/// ignored.
+///
+/// # Example
+///
+/// ```rust
+/// use core::sync::atomic::{AtomicBool, Ordering};
+/// use at_parser_rs::cmux::CmuxCommand;
+/// use at_parser_rs::parser::AtParser;
+/// # const SIZE: usize = 64;
+///
+/// static CMUX: AtomicBool = AtomicBool::new(false);
+///
+/// let mut cmux = CmuxCommand::new(&CMUX);
+/// let commands: &mut [(&str, &str, &mut CmuxCommand)] = &mut [("AT+CMUX", "+CMUX: ", &mut cmux)];
+/// let mut parser: AtParser<CmuxCommand, SIZE> = AtParser::new();
+/// parser.set_commands(commands);
+///
+/// assert!(parser.execute("AT+CMUX=0").is_ok());
+/// assert!(CMUX.swap(false, Ordering::AcqRel));
+/// ```
+#[derive(Debug)]
+pub struct CmuxCommand<'f> {
+    /// Set when the multiplexer is requested
+    requested: &'f AtomicBool,
+}
+
+impl<'f> CmuxCommand<'f> {
+    /// Create a handler raising `requested` on `AT+CMUX=0`.
+    pub const fn new(requested: &'f AtomicBool) -> Self {
+        Self { requested }
+    }
+}
+
+impl<const SIZE: usize, S, E> AtContext<SIZE, S, E> for CmuxCommand<'_> {
+    fn test(&mut self, at_response: &'static str) -> AtResult<'_, SIZE, E> {
+        Ok(at_response!(SIZE, at_response; "(0)"))
+    }
+
+    fn set(&mut self, at_response: &'static str, args: Args) -> AtResult<'_, SIZE, E> {
+        match args.get_u8(0) {
+            Ok(0) => {
+                self.requested.store(true, Ordering::Release);
+                Ok((at_response, AtResponse::Ok))
+            }
+            _ => Err((at_response, AtError::InvalidArgs)),
+        }
+    }
+}
+
+/// Add `byte` to the running FCS: CRC-8, polynomial x^8 + x^2 + x + 1,
+/// reflected.
+fn crc(fcs: u8, byte: u8) -> u8 {
+    let mut fcs = fcs ^ byte;
+    for _ in 0..8 {
+        fcs = if fcs & 1 != 0 { fcs >> 1 ^ 0xe0 } else { fcs >> 1 };
+    }
+    fcs
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec::Vec;
+    use core::cell::RefCell;
+
+    use super::{Cmux, Decoder, Link, Mux, CR, DISC, PF, SABM, UA, UIH};
+    use crate::context::AtContext;
+    use crate::parser::AtParser;
+    use crate::{AtResult, at_response};
+
+    const SIZE: usize = 64;
+
+    struct Version;
+
+    impl AtContext<SIZE> for Version {
+        fn exec(&mut self, at_response: &'static str) -> AtResult<'_, SIZE> {
+            Ok(at_response!(SIZE, at_response; "EC25"))
+        }
+    }
+
+    impl Link for &RefCell<Vec<u8>> {
+        fn send(&mut self, bytes: &[u8]) {
+            self.borrow_mut().extend_from_slice(bytes);
+        }
+    }
+
+    /// Frames sent by the DTE
+    fn dte(frames: &[(u8, u8, &[u8])]) -> Vec<u8> {
+        let wire = RefCell::new(Vec::new());
+        let mux: Mux<&RefCell<Vec<u8>>> = Mux::new(&wire);
+        for &(dlci, control, info) in frames {
+            mux.frame(dlci, true, control, info);
+        }
+        wire.into_inner()
+    }
+
+    /// `(dlci, control, information)` of the frames sent by the modem
+    fn decode(wire: &[u8]) -> Vec<(u8, u8, Vec<u8>)> {
+        let mut decoder: Decoder<31> = Decoder::new();
+        let mut frames = Vec::new();
+        for &byte in wire {
+            if decoder.feed(byte) {
+                frames.push((decoder.dlci(), decoder.control, decoder.info[..decoder.len].to_vec()));
+            }
+        }
+        frames
+    }
+
+    #[test]
+    fn channels_carry_their_parser_until_close_down() {
+        let wire = RefCell::new(Vec::new());
+        let mux: Mux<&RefCell<Vec<u8>>> = Mux::new(&wire);
+        let mut output = mux.channel(1);
+        let mut version = Version;
+        let commands: &mut [(&str, &str, &mut Version)] = &mut [("AT+CGMR", "", &mut version)];
+        let mut parser: AtParser<Version, SIZE> = AtParser::new();
+        parser.set_commands(commands);
+        parser.set_output(&mut output);
+        parser.set_response_framing(true);
+        let mut cmux: Cmux<&RefCell<Vec<u8>>, 2> = Cmux::new(&mux);
+        cmux.set_channel(1, &mut parser);
+
+        assert_eq!(dte(&[(0, SABM | PF, &[])]), [0xf9, 0x03, 0x3f, 0x01, 0x1c, 0xf9]);
+        let mut input = dte(&[
+            (0, SABM | PF, &[]),
+            (1, SABM | PF, &[]),
+            (3, SABM | PF, &[]),
+            (1, UIH, b"AT+CG"),
+            (2, UIH, b"lost"),
+            (1, UIH, b"MR\r"),
+            (1, DISC | PF, &[]),
+            (0, UIH, &[0xc3, 0x01]),
+        ]);
+        // Corrupted frames are dropped
+        let mut corrupted = dte(&[(1, UIH, b"AT\r")]);
+        corrupted[3] ^= 0x02;
+        input.splice(0..0, corrupted);
+        input.extend_from_slice(b"AT\r");
+
+        assert_eq!(cmux.feed_slice(&input), input.len() - 3);
+        assert!(!cmux.is_open());
+        assert!(!cmux.is_channel_open(1));
+
+        let sent = decode(&wire.borrow());
+        let control: Vec<(u8, u8)> = sent.iter()
+            .filter(|(_, control, _)| control & !PF != UIH)
+            .map(|&(dlci, control, _)| (dlci, control))
+            .collect();
+        assert_eq!(control, [(0, UA | PF), (1, UA | PF), (3, 0x0f | PF), (1, UA | PF)]);
+        let response: Vec<u8> = sent.iter()
+            .filter(|(dlci, control, _)| *dlci == 1 && *control == UIH)
+            .flat_map(|(_, _, info)| info.iter().copied())
+            .collect();
+        assert_eq!(response, b"\r\nEC25\r\n\r\nOK\r\n");
+        assert!(sent.iter().any(|(dlci, _, info)| *dlci == 0 && info[..] == [0xc3 & !CR, 0x01]));
+    }
+}
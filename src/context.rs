@@ -18,7 +18,12 @@
  *
  ***************************************************************************/
  
+use core::convert::Infallible;
+use core::fmt;
+
+use crate::response::{ErrorKind, Responder, ResultCode};
 use crate::{Args, AtError, AtResult};
+use osal_rs::utils::Bytes;
 
 /// Trait that defines the context for AT command execution.
 ///
@@ -28,11 +33,22 @@ use crate::{Args, AtError, AtResult};
 /// [`AtError::NotSupported`], so you only need to override the forms your
 /// command actually needs.
 ///
+/// Every form takes `&mut self`: an execute-form command such as `AT+RST` or
+/// `AT&F` updates its handler just like a set command does.
+///
 /// The const generic `SIZE` defines the capacity (in bytes) of the
 /// [`Bytes`](osal_rs::utils::Bytes) response buffer returned by each handler.
 /// All handlers registered in the same [`AtParser`](crate::parser::AtParser)
 /// must use the same `SIZE`.
 ///
+/// The type parameter `S` is the user state shared by all handlers (a driver
+/// struct holding the radio, flash, configuration, ...). The `*_with` methods
+/// reach it through [`Responder::state`]; it defaults to `()`.
+///
+/// The type parameter `E` is the application error type carried by
+/// [`AtError::User`], see [`UserError`](crate::UserError); it defaults to
+/// [`Infallible`].
+///
 /// # Example — minimal handler
 ///
 /// ```rust,no_run
@@ -81,7 +97,29 @@ use crate::{Args, AtError, AtResult};
 ///     }
 /// }
 /// ```
-pub trait AtContext<const SIZE: usize> {
+/// # Example — shared state
+///
+/// ```rust,no_run
+/// use at_parser_rs::context::AtContext;
+/// use at_parser_rs::response::Responder;
+/// use at_parser_rs::{AtError, AtResult, at_response};
+///
+/// const SIZE: usize = 64;
+///
+/// struct Driver { resets: u32 }
+///
+/// struct ResetModule;
+///
+/// impl AtContext<SIZE, Driver> for ResetModule {
+///     fn exec_with(&mut self, at_response: &'static str, responder: &mut Responder<'_, Driver>) -> AtResult<'_, SIZE> {
+///         let driver = responder.state().ok_or((at_response, AtError::NotSupported))?;
+///         driver.resets += 1;
+///         Ok(at_response!(SIZE, at_response; driver.resets))
+///     }
+/// }
+/// // parser.execute_with_state("AT+RST", &mut driver)  →  Ok(("+RST: ", "1"))
+/// ```
+pub trait AtContext<const SIZE: usize, S = (), E = Infallible> {
 
     /// Execute command (`AT+CMD`)
     ///
@@ -94,7 +132,7 @@ pub trait AtContext<const SIZE: usize> {
     ///
     /// # Returns
     ///
-    /// * `Ok((at_response, Bytes<SIZE>))` — response to send back to the caller
+    /// * `Ok((at_response, AtResponse<SIZE>))` — response to send back to the caller
     /// * `Err((at_response, AtError::NotSupported))` — default when not overridden
     ///
     /// # Example
@@ -112,7 +150,7 @@ pub trait AtContext<const SIZE: usize> {
     /// }
     /// // AT+PING  →  Ok(("+PING: ", "PONG"))
     /// ```
-    fn exec(&mut self, at_response: &'static str) -> AtResult<'_, SIZE> {
+    fn exec(&mut self, at_response: &'static str) -> AtResult<'_, SIZE, E> {
         Err((at_response, AtError::NotSupported))
     }
 
@@ -126,7 +164,7 @@ pub trait AtContext<const SIZE: usize> {
     ///
     /// # Returns
     ///
-    /// * `Ok((at_response, Bytes<SIZE>))` — current value/state
+    /// * `Ok((at_response, AtResponse<SIZE>))` — current value/state
     /// * `Err((at_response, AtError::NotSupported))` — default when not overridden
     ///
     /// # Example
@@ -144,7 +182,7 @@ pub trait AtContext<const SIZE: usize> {
     /// }
     /// // AT+VOL?  →  Ok(("+VOL: ", "75"))  (if level == 75)
     /// ```
-    fn query(&mut self, at_response: &'static str) -> AtResult<'_, SIZE> {
+    fn query(&mut self, at_response: &'static str) -> AtResult<'_, SIZE, E> {
         Err((at_response, AtError::NotSupported))
     }
     
@@ -153,14 +191,18 @@ pub trait AtContext<const SIZE: usize> {
     /// Called to report whether a command is supported or to return the
     /// valid parameter ranges accepted by [`set`](AtContext::set).
     ///
+    /// When not overridden, the response is generated from
+    /// [`supported`](AtContext::supported), e.g. `(0-1),(1-65535)`.
+    ///
     /// # Arguments
     ///
     /// * `at_response` — AT response prefix registered for this command
     ///
     /// # Returns
     ///
-    /// * `Ok((at_response, Bytes<SIZE>))` — human-readable description of valid parameters
+    /// * `Ok((at_response, AtResponse<SIZE>))` — human-readable description of valid parameters
     /// * `Err((at_response, AtError::NotSupported))` — default when not overridden
+    ///   and no parameter is declared by [`supported`](AtContext::supported)
     ///
     /// # Example
     ///
@@ -177,8 +219,38 @@ pub trait AtContext<const SIZE: usize> {
     /// }
     /// // AT+VOL=?  →  Ok(("+VOL: ", "(0-100)"))
     /// ```
-    fn test(&mut self, at_response: &'static str) -> AtResult<'_, SIZE> {
-        Err((at_response, AtError::NotSupported))
+    fn test(&mut self, at_response: &'static str) -> AtResult<'_, SIZE, E> {
+        let supported = self.supported();
+        if supported.is_empty() {
+            return Err((at_response, AtError::NotSupported));
+        }
+
+        let mut response = Bytes::<SIZE>::new();
+        response.format(format_args!("{}", SupportedList(supported)));
+        Ok((at_response, response.into()))
+    }
+
+    /// Values accepted by each parameter of [`set`](AtContext::set), in order
+    ///
+    /// Declaring them lets the default [`test`](AtContext::test) answer
+    /// `AT+CMD=?` without per-command code. Empty by default.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// # use at_parser_rs::context::{AtContext, Supported};
+    /// # const SIZE: usize = 64;
+    /// struct Socket;
+    ///
+    /// impl AtContext<SIZE> for Socket {
+    ///     fn supported(&self) -> &[Supported] {
+    ///         &[Supported::Range(0, 1), Supported::Range(1, 65535)]
+    ///     }
+    /// }
+    /// // AT+SOCK=?  →  Ok(("+SOCK: ", "(0-1),(1-65535)"))
+    /// ```
+    fn supported(&self) -> &[Supported] {
+        &[]
     }
 
     /// Set command (`AT+CMD=<args>`)
@@ -196,7 +268,7 @@ pub trait AtContext<const SIZE: usize> {
     ///
     /// # Returns
     ///
-    /// * `Ok((at_response, Bytes<SIZE>))` — confirmation/response
+    /// * `Ok((at_response, AtResponse<SIZE>))` — confirmation/response
     /// * `Err((at_response, AtError::InvalidArgs))` — when arguments are missing or invalid
     /// * `Err((at_response, AtError::NotSupported))` — default when not overridden
     ///
@@ -244,8 +316,481 @@ pub trait AtContext<const SIZE: usize> {
     /// // AT+VOL=200  →  Err(InvalidArgs)
     /// // AT+VOL=     →  Err(InvalidArgs)
     /// ```
-    fn set(&mut self, at_response: &'static str, _args: Args) -> AtResult<'_, SIZE> {
+    fn set(&mut self, at_response: &'static str, _args: Args) -> AtResult<'_, SIZE, E> {
+        Err((at_response, AtError::NotSupported))
+    }
+
+    /// Execute command with a [`Responder`] (`AT+CSQ`, `ATD`, `ATA`)
+    ///
+    /// Called by the parser instead of [`exec`](AtContext::exec). Text
+    /// written to `responder` with `write!` becomes the payload when the
+    /// handler returns an empty one, so responses can be formatted
+    /// incrementally; action commands also send intermediate result codes
+    /// such as `CONNECT` through it while they execute. Forwards to
+    /// [`exec`](AtContext::exec) by default.
+    ///
+    /// # Arguments
+    ///
+    /// * `at_response` — AT response prefix registered for this command
+    /// * `responder` — writer for the response (see [`Responder`])
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// # use core::fmt::Write;
+    /// # use at_parser_rs::context::AtContext;
+    /// # use at_parser_rs::response::Responder;
+    /// # use at_parser_rs::{AtError, AtResponse, AtResult};
+    /// # const SIZE: usize = 64;
+    /// struct Signal { rssi: u8, ber: u8 }
+    ///
+    /// impl AtContext<SIZE> for Signal {
+    ///     fn exec_with(&mut self, at_response: &'static str, responder: &mut Responder<'_>) -> AtResult<'_, SIZE> {
+    ///         write!(responder, "{},{}", self.rssi, self.ber).map_err(|_| (at_response, AtError::InvalidArgs))?;
+    ///         Ok((at_response, AtResponse::Ok))
+    ///     }
+    /// }
+    /// // AT+CSQ  →  Ok(("+CSQ: ", "23,0"))
+    /// ```
+    fn exec_with(&mut self, at_response: &'static str, _responder: &mut Responder<'_, S>) -> AtResult<'_, SIZE, E> {
+        self.exec(at_response)
+    }
+
+    /// Query command with a [`Responder`]
+    ///
+    /// Called by the parser instead of [`query`](AtContext::query), see
+    /// [`exec_with`](AtContext::exec_with); several lines of information text
+    /// can be sent through [`Responder::info`]. Forwards to
+    /// [`query`](AtContext::query) by default.
+    fn query_with(&mut self, at_response: &'static str, _responder: &mut Responder<'_, S>) -> AtResult<'_, SIZE, E> {
+        self.query(at_response)
+    }
+
+    /// Test command with a [`Responder`] (`AT+COPS=?`)
+    ///
+    /// Called by the parser instead of [`test`](AtContext::test), see
+    /// [`query_with`](AtContext::query_with). Forwards to
+    /// [`test`](AtContext::test) by default.
+    fn test_with(&mut self, at_response: &'static str, _responder: &mut Responder<'_, S>) -> AtResult<'_, SIZE, E> {
+        self.test(at_response)
+    }
+
+    /// Set command with a [`Responder`] (`ATD5551234;`, `AT+CMGL="ALL"`)
+    ///
+    /// Called by the parser instead of [`set`](AtContext::set), see
+    /// [`exec_with`](AtContext::exec_with) and
+    /// [`query_with`](AtContext::query_with). Forwards to
+    /// [`set`](AtContext::set) by default.
+    ///
+    /// # Example — intermediate result code
+    ///
+    /// ```rust,no_run
+    /// # use at_parser_rs::context::AtContext;
+    /// # use at_parser_rs::response::{Responder, ResultCode};
+    /// # use at_parser_rs::{Args, AtError, AtResult};
+    /// # const SIZE: usize = 64;
+    /// struct Dialer { busy: bool }
+    ///
+    /// impl AtContext<SIZE> for Dialer {
+    ///     fn set_with(&mut self, at_response: &'static str, _args: Args, responder: &mut Responder<'_>) -> AtResult<'_, SIZE> {
+    ///         if self.busy {
+    ///             return Err((at_response, AtError::Final(ResultCode::Busy)));
+    ///         }
+    ///         responder.intermediate(ResultCode::Connect(None)).ok();
+    ///         Err((at_response, AtError::Final(ResultCode::NoCarrier)))
+    ///     }
+    /// }
+    /// // ATD5551234  →  "CONNECT" ... "NO CARRIER"
+    /// ```
+    ///
+    /// # Example — multi-line listing
+    ///
+    /// ```rust,no_run
+    /// # use at_parser_rs::context::AtContext;
+    /// # use at_parser_rs::response::Responder;
+    /// # use at_parser_rs::{Args, AtError, AtResponse, AtResult};
+    /// # const SIZE: usize = 64;
+    /// struct Messages { stored: [&'static str; 2] }
+    ///
+    /// impl AtContext<SIZE> for Messages {
+    ///     fn set_with(&mut self, at_response: &'static str, _args: Args, responder: &mut Responder<'_>) -> AtResult<'_, SIZE> {
+    ///         for (index, text) in self.stored.iter().enumerate() {
+    ///             responder.info(format_args!("{at_response}{index}")).ok();
+    ///             responder.info(text).ok();
+    ///         }
+    ///         Ok((at_response, AtResponse::Ok))
+    ///     }
+    /// }
+    /// // AT+CMGL="ALL"  →  "+CMGL: 0" / "hello" / "+CMGL: 1" / "world" / "OK"
+    /// ```
+    fn set_with(&mut self, at_response: &'static str, args: Args, _responder: &mut Responder<'_, S>) -> AtResult<'_, SIZE, E> {
+        self.set(at_response, args)
+    }
+
+    /// Input of a multi-step command (`AT+CMGS`)
+    ///
+    /// Once a form method returned
+    /// [`AtResponse::Continue`](crate::AtResponse::Continue), the parser hands
+    /// every byte received by [`feed`](crate::parser::AtParser::feed) to the
+    /// handler, bypassing command line parsing, until it returns a result:
+    /// `None` waits for more input, `Some(result)` completes the command
+    /// (unless the result is `Continue` again) and resumes normal parsing.
+    ///
+    /// Defaults to ending the command with [`AtError::NotSupported`].
+    ///
+    /// # Example — message text ended by Ctrl-Z
+    ///
+    /// ```rust,no_run
+    /// # use at_parser_rs::context::AtContext;
+    /// # use at_parser_rs::response::Responder;
+    /// # use at_parser_rs::{Args, AtError, AtResponse, AtResult, at_response};
+    /// # const SIZE: usize = 64;
+    /// struct SendSms { len: usize }
+    ///
+    /// impl AtContext<SIZE> for SendSms {
+    ///     fn set_with(&mut self, at_response: &'static str, _args: Args, responder: &mut Responder<'_>) -> AtResult<'_, SIZE> {
+    ///         self.len = 0;
+    ///         responder.prompt("> ").map_err(|_| (at_response, AtError::InvalidArgs))?;
+    ///         Ok((at_response, AtResponse::Continue))
+    ///     }
+    ///
+    ///     fn input(&mut self, at_response: &'static str, byte: u8, _responder: &mut Responder<'_>) -> Option<AtResult<'_, SIZE>> {
+    ///         match byte {
+    ///             0x1a => Some(Ok(at_response!(SIZE, at_response; self.len))),   // Ctrl-Z: send
+    ///             0x1b => Some(Ok((at_response, AtResponse::Ok))),               // ESC: cancel
+    ///             _ => { self.len += 1; None }
+    ///         }
+    ///     }
+    /// }
+    /// // AT+CMGS="123"\r  →  "\r\n> "   hello<Ctrl-Z>  →  Ok(("+CMGS: ", "5"))
+    /// ```
+    fn input(&mut self, at_response: &'static str, _byte: u8, _responder: &mut Responder<'_, S>) -> Option<AtResult<'_, SIZE, E>> {
+        Some(Err((at_response, AtError::NotSupported)))
+    }
+
+    /// Payload of a command in data entry mode (`AT+CMGS`)
+    ///
+    /// Once a form method sent its prompt with
+    /// [`Responder::prompt_payload`] and returned
+    /// [`AtResponse::Continue`](crate::AtResponse::Continue), the parser
+    /// collects the bytes received, without the terminating Ctrl-Z, and hands
+    /// them over in one call. Cancelled payloads (ESC) never reach it.
+    ///
+    /// Defaults to ending the command with [`AtError::NotSupported`].
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use at_parser_rs::context::AtContext;
+    /// # use at_parser_rs::parser::AtParser;
+    /// # use at_parser_rs::response::Responder;
+    /// # use at_parser_rs::{Args, AtError, AtResponse, AtResult, at_response};
+    /// # const SIZE: usize = 64;
+    /// struct SendSms;
+    ///
+    /// impl AtContext<SIZE> for SendSms {
+    ///     fn set_with(&mut self, at_response: &'static str, _args: Args, responder: &mut Responder<'_>) -> AtResult<'_, SIZE> {
+    ///         responder.prompt_payload("> ").map_err(|_| (at_response, AtError::InvalidArgs))?;
+    ///         Ok((at_response, AtResponse::Continue))
+    ///     }
+    ///
+    ///     fn payload(&mut self, at_response: &'static str, payload: &[u8], _responder: &mut Responder<'_>) -> AtResult<'_, SIZE> {
+    ///         // send `payload`, answer the message reference
+    ///         Ok(at_response!(SIZE, at_response; payload.len()))
+    ///     }
+    /// }
+    ///
+    /// let mut sms = SendSms;
+    /// let commands: &mut [(&str, &str, &mut SendSms)] = &mut [("AT+CMGS", "+CMGS: ", &mut sms)];
+    /// let mut parser: AtParser<SendSms, SIZE> = AtParser::new();
+    /// parser.set_commands(commands);
+    /// parser.feed_slice(b"AT+CMGS=\"123\"\r");
+    /// let (_, result) = parser.feed_slice(b"hello\x1a");
+    /// assert_eq!(result.unwrap().unwrap().1.to_string(), "5");
+    /// ```
+    fn payload(&mut self, at_response: &'static str, _payload: &[u8], _responder: &mut Responder<'_, S>) -> AtResult<'_, SIZE, E> {
         Err((at_response, AtError::NotSupported))
     }
 
-}
\ No newline at end of file
+    /// Extended command prefix notification
+    ///
+    /// Called right before the command form method with the prefix character
+    /// the command was invoked with (`'+'` for `AT+CMD`, `'#'` for `AT#CMD`),
+    /// when it is one of the parser's
+    /// [extended prefixes](crate::parser::AtParser::set_extended_prefixes).
+    /// Lets a single handler serve several vendor namespaces. Does nothing by
+    /// default.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// # use at_parser_rs::context::AtContext;
+    /// # use at_parser_rs::{AtResult, at_response};
+    /// # const SIZE: usize = 64;
+    /// struct PowerModule { vendor: bool }
+    ///
+    /// impl AtContext<SIZE> for PowerModule {
+    ///     fn prefix(&mut self, prefix: char) {
+    ///         self.vendor = prefix == '#';
+    ///     }
+    ///     fn exec(&mut self, at_response: &'static str) -> AtResult<'_, SIZE> {
+    ///         Ok(at_response!(SIZE, at_response; if self.vendor { "TELIT" } else { "V250" }))
+    ///     }
+    /// }
+    /// // AT#PWR  →  Ok(("+PWR: ", "TELIT"))  (with "+#" as extended prefixes)
+    /// ```
+    fn prefix(&mut self, _prefix: char) {}
+
+    /// Reset notification
+    ///
+    /// Called on every registered handler when `ATZ` has restored the stored
+    /// profile (see [`profile`](crate::profile)), before `OK` is sent. Lets a
+    /// handler re-initialize the state it restored from
+    /// [`SettingsStore::load_user`](crate::profile::SettingsStore::load_user),
+    /// or drop the state of the session. Does nothing by default.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// # use at_parser_rs::context::AtContext;
+    /// # const SIZE: usize = 64;
+    /// struct SmsModule { pending: Option<u8> }
+    ///
+    /// impl AtContext<SIZE> for SmsModule {
+    ///     fn on_reset(&mut self) {
+    ///         self.pending = None;
+    ///     }
+    /// }
+    /// ```
+    fn on_reset(&mut self) {}
+
+}
+
+/// Async command handler, behind the `async` feature.
+///
+/// Used by [`AtParser::execute_async`](crate::parser::AtParser::execute_async)
+/// in place of the [`AtContext`] methods, so handlers can await flash writes
+/// or radio transactions from an async executor such as Embassy. Every method
+/// forwards to the matching [`AtContext`] `*_with` method by default: override
+/// only the forms that need to await, and implement [`AtContext`] (possibly
+/// empty) alongside.
+///
+/// The returned futures carry no `Send` bound, as suits single-threaded
+/// executors.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// use at_parser_rs::context::{AsyncAtContext, AtContext};
+/// use at_parser_rs::response::Responder;
+/// use at_parser_rs::{Args, AtResult, at_response};
+///
+/// const SIZE: usize = 64;
+///
+/// struct Flash;
+///
+/// impl Flash {
+///     async fn write(&mut self, _data: &str) {}
+/// }
+///
+/// impl AtContext<SIZE> for Flash {}
+///
+/// impl AsyncAtContext<SIZE> for Flash {
+///     async fn set(&mut self, at_response: &'static str, args: Args<'_>, _responder: &mut Responder<'_>) -> AtResult<'_, SIZE> {
+///         self.write(args.raw()).await;
+///         Ok(at_response!(SIZE, at_response; "OK"))
+///     }
+/// }
+/// ```
+#[cfg(feature = "async")]
+#[allow(async_fn_in_trait)]
+pub trait AsyncAtContext<const SIZE: usize, S = (), E = Infallible>: AtContext<SIZE, S, E> {
+
+    /// Execute command (`AT+CMD`), see [`AtContext::exec_with`]
+    async fn exec(&mut self, at_response: &'static str, responder: &mut Responder<'_, S>) -> AtResult<'_, SIZE, E> {
+        self.exec_with(at_response, responder)
+    }
+
+    /// Query command (`AT+CMD?`), see [`AtContext::query_with`]
+    async fn query(&mut self, at_response: &'static str, responder: &mut Responder<'_, S>) -> AtResult<'_, SIZE, E> {
+        self.query_with(at_response, responder)
+    }
+
+    /// Test command (`AT+CMD=?`), see [`AtContext::test_with`]
+    async fn test(&mut self, at_response: &'static str, responder: &mut Responder<'_, S>) -> AtResult<'_, SIZE, E> {
+        self.test_with(at_response, responder)
+    }
+
+    /// Set command (`AT+CMD=<args>`), see [`AtContext::set_with`]
+    async fn set(&mut self, at_response: &'static str, args: Args<'_>, responder: &mut Responder<'_, S>) -> AtResult<'_, SIZE, E> {
+        self.set_with(at_response, args, responder)
+    }
+
+}
+
+/// Form of the command being executed, see [`Responder::form`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Form {
+    /// Execute command without parameters (`AT+CMD`)
+    Exec,
+    /// Query the current state (`AT+CMD?`)
+    Query,
+    /// Test command availability or get valid ranges (`AT+CMD=?`)
+    Test,
+    /// Set command with arguments (`AT+CMD=args`)
+    Set,
+}
+
+/// Values accepted by one parameter, declared by [`AtContext::supported`]
+///
+/// Rendered the way 27.007 test responses list them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Supported {
+    /// Inclusive numeric range: `(0-4)`
+    Range(i64, i64),
+    /// List of numbers: `(0,1,5)`
+    Values(&'static [i64]),
+    /// List of strings: `("GSM","UCS2")`
+    Strings(&'static [&'static str]),
+}
+
+impl fmt::Display for Supported {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Supported::Range(low, high) => write!(f, "({low}-{high})"),
+            Supported::Values(values) => {
+                f.write_str("(")?;
+                for (index, value) in values.iter().enumerate() {
+                    if index > 0 {
+                        f.write_str(",")?;
+                    }
+                    write!(f, "{value}")?;
+                }
+                f.write_str(")")
+            }
+            Supported::Strings(values) => {
+                f.write_str("(")?;
+                for (index, value) in values.iter().enumerate() {
+                    if index > 0 {
+                        f.write_str(",")?;
+                    }
+                    write!(f, "\"{value}\"")?;
+                }
+                f.write_str(")")
+            }
+        }
+    }
+}
+
+/// Comma-separated rendering of every parameter of a test response.
+struct SupportedList<'s>(&'s [Supported]);
+
+impl fmt::Display for SupportedList<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (index, supported) in self.0.iter().enumerate() {
+            if index > 0 {
+                f.write_str(",")?;
+            }
+            write!(f, "{supported}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Catch-all handler for command lines that cannot be resolved.
+///
+/// Install it with [`AtParser::set_fallback`](crate::parser::AtParser::set_fallback)
+/// to forward unrecognized commands elsewhere (e.g. AT command passthrough to
+/// a second modem) instead of failing with [`AtError::UnknownCommand`].
+///
+/// # Example
+///
+/// ```rust,no_run
+/// use at_parser_rs::context::AtFallback;
+/// use at_parser_rs::{AtResult, at_response};
+///
+/// const SIZE: usize = 64;
+///
+/// struct Passthrough;
+///
+/// impl AtFallback<SIZE> for Passthrough {
+///     fn unknown(&mut self, line: &str) -> AtResult<'_, SIZE> {
+///         // forward `line` to the second modem and relay its answer
+///         Ok(at_response!(SIZE, ""; "OK"))
+///     }
+/// }
+/// ```
+pub trait AtFallback<const SIZE: usize, E = Infallible> {
+
+    /// Handle a command line holding at least one unknown command.
+    ///
+    /// # Arguments
+    ///
+    /// * `line` — the full command line as received, trimmed (e.g. `"AT+VENDOR=1"`)
+    ///
+    /// # Returns
+    ///
+    /// The result reported for the whole line.
+    fn unknown(&mut self, line: &str) -> AtResult<'_, SIZE, E>;
+
+}
+
+/// Hook observing the commands dispatched by a parser.
+///
+/// Install it with [`AtParser::set_observer`](crate::parser::AtParser::set_observer)
+/// to log commands over defmt or RTT, or to count them, without changing the
+/// dispatch loop. Every method does nothing by default.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// use at_parser_rs::context::{AtObserver, Form};
+/// use at_parser_rs::{AtError, AtResult};
+///
+/// const SIZE: usize = 64;
+///
+/// #[derive(Default)]
+/// struct Counters { commands: u32, failures: u32, syntax_errors: u32 }
+///
+/// impl AtObserver<SIZE> for Counters {
+///     fn on_command(&mut self, _name: &str, _form: Form) {
+///         self.commands += 1;
+///     }
+///
+///     fn on_response(&mut self, result: &AtResult<'_, SIZE>) {
+///         self.failures += u32::from(result.is_err());
+///     }
+///
+///     fn on_parse_error(&mut self, _error: &AtError<'_>, _raw: &str) {
+///         self.syntax_errors += 1;
+///     }
+/// }
+/// ```
+pub trait AtObserver<const SIZE: usize, E = Infallible> {
+
+    /// A command is about to be executed.
+    ///
+    /// `name` is the command as written on the line (`AT+CSQ`, or `+CSQ`
+    /// after a `;`), the registered name for prefix commands (`ATD`).
+    fn on_command(&mut self, _name: &str, _form: Form) {}
+
+    /// A command, or a line served by the fallback, ended with `result`.
+    fn on_response(&mut self, _result: &AtResult<'_, SIZE, E>) {}
+
+    /// The command line `raw` could not be parsed; `raw` is empty when it is
+    /// not valid UTF-8.
+    fn on_parse_error(&mut self, _error: &AtError<'_>, _raw: &str) {}
+
+    /// A command line ended with the final result code `code`, `Ok` with
+    /// the code of a success, `Err` with the kind of the error.
+    ///
+    /// Called once per line, with framing enabled or not, after the
+    /// responses of its commands. `name` is the command the code comes from:
+    /// the one that failed, or the last one of the line; it is the line
+    /// itself when the fallback served it, and empty when the line could not
+    /// be parsed or the code was sent with
+    /// [`complete`](crate::parser::AtParser::complete).
+    fn on_result_code(&mut self, _name: &str, _code: Result<ResultCode, ErrorKind>) {}
+
+}
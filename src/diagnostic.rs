@@ -0,0 +1,213 @@
+/***************************************************************************
+ *
+ * AT Command Parser
+ * Copyright (C) 2026 Antonio Salsi <passy.linux@zresa.it>
+ *
+ * This library is free software; you can redistribute it and/or
+ * modify it under the terms of the GNU Lesser General Public
+ * License as published by the Free Software Foundation; either
+ * version 2.1 of the License, or (at your option) any later version.
+ *
+ * This library is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the GNU
+ * Lesser General Public License for more details.
+ *
+ * You should have received a copy of the GNU Lesser General Public
+ * License along with this library; if not, see <https://www.gnu.org/licenses/>.
+ *
+ ***************************************************************************/
+
+ 
+//! Diagnostics of malformed command lines
+//!
+//! A failed command line is answered with a bare `ERROR`, which tells the
+//! host nothing about what was wrong with it. The parser keeps a
+//! [`Diagnostic`] of the last line that failed to parse or was rejected for
+//! its arguments, read with
+//! [`AtParser::diagnostic`](crate::parser::AtParser::diagnostic): the byte
+//! offset in the line where the problem is, and the [`Reason`].
+//!
+//! ```text
+//! AT+CWJAP="home",x3      → ERROR
+//!                 ^ offset 16: Argument(Invalid(1))
+//! ```
+//!
+//! Handlers report the parameter at fault by failing with
+//! [`AtError::Argument`]; a plain [`AtError::InvalidArgs`] only points at the
+//! arguments as a whole.
+
+use crate::{ArgError, AtError};
+
+/// Why a command line was rejected
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Reason {
+    /// The line is not valid UTF-8
+    InvalidUtf8,
+    /// The token given was expected at the offset: `"AT"` at the start of a
+    /// line naming no known command, a closing `"` at its end
+    Expected(&'static str),
+    /// No command is registered under the name at the offset
+    UnknownCommand,
+    /// The handler rejected the arguments at the offset
+    InvalidArgs,
+    /// The handler rejected the parameter at the offset, as the error says
+    Argument(ArgError),
+    /// The line was discarded for exceeding the maximum length at the offset
+    TooLong,
+}
+
+/// Where and why a command line was rejected
+///
+/// # Example
+///
+/// ```rust
+/// # use at_parser_rs::parser::AtParser;
+/// # use at_parser_rs::context::AtContext;
+/// use at_parser_rs::diagnostic::{Diagnostic, Reason};
+/// # const SIZE: usize = 64;
+/// # struct MyHandler; impl AtContext<SIZE> for MyHandler {}
+///
+/// let mut parser: AtParser<MyHandler, SIZE> = AtParser::new();
+/// assert!(parser.execute("ATE0;+NOPE").is_err());
+/// assert_eq!(parser.diagnostic(), Some(Diagnostic { offset: 5, reason: Reason::UnknownCommand }));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Diagnostic {
+    /// Byte offset in the command line, `AT` prefix included
+    pub offset: usize,
+    /// What is wrong at the offset
+    pub reason: Reason,
+}
+
+impl Diagnostic {
+    /// Diagnose `error`, returned by the command named `name`, with the raw
+    /// arguments `args`, of `line`.
+    ///
+    /// `first` tells whether the command opens the line. Errors other than
+    /// unknown commands and invalid arguments are not the fault of the line
+    /// and get no diagnostic.
+    pub(crate) fn of<E>(line: &str, name: &str, args: Option<&str>, error: &AtError<'_, E>, first: bool) -> Option<Self> {
+        let at = |offset, reason| Some(Self { offset, reason });
+        match error {
+            AtError::UnknownCommand if first && !starts_with_at(line) => at(offset(line, line.trim_start()), Reason::Expected("AT")),
+            AtError::UnknownCommand => at(offset(line, name), Reason::UnknownCommand),
+            AtError::InvalidArgs | AtError::Argument(_) if args.is_some_and(unterminated) => {
+                at(offset(line, line.trim_end()) + line.trim_end().len(), Reason::Expected("\""))
+            }
+            AtError::InvalidArgs => at(args.map_or(offset(line, name), |args| offset(line, args)), Reason::InvalidArgs),
+            AtError::Argument(e) => {
+                let args = args.unwrap_or("");
+                let index = match *e {
+                    ArgError::Missing(index)
+                    | ArgError::Invalid(index)
+                    | ArgError::Unexpected(index)
+                    | ArgError::OddLength(index)
+                    | ArgError::Overflow(index)
+                    | ArgError::InvalidAddress(index)
+                    | ArgError::PortOutOfRange(index) => index,
+                };
+                let param = crate::Args::new(args).get_raw(index).unwrap_or(&args[args.len()..]);
+                at(offset(line, param), Reason::Argument(*e))
+            }
+            _ => None,
+        }
+    }
+
+    /// Diagnose a line that is valid UTF-8 up to `valid_up_to`.
+    pub(crate) fn invalid_utf8(valid_up_to: usize) -> Self {
+        Self { offset: valid_up_to, reason: Reason::InvalidUtf8 }
+    }
+
+    /// Diagnose a line longer than `limit` bytes.
+    pub(crate) fn too_long(limit: usize) -> Self {
+        Self { offset: limit, reason: Reason::TooLong }
+    }
+}
+
+/// Offset of `part` in `line`, `0` when it is not a part of it.
+fn offset(line: &str, part: &str) -> usize {
+    (part.as_ptr() as usize)
+        .checked_sub(line.as_ptr() as usize)
+        .filter(|&offset| offset <= line.len())
+        .unwrap_or(0)
+}
+
+/// Whether `line` opens with the `AT` prefix, in either case.
+fn starts_with_at(line: &str) -> bool {
+    line.trim_start().as_bytes().get(..2).is_some_and(|prefix| prefix.eq_ignore_ascii_case(b"AT"))
+}
+
+/// Whether `args` leaves a quoted string open.
+fn unterminated(args: &str) -> bool {
+    let mut in_quotes = false;
+    let mut escaped = false;
+    for ch in args.chars() {
+        match ch {
+            _ if escaped => escaped = false,
+            '\\' if in_quotes => escaped = true,
+            '"' => in_quotes = !in_quotes,
+            _ => {}
+        }
+    }
+    in_quotes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Diagnostic, Reason};
+    use crate::context::AtContext;
+    use crate::parser::AtParser;
+    use crate::{ArgError, Args, AtError, AtResponse, AtResult};
+
+    const SIZE: usize = 64;
+
+    /// `AT+CWJAP=<ssid>,<mode>`, failing with `+CME ERROR` when executed
+    struct Wifi;
+
+    impl AtContext<SIZE> for Wifi {
+        fn exec(&mut self, at_response: &'static str) -> AtResult<'_, SIZE> {
+            Err((at_response, AtError::Cme(3)))
+        }
+
+        fn set(&mut self, at_response: &'static str, args: Args) -> AtResult<'_, SIZE> {
+            args.get_string(0).filter(|ssid| !ssid.is_empty()).ok_or((at_response, AtError::InvalidArgs))?;
+            args.get_u8(1).map_err(|e| (at_response, AtError::Argument(e)))?;
+            Ok((at_response, AtResponse::Ok))
+        }
+    }
+
+    #[test]
+    fn rejected_lines_are_located() {
+        let mut wifi = Wifi;
+        let commands: &mut [(&str, &str, &mut Wifi)] = &mut [("AT+CWJAP", "", &mut wifi)];
+        let mut parser: AtParser<Wifi, SIZE> = AtParser::new();
+        parser.set_commands(commands);
+        let at = |offset, reason| Some(Diagnostic { offset, reason });
+
+        assert!(parser.execute("AT+CWJAP=\"home\",x3").is_err());
+        assert_eq!(parser.diagnostic(), at(16, Reason::Argument(ArgError::Invalid(1))));
+        assert!(parser.execute("AT+CWJAP=\"home\"").is_err());
+        assert_eq!(parser.diagnostic(), at(15, Reason::Argument(ArgError::Missing(1))));
+        assert!(parser.execute("AT+CWJAP=\"home,1").is_err());
+        assert_eq!(parser.diagnostic(), at(16, Reason::Expected("\"")));
+        assert!(parser.execute("AT+CWJAP=,1").is_err());
+        assert_eq!(parser.diagnostic(), at(9, Reason::InvalidArgs));
+
+        assert!(parser.execute("ATE0+CWJAP=\"x\",1;+CWJAQ").is_err());
+        assert_eq!(parser.diagnostic(), at(17, Reason::UnknownCommand));
+        assert!(parser.execute(" +NOPE").is_err());
+        assert_eq!(parser.diagnostic(), at(1, Reason::Expected("AT")));
+
+        // Not the fault of the line
+        assert!(parser.execute("AT+CWJAP").is_err());
+        assert_eq!(parser.diagnostic(), None);
+
+        parser.feed_slice(b"AT+CW\xffJAP\r");
+        assert_eq!(parser.diagnostic(), at(5, Reason::InvalidUtf8));
+        assert!(parser.execute("AT+CWJAP=\"home\",1").is_ok());
+        assert_eq!(parser.diagnostic(), None);
+    }
+}
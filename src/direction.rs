@@ -0,0 +1,234 @@
+/***************************************************************************
+ *
+ * AT Command Parser
+ * Copyright (C) 2026 Antonio Salsi <passy.linux@zresa.it>
+ *
+ * This library is free software; you can redistribute it and/or
+ * modify it under the terms of the GNU Lesser General Public
+ * License as published by the Free Software Foundation; either
+ * version 2.1 of the License, or (at your option) any later version.
+ *
+ * This library is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the GNU
+ * Lesser General Public License for more details.
+ *
+ * You should have received a copy of the GNU Lesser General Public
+ * License along with this library; if not, see <https://www.gnu.org/licenses/>.
+ *
+ ***************************************************************************/
+
+ 
+//! Half-duplex direction control
+//!
+//! On a shared RS-485 bus a transceiver drives the line only while its
+//! driver enable (DE/RE) pin is asserted, and must release it as soon as the
+//! last bit of a response has left: the next node answers right after. A
+//! [`DirectionControl`] installed with
+//! [`AtParser::set_direction`](crate::parser::AtParser::set_direction) takes
+//! the bus before the parser writes to its output, and is asked to flush the
+//! transmitter and release the bus once the output is complete: the echo of a
+//! character, the response of a line with the URCs held back meanwhile, a
+//! URC, a deferred result.
+//!
+//! ```text
+//! pre_transmit()    DE high
+//! write_str(...)    "\r\nOK\r\n" into the UART
+//! flush()           wait for transmission complete (TC)
+//! post_transmit()   DE low
+//! ```
+//!
+//! The serve adapters (see `serve`) write to their transport themselves and
+//! do not go through it.
+
+use core::fmt::{self, Write};
+
+/// Direction control of a half-duplex link
+///
+/// Every method has an empty default.
+///
+/// # Example
+///
+/// ```rust
+/// use at_parser_rs::direction::DirectionControl;
+///
+/// struct Rs485 { de: bool }
+///
+/// impl DirectionControl for Rs485 {
+///     fn pre_transmit(&mut self) {
+///         self.de = true;    // set the DE pin
+///     }
+///
+///     fn flush(&mut self) {
+///         // wait for the UART to report transmission complete
+///     }
+///
+///     fn post_transmit(&mut self) {
+///         self.de = false;   // clear the DE pin
+///     }
+/// }
+/// ```
+pub trait DirectionControl {
+    /// Take the bus before the first byte is written, e.g. assert DE.
+    fn pre_transmit(&mut self) {}
+
+    /// Wait until the last byte written has left the transmitter, not just
+    /// its FIFO.
+    fn flush(&mut self) {}
+
+    /// Release the bus once flushed, e.g. deassert DE.
+    fn post_transmit(&mut self) {}
+}
+
+/// Output writer of the parser with its direction control
+pub(crate) struct Output<'a> {
+    pub(crate) writer: Option<&'a mut dyn Write>,
+    pub(crate) direction: Option<&'a mut dyn DirectionControl>,
+    /// Whether the bus is taken
+    active: bool,
+}
+
+impl<'a> Output<'a> {
+    pub(crate) const fn new() -> Self {
+        Self { writer: None, direction: None, active: false }
+    }
+
+    /// Start writing to the writer, if any.
+    pub(crate) fn transmission(&mut self) -> Option<Transmission<'_>> {
+        let output: &mut dyn Write = self.writer.as_deref_mut()?;
+        let direction = self.direction.as_deref_mut().map(|direction| direction as &mut dyn DirectionControl);
+        Some(Transmission { output, direction, active: &mut self.active, ends: true })
+    }
+}
+
+/// Output of the parser, taking the bus on the first write and releasing it
+/// when dropped.
+pub(crate) struct Transmission<'t> {
+    output: &'t mut dyn Write,
+    direction: Option<&'t mut dyn DirectionControl>,
+    /// Whether the bus is taken
+    active: &'t mut bool,
+    /// Whether the bus is released on drop, rather than by the transmission
+    /// this one is a part of
+    ends: bool,
+}
+
+impl Transmission<'_> {
+    /// Part of this transmission, leaving the bus taken when dropped.
+    pub(crate) fn part(&mut self) -> Transmission<'_> {
+        Transmission {
+            output: &mut *self.output,
+            direction: self.direction.as_deref_mut().map(|direction| direction as &mut dyn DirectionControl),
+            active: &mut *self.active,
+            ends: false,
+        }
+    }
+
+    /// Turn this transmission into a part of an enclosing one.
+    pub(crate) fn into_part(mut self) -> Self {
+        self.ends = false;
+        self
+    }
+}
+
+impl Write for Transmission<'_> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        if !*self.active
+            && let Some(direction) = self.direction.as_deref_mut() {
+            direction.pre_transmit();
+            *self.active = true;
+        }
+        self.output.write_str(s)
+    }
+}
+
+impl Drop for Transmission<'_> {
+    fn drop(&mut self) {
+        if self.ends
+            && *self.active
+            && let Some(direction) = self.direction.as_deref_mut() {
+            direction.flush();
+            direction.post_transmit();
+            *self.active = false;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::string::String;
+    use core::cell::RefCell;
+    use core::fmt::{self, Write};
+
+    use super::DirectionControl;
+    use crate::context::AtContext;
+    use crate::parser::AtParser;
+    use crate::{AtResult, at_response};
+
+    const SIZE: usize = 64;
+
+    struct Version;
+
+    impl AtContext<SIZE> for Version {
+        fn exec(&mut self, at_response: &'static str) -> AtResult<'_, SIZE> {
+            Ok(at_response!(SIZE, at_response; "EC25"))
+        }
+    }
+
+    /// UART writing to the bus log
+    struct Uart<'l>(&'l RefCell<String>);
+
+    impl Write for Uart<'_> {
+        fn write_str(&mut self, s: &str) -> fmt::Result {
+            self.0.borrow_mut().push_str(s);
+            Ok(())
+        }
+    }
+
+    /// Transceiver logging `[` when taking the bus, `|` when flushed and `]`
+    /// when releasing it
+    struct Transceiver<'l>(&'l RefCell<String>);
+
+    impl DirectionControl for Transceiver<'_> {
+        fn pre_transmit(&mut self) {
+            self.0.borrow_mut().push('[');
+        }
+
+        fn flush(&mut self) {
+            self.0.borrow_mut().push('|');
+        }
+
+        fn post_transmit(&mut self) {
+            self.0.borrow_mut().push(']');
+        }
+    }
+
+    #[test]
+    fn bus_is_taken_around_each_transmission() {
+        let log = RefCell::new(String::new());
+        let mut uart = Uart(&log);
+        let mut transceiver = Transceiver(&log);
+        let mut version = Version;
+        let commands: &mut [(&str, &str, &mut Version)] = &mut [("AT+CGMR", "", &mut version)];
+        let mut parser: AtParser<Version, SIZE> = AtParser::new();
+        parser.set_commands(commands);
+        parser.set_output(&mut uart);
+        parser.set_direction(&mut transceiver);
+        parser.set_response_framing(true);
+
+        parser.feed_slice(b"AT+CGMR\r");
+        parser.emit_urc("+CREG: 1").unwrap();
+        // Held back while a line is received, then sent with its response
+        parser.feed_slice(b"AT");
+        parser.emit_urc("RING").unwrap();
+        parser.feed_slice(b"E1\r");
+        parser.feed_slice(b"A");
+
+        assert_eq!(log.into_inner(), concat!(
+            "[\r\nEC25\r\n\r\nOK\r\n|]",
+            "[\r\n+CREG: 1\r\n|]",
+            "[\r\nOK\r\n\r\nRING\r\n|]",
+            "[A|]",
+        ));
+    }
+}
@@ -0,0 +1,256 @@
+/***************************************************************************
+ *
+ * AT Command Parser
+ * Copyright (C) 2026 Antonio Salsi <passy.linux@zresa.it>
+ *
+ * This library is free software; you can redistribute it and/or
+ * modify it under the terms of the GNU Lesser General Public
+ * License as published by the Free Software Foundation; either
+ * version 2.1 of the License, or (at your option) any later version.
+ *
+ * This library is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the GNU
+ * Lesser General Public License for more details.
+ *
+ * You should have received a copy of the GNU Lesser General Public
+ * License along with this library; if not, see <https://www.gnu.org/licenses/>.
+ *
+ ***************************************************************************/
+
+ 
+//! Parser owning its command table
+//!
+//! [`AtParser`] borrows its table, so the handlers and the table must outlive
+//! it: embedded firmware ends up declaring the table next to the parser and
+//! threading their lifetimes through. A [`StaticAtParser`] owns a table of `N`
+//! slots instead, next to its line buffer, and is built by a `const fn`, so
+//! the whole parser can live in a `static`. Handlers are `&'static mut`
+//! (typically `static` items themselves) and are added at run time with
+//! [`register`](StaticAtParser::register), which fails once the table is
+//! full.
+//!
+//! ```rust
+//! use at_parser_rs::context::AtContext;
+//! use at_parser_rs::fixed::StaticAtParser;
+//! use at_parser_rs::{AtResult, at_response};
+//!
+//! const SIZE: usize = 64;
+//!
+//! struct Ping;
+//!
+//! impl AtContext<SIZE> for Ping {
+//!     fn exec(&mut self, at_response: &'static str) -> AtResult<'_, SIZE> {
+//!         Ok(at_response!(SIZE, at_response; "PONG"))
+//!     }
+//! }
+//!
+//! static mut PING: Ping = Ping;
+//! static mut PARSER: StaticAtParser<8, SIZE> = StaticAtParser::new();
+//!
+//! // SAFETY: both statics are only reached from this task
+//! let parser = unsafe { &mut *&raw mut PARSER };
+//! parser.register("AT+PING", "+PING: ", unsafe { &mut *&raw mut PING }).unwrap();
+//! assert!(parser.execute("AT+PING").is_ok());
+//! ```
+//!
+//! The other settings of the parser are reached through
+//! [`parser`](StaticAtParser::parser).
+
+use core::convert::Infallible;
+use core::mem::MaybeUninit;
+use core::ptr;
+
+use crate::context::AtContext;
+use crate::parser::{AtParser, debug_assert_unique};
+use crate::{AtError, AtResponse, AtResult, UserError};
+
+/// Registered command: name, response prefix and handler.
+type Entry<const SIZE: usize, S, E> = (&'static str, &'static str, &'static mut dyn AtContext<SIZE, S, E>);
+
+/// Parser wrapped by a [`StaticAtParser`].
+pub type Inner<const SIZE: usize, S, E> = AtParser<'static, dyn AtContext<SIZE, S, E>, SIZE, S, E>;
+
+/// Error of [`StaticAtParser::register`]: every slot of the table is taken.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TableFull;
+
+/// Parser owning a table of up to `N` commands; see the
+/// [module documentation](self).
+pub struct StaticAtParser<const N: usize, const SIZE: usize, S: 'static = (), E: 'static = Infallible> {
+    /// Command slots, the first `len` ones registered
+    table: [MaybeUninit<Entry<SIZE, S, E>>; N],
+    /// Number of registered commands
+    len: usize,
+    /// Parser dispatching to the table, installed before each call
+    parser: Inner<SIZE, S, E>,
+}
+
+impl<const N: usize, const SIZE: usize, S: 'static, E: UserError + 'static> StaticAtParser<N, SIZE, S, E> {
+    /// Create a parser with no registered commands.
+    pub const fn new() -> Self {
+        Self { table: [const { MaybeUninit::uninit() }; N], len: 0, parser: AtParser::new() }
+    }
+
+    /// Number of commands the table holds.
+    pub const fn capacity(&self) -> usize {
+        N
+    }
+
+    /// Number of registered commands.
+    pub const fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Whether no command is registered.
+    pub const fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Register `handler` for `command`, answering with the `response`
+    /// prefix.
+    ///
+    /// # Errors
+    ///
+    /// [`TableFull`] when the `N` slots are taken; the table is unchanged.
+    ///
+    /// # Panics
+    ///
+    /// In debug builds, when `command` is already registered.
+    pub fn register(
+        &mut self,
+        command: &'static str,
+        response: &'static str,
+        handler: &'static mut dyn AtContext<SIZE, S, E>,
+    ) -> Result<(), TableFull> {
+        let slot = self.table.get_mut(self.len).ok_or(TableFull)?;
+        slot.write((command, response, handler));
+        self.len += 1;
+        let parser = self.attached();
+        debug_assert_unique(parser.commands);
+        parser.attach(&mut []);
+        Ok(())
+    }
+
+    /// Parser without its table, to change its settings.
+    ///
+    /// The table is only installed by the methods of [`StaticAtParser`]:
+    /// commands run through the returned parser are unknown, and a table set
+    /// on it is replaced by the registered one on the next call.
+    pub fn parser(&mut self) -> &mut Inner<SIZE, S, E> {
+        self.parser.attach(&mut []);
+        &mut self.parser
+    }
+
+    /// Parse and execute a command line, see [`AtParser::execute`].
+    pub fn execute<'b>(&'b mut self, input: &'b str) -> AtResult<'b, SIZE, E> {
+        self.attached().execute(input)
+    }
+
+    /// Parse and execute a command line received as bytes, see
+    /// [`AtParser::execute_bytes`].
+    pub fn execute_bytes<'b>(&'b mut self, input: &'b [u8]) -> AtResult<'b, SIZE, E> {
+        self.attached().execute_bytes(input)
+    }
+
+    /// Execute every command of a line, see [`AtParser::execute_line`].
+    pub fn execute_line<F>(&mut self, line: &str, on_response: F) -> Result<(), (&'static str, AtError<'static, E>)>
+    where
+        F: FnMut(&'static str, AtResponse<'_, SIZE>) {
+        self.attached().execute_line(line, on_response)
+    }
+
+    /// Feed one received byte, see [`AtParser::feed`].
+    pub fn feed(&mut self, byte: u8) -> Option<AtResult<'_, SIZE, E>> {
+        self.attached().feed(byte)
+    }
+
+    /// Feed a chunk of received bytes, see [`AtParser::feed_slice`].
+    pub fn feed_slice(&mut self, bytes: &[u8]) -> (usize, Option<AtResult<'_, SIZE, E>>) {
+        self.attached().feed_slice(bytes)
+    }
+
+    /// Inner parser with the registered table installed.
+    fn attached(&mut self) -> &mut Inner<SIZE, S, E> {
+        // SAFETY: the first `len` slots are initialized. The slice is handed
+        // out as `'static` but stays in the inner parser, which is reached
+        // through `&mut self` only after being installed again, and is
+        // detached before being lent out by `parser`
+        let commands = unsafe {
+            &mut *ptr::slice_from_raw_parts_mut(self.table.as_mut_ptr().cast::<Entry<SIZE, S, E>>(), self.len)
+        };
+        self.parser.attach(commands);
+        &mut self.parser
+    }
+}
+
+impl<const N: usize, const SIZE: usize, S: 'static, E: UserError + 'static> Default for StaticAtParser<N, SIZE, S, E> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+// Some fixtures serve only the tests left out of the reduced builds
+#[cfg_attr(feature = "exec-set-only", allow(unused_imports, dead_code))]
+mod tests {
+    use alloc::boxed::Box;
+    use alloc::format;
+    use alloc::string::String;
+
+    use super::*;
+    use crate::{Args, at_response};
+
+    const SIZE: usize = 32;
+
+    struct Value {
+        value: u32,
+    }
+
+    impl AtContext<SIZE> for Value {
+        fn query(&mut self, at_response: &'static str) -> AtResult<'_, SIZE> {
+            Ok(at_response!(SIZE, at_response; self.value))
+        }
+
+        fn set(&mut self, at_response: &'static str, args: Args) -> AtResult<'_, SIZE> {
+            let value = args.get(0).ok_or((at_response, AtError::InvalidArgs))?;
+            self.value = value.parse().map_err(|_| (at_response, AtError::InvalidArgs))?;
+            Ok(at_response!(SIZE, at_response; "OK"))
+        }
+    }
+
+    fn value() -> &'static mut Value {
+        Box::leak(Box::new(Value { value: 0 }))
+    }
+
+    fn ok(result: Option<AtResult<'_, SIZE>>) -> String {
+        match result {
+            Some(Ok((prefix, bytes))) => format!("{}{}", prefix, bytes),
+            Some(Err((_, e))) => panic!("unexpected error: {:?}", e),
+            None => panic!("no response"),
+        }
+    }
+
+    #[cfg(not(feature = "exec-set-only"))]
+    #[test]
+    fn registered_commands_fill_the_owned_table() {
+        let mut parser: StaticAtParser<2, SIZE> = StaticAtParser::new();
+        assert!(parser.register("AT+ONE", "+ONE: ", value()).is_ok());
+        assert!(parser.register("AT+TWO", "+TWO: ", value()).is_ok());
+        assert_eq!(parser.register("AT+THREE", "+THREE: ", value()), Err(TableFull));
+        assert_eq!((parser.len(), parser.capacity()), (2, 2));
+
+        parser.parser().set_case_insensitive(true);
+        assert!(parser.execute("at+two=7").is_ok());
+        assert_eq!(ok(Some(parser.execute("AT+TWO?"))), "+TWO: 7");
+        assert!(matches!(parser.parser().execute("AT+TWO?"), Err((_, AtError::UnknownCommand))));
+        assert!(matches!(parser.execute("AT+THREE?"), Err((_, AtError::UnknownCommand))));
+
+        assert_eq!(parser.feed_slice(b"AT+ONE=3\r").0, 9);
+        let mut last = None;
+        for &byte in b"AT+ONE?\r" {
+            last = parser.feed(byte).map(|result| ok(Some(result)));
+        }
+        assert_eq!(last.as_deref(), Some("+ONE: 3"));
+    }
+}
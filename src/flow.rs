@@ -0,0 +1,285 @@
+/***************************************************************************
+ *
+ * AT Command Parser
+ * Copyright (C) 2026 Antonio Salsi <passy.linux@zresa.it>
+ *
+ * This library is free software; you can redistribute it and/or
+ * modify it under the terms of the GNU Lesser General Public
+ * License as published by the Free Software Foundation; either
+ * version 2.1 of the License, or (at your option) any later version.
+ *
+ * This library is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the GNU
+ * Lesser General Public License for more details.
+ *
+ * You should have received a copy of the GNU Lesser General Public
+ * License along with this library; if not, see <https://www.gnu.org/licenses/>.
+ *
+ ***************************************************************************/
+
+ 
+//! Software flow control
+//!
+//! Links without RTS/CTS lines throttle each other in band: the receiver
+//! sends XOFF (DC3, `0x13`) when it runs out of room and XON (DC1, `0x11`)
+//! once it caught up. A [`SoftwareFlow`] holds both directions of it, as
+//! selected by `AT+IFC=<DCE_by_DTE>,<DTE_by_DCE>` (see [`IfcCommand`]):
+//!
+//! | `DCE_by_DTE` | XON/XOFF received |
+//! |---|---|
+//! | 0 | data |
+//! | 1 | pause and resume the output, removed from the data |
+//! | 2 | data (RTS, handled by the UART) |
+//! | 3 | pause and resume the output, passed on as data |
+//!
+//! | `DTE_by_DCE` | XON/XOFF sent |
+//! |---|---|
+//! | 0, 2 | never (2: CTS, handled by the UART) |
+//! | 1 | XOFF when the receive buffer is 3/4 full, XON once back to 1/4 |
+//!
+//! The receive side is an [`RxRing`](crate::ring::RxRing) split with
+//! [`split_with_flow`](crate::ring::RxRing::split_with_flow), the transmit
+//! side an [`NbSerial`](crate::serve::NbSerial) polled with
+//! `poll_with_flow`, or any driver checking
+//! [`is_paused`](SoftwareFlow::is_paused) and sending
+//! [`take_control`](SoftwareFlow::take_control) first.
+
+use core::sync::atomic::{AtomicBool, AtomicU8, Ordering};
+
+use crate::context::AtContext;
+use crate::{Args, AtError, AtResponse, AtResult, at_response};
+
+/// Resumes the output (DC1)
+pub const XON: u8 = 0x11;
+/// Pauses the output (DC3)
+pub const XOFF: u8 = 0x13;
+
+/// State of the software flow control of a link
+///
+/// Only uses atomic loads and stores, so it can be a `static` shared by the
+/// receive interrupt, the transmit path and [`IfcCommand`].
+///
+/// # Example
+///
+/// ```rust
+/// use at_parser_rs::flow::{SoftwareFlow, XOFF, XON};
+///
+/// static FLOW: SoftwareFlow = SoftwareFlow::new();
+/// assert!(FLOW.set_ifc(1, 1));
+///
+/// // receive interrupt
+/// assert!(!FLOW.receive(XOFF));   // flow control, not data
+/// assert!(FLOW.is_paused());
+/// FLOW.update(96, 128);           // receive buffer 3/4 full
+///
+/// // transmit path
+/// assert_eq!(FLOW.take_control(), Some(XOFF));
+/// FLOW.receive(XON);
+/// assert!(!FLOW.is_paused());
+/// ```
+#[derive(Debug, Default)]
+pub struct SoftwareFlow {
+    /// How the DTE controls the output, `+IFC` first parameter
+    dce_by_dte: AtomicU8,
+    /// How the input is throttled, `+IFC` second parameter
+    dte_by_dce: AtomicU8,
+    /// Whether the DTE sent XOFF
+    paused: AtomicBool,
+    /// Whether XOFF has been sent to the DTE
+    throttled: AtomicBool,
+    /// XON or XOFF waiting to be sent, 0 for none
+    control: AtomicU8,
+}
+
+impl SoftwareFlow {
+    /// Create the state of a link without flow control (`AT+IFC=0,0`).
+    pub const fn new() -> Self {
+        Self {
+            dce_by_dte: AtomicU8::new(0),
+            dte_by_dce: AtomicU8::new(0),
+            paused: AtomicBool::new(false),
+            throttled: AtomicBool::new(false),
+            control: AtomicU8::new(0),
+        }
+    }
+
+    /// Select the flow control of both directions, as `AT+IFC` does.
+    ///
+    /// Returns `false`, changing nothing, when a value is out of range.
+    /// Leaving in-band control resumes the output; leaving it while the
+    /// input is throttled sends XON.
+    pub fn set_ifc(&self, dce_by_dte: u8, dte_by_dce: u8) -> bool {
+        if dce_by_dte > 3 || dte_by_dce > 2 {
+            return false;
+        }
+        self.dce_by_dte.store(dce_by_dte, Ordering::Release);
+        if !self.pauses() {
+            self.paused.store(false, Ordering::Release);
+        }
+        self.dte_by_dce.store(dte_by_dce, Ordering::Release);
+        if dte_by_dce != 1 && self.throttled.swap(false, Ordering::AcqRel) {
+            self.control.store(XON, Ordering::Release);
+        }
+        true
+    }
+
+    /// Current `(DCE_by_DTE, DTE_by_DCE)` selection.
+    pub fn ifc(&self) -> (u8, u8) {
+        (self.dce_by_dte.load(Ordering::Acquire), self.dte_by_dce.load(Ordering::Acquire))
+    }
+
+    /// Report a byte received from the DTE, from the receive interrupt.
+    ///
+    /// Returns whether the byte is data, `false` for an XON or XOFF removed
+    /// from the stream.
+    pub fn receive(&self, byte: u8) -> bool {
+        if !self.pauses() || (byte != XON && byte != XOFF) {
+            return true;
+        }
+        self.paused.store(byte == XOFF, Ordering::Release);
+        self.dce_by_dte.load(Ordering::Acquire) == 3
+    }
+
+    /// Whether the DTE paused the output with XOFF.
+    pub fn is_paused(&self) -> bool {
+        self.paused.load(Ordering::Acquire)
+    }
+
+    /// Report the fill level of the receive buffer, `len` bytes out of
+    /// `capacity`, queueing XOFF when it reaches 3/4 and XON once it is
+    /// back to 1/4.
+    pub fn update(&self, len: usize, capacity: usize) {
+        if self.dte_by_dce.load(Ordering::Acquire) != 1 {
+            return;
+        }
+        let throttled = self.throttled.load(Ordering::Acquire);
+        if !throttled && len * 4 >= capacity * 3 {
+            self.throttled.store(true, Ordering::Release);
+            self.control.store(XOFF, Ordering::Release);
+        } else if throttled && len * 4 <= capacity {
+            self.throttled.store(false, Ordering::Release);
+            self.control.store(XON, Ordering::Release);
+        }
+    }
+
+    /// Take the XON or XOFF to send to the DTE, ahead of any output and
+    /// even while paused.
+    pub fn take_control(&self) -> Option<u8> {
+        match self.control.swap(0, Ordering::AcqRel) {
+            0 => None,
+            byte => Some(byte),
+        }
+    }
+
+    /// Queue `control` again when the transmitter could not take it after
+    /// [`take_control`](SoftwareFlow::take_control), unless another one has
+    /// been queued meanwhile.
+    pub fn restore_control(&self, control: u8) {
+        let _ = self.control.compare_exchange(0, control, Ordering::AcqRel, Ordering::Acquire);
+    }
+
+    /// Whether XON and XOFF received control the output.
+    fn pauses(&self) -> bool {
+        matches!(self.dce_by_dte.load(Ordering::Acquire), 1 | 3)
+    }
+}
+
+/// Handler of `AT+IFC`, selecting the flow control of a [`SoftwareFlow`]
+///
+/// Both parameters default to 2 (RTS/CTS) when omitted, as in V.250.
+///
+/// # Example
+///
+/// ```rust
+/// use at_parser_rs::flow::{IfcCommand, SoftwareFlow};
+/// use at_parser_rs::parser::AtParser;
+/// # const SIZE: usize = 64;
+///
+/// static FLOW: SoftwareFlow = SoftwareFlow::new();
+///
+/// let mut ifc = IfcCommand::new(&FLOW);
+/// let commands: &mut [(&str, &str, &mut IfcCommand)] = &mut [("AT+IFC", "+IFC: ", &mut ifc)];
+/// let mut parser: AtParser<IfcCommand, SIZE> = AtParser::new();
+/// parser.set_commands(commands);
+///
+/// assert!(parser.execute("AT+IFC=1,1").is_ok());
+/// assert!(parser.execute("AT+IFC=4").is_err());
+/// assert_eq!(FLOW.ifc(), (1, 1));
+/// ```
+#[derive(Debug)]
+pub struct IfcCommand<'f> {
+    /// Flow control selected
+    flow: &'f SoftwareFlow,
+}
+
+impl<'f> IfcCommand<'f> {
+    /// Create a handler selecting the flow control of `flow`.
+    pub const fn new(flow: &'f SoftwareFlow) -> Self {
+        Self { flow }
+    }
+}
+
+impl<const SIZE: usize, S, E> AtContext<SIZE, S, E> for IfcCommand<'_> {
+    fn query(&mut self, at_response: &'static str) -> AtResult<'_, SIZE, E> {
+        let (dce_by_dte, dte_by_dce) = self.flow.ifc();
+        Ok(at_response!(SIZE, at_response; dce_by_dte, dte_by_dce))
+    }
+
+    fn test(&mut self, at_response: &'static str) -> AtResult<'_, SIZE, E> {
+        Ok(at_response!(SIZE, at_response; "(0-3)", "(0-2)"))
+    }
+
+    fn set(&mut self, at_response: &'static str, args: Args) -> AtResult<'_, SIZE, E> {
+        let dce_by_dte = args.get_or(0, 2u8).map_err(|e| (at_response, e.into()))?;
+        let dte_by_dce = args.get_or(1, 2u8).map_err(|e| (at_response, e.into()))?;
+        if !self.flow.set_ifc(dce_by_dte, dte_by_dce) {
+            return Err((at_response, AtError::InvalidArgs));
+        }
+        Ok((at_response, AtResponse::Ok))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{SoftwareFlow, XOFF, XON};
+    use crate::ring::RxRing;
+
+    #[test]
+    fn ring_takes_flow_control_out_and_throttles_the_dte() {
+        let flow = SoftwareFlow::new();
+        let mut ring: RxRing<9> = RxRing::new();
+        let (mut producer, mut consumer) = ring.split_with_flow(&flow);
+        assert!(!flow.set_ifc(4, 0));
+
+        // Without flow control every byte is data
+        producer.push_from_isr(XOFF);
+        assert!(!flow.is_paused());
+        assert_eq!(consumer.pop(), Some(XOFF));
+
+        assert!(flow.set_ifc(1, 1));
+        assert!(producer.push_from_isr(XOFF));
+        assert!(flow.is_paused());
+        assert_eq!(producer.push_slice_from_isr(b"AT+CGMR"), 7);
+        assert_eq!(flow.take_control(), Some(XOFF));
+        assert!(producer.push_from_isr(XON));
+        assert!(!flow.is_paused());
+
+        for _ in 0..5 {
+            consumer.pop();
+        }
+        assert_eq!(flow.take_control(), Some(XON));
+        assert_eq!(flow.take_control(), None);
+
+        // Passed through, and throttling ends with the selection
+        assert!(flow.set_ifc(3, 1));
+        producer.push_from_isr(XOFF);
+        assert!(flow.is_paused());
+        producer.push_slice_from_isr(b"abcd");
+        assert_eq!(flow.take_control(), Some(XOFF));
+        assert!(flow.set_ifc(0, 0));
+        assert!(!flow.is_paused());
+        assert_eq!(flow.take_control(), Some(XON));
+        assert_eq!(consumer.pop(), Some(b'M'));
+    }
+}
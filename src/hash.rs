@@ -0,0 +1,336 @@
+/***************************************************************************
+ *
+ * AT Command Parser
+ * Copyright (C) 2026 Antonio Salsi <passy.linux@zresa.it>
+ *
+ * This library is free software; you can redistribute it and/or
+ * modify it under the terms of the GNU Lesser General Public
+ * License as published by the Free Software Foundation; either
+ * version 2.1 of the License, or (at your option) any later version.
+ *
+ * This library is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the GNU
+ * Lesser General Public License for more details.
+ *
+ * You should have received a copy of the GNU Lesser General Public
+ * License along with this library; if not, see <https://www.gnu.org/licenses/>.
+ *
+ ***************************************************************************/
+
+ 
+//! Perfect-hash command lookup
+//!
+//! [`CommandHash`] is a perfect hash over the names of a command table,
+//! computed in a constant expression: every registered name maps to
+//! its own slot, so [`AtParser::set_hashed_commands`](crate::parser::AtParser::set_hashed_commands)
+//! resolves a command with one hash and a single name comparison, whatever
+//! the size of the table. [`at_hashed_modules!`](crate::at_hashed_modules)
+//! builds the table and its hash together.
+//!
+//! The hash follows the hash-and-displace scheme: names are spread into
+//! buckets of about four, and each bucket gets the displacement placing its
+//! names into free slots. Names are hashed without their `AT` prefix and
+//! folded to upper case, so the same hash serves case-insensitive matching.
+
+use crate::parser::{compare_names, strip_at_prefix};
+
+/// Marks a free slot.
+const EMPTY: u16 = u16::MAX;
+
+/// Average number of names per bucket.
+const BUCKET_SIZE: usize = 4;
+
+/// Seeds tried before giving up.
+const MAX_SEEDS: u64 = 64;
+
+/// Perfect hash over the command names of a table, spread into `SLOTS`
+/// slots.
+///
+/// `SLOTS` is at least the number of names; spare slots make the hash faster
+/// to compute, and [`at_hashed_modules!`](crate::at_hashed_modules) reserves
+/// twice as many slots as names. Built in a constant expression with
+/// [`CommandHash::new`].
+pub struct CommandHash<const SLOTS: usize> {
+    /// Seed of the hash function
+    seed: u64,
+    /// Number of names
+    len: usize,
+    /// Number of buckets
+    buckets: usize,
+    /// Displacements of each bucket
+    displacements: [(u16, u16); SLOTS],
+    /// Index in the table of the name stored at each slot, or [`EMPTY`]
+    slots: [u16; SLOTS],
+}
+
+impl<const SLOTS: usize> CommandHash<SLOTS> {
+    /// Compute the hash of `names`, given in table order.
+    ///
+    /// # Panics
+    ///
+    /// If `names` holds more than `SLOTS` names, or two of them differ only
+    /// in case or `AT` prefix; in a constant expression this fails the build.
+    pub const fn new(names: &[&str]) -> Self {
+        assert!(names.len() <= SLOTS, "the hash needs a slot for every command of the table");
+        assert!(SLOTS < EMPTY as usize, "too many commands for a hashed table");
+
+        let mut seed = 0;
+        while seed < MAX_SEEDS {
+            if let Some(hash) = Self::with_seed(names, seed) {
+                return hash;
+            }
+            seed += 1;
+        }
+        panic!("no perfect hash found for the command names");
+    }
+
+    /// Place every name with the hash function seeded with `seed`, if possible.
+    const fn with_seed(names: &[&str], seed: u64) -> Option<Self> {
+        let len = names.len();
+        let buckets = len.div_ceil(BUCKET_SIZE);
+        let mut keys = [(0u32, 0u32, 0u32); SLOTS];
+        let mut sizes = [0usize; SLOTS];
+        let mut i = 0;
+        while i < len {
+            keys[i] = hash(seed, strip_at_prefix(names[i].as_bytes()));
+            sizes[keys[i].0 as usize % buckets] += 1;
+            i += 1;
+        }
+
+        // Names grouped by bucket: those of bucket `b` start at `first[b]`
+        let mut first = [0usize; SLOTS];
+        let mut b = 1;
+        while b < buckets {
+            first[b] = first[b - 1] + sizes[b - 1];
+            b += 1;
+        }
+        let mut members = [0u16; SLOTS];
+        let mut filled = [0usize; SLOTS];
+        let mut i = 0;
+        while i < len {
+            let b = keys[i].0 as usize % buckets;
+            members[first[b] + filled[b]] = i as u16;
+            filled[b] += 1;
+            i += 1;
+        }
+
+        // Equal names always share a bucket
+        let mut b = 0;
+        while b < buckets {
+            let mut m = first[b];
+            while m < first[b] + sizes[b] {
+                let mut other = m + 1;
+                while other < first[b] + sizes[b] {
+                    let (x, y) = (names[members[m] as usize], names[members[other] as usize]);
+                    if compare_names(strip_at_prefix(x.as_bytes()), strip_at_prefix(y.as_bytes()), true).is_eq() {
+                        panic!("duplicate command name in a hashed table");
+                    }
+                    other += 1;
+                }
+                m += 1;
+            }
+            b += 1;
+        }
+
+        let mut slots = [EMPTY; SLOTS];
+        let mut displacements = [(0u16, 0u16); SLOTS];
+        let mut done = [false; SLOTS];
+        let mut round = 0;
+        // Largest buckets first, while most slots are still free
+        while round < buckets {
+            let mut bucket = usize::MAX;
+            let mut b = 0;
+            while b < buckets {
+                if !done[b] && (bucket == usize::MAX || sizes[b] > sizes[bucket]) {
+                    bucket = b;
+                }
+                b += 1;
+            }
+            done[bucket] = true;
+            round += 1;
+
+            let (start, end) = (first[bucket], first[bucket] + sizes[bucket]);
+            let mut placed = start == end;
+            let mut d1 = 0;
+            'search: while !placed && d1 < SLOTS {
+                let mut d2 = 0;
+                while d2 < SLOTS {
+                    let displacement = (d1 as u16, d2 as u16);
+                    let mut m = start;
+                    while m < end {
+                        let slot = slot(keys[members[m] as usize], displacement, SLOTS);
+                        if slots[slot] != EMPTY {
+                            break;
+                        }
+                        slots[slot] = members[m];
+                        m += 1;
+                    }
+                    if m == end {
+                        displacements[bucket] = displacement;
+                        placed = true;
+                        break 'search;
+                    }
+
+                    // Undo the names of the bucket placed by this attempt
+                    while m > start {
+                        m -= 1;
+                        slots[slot(keys[members[m] as usize], displacement, SLOTS)] = EMPTY;
+                    }
+                    d2 += 1;
+                }
+                d1 += 1;
+            }
+            if !placed {
+                return None;
+            }
+        }
+
+        Some(Self { seed, len, buckets, displacements, slots })
+    }
+
+    /// Number of names covered by the hash.
+    pub const fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Whether the hash covers no name.
+    pub const fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Borrowed view consulted by the parser.
+    pub(crate) fn index(&self) -> HashIndex<'_> {
+        HashIndex { seed: self.seed, displacements: &self.displacements[..self.buckets], slots: &self.slots }
+    }
+}
+
+/// Borrowed [`CommandHash`], independent of the table size.
+#[derive(Clone, Copy)]
+pub(crate) struct HashIndex<'h> {
+    /// Seed of the hash function
+    seed: u64,
+    /// Displacements of each bucket
+    displacements: &'h [(u16, u16)],
+    /// Index in the table of the name stored at each slot
+    slots: &'h [u16],
+}
+
+impl HashIndex<'_> {
+    /// Index of the only table entry `name` (without `AT`) can match.
+    ///
+    /// The caller compares the entry name, as any other name hashes to some
+    /// slot too.
+    pub(crate) fn candidate(&self, name: &str) -> Option<usize> {
+        if self.displacements.is_empty() {
+            return None;
+        }
+        let key = hash(self.seed, name.as_bytes());
+        let displacement = self.displacements[key.0 as usize % self.displacements.len()];
+        match self.slots[slot(key, displacement, self.slots.len())] {
+            EMPTY => None,
+            index => Some(index as usize),
+        }
+    }
+}
+
+/// Bucket selector and the two slot hashes of `name`, folded to upper case.
+const fn hash(seed: u64, name: &[u8]) -> (u32, u32, u32) {
+    // FNV-1a, finalized with the MurmurHash3 mixer
+    let mut h = 0xcbf2_9ce4_8422_2325 ^ seed.wrapping_mul(0x9e37_79b9_7f4a_7c15);
+    let mut i = 0;
+    while i < name.len() {
+        h ^= name[i].to_ascii_uppercase() as u64;
+        h = h.wrapping_mul(0x0100_0000_01b3);
+        i += 1;
+    }
+    h ^= h >> 33;
+    h = h.wrapping_mul(0xff51_afd7_ed55_8ccd);
+    h ^= h >> 33;
+    h = h.wrapping_mul(0xc4ce_b9fe_1a85_ec53);
+    h ^= h >> 33;
+    ((h >> 32) as u32, h as u32, (h.wrapping_mul(0x9e37_79b9_7f4a_7c15) >> 32) as u32)
+}
+
+/// Slot of a name displaced by `(d1, d2)` among `len` slots.
+const fn slot(key: (u32, u32, u32), (d1, d2): (u16, u16), len: usize) -> usize {
+    ((key.1 as u64 + d1 as u64 * key.2 as u64 + d2 as u64) % len as u64) as usize
+}
+
+/// Build a command table like [`at_modules!`](crate::at_modules), together
+/// with its [`CommandHash`] computed at compile time, for
+/// [`set_hashed_commands`](crate::parser::AtParser::set_hashed_commands).
+///
+/// The command names must be constant expressions.
+///
+/// # Example
+///
+/// ```rust
+/// use at_parser_rs::at_hashed_modules;
+/// use at_parser_rs::context::AtContext;
+/// use at_parser_rs::parser::DynAtParser;
+/// use at_parser_rs::{AtResult, at_response};
+///
+/// const SIZE: usize = 64;
+///
+/// struct Info(&'static str);
+/// impl AtContext<SIZE> for Info {
+///     fn exec(&mut self, at_response: &'static str) -> AtResult<'_, SIZE> {
+///         Ok(at_response!(SIZE, at_response; self.0))
+///     }
+/// }
+///
+/// let mut maker = Info("ACME");
+/// let mut model = Info("M1");
+///
+/// let (commands, hash) = at_hashed_modules! {
+///     SIZE;
+///     ("AT+CGMM", "+CGMM: ") => model,
+///     ("AT+CGMI", "+CGMI: ") => maker,
+/// };
+///
+/// let mut parser: DynAtParser<SIZE> = DynAtParser::new();
+/// parser.set_hashed_commands(commands, hash);
+/// assert!(parser.execute("AT+CGMI").is_ok());
+/// ```
+#[macro_export]
+macro_rules! at_hashed_modules {
+    ($size:expr $(, $ty:ty)*; $( ($name:expr, $at_resp:expr) => $module:expr ),* $(,)?) => {{
+        const NAMES: &[&str] = &[$($name),*];
+        const HASH: $crate::hash::CommandHash<{ 2 * NAMES.len() }> = $crate::hash::CommandHash::new(NAMES);
+        ($crate::at_modules!($size $(, $ty)*; $( ($name, $at_resp) => $module ),*), &HASH)
+    }};
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NAMES: &[&str] = &[
+        "AT+CGMI", "AT+CGMM", "AT+CGMR", "AT+CGSN", "ATE", "ATV", "ATQ", "AT&F", "AT+CMEE", "AT+CPIN",
+        "AT+CREG", "AT+CSQ", "AT+COPS", "AT+CGATT", "AT+CGACT", "AT+CGDCONT", "AT+CMGF", "AT+CMGS", "AT+CMGR", "AT+CMGL",
+    ];
+
+    #[test]
+    fn every_name_has_its_own_slot() {
+        const HASH: CommandHash<40> = CommandHash::new(NAMES);
+        let index = HASH.index();
+        for (i, name) in NAMES.iter().enumerate() {
+            let name = &name[2..];
+            assert_eq!(index.candidate(name), Some(i));
+            assert_eq!(index.candidate(&name.to_ascii_lowercase()), Some(i));
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "duplicate command name")]
+    fn duplicate_names_are_rejected() {
+        CommandHash::<8>::new(&["AT+CGMI", "AT+CSQ", "+cgmi"]);
+    }
+
+    #[test]
+    fn empty_tables_have_no_candidate() {
+        const HASH: CommandHash<0> = CommandHash::new(&[]);
+        assert_eq!(HASH.index().candidate("+CGMI"), None);
+    }
+}
@@ -0,0 +1,156 @@
+/***************************************************************************
+ *
+ * AT Command Parser
+ * Copyright (C) 2026 Antonio Salsi <passy.linux@zresa.it>
+ *
+ * This library is free software; you can redistribute it and/or
+ * modify it under the terms of the GNU Lesser General Public
+ * License as published by the Free Software Foundation; either
+ * version 2.1 of the License, or (at your option) any later version.
+ *
+ * This library is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the GNU
+ * Lesser General Public License for more details.
+ *
+ * You should have received a copy of the GNU Lesser General Public
+ * License along with this library; if not, see <https://www.gnu.org/licenses/>.
+ *
+ ***************************************************************************/
+
+ 
+//! Command help tables
+//!
+//! A [`Help`] lists the registered commands with a short description each.
+//! [`at_modules!`](crate::at_modules) builds it from the doc comments of the
+//! table entries when asked with `help;`, and once installed with
+//! [`AtParser::set_help`](crate::parser::AtParser::set_help) every handler
+//! reaches it through [`Responder::help`], so an `AT+CLAC` or `AT+HELP`
+//! handler lists the commands with a single call.
+//!
+//! # Example
+//!
+//! ```rust
+//! use at_parser_rs::at_modules;
+//! use at_parser_rs::context::AtContext;
+//! use at_parser_rs::parser::DynAtParser;
+//! use at_parser_rs::response::Responder;
+//! use at_parser_rs::{AtError, AtResponse, AtResult};
+//!
+//! const SIZE: usize = 64;
+//!
+//! struct Clac;
+//! impl AtContext<SIZE> for Clac {
+//!     fn exec_with(&mut self, at_response: &'static str, responder: &mut Responder<'_>) -> AtResult<'_, SIZE> {
+//!         let help = responder.help().ok_or((at_response, AtError::NotSupported))?;
+//!         help.list(responder).map_err(|_| (at_response, AtError::InvalidArgs))?;
+//!         Ok((at_response, AtResponse::Ok))
+//!     }
+//! }
+//!
+//! struct Reset;
+//! impl AtContext<SIZE> for Reset {}
+//!
+//! let (mut clac, mut reset) = (Clac, Reset);
+//! let (commands, help) = at_modules! {
+//!     SIZE; help;
+//!     /// List the available commands
+//!     ("AT+CLAC", "") => clac,
+//!     /// Restart the device
+//!     ("AT+RST", "") => reset,
+//! };
+//! assert_eq!(help.description("AT+RST"), Some("Restart the device"));
+//!
+//! let mut out = String::new();
+//! let mut parser: DynAtParser<SIZE> = DynAtParser::new();
+//! parser.set_commands(commands);
+//! parser.set_help(help);
+//! parser.set_output(&mut out);
+//! parser.set_response_framing(true);
+//! parser.feed_slice(b"AT+CLAC\r");
+//! # drop(parser);
+//! assert_eq!(out, "\r\nAT+CLAC\r\nAT+RST\r\n\r\nOK\r\n");
+//! ```
+
+use core::fmt;
+
+use crate::response::Responder;
+
+/// One command of a [`Help`] table
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Topic {
+    /// Command name, as registered (`"AT+RST"`)
+    pub command: &'static str,
+    /// Short description, possibly empty
+    pub description: &'static str,
+}
+
+/// Commands with their descriptions, in registration order
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Help {
+    topics: &'static [Topic],
+}
+
+impl Help {
+    /// Help table of the given commands.
+    pub const fn new(topics: &'static [Topic]) -> Self {
+        Self { topics }
+    }
+
+    /// Every command, in registration order.
+    pub fn topics(&self) -> &'static [Topic] {
+        self.topics
+    }
+
+    /// Description of `command`, if listed.
+    pub fn description(&self, command: &str) -> Option<&'static str> {
+        self.topics.iter().find(|topic| topic.command == command).map(|topic| topic.description.trim())
+    }
+
+    /// Send one information line per command name, as answered by
+    /// `AT+CLAC`.
+    pub fn list<S>(&self, responder: &mut Responder<'_, S>) -> fmt::Result {
+        for topic in self.topics {
+            responder.info(topic.command)?;
+        }
+        Ok(())
+    }
+
+    /// Send one information line per command with its description
+    /// (`AT+RST: Restart the device`), for an `AT+HELP` command.
+    pub fn describe<S>(&self, responder: &mut Responder<'_, S>) -> fmt::Result {
+        for topic in self.topics {
+            match topic.description.trim() {
+                "" => responder.info(topic.command)?,
+                description => responder.info(format_args!("{}: {description}", topic.command))?,
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::string::String;
+
+    use super::{Help, Topic};
+    use crate::response::{Framing, Responder};
+
+    const HELP: Help = Help::new(&[
+        Topic { command: "AT+CGMI", description: " Manufacturer" },
+        Topic { command: "AT+CLAC", description: "" },
+    ]);
+
+    #[test]
+    fn commands_are_described_in_order() {
+        let mut buf = [0u8; 8];
+        let mut out = String::new();
+        {
+            let mut responder = Responder::with_output(&mut buf, &mut out, Framing::new());
+            HELP.describe(&mut responder).unwrap();
+        }
+        assert_eq!(out, "\r\nAT+CGMI: Manufacturer\r\nAT+CLAC\r\n");
+        assert_eq!(HELP.description("AT+CGMI"), Some("Manufacturer"));
+        assert_eq!(HELP.description("AT+CGMR"), None);
+    }
+}
@@ -83,10 +83,52 @@
 //!
 //! # Features
 //!
-//! - **`freertos`** (default) — Enable FreeRTOS support via osal-rs
-//! - **`posix`** — Enable POSIX (Linux/macOS) threading support via osal-rs
-//! - **`std`** — Enable standard library support via osal-rs
+//! - **`freertos`** (default) — Enable FreeRTOS support via osal-rs, and a
+//!   ready-made task serving the parser over osal-rs queues (see `task`)
+//! - **`posix`** — Enable POSIX (Linux/macOS) threading support via osal-rs,
+//!   with the same task
+//! - **`std`** — Enable standard library support via osal-rs,
+//!   `AtParser::serve_io` over `std::io` streams (see `serve`), and for
+//!   host-side tools and simulators, commands registered at run time in a
+//!   [`Registry`](registry::Registry) and information text of any length in
+//!   `AtResponse::TextOwned`. With the published osal-rs 0.4.8, combine it
+//!   with `posix`: `std` alone selects no osal-rs backend and does not build
 //! - **`disable_panic`** — Pass-through feature to osal-rs; disables the built-in panic handler
+//! - **`heapless`** — Build response payloads in a `heapless::String` (see `owned`),
+//!   and typed command builders on the DTE side (see `client`)
+//! - **`async`** — Async handlers ([`AsyncAtContext`](context::AsyncAtContext)) dispatched by
+//!   [`AtParser::execute_async`](parser::AtParser::execute_async)
+//! - **`derive`** — The `at_context` attribute macro generating
+//!   [`AtContext`](context::AtContext) implementations, `#[derive(AtResponse)]`
+//!   for typed information responses, and `at_command`
+//!   registering handlers for [`collect_commands!`]
+//! - **`defmt`** — `defmt::Format` for [`AtError`], [`ArgError`], the command
+//!   [`Form`](context::Form) and [`ResultCode`](response::ResultCode),
+//!   logging parse failures without `core::fmt`
+//! - **`perfect-hash`** — Command lookup through a perfect hash computed at
+//!   compile time (see `hash`)
+//! - **`embedded-io`** — `AtParser::serve`, serving commands over any
+//!   `embedded_io::Read` / `Write` transport (see `serve`)
+//! - **`embedded-io-async`** — `AtParser::serve_async`, the same loop over
+//!   `embedded_io_async` drivers, for Embassy tasks (implies `async` and `embedded-io`)
+//! - **`embedded-hal-nb`** — `serve::NbSerial`, polling `nb` serial ports
+//!   without blocking
+//! - **`usbd-serial`** — `serve::UsbCdc`, serving commands over a USB CDC-ACM
+//!   class of `usb-device`, in packets
+//! - **`cmux`** — The 3GPP TS 27.010 multiplexer of `AT+CMUX` (see `cmux`),
+//!   serving a parser per channel over one UART
+//!
+//! The following features trade functionality for flash, on targets with a
+//! few kilobytes to spare:
+//!
+//! - **`exec-set-only`** — Leave out the query and test forms: `AT+CMD?` and
+//!   `AT+CMD=?` are answered with [`AtError::NotSupported`] without reaching
+//!   the handlers, built-in commands included
+//! - **`basic-errors`** — Report `+CME ERROR`, `+CMS ERROR` and vendor
+//!   errors as a plain `ERROR`, leaving out their formatting
+//! - **`no-error-text`** — Leave out the verbose descriptions of `+CME` /
+//!   `+CMS` error codes: `AT+CMEE=2` reports numbers, as `AT+CMEE=1` does
+//! - **`minimal`** — All of the above
 //!
 //! # Thread Safety
 //!
@@ -97,32 +139,324 @@
 #![no_std]
 
 extern crate alloc;
+#[cfg(feature = "std")]
+extern crate std;
 extern crate osal_rs;
+// Lets the code generated by the derive macros name this crate from within it
+#[cfg(feature = "derive")]
+extern crate self as at_parser_rs;
 
+use core::convert::Infallible;
+use core::fmt;
+use core::net::{IpAddr, Ipv4Addr, Ipv6Addr};
 use core::option::Option;
 use core::result::Result;
+use core::str::FromStr;
 
 use alloc::borrow::Cow;
 use alloc::string::String;
 use osal_rs::utils::Bytes;
 
+pub mod abort;
+pub mod client;
+pub mod cme;
+pub mod closure;
+#[cfg(feature = "cmux")]
+pub mod cmux;
+pub mod cms;
 pub mod context;
+pub mod diagnostic;
+pub mod direction;
+pub mod fixed;
+pub mod flow;
+#[cfg(feature = "perfect-hash")]
+pub mod hash;
+pub mod help;
+pub mod line;
+pub mod mode;
+pub mod online;
+#[cfg(feature = "heapless")]
+pub mod owned;
 pub mod parser;
+pub mod profile;
+pub mod registry;
+pub mod response;
+pub mod ring;
+pub mod schema;
+#[cfg(any(feature = "embedded-io", feature = "embedded-hal-nb", feature = "usbd-serial", feature = "std"))]
+pub mod serve;
+pub mod sreg;
+#[cfg(any(feature = "freertos", feature = "posix"))]
+pub mod task;
+pub mod test_response;
+
+/// Implement [`AtContext`](context::AtContext) from the methods of an `impl`
+/// block, behind the `derive` feature.
+///
+/// Mark one method per command form with `#[exec]`, `#[query]`, `#[test]` or
+/// `#[set]`; the generated trait implementation forwards to it. A method
+/// taking a trailing [`Responder`](response::Responder) parameter is wired
+/// into the `*_with` variant of its form.
+///
+/// The attribute takes the response `SIZE`, optionally followed by the user
+/// state and error types of [`AtContext`](context::AtContext):
+/// `#[at_context(SIZE)]`, `#[at_context(SIZE, Driver)]`,
+/// `#[at_context(SIZE, Driver, ModemError)]`.
+///
+/// # Example
+///
+/// ```rust
+/// use at_parser_rs::parser::AtParser;
+/// use at_parser_rs::{Args, AtResponse, AtResult, at_context, at_response};
+///
+/// const SIZE: usize = 64;
+///
+/// struct Volume { level: u8 }
+///
+/// #[at_context(SIZE)]
+/// impl Volume {
+///     #[query]
+///     fn level(&mut self, at_response: &'static str) -> AtResult<'_, SIZE> {
+///         Ok(at_response!(SIZE, at_response; self.level))
+///     }
+///
+///     #[set]
+///     fn set_level(&mut self, at_response: &'static str, args: Args) -> AtResult<'_, SIZE> {
+///         self.level = args.get_u8(0).map_err(|e| (at_response, e.into()))?;
+///         Ok((at_response, AtResponse::Ok))
+///     }
+/// }
+///
+/// let mut volume = Volume { level: 3 };
+/// let commands: &mut [(&str, &str, &mut Volume)] = &mut [("AT+CLVL", "+CLVL: ", &mut volume)];
+/// let mut parser: AtParser<Volume, SIZE> = AtParser::new();
+/// parser.set_commands(commands);
+/// assert!(parser.execute("AT+CLVL=5").is_ok());
+/// ```
+#[cfg(feature = "derive")]
+pub use at_parser_derive::at_context;
+
+/// Register a handler type for a command where it is declared.
+///
+/// `#[at_command(SIZE, "+GMR")]` registers the type for `AT+GMR`, with the
+/// `+GMR: ` response prefix; a third argument sets another prefix. The type
+/// implements `AtContext<SIZE>` and [`Default`], which creates it when the
+/// commands are gathered by [`collect_commands!`]; see the
+/// [`registry`](crate::registry) module.
+///
+/// # Example
+///
+/// ```rust
+/// use at_parser_rs::context::AtContext;
+/// use at_parser_rs::parser::DynAtParser;
+/// use at_parser_rs::{AtResult, at_command, at_response, collect_commands};
+///
+/// const SIZE: usize = 64;
+///
+/// #[at_command(SIZE, "+GMR")]
+/// #[derive(Default)]
+/// struct Revision;
+///
+/// impl AtContext<SIZE> for Revision {
+///     fn exec(&mut self, at_response: &'static str) -> AtResult<'_, SIZE> {
+///         Ok(at_response!(SIZE, at_response; "1.0"))
+///     }
+/// }
+///
+/// let mut registry = collect_commands!(SIZE);
+/// let mut commands = registry.commands();
+/// let mut parser: DynAtParser<SIZE> = DynAtParser::new();
+/// parser.set_commands(&mut commands);
+/// assert!(parser.execute("AT+GMR").is_ok());
+/// ```
+#[cfg(feature = "derive")]
+pub use at_parser_derive::at_command;
+
+/// Parse information responses into a struct with typed fields.
+///
+/// `#[derive(AtResponse)]` implements [`FromArgs`] with one argument per
+/// field, in order, extracted as by [`Args::parse`]: `&str` and `Cow<str>`
+/// fields take quoted strings, `Option` fields accept omitted or missing
+/// arguments. Naming the response with `#[at_response("+CREG")]` also
+/// implements [`client::FromResponse`], parsing whole lines.
+///
+/// # Example
+///
+/// ```rust
+/// use at_parser_rs::client::FromResponse;
+/// use at_parser_rs::{ArgError, Args, AtResponse};
+///
+/// #[derive(AtResponse, Debug, PartialEq)]
+/// #[at_response("+CREG")]
+/// struct Creg<'a> {
+///     mode: u8,
+///     stat: u8,
+///     lac: Option<&'a str>,
+/// }
+///
+/// let creg = Creg::from_line(r#"+CREG: 2,1,"00C3""#).unwrap();
+/// assert_eq!(creg, Ok(Creg { mode: 2, stat: 1, lac: Some("00C3") }));
+/// assert_eq!(Creg::from_line("+CREG: 0,1").unwrap().map(|creg| creg.lac), Ok(None));
+/// assert!(Creg::from_line("+CSQ: 23,99").is_none());
+/// assert_eq!(Args::new("0,1,,4").parse::<Creg>(), Err(ArgError::Unexpected(3)));
+/// ```
+#[cfg(feature = "derive")]
+pub use at_parser_derive::AtResponse;
 
 
 /// Error types that can occur during AT command processing
+///
+/// The type parameter `E` carries the application's own errors in
+/// [`AtError::User`], see [`UserError`]; it defaults to
+/// [`Infallible`] when the application has none.
 #[derive(Debug)]
-pub enum AtError<'a> {
+pub enum AtError<'a, E = Infallible> {
     /// The command is not recognized
     UnknownCommand,
     /// The command is recognized but not supported
     NotSupported,
     /// The command arguments are invalid
     InvalidArgs,
+    /// The argument the error points at is invalid; reported as
+    /// [`InvalidArgs`](AtError::InvalidArgs), with the parameter index kept
+    /// for the [`diagnostic`] of the line
+    Argument(ArgError),
     /// Unhandled error with description
     Unhandled(&'a str),
     /// Unhandled error with description owned
-    UnhandledOwned(String)
+    UnhandledOwned(String),
+    /// Mobile equipment error reported as `+CME ERROR: <code>` (see [`cme`])
+    Cme(u16),
+    /// Message service error reported as `+CMS ERROR: <code>` (see [`cms`])
+    Cms(u16),
+    /// Error of the product's own error space, with an optional description;
+    /// reported as `+CME ERROR: <code>` or in a vendor format, see
+    /// [`Framing::set_vendor_errors`](response::Framing::set_vendor_errors)
+    Vendor(u16, Option<&'static str>),
+    /// The command ended with a final result code other than `OK` / `ERROR`
+    /// (e.g. `NO CARRIER`, `BUSY`)
+    Final(response::ResultCode),
+    /// The command was aborted by a character received while it executed
+    /// (see [`abort`])
+    Aborted,
+    /// The command line exceeded the
+    /// [maximum length](parser::AtParser::set_line_limit) and was discarded
+    LineTooLong,
+    /// Application-defined error, reported as [`UserError::report`] says
+    User(E),
+}
+
+/// Application-defined error carried by [`AtError::User`].
+///
+/// Lets handlers surface domain errors (`SimNotReady`, `FlashBusy`) to the
+/// application while the framing layer reports them with one of the
+/// built-in result codes.
+///
+/// # Example
+///
+/// ```rust
+/// use at_parser_rs::{AtError, UserError, cme};
+///
+/// #[derive(Debug)]
+/// enum ModemError { SimNotReady, FlashBusy }
+///
+/// impl UserError for ModemError {
+///     fn report(&self) -> AtError<'static> {
+///         match self {
+///             ModemError::SimNotReady => AtError::Cme(cme::SIM_NOT_INSERTED),
+///             ModemError::FlashBusy => AtError::Final(at_parser_rs::response::ResultCode::Error),
+///         }
+///     }
+/// }
+/// ```
+pub trait UserError {
+    /// Built-in error reported in place of this one: `+CME ERROR: <n>`, a
+    /// final result code, ... Defaults to `ERROR`.
+    fn report(&self) -> AtError<'static> {
+        AtError::Final(response::ResultCode::Error)
+    }
+}
+
+impl UserError for Infallible {
+    fn report(&self) -> AtError<'static> {
+        match *self {}
+    }
+}
+
+impl<E> AtError<'_, E> {
+    /// Convert into an error that does not borrow from the handler.
+    ///
+    /// [`AtError::Unhandled`] descriptions are copied into
+    /// [`AtError::UnhandledOwned`]; every other variant is returned unchanged.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use at_parser_rs::AtError;
+    ///
+    /// let error: AtError<'static> = AtError::Unhandled("busy").into_owned();
+    /// assert!(matches!(error, AtError::UnhandledOwned(ref msg) if msg == "busy"));
+    /// ```
+    pub fn into_owned(self) -> AtError<'static, E> {
+        match self {
+            AtError::UnknownCommand => AtError::UnknownCommand,
+            AtError::NotSupported => AtError::NotSupported,
+            AtError::InvalidArgs => AtError::InvalidArgs,
+            AtError::Argument(e) => AtError::Argument(e),
+            AtError::Unhandled(msg) => AtError::UnhandledOwned(String::from(msg)),
+            AtError::UnhandledOwned(msg) => AtError::UnhandledOwned(msg),
+            AtError::Cme(code) => AtError::Cme(code),
+            AtError::Cms(code) => AtError::Cms(code),
+            AtError::Vendor(code, text) => AtError::Vendor(code, text),
+            AtError::Final(code) => AtError::Final(code),
+            AtError::Aborted => AtError::Aborted,
+            AtError::LineTooLong => AtError::LineTooLong,
+            AtError::User(e) => AtError::User(e),
+        }
+    }
+}
+
+impl<'a> AtError<'a> {
+    /// Widen an error raised by the parser itself into one of the error type
+    /// of the handlers.
+    pub(crate) fn widen<E>(self) -> AtError<'a, E> {
+        match self {
+            AtError::UnknownCommand => AtError::UnknownCommand,
+            AtError::NotSupported => AtError::NotSupported,
+            AtError::InvalidArgs => AtError::InvalidArgs,
+            AtError::Argument(e) => AtError::Argument(e),
+            AtError::Unhandled(msg) => AtError::Unhandled(msg),
+            AtError::UnhandledOwned(msg) => AtError::UnhandledOwned(msg),
+            AtError::Cme(code) => AtError::Cme(code),
+            AtError::Cms(code) => AtError::Cms(code),
+            AtError::Vendor(code, text) => AtError::Vendor(code, text),
+            AtError::Final(code) => AtError::Final(code),
+            AtError::Aborted => AtError::Aborted,
+            AtError::LineTooLong => AtError::LineTooLong,
+            AtError::User(never) => match never {},
+        }
+    }
+}
+
+#[cfg(feature = "defmt")]
+impl<E: defmt::Format> defmt::Format for AtError<'_, E> {
+    fn format(&self, f: defmt::Formatter<'_>) {
+        match self {
+            AtError::UnknownCommand => defmt::write!(f, "UnknownCommand"),
+            AtError::NotSupported => defmt::write!(f, "NotSupported"),
+            AtError::InvalidArgs => defmt::write!(f, "InvalidArgs"),
+            AtError::Argument(e) => defmt::write!(f, "Argument({})", e),
+            AtError::Unhandled(msg) => defmt::write!(f, "Unhandled({=str})", msg),
+            AtError::UnhandledOwned(msg) => defmt::write!(f, "Unhandled({=str})", msg.as_str()),
+            AtError::Cme(code) => defmt::write!(f, "Cme({=u16})", code),
+            AtError::Cms(code) => defmt::write!(f, "Cms({=u16})", code),
+            AtError::Vendor(code, _) => defmt::write!(f, "Vendor({=u16})", code),
+            AtError::Final(code) => defmt::write!(f, "Final({})", code),
+            AtError::Aborted => defmt::write!(f, "Aborted"),
+            AtError::LineTooLong => defmt::write!(f, "LineTooLong"),
+            AtError::User(e) => defmt::write!(f, "User({})", e),
+        }
+    }
 }
 
 /// Result type for AT command operations.
@@ -131,39 +465,350 @@ pub enum AtError<'a> {
 /// (`&'static str`) that was registered alongside the command, so callers can always
 /// reconstruct the full response line.
 ///
-/// - `Ok((prefix, bytes))` — successful response with the AT prefix and payload
+/// - `Ok((prefix, response))` — successful response with the AT prefix, see [`AtResponse`](enum@AtResponse)
 /// - `Err((prefix, error))` — failure with the AT prefix and error kind
-pub type AtResult<'a, const SIZE: usize> = Result<(&'static str, Bytes<SIZE>), (&'static str, AtError<'a>)>;
+///
+/// The lifetime `'a` lets both borrow for the duration of one call: the
+/// handler's own buffers through [`AtResponse::Text`], or the parser's
+/// response buffer for the text written to a
+/// [`Responder`](response::Responder).
+pub type AtResult<'a, const SIZE: usize, E = Infallible> = Result<(&'static str, AtResponse<'a, SIZE>), (&'static str, AtError<'a, E>)>;
+
+/// Convert `result` into one that does not borrow, see
+/// [`AtResponse::into_owned`] and [`AtError::into_owned`].
+pub fn into_owned<const SIZE: usize, E>(result: AtResult<'_, SIZE, E>) -> AtResult<'static, SIZE, E> {
+    result
+        .map(|(at_response, response)| (at_response, response.into_owned()))
+        .map_err(|(at_response, e)| (at_response, e.into_owned()))
+}
+
+/// Successful outcome of an AT command.
+///
+/// Tells the framing layer and callers what to send without relying on
+/// string conventions. [`at_response!`] builds an [`AtResponse::InfoText`];
+/// a `Bytes` payload converts into one with `into()`.
+pub enum AtResponse<'a, const SIZE: usize> {
+    /// No information text, just the `OK` final result code
+    Ok,
+    /// Information text followed by `OK`
+    InfoText(Bytes<SIZE>),
+    /// Information text borrowed from a buffer that outlives the call,
+    /// followed by `OK`; sent without being copied into a `Bytes<SIZE>`
+    Text(&'a str),
+    /// Information text of any length, owned, followed by `OK`; for
+    /// host-side tools and simulators (`std` feature)
+    #[cfg(feature = "std")]
+    TextOwned(String),
+    /// A final result code other than `OK` ending the command successfully
+    /// (e.g. `CONNECT` before entering the online data state)
+    FinalCode(response::ResultCode),
+    /// The final result code is sent later, once the command completes, with
+    /// [`AtParser::complete`](parser::AtParser::complete)
+    Deferred,
+    /// The command needs more input (e.g. the message text of `+CMGS`): the
+    /// parser hands the following bytes to
+    /// [`AtContext::input`](context::AtContext::input) until it completes
+    Continue,
+}
+
+impl<const SIZE: usize> AtResponse<'_, SIZE> {
+    /// Information text carried by the response, if any.
+    ///
+    /// An empty [`AtResponse::InfoText`] or [`AtResponse::Text`] counts as no
+    /// text.
+    pub fn text(&self) -> Option<&dyn fmt::Display> {
+        match self {
+            AtResponse::InfoText(text) if response::has_text(text) => Some(text),
+            AtResponse::Text(text) if !text.is_empty() => Some(text),
+            #[cfg(feature = "std")]
+            AtResponse::TextOwned(text) if !text.is_empty() => Some(text),
+            _ => None,
+        }
+    }
+
+    /// Convert into a response that does not borrow, copying borrowed
+    /// [`AtResponse::Text`] into an [`AtResponse::InfoText`] of `SIZE` bytes.
+    ///
+    /// Text longer than `SIZE` bytes is truncated: send a response before
+    /// copying it, as the parser does.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use at_parser_rs::AtResponse;
+    ///
+    /// let line = String::from("+CSQ: 23,0");
+    /// let response: AtResponse<'static, 16> = AtResponse::Text(&line).into_owned();
+    /// assert_eq!(response.to_string(), "+CSQ: 23,0");
+    /// ```
+    pub fn into_owned(self) -> AtResponse<'static, SIZE> {
+        match self {
+            AtResponse::Ok => AtResponse::Ok,
+            AtResponse::InfoText(text) => AtResponse::InfoText(text),
+            AtResponse::Text(text) => AtResponse::InfoText(Bytes::from_str(text)),
+            #[cfg(feature = "std")]
+            AtResponse::TextOwned(text) => AtResponse::TextOwned(text),
+            AtResponse::FinalCode(code) => AtResponse::FinalCode(code),
+            AtResponse::Deferred => AtResponse::Deferred,
+            AtResponse::Continue => AtResponse::Continue,
+        }
+    }
+}
+
+impl<const SIZE: usize> From<Bytes<SIZE>> for AtResponse<'_, SIZE> {
+    fn from(text: Bytes<SIZE>) -> Self {
+        AtResponse::InfoText(text)
+    }
+}
+
+#[cfg(feature = "std")]
+impl<const SIZE: usize> From<String> for AtResponse<'_, SIZE> {
+    fn from(text: String) -> Self {
+        AtResponse::TextOwned(text)
+    }
+}
+
+/// Writes the information text; every other response renders as nothing.
+impl<const SIZE: usize> fmt::Display for AtResponse<'_, SIZE> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AtResponse::InfoText(text) => write!(f, "{text}"),
+            AtResponse::Text(text) => f.write_str(text),
+            #[cfg(feature = "std")]
+            AtResponse::TextOwned(text) => f.write_str(text),
+            _ => Ok(()),
+        }
+    }
+}
+
+impl<const SIZE: usize> fmt::Debug for AtResponse<'_, SIZE> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AtResponse::Ok => f.write_str("Ok"),
+            AtResponse::InfoText(text) => f.debug_tuple("InfoText").field(&format_args!("\"{text}\"")).finish(),
+            AtResponse::Text(text) => f.debug_tuple("Text").field(text).finish(),
+            #[cfg(feature = "std")]
+            AtResponse::TextOwned(text) => f.debug_tuple("TextOwned").field(text).finish(),
+            AtResponse::FinalCode(code) => f.debug_tuple("FinalCode").field(code).finish(),
+            AtResponse::Deferred => f.write_str("Deferred"),
+            AtResponse::Continue => f.write_str("Continue"),
+        }
+    }
+}
+
+/// Error returned by the typed [`Args`] getters
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum ArgError {
+    /// No argument at the given index
+    Missing(usize),
+    /// The argument at the given index cannot be parsed as the requested type
+    Invalid(usize),
+    /// The argument at the given index is beyond the accepted count
+    Unexpected(usize),
+    /// The hex argument at the given index has an odd number of digits
+    OddLength(usize),
+    /// The argument at the given index does not fit the caller's buffer
+    Overflow(usize),
+    /// The argument at the given index is not an IP address of the
+    /// requested family
+    InvalidAddress(usize),
+    /// The argument at the given index is a number outside the port range
+    /// `1..=65535`
+    PortOutOfRange(usize),
+}
+
+/// Expected shape of a parameter, checked by [`Args::validate`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ParamKind {
+    /// An unquoted decimal integer, optionally signed
+    Int,
+    /// A double-quoted string
+    String,
+    /// Any value, including an omitted one
+    Any,
+}
+
+impl<E> From<ArgError> for AtError<'_, E> {
+    /// Every argument error is reported as [`AtError::InvalidArgs`]; wrap it
+    /// in [`AtError::Argument`] to keep the parameter index.
+    fn from(_: ArgError) -> Self {
+        AtError::InvalidArgs
+    }
+}
 
 /// Structure holding the arguments passed to an AT command
+///
+/// The boundaries of the first [`Args::INDEXED`] parameters are recorded
+/// when the arguments are built, so looking a parameter up by index is an
+/// array access instead of a new scan of the raw string; parameters past
+/// them are found by scanning on from the last recorded one.
+#[derive(Clone, Copy)]
 pub struct Args<'a> {
     /// Raw argument string (comma-separated values)
-    pub raw: &'a str,
+    raw: &'a str,
+    /// Byte range in `raw` of each recorded parameter, quotes included
+    bounds: [(u16, u16); Args::INDEXED],
+    /// Number of parameters recorded in `bounds`
+    len: u8,
+    /// Whether `bounds` holds every parameter of `raw`
+    complete: bool,
 }
 
 impl<'a> Args<'a> {
+    /// Number of parameters whose boundaries are recorded by [`Args::new`].
+    pub const INDEXED: usize = 8;
+
+    /// Split `raw` (comma-separated values) into its parameters.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use at_parser_rs::Args;
+    ///
+    /// // AT+CIPSTART="TCP","10.0.0.1",80
+    /// let args = Args::new(r#""TCP","10.0.0.1",80"#);
+    /// assert_eq!(args.raw(), r#""TCP","10.0.0.1",80"#);
+    /// assert_eq!(args.get(1).as_deref(), Some("10.0.0.1"));
+    /// ```
+    pub const fn new(raw: &'a str) -> Self {
+        let bytes = raw.as_bytes();
+        let mut bounds = [(0, 0); Args::INDEXED];
+        let mut len = 0;
+        let mut start = 0;
+        let mut in_quotes = false;
+        let mut escaped = false;
+        let mut depth = 0usize;
+
+        let mut offset = 0;
+        loop {
+            let at_end = offset == bytes.len();
+            if !at_end {
+                let byte = bytes[offset];
+                let split = if escaped {
+                    escaped = false;
+                    false
+                } else if in_quotes {
+                    match byte {
+                        b'\\' => escaped = true,
+                        b'"' => in_quotes = false,
+                        _ => {}
+                    }
+                    false
+                } else {
+                    match byte {
+                        b'"' => in_quotes = true,
+                        b'(' => depth += 1,
+                        b')' => depth = depth.saturating_sub(1),
+                        _ => {}
+                    }
+                    byte == b',' && depth == 0
+                };
+                if !split {
+                    offset += 1;
+                    continue;
+                }
+            }
+
+            if len == Args::INDEXED || offset > u16::MAX as usize {
+                return Self { raw, bounds, len: len as u8, complete: false };
+            }
+            bounds[len] = (start as u16, offset as u16);
+            len += 1;
+            if at_end {
+                return Self { raw, bounds, len: len as u8, complete: true };
+            }
+            offset += 1;
+            start = offset;
+        }
+    }
+
+    /// Raw argument string (comma-separated values).
+    pub const fn raw(&self) -> &'a str {
+        self.raw
+    }
+
     /// Get an argument by index (0-based)
     /// Arguments are separated by commas, except when they are inside
-    /// double-quoted strings.
+    /// double-quoted strings or parenthesized lists such as `(2,3)`.
     ///
     /// When an argument is wrapped in double quotes, the outer quotes are
     /// removed from the returned value and escaped quotes (`\"`) are
     /// decoded to `"`.
     pub fn get(&self, index: usize) -> Option<Cow<'a, str>> {
-        let (arg, quoted) = self.find(index)?;
-
-        if quoted {
-            Some(Self::decode_quoted(arg))
-        } else {
-            Some(Cow::Borrowed(arg))
-        }
+        self.find(index).get()
     }
 
     /// Get an argument by index without decoding escape sequences.
     ///
     /// Quoted arguments are still returned without the surrounding quotes.
     pub fn get_raw(&self, index: usize) -> Option<&'a str> {
-        self.find(index).map(|(arg, _)| arg)
+        self.find(index).get_raw()
+    }
+
+    /// Get an argument by index, telling omitted parameters apart from
+    /// missing ones.
+    ///
+    /// - `None` — there is no parameter at `index` at all
+    /// - `Some(None)` — the parameter position exists but was left empty
+    ///   (e.g. the first two parameters of `AT+CMD=,,3`)
+    /// - `Some(Some(value))` — the parameter was given; an explicit `""` is
+    ///   returned as an empty string, not as omitted
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use at_parser_rs::Args;
+    ///
+    /// // AT+CMD=,"",3
+    /// let args = Args::new(r#","",3"#);
+    /// assert_eq!(args.get_opt(0), Some(None));
+    /// assert_eq!(args.get_opt(1).flatten().as_deref(), Some(""));
+    /// assert_eq!(args.get_opt(2).flatten().as_deref(), Some("3"));
+    /// assert_eq!(args.get_opt(3), None);
+    /// ```
+    pub fn get_opt(&self, index: usize) -> Option<Option<Cow<'a, str>>> {
+        self.find(index).get_opt()
+    }
+
+    /// Get an argument by index parsed as `T`, falling back to `default`
+    /// when the parameter is omitted or missing.
+    ///
+    /// A parameter that is present but cannot be parsed is still an error.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use at_parser_rs::{ArgError, Args};
+    ///
+    /// // AT+CMD=,5,x
+    /// let args = Args::new(",5,x");
+    /// assert_eq!(args.get_or(0, 1u8), Ok(1));
+    /// assert_eq!(args.get_or(1, 1u8), Ok(5));
+    /// assert_eq!(args.get_or(2, 1u8), Err(ArgError::Invalid(2)));
+    /// assert_eq!(args.get_or(3, 1u8), Ok(1));
+    /// ```
+    pub fn get_or<T: FromStr>(&self, index: usize, default: T) -> Result<T, ArgError> {
+        self.find(index).get_or(default)
+    }
+
+    /// Whether the argument at `index` was sent as a double-quoted string.
+    ///
+    /// Returns `None` when there is no argument at that index. This lets a
+    /// handler tell `AT+CMD="1"` (a string) apart from `AT+CMD=1` (a number).
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use at_parser_rs::Args;
+    ///
+    /// let args = Args::new(r#""my,ssid",1"#);
+    /// assert_eq!(args.is_quoted(0), Some(true));
+    /// assert_eq!(args.is_quoted(1), Some(false));
+    /// assert_eq!(args.is_quoted(2), None);
+    /// ```
+    pub fn is_quoted(&self, index: usize) -> Option<bool> {
+        self.find(index).is_quoted()
     }
 
     /// Backward-compatible alias for [`Args::get`].
@@ -171,45 +816,335 @@ impl<'a> Args<'a> {
         self.get(index)
     }
 
-    fn find(&self, index: usize) -> Option<(&'a str, bool)> {
-        let mut current_index = 0;
-        let mut start = 0;
-        let mut in_quotes = false;
-        let mut escaped = false;
+    /// Get an argument by index parsed as `T`.
+    ///
+    /// Surrounding whitespace is ignored.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use at_parser_rs::{ArgError, Args};
+    ///
+    /// let args = Args::new("1, 2,x");
+    /// assert_eq!(args.get_as::<u8>(1), Ok(2));
+    /// assert_eq!(args.get_as::<u8>(2), Err(ArgError::Invalid(2)));
+    /// assert_eq!(args.get_as::<u8>(3), Err(ArgError::Missing(3)));
+    /// ```
+    pub fn get_as<T: FromStr>(&self, index: usize) -> Result<T, ArgError> {
+        self.find(index).get_as()
+    }
 
-        for (offset, ch) in self.raw.char_indices() {
-            if escaped {
-                escaped = false;
-                continue;
-            }
+    /// Get an argument by index as `u8`.
+    pub fn get_u8(&self, index: usize) -> Result<u8, ArgError> {
+        self.get_as(index)
+    }
 
-            if in_quotes {
-                match ch {
-                    '\\' => escaped = true,
-                    '"' => in_quotes = false,
-                    _ => {}
-                }
-                continue;
-            }
+    /// Get an argument by index as `u16`.
+    pub fn get_u16(&self, index: usize) -> Result<u16, ArgError> {
+        self.get_as(index)
+    }
 
-            match ch {
-                '"' => in_quotes = true,
-                ',' => {
-                    if current_index == index {
-                        return Some(Self::normalize(&self.raw[start..offset]));
-                    }
+    /// Get an argument by index as `u32`.
+    pub fn get_u32(&self, index: usize) -> Result<u32, ArgError> {
+        self.get_as(index)
+    }
 
-                    current_index += 1;
-                    start = offset + ch.len_utf8();
-                }
-                _ => {}
-            }
+    /// Get an argument by index as `i16`.
+    pub fn get_i16(&self, index: usize) -> Result<i16, ArgError> {
+        self.get_as(index)
+    }
+
+    /// Get an argument by index as `i32`.
+    pub fn get_i32(&self, index: usize) -> Result<i32, ArgError> {
+        self.get_as(index)
+    }
+
+    /// Get an argument by index as a boolean flag: `0` or `1`.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use at_parser_rs::Args;
+    ///
+    /// // AT+CMD=1
+    /// let args = Args::new("1");
+    /// assert_eq!(args.get_bool(0), Ok(true));
+    /// ```
+    pub fn get_bool(&self, index: usize) -> Result<bool, ArgError> {
+        match self.get_u8(index)? {
+            0 => Ok(false),
+            1 => Ok(true),
+            _ => Err(ArgError::Invalid(index)),
+        }
+    }
+
+    /// Get an argument by index as an IPv4 address (`192.168.1.10`).
+    ///
+    /// The argument may be quoted, as in
+    /// `AT+CIPSTART="TCP","192.168.1.10",8080`. Anything else is reported
+    /// as [`ArgError::InvalidAddress`].
+    pub fn get_ipv4(&self, index: usize) -> Result<Ipv4Addr, ArgError> {
+        self.get_address(index)
+    }
+
+    /// Get an argument by index as an IPv6 address (`fe80::1`).
+    pub fn get_ipv6(&self, index: usize) -> Result<Ipv6Addr, ArgError> {
+        self.get_address(index)
+    }
+
+    /// Get an argument by index as an IPv4 or IPv6 address.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use core::net::{IpAddr, Ipv4Addr};
+    /// use at_parser_rs::{ArgError, Args};
+    ///
+    /// // AT+CIPSTART="TCP","192.168.1.10",8080
+    /// let args = Args::new(r#""TCP","192.168.1.10",8080"#);
+    /// assert_eq!(args.get_ip(1), Ok(IpAddr::V4(Ipv4Addr::new(192, 168, 1, 10))));
+    /// assert_eq!(args.get_port(2), Ok(8080));
+    /// assert_eq!(args.get_ip(0), Err(ArgError::InvalidAddress(0)));
+    /// ```
+    pub fn get_ip(&self, index: usize) -> Result<IpAddr, ArgError> {
+        self.get_address(index)
+    }
+
+    /// Get an argument by index as a port number in `1..=65535`.
+    ///
+    /// A number outside that range, port `0` included, is rejected with
+    /// [`ArgError::PortOutOfRange`]; an argument that is not a number with
+    /// [`ArgError::Invalid`].
+    pub fn get_port(&self, index: usize) -> Result<u16, ArgError> {
+        let arg = self.get(index).ok_or(ArgError::Missing(index))?;
+        let arg = arg.trim();
+        let digits = arg.strip_prefix(['+', '-']).unwrap_or(arg);
+
+        match arg.parse() {
+            Ok(0) => Err(ArgError::PortOutOfRange(index)),
+            Ok(port) => Ok(port),
+            // a number past any integer type is still a number
+            Err(_) if !digits.is_empty() && digits.bytes().all(|byte| byte.is_ascii_digit()) => Err(ArgError::PortOutOfRange(index)),
+            Err(_) => Err(ArgError::Invalid(index)),
         }
+    }
+
+    /// Extract several typed arguments in order, one per tuple element.
+    ///
+    /// Each element implements [`FromArg`]; use `Option<T>` for parameters
+    /// that may be omitted. More arguments than tuple elements is reported
+    /// as [`ArgError::Unexpected`].
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use at_parser_rs::{ArgError, Args};
+    ///
+    /// // AT+CMD=3,"name"
+    /// let args = Args::new(r#"3,"name""#);
+    /// let (id, name, port) = args.parse::<(u8, &str, Option<u16>)>().unwrap();
+    /// assert_eq!((id, name, port), (3, "name", None));
+    ///
+    /// let args = Args::new("x");
+    /// assert_eq!(args.parse::<(u8,)>(), Err(ArgError::Invalid(0)));
+    /// ```
+    pub fn parse<T: FromArgs<'a>>(&self) -> Result<T, ArgError> {
+        T::from_args(self)
+    }
+
+    /// Get a parenthesized sub-list argument as its own [`Args`].
+    ///
+    /// Returns `None` when there is no argument at `index` or it is not
+    /// wrapped in `(` and `)`.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use at_parser_rs::Args;
+    ///
+    /// // AT+FOO=1,(2,3),4
+    /// let args = Args::new("1,(2,3),4");
+    /// assert_eq!(args.get(2).as_deref(), Some("4"));
+    ///
+    /// let list = args.get_list(1).unwrap();
+    /// assert_eq!(list.get_u8(0), Ok(2));
+    /// assert_eq!(list.get_u8(1), Ok(3));
+    /// assert!(args.get_list(0).is_none());
+    /// ```
+    pub fn get_list(&self, index: usize) -> Option<Args<'a>> {
+        let (arg, quoted) = self.param(index)?;
 
-        if current_index == index {
-            Some(Self::normalize(&self.raw[start..]))
+        if quoted {
+            return None;
+        }
+
+        let raw = arg.trim().strip_prefix('(')?.strip_suffix(')')?;
+        Some(Args::new(raw))
+    }
+
+    /// Decode a hex-string argument into `buf` and return the number of
+    /// bytes written.
+    ///
+    /// Both upper- and lower-case digits are accepted, and the argument may
+    /// be quoted. Nothing is allocated; `buf` is only partially written when
+    /// an error is returned.
+    ///
+    /// # Errors
+    ///
+    /// - [`ArgError::Missing`] — no argument at `index`
+    /// - [`ArgError::OddLength`] — the digit count is odd
+    /// - [`ArgError::Invalid`] — a character is not a hex digit
+    /// - [`ArgError::Overflow`] — the decoded bytes do not fit in `buf`
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use at_parser_rs::{ArgError, Args};
+    ///
+    /// // AT+SEND=48656C6C6F
+    /// let args = Args::new("48656C6C6F,123");
+    /// let mut buf = [0u8; 8];
+    /// assert_eq!(args.get_hex(0, &mut buf), Ok(5));
+    /// assert_eq!(&buf[..5], b"Hello");
+    /// assert_eq!(args.get_hex(1, &mut buf), Err(ArgError::OddLength(1)));
+    /// ```
+    pub fn get_hex(&self, index: usize, buf: &mut [u8]) -> Result<usize, ArgError> {
+        let digits = self.get_raw(index).ok_or(ArgError::Missing(index))?.trim().as_bytes();
+
+        if digits.len() % 2 != 0 {
+            return Err(ArgError::OddLength(index));
+        }
+        if digits.len() / 2 > buf.len() {
+            return Err(ArgError::Overflow(index));
+        }
+
+        for (byte, pair) in buf.iter_mut().zip(digits.chunks_exact(2)) {
+            let high = (pair[0] as char).to_digit(16).ok_or(ArgError::Invalid(index))?;
+            let low = (pair[1] as char).to_digit(16).ok_or(ArgError::Invalid(index))?;
+            *byte = (high << 4 | low) as u8;
+        }
+
+        Ok(digits.len() / 2)
+    }
+
+    /// Check that between `min` and `max` arguments were given and return
+    /// the count.
+    ///
+    /// An empty argument string counts as zero arguments. Too few arguments
+    /// report [`ArgError::Missing`] with the first missing index, too many
+    /// report [`ArgError::Unexpected`] with the first surplus index.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use at_parser_rs::{ArgError, Args};
+    ///
+    /// let args = Args::new("1,2,3");
+    /// assert_eq!(args.expect(1, 3), Ok(3));
+    /// assert_eq!(args.expect(4, 5), Err(ArgError::Missing(3)));
+    /// assert_eq!(args.expect(0, 2), Err(ArgError::Unexpected(2)));
+    /// ```
+    pub fn expect(&self, min: usize, max: usize) -> Result<usize, ArgError> {
+        let count = if self.raw.trim().is_empty() { 0 } else { self.count() };
+
+        if count < min {
+            Err(ArgError::Missing(count))
+        } else if count > max {
+            Err(ArgError::Unexpected(max))
         } else {
-            None
+            Ok(count)
+        }
+    }
+
+    /// Check the arguments against a list of expected kinds, one per
+    /// parameter.
+    ///
+    /// The number of arguments must match `kinds` exactly; omitted
+    /// parameters are only accepted where the kind is [`ParamKind::Any`].
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use at_parser_rs::{ArgError, Args, ParamKind};
+    ///
+    /// // AT+CWJAP="ssid","pass",1
+    /// let args = Args::new(r#""ssid","pass",1"#);
+    /// let kinds = [ParamKind::String, ParamKind::String, ParamKind::Int];
+    /// assert_eq!(args.validate(&kinds), Ok(()));
+    ///
+    /// let args = Args::new(r#""ssid",pass,1"#);
+    /// assert_eq!(args.validate(&kinds), Err(ArgError::Invalid(1)));
+    /// ```
+    pub fn validate(&self, kinds: &[ParamKind]) -> Result<(), ArgError> {
+        self.expect(kinds.len(), kinds.len())?;
+
+        for (index, (kind, (arg, quoted))) in kinds.iter().zip(Split::new(self.raw)).enumerate() {
+            let valid = match kind {
+                ParamKind::Int => !quoted && arg.trim().parse::<i64>().is_ok(),
+                ParamKind::String => quoted,
+                ParamKind::Any => true,
+            };
+
+            if !valid {
+                return Err(ArgError::Invalid(index));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Iterate over the arguments in order, decoded as by [`Args::get`].
+    ///
+    /// The raw string is scanned once, so looping over a variable number
+    /// of parameters does not re-split it for every index.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use at_parser_rs::Args;
+    ///
+    /// let args = Args::new(r#"1,"a,b",3"#);
+    /// let mut iter = args.iter();
+    /// assert_eq!(iter.next().as_deref(), Some("1"));
+    /// assert_eq!(iter.next().as_deref(), Some("a,b"));
+    /// assert_eq!(iter.next().as_deref(), Some("3"));
+    /// assert_eq!(iter.next(), None);
+    /// ```
+    pub fn iter(&self) -> Iter<'a> {
+        Iter { split: Split::new(self.raw) }
+    }
+
+    fn get_address<T: FromStr>(&self, index: usize) -> Result<T, ArgError> {
+        self.get_as(index).map_err(|e| match e {
+            ArgError::Invalid(index) => ArgError::InvalidAddress(index),
+            e => e,
+        })
+    }
+
+    fn find(&self, index: usize) -> Param<'a> {
+        Param { index, param: self.param(index) }
+    }
+
+    /// Parameter at `index`, without its quotes, with its quoted flag.
+    pub(crate) fn param(&self, index: usize) -> Option<(&'a str, bool)> {
+        match self.bounds[..self.len as usize].get(index) {
+            Some(&(start, end)) => Some(Self::normalize(&self.raw[start as usize..end as usize])),
+            None if self.complete => None,
+            None => self.rest().nth(index - self.len as usize),
+        }
+    }
+
+    /// Number of parameters; an empty argument string holds one omitted
+    /// parameter.
+    fn count(&self) -> usize {
+        if self.complete { self.len as usize } else { self.len as usize + self.rest().count() }
+    }
+
+    /// Splitter over the parameters past the recorded ones.
+    fn rest(&self) -> Split<'a> {
+        match self.len as usize {
+            0 => Split::new(self.raw),
+            len => Split::new(&self.raw[self.bounds[len - 1].1 as usize + 1..]),
         }
     }
 
@@ -254,54 +1189,258 @@ impl<'a> Args<'a> {
             decoded.push('\\');
         }
 
-        Cow::Owned(decoded)
+        Cow::Owned(decoded)
+    }
+}
+
+/// A value that can be extracted from a single argument, used by
+/// [`Args::parse`]
+pub trait FromArg<'a>: Sized {
+    /// Extract the argument at `index`.
+    fn from_arg(args: &Args<'a>, index: usize) -> Result<Self, ArgError>;
+}
+
+macro_rules! from_arg_parsed {
+    ($($ty:ty),*) => {
+        $(
+            impl<'a> FromArg<'a> for $ty {
+                fn from_arg(args: &Args<'a>, index: usize) -> Result<Self, ArgError> {
+                    args.get_as(index)
+                }
+            }
+        )*
+    };
+}
+
+from_arg_parsed!(u8, u16, u32, u64, i8, i16, i32, i64);
+
+macro_rules! from_arg_address {
+    ($($ty:ty),*) => {
+        $(
+            impl<'a> FromArg<'a> for $ty {
+                fn from_arg(args: &Args<'a>, index: usize) -> Result<Self, ArgError> {
+                    args.get_address(index)
+                }
+            }
+        )*
+    };
+}
+
+from_arg_address!(IpAddr, Ipv4Addr, Ipv6Addr);
+
+impl<'a> FromArg<'a> for bool {
+    fn from_arg(args: &Args<'a>, index: usize) -> Result<Self, ArgError> {
+        args.get_bool(index)
+    }
+}
+
+/// Borrowed as by [`Args::get_raw`]: quotes are removed but escape
+/// sequences are not decoded. Use `Cow<str>` to decode them.
+impl<'a> FromArg<'a> for &'a str {
+    fn from_arg(args: &Args<'a>, index: usize) -> Result<Self, ArgError> {
+        args.get_raw(index).ok_or(ArgError::Missing(index))
+    }
+}
+
+impl<'a> FromArg<'a> for Cow<'a, str> {
+    fn from_arg(args: &Args<'a>, index: usize) -> Result<Self, ArgError> {
+        args.get(index).ok_or(ArgError::Missing(index))
+    }
+}
+
+/// `None` when the parameter is omitted or missing (see [`Args::get_opt`]).
+impl<'a, T: FromArg<'a>> FromArg<'a> for Option<T> {
+    fn from_arg(args: &Args<'a>, index: usize) -> Result<Self, ArgError> {
+        match args.get_opt(index) {
+            Some(Some(_)) => T::from_arg(args, index).map(Some),
+            _ => Ok(None),
+        }
+    }
+}
+
+/// A tuple of [`FromArg`] values extracted in order by [`Args::parse`]
+pub trait FromArgs<'a>: Sized {
+    /// Extract every element from `args`.
+    fn from_args(args: &Args<'a>) -> Result<Self, ArgError>;
+}
+
+macro_rules! from_args_tuple {
+    ($count:expr; $($ty:ident $index:tt),+) => {
+        impl<'a, $($ty: FromArg<'a>),+> FromArgs<'a> for ($($ty,)+) {
+            fn from_args(args: &Args<'a>) -> Result<Self, ArgError> {
+                if args.param($count).is_some() {
+                    return Err(ArgError::Unexpected($count));
+                }
+
+                Ok(($($ty::from_arg(args, $index)?,)+))
+            }
+        }
+    };
+}
+
+from_args_tuple!(1; A 0);
+from_args_tuple!(2; A 0, B 1);
+from_args_tuple!(3; A 0, B 1, C 2);
+from_args_tuple!(4; A 0, B 1, C 2, D 3);
+from_args_tuple!(5; A 0, B 1, C 2, D 3, E 4);
+from_args_tuple!(6; A 0, B 1, C 2, D 3, E 4, F 5);
+
+impl<'a> IntoIterator for Args<'a> {
+    type Item = Cow<'a, str>;
+    type IntoIter = Iter<'a>;
+
+    fn into_iter(self) -> Iter<'a> {
+        self.iter()
+    }
+}
+
+impl<'a> IntoIterator for &Args<'a> {
+    type Item = Cow<'a, str>;
+    type IntoIter = Iter<'a>;
+
+    fn into_iter(self) -> Iter<'a> {
+        self.iter()
+    }
+}
+
+/// Arguments compare and print as their raw string; the recorded
+/// boundaries follow from it.
+impl PartialEq for Args<'_> {
+    fn eq(&self, other: &Self) -> bool {
+        self.raw == other.raw
+    }
+}
+
+impl Eq for Args<'_> {}
+
+impl fmt::Debug for Args<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Args").field("raw", &self.raw).finish()
+    }
+}
+
+/// Iterator over the arguments of an [`Args`], created by [`Args::iter`]
+pub struct Iter<'a> {
+    split: Split<'a>,
+}
+
+impl<'a> Iterator for Iter<'a> {
+    type Item = Cow<'a, str>;
+
+    fn next(&mut self) -> Option<Cow<'a, str>> {
+        let (arg, quoted) = self.split.next()?;
+        Some(Param::decode(arg, quoted))
+    }
+}
+
+/// The parameter found at an index of an [`Args`], with the accessors
+/// reading it
+#[derive(Clone, Copy)]
+struct Param<'a> {
+    index: usize,
+    /// The parameter without its quotes, with its quoted flag; `None` past
+    /// the last one
+    param: Option<(&'a str, bool)>,
+}
+
+impl<'a> Param<'a> {
+    fn get(self) -> Option<Cow<'a, str>> {
+        self.param.map(|(arg, quoted)| Self::decode(arg, quoted))
+    }
+
+    fn get_raw(self) -> Option<&'a str> {
+        self.param.map(|(arg, _)| arg)
+    }
+
+    fn get_opt(self) -> Option<Option<Cow<'a, str>>> {
+        match self.param? {
+            (arg, false) if arg.trim().is_empty() => Some(None),
+            (arg, quoted) => Some(Some(Self::decode(arg, quoted))),
+        }
+    }
+
+    fn is_quoted(self) -> Option<bool> {
+        self.param.map(|(_, quoted)| quoted)
+    }
+
+    fn get_as<T: FromStr>(self) -> Result<T, ArgError> {
+        let arg = self.get().ok_or(ArgError::Missing(self.index))?;
+        self.parse(&arg)
+    }
+
+    fn get_or<T: FromStr>(self, default: T) -> Result<T, ArgError> {
+        match self.get_opt() {
+            Some(Some(arg)) => self.parse(&arg),
+            _ => Ok(default),
+        }
+    }
+
+    fn parse<T: FromStr>(self, arg: &str) -> Result<T, ArgError> {
+        arg.trim().parse().map_err(|_| ArgError::Invalid(self.index))
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::Args;
+    fn decode(arg: &'a str, quoted: bool) -> Cow<'a, str> {
+        if quoted {
+            Args::decode_quoted(arg)
+        } else {
+            Cow::Borrowed(arg)
+        }
+    }
+}
 
-    #[test]
-    fn get_splits_plain_arguments() {
-        let args = Args { raw: "foo,bar,baz" };
+/// Quote- and parenthesis-aware splitter yielding each argument with its
+/// quoted flag
+pub(crate) struct Split<'a> {
+    rest: Option<&'a str>,
+}
 
-        assert_eq!(args.get(0).as_deref(), Some("foo"));
-        assert_eq!(args.get(1).as_deref(), Some("bar"));
-        assert_eq!(args.get(2).as_deref(), Some("baz"));
-        assert_eq!(args.get(3), None);
+impl<'a> Split<'a> {
+    pub(crate) fn new(raw: &'a str) -> Self {
+        Self { rest: Some(raw) }
     }
+}
 
-    #[test]
-    fn get_keeps_commas_inside_quoted_arguments() {
-        let args = Args { raw: "i,\"ciao, sono antonio\",secret" };
+impl<'a> Iterator for Split<'a> {
+    type Item = (&'a str, bool);
 
-        assert_eq!(args.get(0).as_deref(), Some("i"));
-        assert_eq!(args.get(1).as_deref(), Some("ciao, sono antonio"));
-        assert_eq!(args.get(2).as_deref(), Some("secret"));
-    }
+    fn next(&mut self) -> Option<(&'a str, bool)> {
+        let rest = self.rest?;
+        let mut in_quotes = false;
+        let mut escaped = false;
+        let mut depth = 0usize;
 
-    #[test]
-    fn get_decodes_escaped_quotes() {
-        let args = Args { raw: r#"i,"ciao, sono \"antonio\"",mysecretpassword"# };
+        for (offset, ch) in rest.char_indices() {
+            if escaped {
+                escaped = false;
+                continue;
+            }
 
-        assert_eq!(args.get_raw(1), Some(r#"ciao, sono \"antonio\""#));
-        assert_eq!(args.get(1).as_deref(), Some("ciao, sono \"antonio\""));
-        assert_eq!(args.get(2).as_deref(), Some("mysecretpassword"));
-    }
+            if in_quotes {
+                match ch {
+                    '\\' => escaped = true,
+                    '"' => in_quotes = false,
+                    _ => {}
+                }
+                continue;
+            }
 
-    #[test]
-    fn get_handles_empty_arguments() {
-        let args = Args { raw: "first,,\"\",last" };
+            match ch {
+                '"' => in_quotes = true,
+                '(' => depth += 1,
+                ')' => depth = depth.saturating_sub(1),
+                ',' if depth == 0 => {
+                    self.rest = Some(&rest[offset + ch.len_utf8()..]);
+                    return Some(Args::normalize(&rest[..offset]));
+                }
+                _ => {}
+            }
+        }
 
-        assert_eq!(args.get(0).as_deref(), Some("first"));
-        assert_eq!(args.get(1).as_deref(), Some(""));
-        assert_eq!(args.get(2).as_deref(), Some(""));
-        assert_eq!(args.get(3).as_deref(), Some("last"));
+        self.rest = None;
+        Some(Args::normalize(rest))
     }
 }
 
-
 /// Wraps a value in double-quote characters (`"`).
 ///
 /// Expands to a string literal `"\"<value>\""` suitable for use inside
@@ -392,175 +1531,663 @@ macro_rules! at_response {
     ($size:expr, $at_resp:expr; $a1:expr) => {{
         let mut response = osal_rs::utils::Bytes::<{$size}>::new();
         response.format(format_args!("{}", $a1));
-        ($at_resp, response)
+        ($at_resp, $crate::AtResponse::InfoText(response))
     }};
     ($size:expr, $at_resp:expr; $a1:expr, $a2:expr) => {{
         let mut response = osal_rs::utils::Bytes::<{$size}>::new();
         response.format(format_args!("{},{}", $a1, $a2));
-        ($at_resp, response)
+        ($at_resp, $crate::AtResponse::InfoText(response))
     }};
     ($size:expr, $at_resp:expr; $a1:expr, $a2:expr, $a3:expr) => {{
         let mut response = osal_rs::utils::Bytes::<{$size}>::new();
         response.format(format_args!("{},{},{}", $a1, $a2, $a3));
-        ($at_resp, response)
+        ($at_resp, $crate::AtResponse::InfoText(response))
     }};
     ($size:expr, $at_resp:expr; $a1:expr, $a2:expr, $a3:expr, $a4:expr) => {{
         let mut response = osal_rs::utils::Bytes::<{$size}>::new();
         response.format(format_args!("{},{},{},{}", $a1, $a2, $a3, $a4));
-        ($at_resp, response)
+        ($at_resp, $crate::AtResponse::InfoText(response))
     }};
     ($size:expr, $at_resp:expr; $a1:expr, $a2:expr, $a3:expr, $a4:expr, $a5:expr) => {{
         let mut response = osal_rs::utils::Bytes::<{$size}>::new();
         response.format(format_args!("{},{},{},{},{}", $a1, $a2, $a3, $a4, $a5));
-        ($at_resp, response)
+        ($at_resp, $crate::AtResponse::InfoText(response))
     }};
     ($size:expr, $at_resp:expr; $a1:expr, $a2:expr, $a3:expr, $a4:expr, $a5:expr, $a6:expr) => {{
         let mut response = osal_rs::utils::Bytes::<{$size}>::new();
         response.format(format_args!("{},{},{},{},{},{}", $a1, $a2, $a3, $a4, $a5, $a6));
-        ($at_resp, response)
+        ($at_resp, $crate::AtResponse::InfoText(response))
     }};
 }
 
 
 
-/// Declares a static `COMMANDS` table mapping AT command strings to their handlers.
-///
-/// This macro expands into a `static COMMANDS` binding of type
-/// `&[(&'static str, &mut dyn AtContext<SIZE>)]`, which can then be passed to
+/// Builds the command table passed to
 /// [`AtParser::set_commands`](crate::parser::AtParser::set_commands).
 ///
+/// Expands to a `&mut [(&'static str, &'static str, &mut dyn AtContext<SIZE>)]`
+/// borrowing every handler, so handlers of different types live in one table
+/// served by a [`DynAtParser`](crate::parser::DynAtParser). Bind it with `let`
+/// so the table lives as long as the parser.
+///
 /// # Syntax
 ///
 /// ```rust,ignore
-/// at_modules! {
-///     SIZE;
-///     ("AT+CMD1", "+CMD1: ") => HANDLER1,
-///     ("AT+CMD2", "+CMD2: ") => HANDLER2,
-/// }
+/// let commands = at_modules! {
+///     SIZE;                                   // or `SIZE, State;` / `SIZE, State, Error;`
+///     ("AT+CMD1", "+CMD1: ") => handler1,
+///     ("AT+CMD2", "+CMD2: ") => handler2,
+/// };
 /// ```
 ///
 /// - `SIZE` — `const usize` that defines the response buffer capacity (must match the
 ///   capacity used by [`AtParser`](crate::parser::AtParser) and every [`AtContext`](crate::context::AtContext) impl).
+/// - `State`, `Error` — optional user state and error types of the handlers, see
+///   [`AtContext`](crate::context::AtContext).
 /// - `"AT+CMD"` — the AT command string the parser will match against the input.
 /// - `"+CMD: "` — the AT response prefix forwarded to every handler method.
-/// - `HANDLER` — a `static mut` variable that implements [`AtContext<SIZE>`](crate::context::AtContext).
-///
-/// # Safety
-///
-/// The macro uses an `unsafe` block internally to obtain `&mut` references to
-/// `static mut` items.  It is the caller's responsibility to ensure:
+/// - `handler` — a place expression (local variable, struct field) holding a
+///   handler, borrowed mutably for as long as the table is used.
 ///
-/// - **Single-threaded access only** — do not call this in a multi-threaded or
-///   RTOS context without external synchronisation.
-/// - **One call site** — the generated `COMMANDS` symbol is `static`; defining it
-///   more than once in the same scope will cause a compile error.
+/// Command names must be constant expressions: a name registered twice, which
+/// would never be dispatched, fails to compile.
 ///
-/// # Limitations
+/// ```rust,compile_fail
+/// # use at_parser_rs::at_modules;
+/// # use at_parser_rs::context::AtContext;
+/// # const SIZE: usize = 64;
+/// # struct Info; impl AtContext<SIZE> for Info {}
+/// # let (mut firmware, mut version) = (Info, Info);
+/// let commands = at_modules! {
+///     SIZE;
+///     ("AT+GMR", "+GMR: ") => firmware,
+///     ("AT+GMR", "+GMR: ") => version,
+/// };
+/// ```
 ///
-/// - All handlers must implement `AtContext` with the **same** `SIZE` constant.
-/// - The generated symbol is always named `COMMANDS`; rename it after expansion
-///   if you need multiple tables.
+/// Entries may carry `///` doc comments. Written `SIZE; help;`, the macro
+/// returns the table together with a `&'static` [`Help`](crate::help::Help)
+/// listing every command with its doc comment, for
+/// [`AtParser::set_help`](crate::parser::AtParser::set_help).
 ///
-/// # Example — basic usage
+/// # Example
 ///
-/// ```rust,no_run
+/// ```rust
 /// use at_parser_rs::at_modules;
 /// use at_parser_rs::context::AtContext;
+/// use at_parser_rs::parser::DynAtParser;
 /// use at_parser_rs::{Args, AtResult, AtError, at_response};
 ///
 /// const SIZE: usize = 64;
 ///
 /// struct EchoModule { echo: bool }
 /// impl AtContext<SIZE> for EchoModule {
-///     fn query(&mut self, at_response: &'static str) -> AtResult<SIZE> {
+///     fn query(&mut self, at_response: &'static str) -> AtResult<'_, SIZE> {
 ///         Ok(at_response!(SIZE, at_response; if self.echo { 1u8 } else { 0u8 }))
 ///     }
-///     fn set(&mut self, at_response: &'static str, args: Args) -> AtResult<SIZE> {
-///         let value = args.get(0).ok_or((at_response, AtError::InvalidArgs))?;
-///         match value.as_ref() {
-///             "0" => { self.echo = false; Ok(at_response!(SIZE, at_response; "OK")) }
-///             "1" => { self.echo = true;  Ok(at_response!(SIZE, at_response; "OK")) }
-///             _ => Err((at_response, AtError::InvalidArgs)),
-///         }
+///     fn set(&mut self, at_response: &'static str, args: Args) -> AtResult<'_, SIZE> {
+///         self.echo = args.get_bool(0).map_err(|e| (at_response, e.into()))?;
+///         Ok(at_response!(SIZE, at_response; "OK"))
 ///     }
 /// }
 ///
 /// struct ResetModule;
 /// impl AtContext<SIZE> for ResetModule {
-///     fn exec(&mut self, at_response: &'static str) -> AtResult<SIZE> {
+///     fn exec(&mut self, at_response: &'static str) -> AtResult<'_, SIZE> {
 ///         Ok(at_response!(SIZE, at_response; "OK"))
 ///     }
 /// }
 ///
-/// static mut ECHO:  EchoModule  = EchoModule { echo: false };
-/// static mut RESET: ResetModule = ResetModule;
+/// let mut echo = EchoModule { echo: false };
+/// let mut reset = ResetModule;
 ///
-/// at_modules! {
+/// let commands = at_modules! {
 ///     SIZE;
-///     ("AT+ECHO", "+ECHO: ") => ECHO,
-///     ("AT+RST",  "+RST: ")  => RESET,
-/// }
+///     ("AT+ECHO", "+ECHO: ") => echo,
+///     ("AT+RST",  "+RST: ")  => reset,
+/// };
 ///
-/// // COMMANDS is now available in scope:
-/// // parser.set_commands(COMMANDS);
+/// let mut parser: DynAtParser<SIZE> = DynAtParser::new();
+/// parser.set_commands(commands);
+/// assert!(parser.execute("AT+ECHO=1;+RST").is_ok());
 /// ```
+#[macro_export]
+macro_rules! at_modules {
+    ($size:expr $(, $ty:ty)*; help; $( $(#[doc = $doc:literal])* ($name:expr, $at_resp:expr) => $module:expr ),* $(,)?) => {{
+        const HELP: $crate::help::Help = $crate::help::Help::new(&[
+            $( $crate::help::Topic { command: $name, description: ::core::concat!("" $(, $doc)*) }, )*
+        ]);
+        ($crate::at_modules!($size $(, $ty)*; $( ($name, $at_resp) => $module ),*), &HELP)
+    }};
+    (@table $handler:ty; $( $(#[doc = $doc:literal])* ($name:expr, $at_resp:expr) => $module:expr ),* $(,)?) => {{
+        const {
+            let names: &[&str] = &[$($name),*];
+            $(
+                ::core::assert!(
+                    $crate::parser::count_command(names, $name) == 1,
+                    ::core::concat!("command ", ::core::stringify!($name), " is registered more than once"),
+                );
+            )*
+        };
+        &mut [
+            $(
+                ($name, $at_resp, &mut $module as $handler),
+            )*
+        ]
+    }};
+    ($size:expr; $($entries:tt)*) => {
+        $crate::at_modules!(@table &mut dyn $crate::context::AtContext<{ $size }>; $($entries)*)
+    };
+    ($size:expr, $state:ty; $($entries:tt)*) => {
+        $crate::at_modules!(@table &mut dyn $crate::context::AtContext<{ $size }, $state>; $($entries)*)
+    };
+    ($size:expr, $state:ty, $error:ty; $($entries:tt)*) => {
+        $crate::at_modules!(@table &mut dyn $crate::context::AtContext<{ $size }, $state, $error>; $($entries)*)
+    };
+}
+
+/// Build a command table like [`at_modules!`], checking at compile time that
+/// it is sorted by name as [`set_sorted_commands`](crate::parser::AtParser::set_sorted_commands)
+/// requires.
 ///
-/// # Example — single handler
+/// Entries are written in [`command_order`](crate::parser::command_order):
+/// names compared without `AT`, ignoring case. The command names must be
+/// constant expressions.
 ///
-/// ```rust,no_run
-/// use at_parser_rs::at_modules;
+/// # Example
+///
+/// ```rust
+/// use at_parser_rs::at_sorted_modules;
 /// use at_parser_rs::context::AtContext;
+/// use at_parser_rs::parser::DynAtParser;
 /// use at_parser_rs::{AtResult, at_response};
 ///
-/// const SIZE: usize = 32;
+/// const SIZE: usize = 64;
 ///
-/// struct PingModule;
-/// impl AtContext<SIZE> for PingModule {
-///     fn exec(&mut self, at_response: &'static str) -> AtResult<SIZE> {
-///         Ok(at_response!(SIZE, at_response; "PONG"))
+/// struct Info(&'static str);
+/// impl AtContext<SIZE> for Info {
+///     fn exec(&mut self, at_response: &'static str) -> AtResult<'_, SIZE> {
+///         Ok(at_response!(SIZE, at_response; self.0))
 ///     }
 /// }
 ///
-/// static mut PING: PingModule = PingModule;
+/// let mut maker = Info("ACME");
+/// let mut model = Info("M1");
 ///
-/// at_modules! {
+/// let commands = at_sorted_modules! {
 ///     SIZE;
-///     ("AT+PING", "+PING: ") => PING,
-/// }
+///     ("AT+CGMI", "+CGMI: ") => maker,
+///     ("AT+CGMM", "+CGMM: ") => model,
+/// };
+///
+/// let mut parser: DynAtParser<SIZE> = DynAtParser::new();
+/// parser.set_sorted_commands(commands);
+/// assert!(parser.execute("AT+CGMM").is_ok());
 /// ```
 ///
-/// # Recommended alternative
+/// Out-of-order entries fail to compile:
 ///
-/// For multi-type handler tables or when `static mut` is undesirable, prefer the
-/// explicit slice approach — it requires no `unsafe` at the call site and allows
-/// mixing handler types via trait objects:
+/// ```rust,compile_fail
+/// # use at_parser_rs::at_sorted_modules;
+/// # use at_parser_rs::context::AtContext;
+/// # const SIZE: usize = 64;
+/// # struct Info; impl AtContext<SIZE> for Info {}
+/// # let (mut maker, mut model) = (Info, Info);
+/// let commands = at_sorted_modules! {
+///     SIZE;
+///     ("AT+CGMM", "+CGMM: ") => model,
+///     ("AT+CGMI", "+CGMI: ") => maker,
+/// };
+/// ```
+#[macro_export]
+macro_rules! at_sorted_modules {
+    ($size:expr $(, $ty:ty)*; $( $(#[doc = $doc:literal])* ($name:expr, $at_resp:expr) => $module:expr ),* $(,)?) => {{
+        const { assert!($crate::parser::is_sorted(&[$($name),*]), "command table is not sorted by name") };
+        $crate::at_modules!($size $(, $ty)*; $( ($name, $at_resp) => $module ),*)
+    }};
+}
+
+/// Build a command table like [`at_modules!`], together with the array of
+/// its names, for
+/// [`set_split_commands`](parser::AtParser::set_split_commands).
 ///
-/// ```rust,no_run
+/// The names are placed in a constant, so the array lives in flash next to
+/// the strings it points at.
+///
+/// # Example
+///
+/// ```rust
+/// use at_parser_rs::at_split_modules;
 /// use at_parser_rs::context::AtContext;
+/// use at_parser_rs::parser::DynAtParser;
+/// use at_parser_rs::{AtResult, at_response};
 ///
 /// const SIZE: usize = 64;
 ///
-/// let mut echo  = EchoModule { echo: false };
-/// let mut reset = ResetModule;
+/// struct Info(&'static str);
+/// impl AtContext<SIZE> for Info {
+///     fn exec(&mut self, at_response: &'static str) -> AtResult<'_, SIZE> {
+///         Ok(at_response!(SIZE, at_response; self.0))
+///     }
+/// }
 ///
-/// let commands: &mut [(&str, &str, &mut dyn AtContext<SIZE>)] = &mut [
-///     ("AT+ECHO", "+ECHO: ", &mut echo),
-///     ("AT+RST",  "+RST: ",  &mut reset),
-/// ];
-/// parser.set_commands(commands);
+/// let mut maker = Info("ACME");
+/// let mut model = Info("M1");
+///
+/// let (commands, names) = at_split_modules! {
+///     SIZE;
+///     ("AT+CGMI", "+CGMI: ") => maker,
+///     ("AT+CGMM", "+CGMM: ") => model,
+/// };
+/// assert_eq!(names, ["AT+CGMI", "AT+CGMM"]);
+///
+/// let mut parser: DynAtParser<SIZE> = DynAtParser::new();
+/// parser.set_split_commands(commands, names).unwrap();
+/// assert!(parser.execute("AT+CGMM").is_ok());
 /// ```
 #[macro_export]
-macro_rules! at_modules {
-    (
-        $size:expr;
-        $( ($name:expr, $at_resp:expr) => $module:ident ),* $(,)?
-    ) => {
-        static COMMANDS: &mut [(&'static str, &'static str, &mut dyn AtContext<$size>)] = unsafe {
-            &mut [
-                $(
-                    ($name, $at_resp, &mut $module),
-                )*
-            ]
+macro_rules! at_split_modules {
+    ($size:expr $(, $ty:ty)*; $( ($name:expr, $at_resp:expr) => $module:expr ),* $(,)?) => {{
+        const NAMES: &[&str] = &[$($name),*];
+        ($crate::at_modules!($size $(, $ty)*; $( ($name, $at_resp) => $module ),*), NAMES)
+    }};
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ArgError, Args, AtError, AtResponse, AtResult};
+    use crate::context::AtContext;
+    use crate::parser::DynAtParser;
+    use crate::response::Responder;
+    use alloc::format;
+    use alloc::vec::Vec;
+
+    #[test]
+    fn typed_getters_parse_arguments() {
+        let args = Args::new("1, 65535 ,-2,70000,2,x");
+
+        assert_eq!(args.get_bool(0), Ok(true));
+        assert_eq!(args.get_u16(1), Ok(65535));
+        assert_eq!(args.get_i16(2), Ok(-2));
+        assert_eq!(args.get_i32(2), Ok(-2));
+        assert_eq!(args.get_u32(3), Ok(70000));
+        assert_eq!(args.get_u16(3), Err(ArgError::Invalid(3)));
+        assert_eq!(args.get_bool(4), Err(ArgError::Invalid(4)));
+        assert_eq!(args.get_u8(5), Err(ArgError::Invalid(5)));
+        assert_eq!(args.get_u8(6), Err(ArgError::Missing(6)));
+        let error: AtError<'_> = ArgError::Missing(6).into();
+        assert!(matches!(error, AtError::InvalidArgs));
+    }
+
+    #[test]
+    fn get_splits_plain_arguments() {
+        let args = Args::new("foo,bar,baz");
+
+        assert_eq!(args.get(0).as_deref(), Some("foo"));
+        assert_eq!(args.get(1).as_deref(), Some("bar"));
+        assert_eq!(args.get(2).as_deref(), Some("baz"));
+        assert_eq!(args.get(3), None);
+    }
+
+    #[test]
+    fn get_keeps_commas_inside_quoted_arguments() {
+        let args = Args::new("i,\"ciao, sono antonio\",secret");
+
+        assert_eq!(args.get(0).as_deref(), Some("i"));
+        assert_eq!(args.get(1).as_deref(), Some("ciao, sono antonio"));
+        assert_eq!(args.get(2).as_deref(), Some("secret"));
+    }
+
+    #[test]
+    fn get_decodes_escaped_quotes() {
+        let args = Args::new(r#"i,"ciao, sono \"antonio\"",mysecretpassword"#);
+
+        assert_eq!(args.get_raw(1), Some(r#"ciao, sono \"antonio\""#));
+        assert_eq!(args.get(1).as_deref(), Some("ciao, sono \"antonio\""));
+        assert_eq!(args.get(2).as_deref(), Some("mysecretpassword"));
+    }
+
+    #[test]
+    fn get_decodes_escaped_backslashes() {
+        let args = Args::new(r#""my,ssid","pa\"ss\\",7"#);
+
+        assert_eq!(args.get(0).as_deref(), Some("my,ssid"));
+        assert_eq!(args.get(1).as_deref(), Some(r#"pa"ss\"#));
+        assert_eq!(args.get_raw(1), Some(r#"pa\"ss\\"#));
+        assert_eq!(args.is_quoted(1), Some(true));
+        assert_eq!(args.is_quoted(2), Some(false));
+        assert_eq!(args.get(2).as_deref(), Some("7"));
+    }
+
+    #[test]
+    fn get_opt_distinguishes_omitted_from_missing() {
+        let args = Args::new(", ,3");
+
+        assert_eq!(args.get_opt(0), Some(None));
+        assert_eq!(args.get_opt(1), Some(None));
+        assert_eq!(args.get_opt(2).flatten().as_deref(), Some("3"));
+        assert_eq!(args.get_opt(3), None);
+        assert_eq!(args.get_or(0, 7u8), Ok(7));
+        assert_eq!(args.get_or(2, 7u8), Ok(3));
+        assert_eq!(args.get_or(9, 7u8), Ok(7));
+    }
+
+    #[test]
+    fn iter_yields_every_argument() {
+        let args = Args::new(r#"1,,"x,\"y\"",4"#);
+        let collected: Vec<_> = args.iter().collect();
+
+        assert_eq!(collected, ["1", "", "x,\"y\"", "4"]);
+        assert_eq!((&args).into_iter().count(), 4);
+        assert_eq!(args.into_iter().last().as_deref(), Some("4"));
+    }
+
+    #[test]
+    fn recorded_boundaries_match_a_scan_of_the_raw_string() {
+        let long = "x".repeat(usize::from(u16::MAX) + 8);
+        let past_bounds = format!("1,{long},3");
+        for raw in [
+            "",
+            r#"1,,"x,\"y\"",(2,3), 5 "#,
+            "0,1,2,3,4,5,6,7,8,\"9\",(1,0),11",
+            past_bounds.as_str(),
+        ] {
+            let args = Args::new(raw);
+            let scanned: Vec<_> = super::Split::new(raw).collect();
+            for index in 0..scanned.len() + 1 {
+                assert_eq!(args.param(index), scanned.get(index).copied(), "{index} of {raw:.20}");
+            }
+            assert_eq!(args.count(), scanned.len());
+        }
+        assert_eq!(Args::new(&past_bounds).get_u8(2), Ok(3));
+    }
+
+    #[test]
+    fn expect_and_validate_check_arity_and_shape() {
+        use super::ParamKind;
+
+        assert_eq!(Args::new("").expect(0, 0), Ok(0));
+        assert_eq!(Args::new("").expect(1, 2), Err(ArgError::Missing(0)));
+        assert_eq!(Args::new("1,2").expect(1, 1), Err(ArgError::Unexpected(1)));
+
+        let kinds = [ParamKind::Int, ParamKind::Any, ParamKind::String];
+        assert_eq!(Args::new(r#"-3,,"x""#).validate(&kinds), Ok(()));
+        assert_eq!(Args::new(r#"-3,,x"#).validate(&kinds), Err(ArgError::Invalid(2)));
+        assert_eq!(Args::new(r#""3",,"x""#).validate(&kinds), Err(ArgError::Invalid(0)));
+        assert_eq!(Args::new("1,2").validate(&kinds), Err(ArgError::Missing(2)));
+    }
+
+    #[test]
+    fn get_hex_decodes_into_caller_buffer() {
+        let args = Args::new(r#"00ff7A,"0a0B",abc,0g,,"#);
+        let mut buf = [0u8; 3];
+
+        assert_eq!(args.get_hex(0, &mut buf), Ok(3));
+        assert_eq!(buf, [0x00, 0xff, 0x7a]);
+        assert_eq!(args.get_hex(1, &mut buf), Ok(2));
+        assert_eq!(&buf[..2], [0x0a, 0x0b]);
+        assert_eq!(args.get_hex(2, &mut buf), Err(ArgError::OddLength(2)));
+        assert_eq!(args.get_hex(3, &mut buf), Err(ArgError::Invalid(3)));
+        assert_eq!(args.get_hex(4, &mut buf), Ok(0));
+        assert_eq!(args.get_hex(0, &mut buf[..2]), Err(ArgError::Overflow(0)));
+        assert_eq!(args.get_hex(6, &mut buf), Err(ArgError::Missing(6)));
+    }
+
+    #[test]
+    fn network_getters_parse_addresses_and_ports() {
+        use core::net::{Ipv4Addr, Ipv6Addr};
+
+        let args = Args::new(r#""10.0.0.1",fe80::1,"256.0.0.1",0,65535,65536"#);
+
+        assert_eq!(args.get_ipv4(0), Ok(Ipv4Addr::new(10, 0, 0, 1)));
+        assert_eq!(args.get_ipv6(1), Ok(Ipv6Addr::new(0xfe80, 0, 0, 0, 0, 0, 0, 1)));
+        assert!(args.get_ip(1).is_ok());
+        assert_eq!(args.get_port(4), Ok(65535));
+    }
+
+    #[test]
+    fn network_getters_tell_why_an_argument_is_rejected() {
+        use core::net::Ipv4Addr;
+
+        let args = Args::new(r#""256.0.0.1",fe80::1,"TCP",0,65536,-1,http,99999999999999999999999999999999999999999,-"#);
+
+        assert_eq!(args.get_ipv4(0), Err(ArgError::InvalidAddress(0)));
+        assert_eq!(args.get_ipv4(1), Err(ArgError::InvalidAddress(1)));
+        assert_eq!(args.get_ipv6(0), Err(ArgError::InvalidAddress(0)));
+        assert_eq!(args.get_ip(2), Err(ArgError::InvalidAddress(2)));
+        assert_eq!(args.get_ip(9), Err(ArgError::Missing(9)));
+        assert_eq!(Args::new("fe80::1").parse::<(Ipv4Addr,)>(), Err(ArgError::InvalidAddress(0)));
+
+        assert_eq!(args.get_port(3), Err(ArgError::PortOutOfRange(3)));
+        assert_eq!(args.get_port(4), Err(ArgError::PortOutOfRange(4)));
+        assert_eq!(args.get_port(5), Err(ArgError::PortOutOfRange(5)));
+        assert_eq!(args.get_port(6), Err(ArgError::Invalid(6)));
+        assert_eq!(args.get_port(7), Err(ArgError::PortOutOfRange(7)));
+        assert_eq!(args.get_port(8), Err(ArgError::Invalid(8)));
+        assert_eq!(args.get_port(9), Err(ArgError::Missing(9)));
+    }
+
+    #[test]
+    fn parse_destructures_typed_tuples() {
+        use alloc::borrow::Cow;
+
+        let args = Args::new(r#"7,"a\"b",,1"#);
+        let parsed = args.parse::<(u8, Cow<str>, Option<u16>, bool)>();
+        assert_eq!(parsed, Ok((7, Cow::Borrowed("a\"b"), None, true)));
+
+        let (id, raw) = Args::new(r#"1,"x\"y""#).parse::<(i32, &str)>().unwrap();
+        assert_eq!((id, raw), (1, r#"x\"y"#));
+
+        assert_eq!(args.parse::<(u8, Cow<str>)>(), Err(ArgError::Unexpected(2)));
+        assert_eq!(args.parse::<(u8, Cow<str>, u16, bool)>(), Err(ArgError::Invalid(2)));
+        assert_eq!(Args::new("1").parse::<(u8, u8)>(), Err(ArgError::Missing(1)));
+        assert_eq!(Args::new("1").parse::<(u8, Option<u8>)>(), Ok((1, None)));
+    }
+
+    #[test]
+    fn get_keeps_bracketed_lists_together() {
+        let args = Args::new(r#"1,(2,(3,4)),"(",5"#);
+
+        assert_eq!(args.iter().count(), 4);
+        assert_eq!(args.get(1).as_deref(), Some("(2,(3,4))"));
+        assert_eq!(args.get(2).as_deref(), Some("("));
+        assert_eq!(args.get(3).as_deref(), Some("5"));
+
+        let list = args.get_list(1).unwrap();
+        assert_eq!(list.get_u8(0), Ok(2));
+        assert_eq!(list.get_list(1).and_then(|inner| inner.get_u8(1).ok()), Some(4));
+        assert!(args.get_list(2).is_none());
+        assert!(args.get_list(9).is_none());
+    }
+
+    #[test]
+    fn get_handles_empty_arguments() {
+        let args = Args::new("first,,\"\",last");
+
+        assert_eq!(args.get(0).as_deref(), Some("first"));
+        assert_eq!(args.get(1).as_deref(), Some(""));
+        assert_eq!(args.get(2).as_deref(), Some(""));
+        assert_eq!(args.get(3).as_deref(), Some("last"));
+    }
+
+    const SIZE: usize = 32;
+
+    struct Ping;
+
+    impl AtContext<SIZE> for Ping {
+        fn exec(&mut self, at_response: &'static str) -> AtResult<'_, SIZE> {
+            Ok(at_response!(SIZE, at_response; "PONG"))
+        }
+    }
+
+    struct Counter {
+        count: u8,
+    }
+
+    impl AtContext<SIZE> for Counter {
+        fn exec(&mut self, at_response: &'static str) -> AtResult<'_, SIZE> {
+            self.count += 1;
+            Ok((at_response, AtResponse::Ok))
+        }
+    }
+
+    impl AtContext<SIZE, u8> for Counter {
+        fn exec_with(&mut self, at_response: &'static str, responder: &mut Responder<'_, u8>) -> AtResult<'_, SIZE> {
+            self.count += responder.state().map_or(0, |step| *step);
+            Ok((at_response, AtResponse::Ok))
+        }
+    }
+
+    #[test]
+    fn at_modules_builds_a_table_for_the_parser() {
+        let mut ping = Ping;
+        let mut counter = Counter { count: 0 };
+        {
+            let commands = at_modules! {
+                SIZE;
+                ("AT+PING", "+PING: ") => ping,
+                ("AT+CNT", "+CNT: ") => counter,
+            };
+            let mut parser: DynAtParser<SIZE> = DynAtParser::new();
+            parser.set_commands(commands);
+
+            assert!(matches!(parser.execute("AT+PING"), Ok(("+PING: ", AtResponse::InfoText(text))) if format!("{text}") == "PONG"));
+            assert!(parser.execute("AT+CNT;+CNT").is_ok());
+        }
+        assert_eq!(counter.count, 2);
+
+        let mut step = 5;
+        {
+            let commands = at_modules! {
+                SIZE, u8;
+                ("AT+CNT", "+CNT: ") => counter,
+            };
+            let mut parser: DynAtParser<SIZE, u8> = DynAtParser::new();
+            parser.set_commands(commands);
+            assert!(parser.execute_with_state("AT+CNT", &mut step).is_ok());
+        }
+        assert_eq!(counter.count, 7);
+    }
+
+    #[cfg(all(feature = "derive", not(feature = "exec-set-only")))]
+    struct Clock {
+        hour: u8,
+    }
+
+    #[cfg(all(feature = "derive", not(feature = "exec-set-only")))]
+    #[crate::at_context(SIZE)]
+    impl Clock {
+        #[exec]
+        fn reset(&mut self, at_response: &'static str) -> AtResult<'_, SIZE> {
+            self.hour = 0;
+            Ok((at_response, AtResponse::Ok))
+        }
+
+        #[query]
+        fn hour(&mut self, at_response: &'static str, responder: &mut Responder<'_>) -> AtResult<'_, SIZE> {
+            use core::fmt::Write;
+
+            write!(responder, "{}", self.hour).map_err(|_| (at_response, AtError::InvalidArgs))?;
+            Ok((at_response, AtResponse::Ok))
+        }
+
+        #[set]
+        fn set_hour(&mut self, at_response: &'static str, args: Args) -> AtResult<'_, SIZE> {
+            self.hour = args.get_u8(0).map_err(|e| (at_response, e.into()))?;
+            Ok((at_response, AtResponse::Ok))
+        }
+    }
+
+    #[cfg(all(feature = "derive", not(feature = "exec-set-only")))]
+    #[test]
+    fn at_context_forwards_marked_methods() {
+        let mut clock = Clock { hour: 7 };
+        let mut response = [0u8; SIZE];
+        let commands = at_modules! {
+            SIZE;
+            ("AT+CCLK", "+CCLK: ") => clock,
         };
-    };
-}
\ No newline at end of file
+        let mut parser: DynAtParser<SIZE> = DynAtParser::new();
+        parser.set_response_buffer(&mut response);
+        parser.set_commands(commands);
+
+        assert!(matches!(parser.execute("AT+CCLK?"), Ok(("+CCLK: ", AtResponse::Text("7")))));
+        assert!(parser.execute("AT+CCLK=12").is_ok());
+        assert!(matches!(parser.execute("AT+CCLK?"), Ok((_, AtResponse::Text("12")))));
+        assert!(parser.execute("AT+CCLK").is_ok());
+        assert!(matches!(parser.execute("AT+CCLK=?"), Err((_, AtError::NotSupported))));
+        assert!(matches!(parser.execute("AT+CCLK?"), Ok((_, AtResponse::Text("0")))));
+    }
+
+    #[cfg(feature = "derive")]
+    #[derive(crate::AtResponse, Debug, PartialEq)]
+    #[at_response("+CSQ:")]
+    struct Csq(u8, u8);
+
+    #[cfg(feature = "derive")]
+    #[derive(crate::AtResponse, Debug, PartialEq)]
+    #[at_response("+COPS")]
+    struct Cops<'r> {
+        mode: u8,
+        format: Option<u8>,
+        pub operator: Option<alloc::borrow::Cow<'r, str>>,
+    }
+
+    #[cfg(feature = "derive")]
+    #[test]
+    fn derived_responses_parse_information_lines() {
+        use crate::client::{FromResponse, Response, ResponseParser};
+
+        let mut modem: ResponseParser<64> = ResponseParser::new();
+        let mut csq = None;
+        for &byte in b"AT+CSQ\r\r\n+CSQ: 23,99\r\n" {
+            if let Some(Response::Info(args)) = modem.push(byte) {
+                csq = Some(args.parse::<Csq>());
+            }
+        }
+        assert_eq!(csq, Some(Ok(Csq(23, 99))));
+        assert_eq!(Csq::from_line("+CSQ: 23"), Some(Err(ArgError::Missing(1))));
+
+        let cops = Cops::from_line(r#"+COPS: 0,0,"Vodafone \"IT\"""#).unwrap().unwrap();
+        assert_eq!(cops.operator.as_deref(), Some(r#"Vodafone "IT""#));
+        assert_eq!(Cops::from_line("+COPS: 2"), Some(Ok(Cops { mode: 2, format: None, operator: None })));
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn registered_handlers_answer_with_owned_text() {
+        use crate::registry::Registry;
+        use alloc::string::String;
+
+        struct Banner(usize);
+
+        impl AtContext<16> for Banner {
+            fn exec(&mut self, at_response: &'static str) -> AtResult<'_, 16> {
+                Ok((at_response, String::from("x").repeat(self.0).into()))
+            }
+        }
+
+        let mut registry: Registry<16> = Registry::new();
+        registry.register("AT+BANNER", "+BANNER: ", Banner(40));
+        registry.register("AT+EMPTY", "", Banner(0));
+        assert_eq!(registry.len(), 2);
+
+        let mut commands = registry.commands();
+        let mut parser: DynAtParser<16> = DynAtParser::new();
+        parser.set_commands(&mut commands);
+        let result = parser.execute("AT+BANNER");
+        assert!(matches!(result, Ok(("+BANNER: ", AtResponse::TextOwned(ref text))) if text.len() == 40));
+        assert!(matches!(parser.execute("AT+EMPTY"), Ok((_, ref response)) if response.text().is_none()));
+    }
+
+    #[cfg(feature = "defmt")]
+    #[test]
+    fn errors_are_loggable_with_defmt() {
+        fn loggable<T: defmt::Format>(_: &T) {}
+
+        loggable(&AtError::<'_, core::convert::Infallible>::Unhandled("busy"));
+        loggable(&AtError::<'_, ArgError>::User(ArgError::Invalid(1)));
+        loggable(&crate::context::Form::Query);
+        loggable(&crate::response::ResultCode::Connect(Some(115200)));
+    }
+}
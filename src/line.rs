@@ -0,0 +1,394 @@
+/***************************************************************************
+ *
+ * AT Command Parser
+ * Copyright (C) 2026 Antonio Salsi <passy.linux@zresa.it>
+ *
+ * This library is free software; you can redistribute it and/or
+ * modify it under the terms of the GNU Lesser General Public
+ * License as published by the Free Software Foundation; either
+ * version 2.1 of the License, or (at your option) any later version.
+ *
+ * This library is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the GNU
+ * Lesser General Public License for more details.
+ *
+ * You should have received a copy of the GNU Lesser General Public
+ * License along with this library; if not, see <https://www.gnu.org/licenses/>.
+ *
+ ***************************************************************************/
+
+//! Line accumulation layer
+//!
+//! Collects incoming bytes into complete command lines. The characters that
+//! terminate a command line and that format response lines are configurable
+//! at runtime, mirroring the V.250 `S3` (command line termination character)
+//! and `S4` (response formatting character) registers.
+
+/// Line ending convention used on the command or the response side.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LineEnding {
+    /// Carriage return only (`\r`), the V.250 default for `S3`
+    Cr,
+    /// Line feed only (`\n`)
+    Lf,
+    /// Carriage return followed by line feed (`\r\n`)
+    CrLf,
+}
+
+impl LineEnding {
+    /// Characters emitted for this line ending.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use at_parser_rs::line::LineEnding;
+    ///
+    /// assert_eq!(LineEnding::CrLf.as_str(), "\r\n");
+    /// ```
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            LineEnding::Cr => "\r",
+            LineEnding::Lf => "\n",
+            LineEnding::CrLf => "\r\n",
+        }
+    }
+
+    /// Returns `true` when `byte` completes a command line.
+    ///
+    /// With [`LineEnding::CrLf`] the line is completed by the `\n`.
+    pub const fn terminates(self, byte: u8) -> bool {
+        match self {
+            LineEnding::Cr => byte == b'\r',
+            LineEnding::Lf | LineEnding::CrLf => byte == b'\n',
+        }
+    }
+
+    /// Returns `true` when `byte` is a line ending character that is not a
+    /// terminator for this convention and must be dropped from the line.
+    pub const fn discards(self, byte: u8) -> bool {
+        match self {
+            LineEnding::Cr => byte == b'\n',
+            LineEnding::Lf | LineEnding::CrLf => byte == b'\r',
+        }
+    }
+}
+
+/// Fixed-capacity buffer that accumulates bytes into command lines.
+///
+/// Bytes are appended with [`push`](LineBuffer::push) until the configured
+/// [`LineEnding`] completes the line; the line is then retrieved with
+/// [`take`](LineBuffer::take), which also resets the buffer for the next line.
+/// A line longer than the [limit](LineBuffer::set_limit), `SIZE` bytes by
+/// default, is discarded up to its terminator and reported as
+/// [`overflowed`](LineBuffer::overflowed).
+///
+/// The last line taken is kept in a second `SIZE`-byte buffer so that the
+/// V.250 `A/` command (repeat last command line) can be served.
+///
+/// # Example
+///
+/// ```rust
+/// use at_parser_rs::line::{LineBuffer, LineEnding};
+///
+/// let mut line: LineBuffer<32> = LineBuffer::new();
+/// line.set_ending(LineEnding::Lf);
+///
+/// let mut complete = false;
+/// for &byte in b"AT+GMR\r\n" {
+///     complete = line.push(byte);
+/// }
+/// assert!(complete);
+/// assert_eq!(line.take(), b"AT+GMR");
+/// ```
+pub struct LineBuffer<const SIZE: usize> {
+    /// Storage for the line being accumulated
+    buf: [u8; SIZE],
+    /// Number of valid bytes in `buf`
+    len: usize,
+    /// Copy of the last line returned by `take`, replayed by `A/`
+    last: [u8; SIZE],
+    /// Number of valid bytes in `last`
+    last_len: usize,
+    /// Command line termination convention
+    ending: LineEnding,
+    /// Command line editing character (V.250 `S5`)
+    backspace: u8,
+    /// Maximum length of a command line, at most `SIZE`
+    limit: usize,
+    /// Whether the line being received exceeded `limit`
+    overflowed: bool,
+    /// Whether bytes before the `AT` prefix are dropped
+    resync: bool,
+}
+
+impl<const SIZE: usize> LineBuffer<SIZE> {
+
+    /// Create an empty line buffer terminated by `\r` (V.250 default).
+    pub const fn new() -> Self {
+        Self {
+            buf: [0; SIZE],
+            len: 0,
+            last: [0; SIZE],
+            last_len: 0,
+            ending: LineEnding::Cr,
+            backspace: 0x08,
+            limit: SIZE,
+            overflowed: false,
+            resync: false,
+        }
+    }
+
+    /// Current command line termination convention.
+    pub const fn ending(&self) -> LineEnding {
+        self.ending
+    }
+
+    /// Change the command line termination convention.
+    ///
+    /// Takes effect from the next received byte; bytes already buffered are kept.
+    pub fn set_ending(&mut self, ending: LineEnding) {
+        self.ending = ending;
+    }
+
+    /// Current command line editing character.
+    pub const fn backspace(&self) -> u8 {
+        self.backspace
+    }
+
+    /// Change the command line editing character (V.250 `S5`, `8` by default).
+    ///
+    /// Receiving this character removes the last buffered byte.
+    pub fn set_backspace(&mut self, backspace: u8) {
+        self.backspace = backspace;
+    }
+
+    /// Maximum length of a command line.
+    pub const fn limit(&self) -> usize {
+        self.limit
+    }
+
+    /// Change the maximum length of a command line, terminator excluded.
+    ///
+    /// `limit` is capped at `SIZE`; takes effect from the next received byte.
+    pub fn set_limit(&mut self, limit: usize) {
+        self.limit = limit.min(SIZE);
+    }
+
+    /// Returns `true` when the line just terminated exceeded the
+    /// [limit](LineBuffer::set_limit) and was discarded.
+    ///
+    /// Cleared by [`take`](LineBuffer::take) and [`clear`](LineBuffer::clear).
+    pub const fn overflowed(&self) -> bool {
+        self.overflowed
+    }
+
+    /// Whether lines resynchronize on the `AT` prefix.
+    pub const fn resync(&self) -> bool {
+        self.resync
+    }
+
+    /// Enable or disable resynchronization on the `AT` prefix.
+    ///
+    /// V.250 has the DCE ignore everything received before `AT` (or `A/`):
+    /// line noise or the partial characters sent while it was resetting.
+    /// When enabled, bytes are dropped until an `A` followed by `T` or `/`,
+    /// in either case, opens the line, so `x\u{0}AT+GMR` yields `AT+GMR` and
+    /// a line of noise alone yields nothing. Lines without the prefix are
+    /// then dropped too. Disabled by default.
+    pub fn set_resync(&mut self, enabled: bool) {
+        self.resync = enabled;
+    }
+
+    /// Number of bytes currently buffered.
+    pub const fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns `true` when no byte is buffered.
+    pub const fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Append `byte` to the buffer.
+    ///
+    /// Returns `true` when `byte` terminates a non-empty line, which can then
+    /// be retrieved with [`take`](LineBuffer::take). Terminators received on an
+    /// empty line (e.g. the `\n` of a `\r\n` pair with [`LineEnding::Cr`]) are
+    /// ignored.
+    ///
+    /// Once the line exceeds the [limit](LineBuffer::set_limit) every byte up
+    /// to the terminator is dropped; the terminator then returns `true` with
+    /// [`overflowed`](LineBuffer::overflowed) set.
+    ///
+    /// When the buffer holds `A/` (V.250 repeat command, no terminator needed)
+    /// the previous line is restored and `true` is returned, unless no line
+    /// has been taken yet.
+    pub fn push(&mut self, byte: u8) -> bool {
+        if self.ending.terminates(byte) {
+            if self.resync && self.len == 1 {
+                // A lone `A` is noise too
+                self.len = 0;
+            }
+            return self.len > 0 || self.overflowed;
+        }
+
+        if self.overflowed || self.ending.discards(byte) {
+            return false;
+        }
+
+        if byte == self.backspace {
+            self.len = self.len.saturating_sub(1);
+            return false;
+        }
+
+        if self.resync && !self.prefixed(byte) {
+            self.len = 0;
+            return false;
+        }
+
+        if self.len == self.limit {
+            self.overflowed = true;
+            return false;
+        }
+        self.buf[self.len] = byte;
+        self.len += 1;
+
+        if self.buf[..self.len].eq_ignore_ascii_case(b"A/") {
+            self.buf[..self.last_len].copy_from_slice(&self.last[..self.last_len]);
+            self.len = self.last_len;
+            return self.len > 0;
+        }
+
+        false
+    }
+
+    /// Whether `byte` keeps the line on the `AT` prefix, restarting it on a
+    /// repeated `A`.
+    fn prefixed(&mut self, byte: u8) -> bool {
+        match self.len {
+            0 => byte.eq_ignore_ascii_case(&b'a'),
+            1 if byte.eq_ignore_ascii_case(&b'a') => {
+                self.len = 0;
+                true
+            }
+            1 => byte.eq_ignore_ascii_case(&b't') || byte == b'/',
+            _ => true,
+        }
+    }
+
+    /// Return the buffered line and reset the buffer.
+    ///
+    /// The line is remembered for a later `A/`. The returned slice stays
+    /// valid until the next mutation of the buffer.
+    pub fn take(&mut self) -> &[u8] {
+        let len = self.len;
+        self.len = 0;
+        self.overflowed = false;
+        self.last[..len].copy_from_slice(&self.buf[..len]);
+        self.last_len = len;
+        &self.buf[..len]
+    }
+
+    /// Append `byte` verbatim, without interpreting terminators or editing
+    /// characters, returning `false` when the buffer is full.
+    pub(crate) fn push_raw(&mut self, byte: u8) -> bool {
+        if self.len == SIZE {
+            return false;
+        }
+        self.buf[self.len] = byte;
+        self.len += 1;
+        true
+    }
+
+    /// Bytes buffered so far.
+    pub(crate) fn as_bytes(&self) -> &[u8] {
+        &self.buf[..self.len]
+    }
+
+    /// Discard any buffered byte.
+    pub fn clear(&mut self) {
+        self.len = 0;
+        self.overflowed = false;
+    }
+}
+
+impl<const SIZE: usize> Default for LineBuffer<SIZE> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{LineBuffer, LineEnding};
+
+    fn feed<const SIZE: usize>(line: &mut LineBuffer<SIZE>, bytes: &[u8]) -> usize {
+        bytes.iter().filter(|&&b| line.push(b)).count()
+    }
+
+    #[test]
+    fn cr_terminates_and_ignores_lf() {
+        let mut line: LineBuffer<16> = LineBuffer::new();
+
+        assert_eq!(feed(&mut line, b"AT\r\n"), 1);
+        assert_eq!(line.take(), b"AT");
+        assert!(line.is_empty());
+    }
+
+    #[test]
+    fn lf_terminates_and_drops_cr() {
+        let mut line: LineBuffer<16> = LineBuffer::new();
+        line.set_ending(LineEnding::CrLf);
+
+        assert_eq!(feed(&mut line, b"AT+A\r"), 0);
+        assert_eq!(feed(&mut line, b"\n"), 1);
+        assert_eq!(line.take(), b"AT+A");
+    }
+
+    #[test]
+    fn a_slash_restores_last_line() {
+        let mut line: LineBuffer<16> = LineBuffer::new();
+
+        assert_eq!(feed(&mut line, b"a/"), 0);
+        assert_eq!(feed(&mut line, b"\r"), 0);
+        assert_eq!(feed(&mut line, b"ATI\r"), 1);
+        assert_eq!(line.take(), b"ATI");
+        assert_eq!(feed(&mut line, b"A/"), 1);
+        assert_eq!(line.take(), b"ATI");
+    }
+
+    #[test]
+    fn backspace_removes_last_byte() {
+        let mut line: LineBuffer<16> = LineBuffer::new();
+
+        assert_eq!(feed(&mut line, b"ATX\x08I\r"), 1);
+        assert_eq!(line.take(), b"ATI");
+    }
+
+    #[test]
+    fn noise_before_the_prefix_is_dropped_with_resync() {
+        let mut line: LineBuffer<16> = LineBuffer::new();
+        line.set_resync(true);
+
+        assert_eq!(feed(&mut line, b"\xff\x00+x\r"), 0);
+        assert_eq!(feed(&mut line, b"A\r"), 0);
+        assert_eq!(feed(&mut line, b"zAaXat+gmr\r"), 1);
+        assert_eq!(line.take(), b"at+gmr");
+        assert_eq!(feed(&mut line, b"~a/"), 1);
+        assert_eq!(line.take(), b"at+gmr");
+    }
+
+    #[test]
+    fn overflowing_lines_are_discarded() {
+        let mut line: LineBuffer<8> = LineBuffer::new();
+        line.set_limit(4);
+
+        assert_eq!(feed(&mut line, b"AT+LONG\r"), 1);
+        assert!(line.overflowed());
+        line.clear();
+        assert_eq!(feed(&mut line, b"AT+A\r"), 1);
+        assert!(!line.overflowed());
+        assert_eq!(line.take(), b"AT+A");
+    }
+}
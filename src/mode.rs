@@ -0,0 +1,139 @@
+/***************************************************************************
+ *
+ * AT Command Parser
+ * Copyright (C) 2026 Antonio Salsi <passy.linux@zresa.it>
+ *
+ * This library is free software; you can redistribute it and/or
+ * modify it under the terms of the GNU Lesser General Public
+ * License as published by the Free Software Foundation; either
+ * version 2.1 of the License, or (at your option) any later version.
+ *
+ * This library is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the GNU
+ * Lesser General Public License for more details.
+ *
+ * You should have received a copy of the GNU Lesser General Public
+ * License along with this library; if not, see <https://www.gnu.org/licenses/>.
+ *
+ ***************************************************************************/
+
+ 
+//! Parser modes
+//!
+//! Besides parsing command lines, the parser hands the received bytes to a
+//! handler collecting its input (data entry, see
+//! [`AtResponse::Continue`](crate::AtResponse::Continue)) or to the
+//! [`DataSink`](crate::online::DataSink) of a connection. [`Mode`] names
+//! these states and a [`ModeListener`] is told about every change, so the
+//! application can reconfigure its receive DMA, flow control or status LEDs
+//! along with the parser:
+//!
+//! ```text
+//! Command        ── Continue ───────────────────────► DataEntry
+//! DataEntry      ── result / cancel_input ──────────► Command (OnlineCommand)
+//! Command        ── CONNECT / enter_online_data ────► OnlineData
+//! OnlineData     ── +++ / enter_online_command ─────► OnlineCommand
+//! OnlineCommand  ── ATO (CONNECT) / enter_online_data ► OnlineData
+//! OnlineData, OnlineCommand ── hang_up ─────────────► Command
+//! ```
+
+/// Mode of the parser, see [`AtParser::mode`](crate::parser::AtParser::mode)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Mode {
+    /// Received bytes form command lines
+    Command,
+    /// Received bytes go to the handler awaiting input
+    DataEntry,
+    /// Connected; received bytes go to the data sink
+    OnlineData,
+    /// Connected, received bytes form command lines
+    OnlineCommand,
+}
+
+/// Receiver of the mode changes of the parser
+pub trait ModeListener {
+    /// The parser left `from` for `to`.
+    fn on_mode(&mut self, from: Mode, to: Mode);
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec::Vec;
+
+    use super::{Mode, ModeListener};
+    use crate::context::AtContext;
+    use crate::online::DataSink;
+    use crate::parser::AtParser;
+    use crate::response::{Responder, ResultCode};
+    use crate::{Args, AtError, AtResponse, AtResult};
+
+    const SIZE: usize = 64;
+
+    /// Dials with `ATD`, sends a message with `AT+CMGS`
+    struct Modem {
+        dial: bool,
+    }
+
+    impl AtContext<SIZE> for Modem {
+        fn set_with(&mut self, at_response: &'static str, _args: Args, responder: &mut Responder<'_>) -> AtResult<'_, SIZE> {
+            if self.dial {
+                return Ok((at_response, AtResponse::FinalCode(ResultCode::Connect(None))));
+            }
+            responder.prompt_payload("> ").map_err(|_| (at_response, AtError::InvalidArgs))?;
+            Ok((at_response, AtResponse::Continue))
+        }
+
+        fn payload(&mut self, at_response: &'static str, _payload: &[u8], _responder: &mut Responder<'_>) -> AtResult<'_, SIZE> {
+            Ok((at_response, AtResponse::Ok))
+        }
+    }
+
+    struct Discard;
+
+    impl DataSink for Discard {
+        fn data(&mut self, _bytes: &[u8]) {}
+    }
+
+    struct Changes(Vec<(Mode, Mode)>);
+
+    impl ModeListener for Changes {
+        fn on_mode(&mut self, from: Mode, to: Mode) {
+            self.0.push((from, to));
+        }
+    }
+
+    #[test]
+    fn listener_sees_every_mode_change() {
+        let mut changes = Changes(Vec::new());
+        let mut sink = Discard;
+        let (mut dial, mut sms) = (Modem { dial: true }, Modem { dial: false });
+        let prefixes: &mut [(&str, &str, &mut Modem)] = &mut [("ATD", "", &mut dial)];
+        let commands: &mut [(&str, &str, &mut Modem)] = &mut [("AT+CMGS", "", &mut sms)];
+        let mut parser: AtParser<Modem, SIZE> = AtParser::new();
+        parser.set_prefix_commands(prefixes);
+        parser.set_commands(commands);
+        parser.set_data_sink(&mut sink);
+        parser.set_mode_listener(&mut changes);
+
+        parser.feed_slice(b"AT+CMGS=1\r");
+        assert_eq!(parser.mode(), Mode::DataEntry);
+        assert!(!parser.enter_online_data());
+        parser.feed_slice(b"hi\x1a");
+        parser.feed_slice(b"ATD1\r");
+        assert!(parser.enter_online_command().is_some());
+        assert!(parser.enter_online_command().is_none());
+        assert!(parser.enter_online_data());
+        parser.hang_up();
+        parser.clear_mode_listener();
+
+        assert_eq!(changes.0, [
+            (Mode::Command, Mode::DataEntry),
+            (Mode::DataEntry, Mode::Command),
+            (Mode::Command, Mode::OnlineData),
+            (Mode::OnlineData, Mode::OnlineCommand),
+            (Mode::OnlineCommand, Mode::OnlineData),
+            (Mode::OnlineData, Mode::Command),
+        ]);
+    }
+}
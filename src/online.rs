@@ -0,0 +1,345 @@
+/***************************************************************************
+ *
+ * AT Command Parser
+ * Copyright (C) 2026 Antonio Salsi <passy.linux@zresa.it>
+ *
+ * This library is free software; you can redistribute it and/or
+ * modify it under the terms of the GNU Lesser General Public
+ * License as published by the Free Software Foundation; either
+ * version 2.1 of the License, or (at your option) any later version.
+ *
+ * This library is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the GNU
+ * Lesser General Public License for more details.
+ *
+ * You should have received a copy of the GNU Lesser General Public
+ * License along with this library; if not, see <https://www.gnu.org/licenses/>.
+ *
+ ***************************************************************************/
+
+ 
+//! Online data mode
+//!
+//! V.250 distinguishes the command state, where received characters form
+//! command lines, from the online data state entered once a connection is
+//! established: every character then goes to the remote end. With a
+//! [`DataSink`] installed (see
+//! [`AtParser::set_data_sink`](crate::parser::AtParser::set_data_sink)) the
+//! parser enters the online data state as soon as a command fed to it ends
+//! with `CONNECT`, and hands the bytes fed afterwards to the sink.
+//!
+//! The DTE returns to command mode without dropping the connection with the
+//! escape sequence: a guard time of silence, three escape characters (`S2`,
+//! `+`) each less than a guard time apart, another guard time of silence. The
+//! guard time is `S12`, in fiftieths of a second (1 s by default); the
+//! escape characters are held back and forwarded to the sink if the sequence
+//! turns out to be data.
+//!
+//! ```text
+//! ATD123;   → CONNECT             online data: bytes → DataSink
+//! <1 s> +++ <1 s>   → OK          online command: ATO (CONNECT) resumes data
+//! carrier lost      → NO CARRIER  command state
+//! ```
+//!
+//! A PPP session (`ATD*99#`) ends through LCP rather than the escape
+//! sequence, and its binary frames must reach the PPP stack untouched: the
+//! dial handler claims the stream with a [`Handoff`] before answering
+//! `CONNECT`, and the PPP stack releases it on LCP termination, the parser
+//! then resuming in command mode with `NO CARRIER`.
+
+use core::sync::atomic::{AtomicBool, Ordering};
+use core::time::Duration;
+
+/// Receiver of the data sent by the DTE in online data mode
+pub trait DataSink {
+    /// Bytes received from the DTE, to be sent to the remote end.
+    fn data(&mut self, bytes: &[u8]);
+}
+
+/// Handoff of the byte stream to an external stack, such as PPP
+///
+/// Shared by the dial handler, the parser (see
+/// [`AtParser::set_handoff`](crate::parser::AtParser::set_handoff)) and the
+/// stack; only uses atomic loads and stores, so it can be a `static`.
+///
+/// # Example
+///
+/// ```rust
+/// use at_parser_rs::online::Handoff;
+///
+/// static PPP: Handoff = Handoff::new();
+///
+/// // dial handler of ATD*99#, before answering CONNECT
+/// PPP.claim();
+/// // PPP stack, on LCP Terminate-Ack
+/// PPP.release();
+/// ```
+#[derive(Debug, Default)]
+pub struct Handoff {
+    /// Whether the next connection hands the stream over
+    claimed: AtomicBool,
+    /// Whether the stack handed the stream back
+    released: AtomicBool,
+}
+
+impl Handoff {
+    /// Create a handoff with the stream not claimed.
+    pub const fn new() -> Self {
+        Self { claimed: AtomicBool::new(false), released: AtomicBool::new(false) }
+    }
+
+    /// Hand the stream of the connection answered next with `CONNECT` to the
+    /// data sink untouched: no escape sequence is detected.
+    pub fn claim(&self) {
+        self.released.store(false, Ordering::Release);
+        self.claimed.store(true, Ordering::Release);
+    }
+
+    /// Hand the stream back to the parser, e.g. once LCP terminated.
+    pub fn release(&self) {
+        self.released.store(true, Ordering::Release);
+    }
+
+    /// Take the claim made for a connection.
+    pub(crate) fn take_claim(&self) -> bool {
+        self.claimed.swap(false, Ordering::AcqRel)
+    }
+
+    /// Take the release of the stream.
+    pub(crate) fn take_release(&self) -> bool {
+        self.released.swap(false, Ordering::AcqRel)
+    }
+}
+
+/// Connection state of the parser
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OnlineState {
+    /// No connection; received characters form command lines
+    Command,
+    /// Connected; received characters go to the [`DataSink`]
+    Data,
+    /// Connected, returned to command mode with the escape sequence
+    OnlineCommand,
+}
+
+/// Escape sequence detector of the online data state
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct Escape {
+    /// When the last byte was received
+    last: Option<Duration>,
+    /// Number of escape characters received and held back
+    held: u8,
+}
+
+impl Escape {
+    /// Detector for a data state entered at `now`.
+    pub(crate) const fn new(now: Option<Duration>) -> Self {
+        Self { last: now, held: 0 }
+    }
+
+    /// Receive `byte` at `now`, the time of the clock if any.
+    ///
+    /// Returns the number of escape characters held back so far to forward
+    /// to the sink before `byte`, and whether `byte` is to be forwarded as
+    /// well.
+    pub(crate) fn byte(&mut self, byte: u8, now: Option<Duration>, escape: u8, guard: Duration) -> (u8, bool) {
+        let idle = self.elapsed(now, guard);
+        self.last = now;
+        // S2 above 127 disables the escape sequence
+        if byte != escape || escape > 127 {
+            return (core::mem::take(&mut self.held), true);
+        }
+
+        match self.held {
+            0 if idle => {
+                self.held = 1;
+                (0, false)
+            }
+            1 | 2 if !idle || now.is_none() => {
+                self.held += 1;
+                (0, false)
+            }
+            // Too late or too many: the sequence is data, a new one may
+            // start with this character
+            _ if idle => (core::mem::replace(&mut self.held, 1), false),
+            _ => (core::mem::take(&mut self.held), true),
+        }
+    }
+
+    /// Whether the escape sequence is complete at `now`: three escape
+    /// characters followed by a guard time without data.
+    pub(crate) fn escaped(&self, now: Option<Duration>, guard: Duration) -> bool {
+        self.held == 3 && self.elapsed(now, guard)
+    }
+
+    /// Whether a guard time passed since the last byte; always without a
+    /// clock.
+    fn elapsed(&self, now: Option<Duration>, guard: Duration) -> bool {
+        match (self.last, now) {
+            (Some(last), Some(now)) => now.saturating_sub(last) >= guard,
+            _ => true,
+        }
+    }
+}
+
+/// Guard time encoded in `S12`, in fiftieths of a second.
+pub(crate) fn guard_time(s12: u8) -> Duration {
+    Duration::from_millis(u64::from(s12) * 20)
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::string::String;
+    use alloc::vec::Vec;
+    use core::cell::{Cell, RefCell};
+    use core::fmt::{self, Write};
+    use core::time::Duration;
+
+    use super::{DataSink, Handoff, OnlineState};
+    use crate::client::Clock;
+    use crate::context::AtContext;
+    use crate::parser::AtParser;
+    use crate::response::ResultCode;
+    use crate::{Args, AtError, AtResponse, AtResult};
+
+    const SIZE: usize = 64;
+
+    /// Dials with `ATD`, resumes with `ATO`
+    struct Modem;
+
+    impl AtContext<SIZE> for Modem {
+        fn exec(&mut self, at_response: &'static str) -> AtResult<'_, SIZE> {
+            Ok((at_response, AtResponse::FinalCode(ResultCode::Connect(None))))
+        }
+
+        fn set(&mut self, at_response: &'static str, _args: Args) -> AtResult<'_, SIZE> {
+            Ok((at_response, AtResponse::FinalCode(ResultCode::Connect(None))))
+        }
+    }
+
+    /// Clock advanced by hand
+    struct Ticks(Cell<Duration>);
+
+    impl Ticks {
+        fn advance(&self, millis: u64) {
+            self.0.set(self.0.get() + Duration::from_millis(millis));
+        }
+    }
+
+    impl Clock for Ticks {
+        fn now(&self) -> Duration {
+            self.0.get()
+        }
+    }
+
+    struct Link(Vec<u8>);
+
+    impl DataSink for Link {
+        fn data(&mut self, bytes: &[u8]) {
+            self.0.extend_from_slice(bytes);
+        }
+    }
+
+    struct Uart<'l>(&'l RefCell<String>);
+
+    impl Write for Uart<'_> {
+        fn write_str(&mut self, s: &str) -> fmt::Result {
+            self.0.borrow_mut().push_str(s);
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn escape_needs_guard_times_and_hang_up_reports_no_carrier() {
+        let log = RefCell::new(String::new());
+        let mut uart = Uart(&log);
+        let ticks = Ticks(Cell::new(Duration::ZERO));
+        let mut link = Link(Vec::new());
+        let (mut dial, mut resume) = (Modem, Modem);
+        let prefixes: &mut [(&str, &str, &mut Modem)] = &mut [("ATD", "", &mut dial)];
+        let commands: &mut [(&str, &str, &mut Modem)] = &mut [("ATO", "", &mut resume)];
+        let mut parser: AtParser<Modem, SIZE> = AtParser::new();
+        parser.set_prefix_commands(prefixes);
+        parser.set_commands(commands);
+        parser.set_output(&mut uart);
+        parser.set_response_framing(true);
+        parser.set_data_sink(&mut link);
+        parser.set_clock(&ticks);
+
+        parser.feed_slice(b"ATE0\r");
+        parser.feed_slice(b"ATD123\r");
+        assert_eq!(parser.online_state(), OnlineState::Data);
+
+        // Without the leading guard time the escape characters are data
+        ticks.advance(1_100);
+        parser.feed_slice(b"a+b+++");
+        ticks.advance(1_100);
+        assert!(parser.poll_escape().is_none());
+
+        for _ in 0..3 {
+            parser.feed(b'+');
+            ticks.advance(100);
+        }
+        assert!(parser.poll_escape().is_none());
+        ticks.advance(1_000);
+        assert!(matches!(parser.poll_escape(), Some(Ok(("", AtResponse::Ok)))));
+        assert_eq!(parser.online_state(), OnlineState::OnlineCommand);
+
+        parser.feed_slice(b"ATO\r");
+        assert_eq!(parser.online_state(), OnlineState::Data);
+
+        // An interrupted sequence is forwarded as data
+        ticks.advance(1_100);
+        parser.feed_slice(b"++x");
+
+        assert!(matches!(parser.hang_up(), Some(Err(("", AtError::Final(ResultCode::NoCarrier))))));
+        assert_eq!(parser.online_state(), OnlineState::Command);
+        assert!(parser.hang_up().is_none());
+        parser.clear_data_sink();
+
+        assert_eq!(link.0, b"a+b+++++x");
+        assert_eq!(log.into_inner(), concat!(
+            "\r\nOK\r\n",
+            "\r\nCONNECT\r\n",
+            "\r\nOK\r\n",
+            "\r\nCONNECT\r\n",
+            "\r\nNO CARRIER\r\n",
+        ));
+    }
+
+    #[test]
+    fn claimed_stream_is_untouched_until_released() {
+        let log = RefCell::new(String::new());
+        let mut uart = Uart(&log);
+        let ticks = Ticks(Cell::new(Duration::ZERO));
+        let handoff = Handoff::new();
+        let mut link = Link(Vec::new());
+        let mut dial = Modem;
+        let prefixes: &mut [(&str, &str, &mut Modem)] = &mut [("ATD", "", &mut dial)];
+        let mut parser: AtParser<Modem, SIZE> = AtParser::new();
+        parser.set_prefix_commands(prefixes);
+        parser.set_output(&mut uart);
+        parser.set_response_framing(true);
+        parser.set_data_sink(&mut link);
+        parser.set_clock(&ticks);
+        parser.set_handoff(&handoff);
+
+        handoff.claim();
+        parser.feed_slice(b"ATD*99#\r");
+        ticks.advance(1_100);
+        parser.feed_slice(b"\x7e+++");
+        ticks.advance(1_100);
+        assert!(parser.poll_escape().is_none());
+        assert_eq!(parser.online_state(), OnlineState::Data);
+
+        // The stream is back to command lines from the next byte on
+        handoff.release();
+        assert!(parser.feed_slice(b"ATE0\r").1.unwrap().is_ok());
+        assert_eq!(parser.online_state(), OnlineState::Command);
+        parser.clear_data_sink();
+
+        assert_eq!(link.0, b"\x7e+++");
+        assert_eq!(log.into_inner(), "\r\nCONNECT\r\n\r\nNO CARRIER\r\n\r\nOK\r\n");
+    }
+}
@@ -0,0 +1,99 @@
+/***************************************************************************
+ *
+ * AT Command Parser
+ * Copyright (C) 2026 Antonio Salsi <passy.linux@zresa.it>
+ *
+ * This library is free software; you can redistribute it and/or
+ * modify it under the terms of the GNU Lesser General Public
+ * License as published by the Free Software Foundation; either
+ * version 2.1 of the License, or (at your option) any later version.
+ *
+ * This library is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the GNU
+ * Lesser General Public License for more details.
+ *
+ * You should have received a copy of the GNU Lesser General Public
+ * License along with this library; if not, see <https://www.gnu.org/licenses/>.
+ *
+ ***************************************************************************/
+
+ 
+//! Owned response payloads (`heapless` feature)
+//!
+//! Lets handlers build their response in a [`heapless::String`] with the
+//! parser capacity `SIZE`, using `write!` or `push_str`, and hand it back as
+//! the payload of an [`AtResult`].
+
+use heapless::String;
+use osal_rs::utils::Bytes;
+
+use crate::{AtResponse, AtResult};
+
+/// Conversion of an owned string into a response payload.
+pub trait IntoPayload<const SIZE: usize> {
+    /// Copy the text into a [`Bytes`] payload of the same capacity.
+    fn into_payload(self) -> Bytes<SIZE>;
+}
+
+impl<const SIZE: usize> IntoPayload<SIZE> for &String<SIZE> {
+    fn into_payload(self) -> Bytes<SIZE> {
+        let mut payload = Bytes::<SIZE>::new();
+        payload.format(format_args!("{}", self.as_str()));
+        payload
+    }
+}
+
+impl<const SIZE: usize> IntoPayload<SIZE> for String<SIZE> {
+    fn into_payload(self) -> Bytes<SIZE> {
+        (&self).into_payload()
+    }
+}
+
+/// Successful result carrying `text` as payload.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// use core::fmt::Write;
+/// use at_parser_rs::context::AtContext;
+/// use at_parser_rs::owned::respond;
+/// use at_parser_rs::{AtError, AtResult};
+///
+/// const SIZE: usize = 64;
+///
+/// struct Signal { rssi: u8, ber: u8 }
+///
+/// impl AtContext<SIZE> for Signal {
+///     fn exec(&mut self, at_response: &'static str) -> AtResult<'_, SIZE> {
+///         let mut text = heapless::String::<SIZE>::new();
+///         write!(text, "{},{}", self.rssi, self.ber).map_err(|_| (at_response, AtError::InvalidArgs))?;
+///         respond(at_response, text)
+///     }
+/// }
+/// // AT+CSQ  →  Ok(("+CSQ: ", "23,0"))
+/// ```
+pub fn respond<const SIZE: usize>(at_response: &'static str, text: impl IntoPayload<SIZE>) -> AtResult<'static, SIZE> {
+    Ok((at_response, AtResponse::InfoText(text.into_payload())))
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::format;
+    use core::fmt::Write;
+
+    use super::respond;
+
+    const SIZE: usize = 16;
+
+    #[test]
+    fn heapless_strings_become_payloads() {
+        let mut text = heapless::String::<SIZE>::new();
+        write!(text, "{},{}", 23, 0).unwrap();
+
+        let (at_response, payload) = respond::<SIZE>("+CSQ: ", &text).unwrap();
+        assert_eq!(format!("{at_response}{payload}"), "+CSQ: 23,0");
+        let (_, payload) = respond::<SIZE>("", text).unwrap();
+        assert_eq!(format!("{payload}"), "23,0");
+    }
+}
@@ -18,8 +18,31 @@
  *
  ***************************************************************************/
  
-use crate::context::AtContext;
-use crate::{AtError, AtResult, Args};
+use crate::abort::Abort;
+use crate::context::{AtContext, AtFallback, AtObserver, Form};
+use crate::client::Clock;
+use crate::cme::CmeMode;
+use crate::diagnostic::Diagnostic;
+use crate::direction::{DirectionControl, Output, Transmission};
+#[cfg(feature = "async")]
+use crate::context::AsyncAtContext;
+#[cfg(feature = "perfect-hash")]
+use crate::hash::{CommandHash, HashIndex};
+use crate::help::Help;
+use crate::line::{LineBuffer, LineEnding};
+use crate::mode::{Mode, ModeListener};
+use crate::online::{self, DataSink, Escape, Handoff, OnlineState};
+use crate::profile::{Settings, SettingsStore, SettingsView, View};
+use crate::response::{CallProgress, ErrorKind, Framing, Payload, Pending, Responder, ResultCode};
+use crate::sreg::{self, SRegisters};
+use core::cell::Cell;
+use core::cmp::Ordering;
+use core::convert::Infallible;
+use core::fmt::{self, Write};
+use core::time::Duration;
+
+use crate::{AtError, AtResponse, AtResult, Args, UserError, into_owned};
+use osal_rs::utils::Bytes;
 
 /*
 AT Command Forms:
@@ -41,6 +64,18 @@ enum AtForm<'a> {
     Set(Args<'a>),
 }
 
+impl AtForm<'_> {
+    /// Form reported to handlers through the responder.
+    const fn kind(&self) -> Form {
+        match self {
+            AtForm::Exec => Form::Exec,
+            AtForm::Query => Form::Query,
+            AtForm::Test => Form::Test,
+            AtForm::Set(_) => Form::Set,
+        }
+    }
+}
+
 /// The main AT command parser
 ///
 /// Generic over `T` which must implement the [`AtContext<SIZE>`](crate::context::AtContext) trait,
@@ -58,16 +93,21 @@ enum AtForm<'a> {
 /// # Usage Patterns
 ///
 /// ## With trait objects (recommended for mixed types):
+///
+/// Each command is usually its own struct; [`DynAtParser`] names the parser
+/// holding them as `&mut dyn AtContext` entries.
+///
 /// ```rust,no_run
-/// # use at_parser_rs::parser::AtParser;
+/// # use at_parser_rs::parser::DynAtParser;
 /// # use at_parser_rs::context::AtContext;
-/// # struct Dummy; impl AtContext<64> for Dummy {}
-/// # let mut echo_handler = Dummy; let mut reset_handler = Dummy;
+/// # struct Echo; impl AtContext<64> for Echo {}
+/// # struct Reset; impl AtContext<64> for Reset {}
+/// # let mut echo_handler = Echo; let mut reset_handler = Reset;
 /// const SIZE: usize = 64;
-/// let mut parser: AtParser<dyn AtContext<SIZE>, SIZE> = AtParser::new();
-/// let commands: &mut [(&str, &mut dyn AtContext<SIZE>)] = &mut [
-///     ("AT+ECHO", &mut echo_handler),
-///     ("AT+RST", &mut reset_handler),
+/// let mut parser: DynAtParser<SIZE> = DynAtParser::new();
+/// let commands: &mut [(&str, &str, &mut dyn AtContext<SIZE>)] = &mut [
+///     ("AT+ECHO", "+ECHO: ", &mut echo_handler),
+///     ("AT+RST", "+RST: ", &mut reset_handler),
 /// ];
 /// parser.set_commands(commands);
 /// ```
@@ -80,22 +120,162 @@ enum AtForm<'a> {
 /// # let mut handler1 = MyHandler; let mut handler2 = MyHandler;
 /// const SIZE: usize = 64;
 /// let mut parser: AtParser<MyHandler, SIZE> = AtParser::new();
-/// let commands: &mut [(&str, &mut MyHandler)] = &mut [
-///     ("AT+CMD1", &mut handler1),
-///     ("AT+CMD2", &mut handler2),
+/// let commands: &mut [(&str, &str, &mut MyHandler)] = &mut [
+///     ("AT+CMD1", "+CMD1: ", &mut handler1),
+///     ("AT+CMD2", "+CMD2: ", &mut handler2),
 /// ];
 /// parser.set_commands(commands);
 /// ```
-pub struct AtParser<'a, T, const SIZE: usize>
+pub struct AtParser<'a, T, const SIZE: usize, S = (), E = Infallible>
 where
-    T: AtContext<SIZE> + ?Sized {
+    T: AtContext<SIZE, S, E> + ?Sized {
     /// Array of registered commands with their command, AT response prefix, and handler
     pub commands: &'a mut [(&'static str, &'static str, &'a mut T)],
+    /// Whether `commands` is sorted by name and looked up by binary search
+    sorted: bool,
+    /// Index in `commands` of the last command matched by name
+    last_command: Cell<Option<usize>>,
+    /// Identifiers of the names of `commands`, compared before the names
+    ids: Option<&'a [u32]>,
+    /// Names of `commands` in a dense array, walked by the lookup
+    names: Option<&'a [&'static str]>,
+    /// Perfect hash over the names of `commands`
+    #[cfg(feature = "perfect-hash")]
+    hash: Option<HashIndex<'a>>,
+    /// Array of commands matched on a prefix (e.g. `ATD`), see [`set_prefix_commands`](AtParser::set_prefix_commands)
+    pub prefix_commands: &'a mut [(&'static str, &'static str, &'a mut T)],
+    /// Line buffer used by [`feed`](AtParser::feed) to accumulate incoming bytes
+    line: LineBuffer<SIZE>,
+    /// Response framing settings (line ending, `ATV`)
+    framing: Framing,
+    /// Optional S-register file served by `ATS<n>=` / `ATS<n>?`
+    registers: Option<SRegisters>,
+    /// Whether command names are matched ignoring ASCII case
+    case_insensitive: bool,
+    /// Maximum number of parameters handed to a handler
+    arg_limit: usize,
+    /// Whether text after the `?` / `=?` of an extended command is rejected
+    strict: bool,
+    /// Extended command prefix characters served by a single registration
+    prefixes: &'static str,
+    /// Handler receiving the lines holding unknown commands
+    fallback: Option<&'a mut dyn AtFallback<SIZE, E>>,
+    /// Hook observing the dispatched commands
+    observer: Option<&'a mut dyn AtObserver<SIZE, E>>,
+    /// Writer towards the DTE (echo and responses), with its direction
+    /// control
+    output: Output<'a>,
+    /// Whether received characters are echoed back (`ATE1`)
+    echo: bool,
+    /// Whether results of fed lines are framed and written to the output
+    framed: bool,
+    /// Unsolicited result codes held back while a command line is received
+    urcs: Pending<SIZE>,
+    /// Caller-owned buffer handlers write their response into
+    response_buffer: &'a mut [u8],
+    /// User state handed to every handler
+    state: Option<&'a mut S>,
+    /// Flag aborting the executing handler, shared with the receive path
+    abort: Option<&'a Abort>,
+    /// Help table handed to every handler
+    help: Option<&'a Help>,
+    /// Handler receiving the input, after it returned [`AtResponse::Continue`]
+    awaiting: Option<Target>,
+    /// Buffer of the payloads collected for [`AtContext::payload`], in place
+    /// of the line buffer
+    payload_buffer: Option<&'a mut [u8]>,
+    /// Receiver of the online data, enabling the online data state
+    sink: Option<&'a mut dyn DataSink>,
+    /// Time source of the escape guard times
+    clock: Option<&'a dyn Clock>,
+    /// Connection state
+    online: OnlineState,
+    /// Escape sequence detector of the online data state
+    escape: Escape,
+    /// Handoff of the stream to an external stack, if any
+    handoff: Option<&'a Handoff>,
+    /// Whether the online data is handed over untouched, see [`Handoff`]
+    transparent: bool,
+    /// Mode last reported to the listener
+    mode: Mode,
+    /// Receiver of the mode changes
+    listener: Option<&'a mut dyn ModeListener>,
+    /// Storage of the profile served by `AT&W` and `ATZ`
+    store: Option<&'a mut dyn SettingsStore>,
+    /// Settings of the application listed by `AT&V`
+    views: &'a mut [&'a mut dyn SettingsView],
+    /// Where and why the last rejected line failed
+    diagnostic: Option<Diagnostic>,
+}
+
+/// Parser dispatching to handlers of different types through
+/// `&mut dyn AtContext` entries.
+///
+/// The handlers may borrow data living as long as the parser.
+pub type DynAtParser<'a, const SIZE: usize, S = (), E = Infallible> = AtParser<'a, dyn AtContext<SIZE, S, E> + 'a, SIZE, S, E>;
+
+/// Borrow the state of `parser` needed to send a response, field by field so
+/// that a response borrowing the command tables can be sent.
+macro_rules! reply {
+    ($parser:expr) => {
+        Reply {
+            framing: response_framing($parser.framing, $parser.registers.as_ref()),
+            output: &mut $parser.output,
+            framed: $parser.framed,
+            urcs: &mut $parser.urcs,
+            awaiting: $parser.awaiting.is_some(),
+            sink: $parser.sink.is_some(),
+            online: &mut $parser.online,
+            escape: &mut $parser.escape,
+            clock: $parser.clock,
+            handoff: $parser.handoff,
+            transparent: &mut $parser.transparent,
+            mode: &mut $parser.mode,
+            listener: $parser.listener.as_deref_mut(),
+        }
+    };
+}
+
+/// Split the fields of a parser, or of a dispatcher being reborrowed, into
+/// a [`Dispatcher`]; the fields stored differently by the two are passed in.
+macro_rules! dispatcher {
+    ($source:expr, $registers:expr, $output:expr, $buffer:expr, $diagnostic:expr) => {
+        Dispatcher {
+            commands: $source.commands,
+            sorted: $source.sorted,
+            last_command: &$source.last_command,
+            ids: $source.ids,
+            names: $source.names,
+            #[cfg(feature = "perfect-hash")]
+            hash: $source.hash,
+            prefix_commands: $source.prefix_commands,
+            registers: $registers,
+            case_insensitive: $source.case_insensitive,
+            arg_limit: $source.arg_limit,
+            strict: $source.strict,
+            prefixes: $source.prefixes,
+            fallback: $source.fallback.as_deref_mut(),
+            observer: $source.observer.as_deref_mut(),
+            echo: &mut $source.echo,
+            framing: &mut $source.framing,
+            output: $output,
+            framed: $source.framed,
+            buffer: $buffer,
+            state: $source.state.as_deref_mut(),
+            abort: $source.abort,
+            help: $source.help,
+            awaiting: &mut $source.awaiting,
+            store: $source.store.as_deref_mut(),
+            views: &mut *$source.views,
+            diagnostic: $diagnostic,
+        }
+    };
 }
 
-impl<'a, T, const SIZE: usize> AtParser<'a, T, SIZE>
+impl<'a, T, const SIZE: usize, S, E> AtParser<'a, T, SIZE, S, E>
 where
-    T: AtContext<SIZE> + ?Sized {
+    T: AtContext<SIZE, S, E> + ?Sized,
+    E: UserError {
 
     /// Create a new empty parser with no registered commands.
     ///
@@ -113,7 +293,46 @@ where
     /// // parser has no commands yet; execute() will return Err(UnknownCommand)
     /// ```
     pub const fn new() -> Self {
-        Self { commands: & mut [] }
+        Self {
+            commands: &mut [],
+            sorted: false,
+            last_command: Cell::new(None),
+            ids: None,
+            names: None,
+            #[cfg(feature = "perfect-hash")]
+            hash: None,
+            prefix_commands: &mut [],
+            line: LineBuffer::new(),
+            framing: Framing::new(),
+            registers: None,
+            case_insensitive: false,
+            arg_limit: usize::MAX,
+            strict: false,
+            prefixes: "+",
+            fallback: None,
+            observer: None,
+            output: Output::new(),
+            echo: false,
+            framed: false,
+            urcs: Pending::new(),
+            response_buffer: &mut [],
+            state: None,
+            abort: None,
+            help: None,
+            awaiting: None,
+            payload_buffer: None,
+            sink: None,
+            clock: None,
+            online: OnlineState::Command,
+            escape: Escape::new(None),
+            handoff: None,
+            transparent: false,
+            mode: Mode::Command,
+            listener: None,
+            store: None,
+            views: &mut [],
+            diagnostic: None,
+        }
     }
 
     /// Register the commands that this parser will dispatch.
@@ -124,7 +343,8 @@ where
     /// - `at_response` — prefix forwarded to every handler method (e.g. `"+ECHO: "`)
     /// - `handler` — mutable reference to the [`AtContext`] implementation
     ///
-    /// Matching is case-sensitive.
+    /// Matching is case-sensitive unless enabled with
+    /// [`set_case_insensitive`](AtParser::set_case_insensitive).
     ///
     /// # Arguments
     ///
@@ -172,123 +392,4827 @@ where
     /// parser.set_commands(commands);
     /// ```
     pub fn set_commands(&mut self, commands: &'a mut [(&'static str, &'static str, &'a mut T)]) {
+        debug_assert_unique(commands);
+        self.attach(commands);
+    }
+
+    /// Install an unsorted table already checked for duplicates, as
+    /// [`StaticAtParser`](crate::fixed::StaticAtParser) does before each call.
+    pub(crate) fn attach(&mut self, commands: &'a mut [(&'static str, &'static str, &'a mut T)]) {
         self.commands = commands;
+        self.last_command.set(None);
+        self.sorted = false;
+        self.ids = None;
+        self.names = None;
+        #[cfg(feature = "perfect-hash")]
+        {
+            self.hash = None;
+        }
     }
 
-    /// Parse and execute an AT command string.
+    /// Register commands sorted by name, looked up by binary search.
     ///
-    /// Leading and trailing whitespace is stripped before parsing.
-    /// The command name is matched against the registered commands; if found,
-    /// the appropriate handler method is called based on the command form
-    /// detected from the suffix.
+    /// Large tables are resolved in `O(log n)` comparisons instead of a
+    /// linear scan. The table must be ordered by [`command_order`]: build it
+    /// with [`at_sorted_modules!`](crate::at_sorted_modules), which checks the
+    /// order at compile time, or sort it with [`sort_commands`]. Debug builds
+    /// assert that it is sorted.
     ///
-    /// | Input suffix | Dispatches to |
-    /// |---|---|
-    /// | *(none)* | [`exec`](crate::context::AtContext::exec) |
-    /// | `?` | [`query`](crate::context::AtContext::query) |
-    /// | `=?` | [`test`](crate::context::AtContext::test) |
-    /// | `=<args>` | [`set`](crate::context::AtContext::set) |
+    /// # Example
     ///
-    /// # Arguments
+    /// ```rust,no_run
+    /// # use at_parser_rs::parser::DynAtParser;
+    /// # use at_parser_rs::context::AtContext;
+    /// # use at_parser_rs::at_sorted_modules;
+    /// # const SIZE: usize = 64;
+    /// # struct Info; impl AtContext<SIZE> for Info {}
+    /// let (mut cgmi, mut cgmm, mut cgmr) = (Info, Info, Info);
+    /// let commands = at_sorted_modules! {
+    ///     SIZE;
+    ///     ("AT+CGMI", "+CGMI: ") => cgmi,
+    ///     ("AT+CGMM", "+CGMM: ") => cgmm,
+    ///     ("AT+CGMR", "+CGMR: ") => cgmr,
+    /// };
+    /// let mut parser: DynAtParser<SIZE> = DynAtParser::new();
+    /// parser.set_sorted_commands(commands);
+    /// ```
+    pub fn set_sorted_commands(&mut self, commands: &'a mut [(&'static str, &'static str, &'a mut T)]) {
+        debug_assert!(
+            commands.windows(2).all(|pair| command_order(pair[0].0, pair[1].0).is_lt()),
+            "command table is not sorted by name"
+        );
+        debug_assert_unique(commands);
+        self.attach(commands);
+        self.sorted = true;
+    }
+
+    /// Register commands looked up through a perfect hash of their names.
     ///
-    /// * `input` — raw AT command string (e.g. `"AT+CMD?"`, `"AT+CMD=1,2"`)
+    /// A command is resolved with one hash and a single name comparison,
+    /// whatever the size of the table. `hash` must be computed over the
+    /// names of `commands`, in table order: build both with
+    /// [`at_hashed_modules!`](crate::at_hashed_modules). Debug builds assert
+    /// that they match.
     ///
-    /// # Returns
+    /// # Example
     ///
-    /// * `Ok(Bytes<SIZE>)` — response buffer returned by the matched handler
-    /// * `Err(AtError::UnknownCommand)` — no handler found for the command name
-    /// * `Err(AtError::NotSupported)` — handler found but the requested form is not implemented
-    /// * `Err(AtError::InvalidArgs)` — handler returned an argument error
+    /// ```rust,no_run
+    /// # use at_parser_rs::parser::DynAtParser;
+    /// # use at_parser_rs::context::AtContext;
+    /// # use at_parser_rs::at_hashed_modules;
+    /// # const SIZE: usize = 64;
+    /// # struct Info; impl AtContext<SIZE> for Info {}
+    /// let (mut cgmi, mut cgmm) = (Info, Info);
+    /// let (commands, hash) = at_hashed_modules! {
+    ///     SIZE;
+    ///     ("AT+CGMI", "+CGMI: ") => cgmi,
+    ///     ("AT+CGMM", "+CGMM: ") => cgmm,
+    /// };
+    /// let mut parser: DynAtParser<SIZE> = DynAtParser::new();
+    /// parser.set_hashed_commands(commands, hash);
+    /// ```
+    #[cfg(feature = "perfect-hash")]
+    pub fn set_hashed_commands<const SLOTS: usize>(
+        &mut self,
+        commands: &'a mut [(&'static str, &'static str, &'a mut T)],
+        hash: &'a CommandHash<SLOTS>,
+    ) {
+        let index = hash.index();
+        debug_assert!(
+            commands.len() == hash.len()
+                && commands.iter().enumerate().all(|(i, (name, _, _))| index.candidate(strip_at(name, true)) == Some(i)),
+            "the hash does not match the command table"
+        );
+        self.attach(commands);
+        self.hash = Some(index);
+    }
+
+    /// Register commands with their names interned as identifiers.
+    ///
+    /// The [`command_id`] of every name is computed here into `ids`, and the
+    /// lookup compares the identifier of the received name with each entry
+    /// before comparing names: on large tables of long vendor names
+    /// (`AT+QCFGEXT`, `AT+USECPRF`), only the entries with the same
+    /// identifier are compared byte by byte.
+    ///
+    /// # Errors
+    ///
+    /// [`TooFewIds`] when `ids` is shorter than `commands`; the parser is
+    /// unchanged.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// # use at_parser_rs::parser::DynAtParser;
+    /// # use at_parser_rs::context::AtContext;
+    /// # const SIZE: usize = 64;
+    /// # struct Info; impl AtContext<SIZE> for Info {}
+    /// let (mut qcfg, mut qeng) = (Info, Info);
+    /// let commands: &mut [(&str, &str, &mut dyn AtContext<SIZE>)] = &mut [
+    ///     ("AT+QCFGEXT", "+QCFGEXT: ", &mut qcfg),
+    ///     ("AT+QENGEXT", "+QENGEXT: ", &mut qeng),
+    /// ];
+    /// let mut ids = [0; 2];
+    /// let mut parser: DynAtParser<SIZE> = DynAtParser::new();
+    /// parser.set_interned_commands(commands, &mut ids).unwrap();
+    /// ```
+    pub fn set_interned_commands(
+        &mut self,
+        commands: &'a mut [(&'static str, &'static str, &'a mut T)],
+        ids: &'a mut [u32],
+    ) -> Result<(), TooFewIds> {
+        if ids.len() < commands.len() {
+            return Err(TooFewIds);
+        }
+        for (id, (name, _, _)) in ids.iter_mut().zip(commands.iter()) {
+            *id = command_id(name);
+        }
+        self.set_commands(commands);
+        self.ids = Some(ids);
+        Ok(())
+    }
+
+    /// Register commands together with their names in a separate array.
+    ///
+    /// The lookup walks `names`, a dense array of `&str`, instead of the
+    /// table entries, so the handler pointers of the entries it skips are not
+    /// pulled into the cache; only the matched entry is read. This pays off on
+    /// cores fetching the table from slow flash (Cortex-M without cache).
+    /// `names` must list the names of `commands` in table order: build both
+    /// with [`at_split_modules!`](crate::at_split_modules).
+    ///
+    /// # Errors
+    ///
+    /// [`NamesMismatch`] when `names` does not list the names of `commands`
+    /// in table order; the parser is unchanged.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// # use at_parser_rs::parser::DynAtParser;
+    /// # use at_parser_rs::context::AtContext;
+    /// # use at_parser_rs::at_split_modules;
+    /// # const SIZE: usize = 64;
+    /// # struct Info; impl AtContext<SIZE> for Info {}
+    /// let (mut cgmi, mut cgmm) = (Info, Info);
+    /// let (commands, names) = at_split_modules! {
+    ///     SIZE;
+    ///     ("AT+CGMI", "+CGMI: ") => cgmi,
+    ///     ("AT+CGMM", "+CGMM: ") => cgmm,
+    /// };
+    /// let mut parser: DynAtParser<SIZE> = DynAtParser::new();
+    /// parser.set_split_commands(commands, names).unwrap();
+    /// ```
+    pub fn set_split_commands(
+        &mut self,
+        commands: &'a mut [(&'static str, &'static str, &'a mut T)],
+        names: &'a [&'static str],
+    ) -> Result<(), NamesMismatch> {
+        if commands.len() != names.len() || commands.iter().zip(names).any(|((command, _, _), name)| command != name) {
+            return Err(NamesMismatch);
+        }
+        self.set_commands(commands);
+        self.names = Some(names);
+        Ok(())
+    }
+
+    /// Register commands that are matched on a prefix instead of a full name.
+    ///
+    /// Dial-style commands such as `ATD5551234;` or `ATD>SM1` carry their
+    /// argument directly after the command letter, with no `=`. When a basic
+    /// command position of the line starts with a registered prefix (e.g.
+    /// `"ATD"` → `D`), the **whole remainder of the line**, `;` included, is
+    /// passed verbatim to [`set`](crate::context::AtContext::set) as
+    /// [`Args::raw`](crate::Args::raw); with nothing after the prefix,
+    /// [`exec`](crate::context::AtContext::exec) is called instead.
+    ///
+    /// Prefixes are tried in registration order before the regular commands.
+    ///
+    /// # Arguments
+    ///
+    /// * `commands` — mutable slice of `(prefix, at_response, handler)` triples
     ///
     /// # Example
     ///
     /// ```rust,no_run
     /// # use at_parser_rs::parser::AtParser;
     /// # use at_parser_rs::context::AtContext;
-    /// # use at_parser_rs::{Args, AtResult, AtError, at_response};
+    /// # use at_parser_rs::{Args, AtResult, at_response};
     /// # const SIZE: usize = 64;
-    /// struct EchoModule { enabled: bool }
-    /// impl AtContext<SIZE> for EchoModule {
-    ///     fn query(&mut self, at_response: &'static str) -> AtResult<'_, SIZE> {
-    ///         Ok(at_response!(SIZE, at_response; if self.enabled { 1u8 } else { 0u8 }))
-    ///     }
+    /// struct DialModule;
+    /// impl AtContext<SIZE> for DialModule {
     ///     fn set(&mut self, at_response: &'static str, args: Args) -> AtResult<'_, SIZE> {
-    ///         let value = args.get(0).ok_or((at_response, AtError::InvalidArgs))?;
-    ///         match value.as_ref() {
-    ///             "0" => { self.enabled = false; Ok(at_response!(SIZE, at_response; "OK")) }
-    ///             "1" => { self.enabled = true;  Ok(at_response!(SIZE, at_response; "OK")) }
-    ///             _ => Err((at_response, AtError::InvalidArgs)),
-    ///         }
+    ///         // args.raw() == "5551234;" for ATD5551234;
+    ///         Ok(at_response!(SIZE, at_response; args.raw()))
     ///     }
     /// }
     ///
-    /// let mut echo = EchoModule { enabled: false };
-    /// let mut parser: AtParser<EchoModule, SIZE> = AtParser::new();
-    /// let commands: &mut [(&str, &str, &mut EchoModule)] = &mut [
-    ///     ("AT+ECHO", "+ECHO: ", &mut echo)
+    /// let mut dial = DialModule;
+    /// let mut parser: AtParser<DialModule, SIZE> = AtParser::new();
+    ///
+    /// let prefixes: &mut [(&str, &str, &mut DialModule)] = &mut [
+    ///     ("ATD", "", &mut dial),
     /// ];
-    /// parser.set_commands(commands);
+    /// parser.set_prefix_commands(prefixes);
+    /// parser.execute("ATD5551234;");   // Ok(("", "5551234;"))
+    /// ```
+    pub fn set_prefix_commands(&mut self, commands: &'a mut [(&'static str, &'static str, &'a mut T)]) {
+        debug_assert_unique(commands);
+        self.prefix_commands = commands;
+    }
+
+    /// Install a catch-all handler for unknown commands.
     ///
-    /// assert!(parser.execute("AT+ECHO=1").is_ok());    // Ok(("+ECHO: ", "OK"))
-    /// assert!(parser.execute("AT+ECHO?").is_ok());     // Ok(("+ECHO: ", "1"))
-    /// assert!(parser.execute("AT+UNKNOWN").is_err());  // Err(("", UnknownCommand))
-    /// assert!(parser.execute("AT+ECHO=9").is_err());   // Err(("+ECHO: ", InvalidArgs))
+    /// Before executing a line, the parser checks that every command in it can
+    /// be resolved. If one cannot, nothing is executed locally and the full
+    /// line is passed to [`AtFallback::unknown`], whose result is returned in
+    /// place of [`AtError::UnknownCommand`]. This applies to
+    /// [`execute`](AtParser::execute), [`execute_line`](AtParser::execute_line)
+    /// and the [`feed`](AtParser::feed) path.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// # use at_parser_rs::parser::AtParser;
+    /// # use at_parser_rs::context::{AtContext, AtFallback};
+    /// # use at_parser_rs::{AtResult, at_response};
+    /// # const SIZE: usize = 64;
+    /// # struct MyHandler; impl AtContext<SIZE> for MyHandler {}
+    /// struct Passthrough;
+    /// impl AtFallback<SIZE> for Passthrough {
+    ///     fn unknown(&mut self, line: &str) -> AtResult<'_, SIZE> {
+    ///         // forward `line` to the second modem
+    ///         Ok(at_response!(SIZE, ""; "OK"))
+    ///     }
+    /// }
+    ///
+    /// let mut passthrough = Passthrough;
+    /// let mut parser: AtParser<MyHandler, SIZE> = AtParser::new();
+    /// parser.set_fallback(&mut passthrough);
+    /// parser.execute("AT+VENDOR=1");   // handled by Passthrough
     /// ```
-    pub fn execute<'b>(&'b mut self, input: &'b str) -> AtResult<'b, SIZE> {
-        let input = input.trim();
-        let (name, form) = parse(input).map_err(|e| ("", e))?;
+    pub fn set_fallback(&mut self, fallback: &'a mut dyn AtFallback<SIZE, E>) {
+        self.fallback = Some(fallback);
+    }
 
-        // Find the command handler
-        let (_, at_response, module) = self.commands
-            .iter_mut()
-            .find(|(n, _, _)| *n == name)
-            .ok_or(("", AtError::UnknownCommand))?;
+    /// Remove the catch-all handler; unknown commands fail with
+    /// [`AtError::UnknownCommand`] again.
+    pub fn clear_fallback(&mut self) {
+        self.fallback = None;
+    }
 
-        // Dispatch to the appropriate handler method
-        match form {
-            AtForm::Exec => module.exec(at_response),
-            AtForm::Query => module.query(at_response),
-            AtForm::Test => module.test(at_response),
-            AtForm::Set(args) => module.set(at_response, args),
-        }
+    /// Install a hook observing every command dispatched by
+    /// [`execute`](AtParser::execute), [`execute_line`](AtParser::execute_line)
+    /// and the [`feed`](AtParser::feed) path, see [`AtObserver`].
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use at_parser_rs::parser::AtParser;
+    /// # use at_parser_rs::context::{AtContext, AtObserver, Form};
+    /// # const SIZE: usize = 64;
+    /// # struct MyHandler; impl AtContext<SIZE> for MyHandler {}
+    /// struct Trace(u32);
+    /// impl AtObserver<SIZE> for Trace {
+    ///     fn on_command(&mut self, name: &str, form: Form) {
+    ///         self.0 += 1;   // or defmt::info!("{=str} {}", name, form)
+    ///     }
+    /// }
+    ///
+    /// let mut trace = Trace(0);
+    /// let mut parser: AtParser<MyHandler, SIZE> = AtParser::new();
+    /// parser.set_observer(&mut trace);
+    /// parser.execute("ATE0;+CMEE=1").ok();
+    /// parser.clear_observer();
+    /// assert_eq!(trace.0, 2);
+    /// ```
+    pub fn set_observer(&mut self, observer: &'a mut dyn AtObserver<SIZE, E>) {
+        self.observer = Some(observer);
     }
-}
 
-/// Parse an AT command string into its name and form.
-///
-/// Examines the suffix of `input` (after trimming whitespace) to determine
-/// which AT command form was requested, then returns the bare command name
-/// together with the detected [`AtForm`].
-///
-/// | Suffix | Resulting form |
-/// |---|---|
-/// | `=?` | [`AtForm::Test`] |
-/// | `?` | [`AtForm::Query`] |
-/// | `=<args>` | [`AtForm::Set`] with the text after `=` as raw args |
-/// | *(none)* | [`AtForm::Exec`] |
-///
-/// This function never returns an error; every well-formed AT command string
-/// maps to exactly one form.
-///
-/// # Arguments
-///
-/// * `input` — pre-trimmed AT command string (trimming is reapplied internally)
-///
-/// # Returns
-///
-/// `Ok((command_name, form))` where `command_name` is a slice of `input`
-/// with the suffix removed.
-fn parse<'a>(input: &'a str) -> Result<(&'a str, AtForm<'a>), AtError<'a>> {
-    let input = input.trim();
+    /// Remove the observing hook.
+    pub fn clear_observer(&mut self) {
+        self.observer = None;
+    }
 
-    // Check suffixes to determine command form
-    if let Some(cmd) = input.strip_suffix("=?") {
-        Ok((cmd, AtForm::Test))
-    } else if let Some(cmd) = input.strip_suffix('?') {
-        Ok((cmd, AtForm::Query))
-    } else if let Some((cmd, args)) = input.split_once('=') {
-        Ok((cmd, AtForm::Set(Args { raw: args })))
-    } else {
-        Ok((input, AtForm::Exec))
+    /// Where and why the last command line was rejected, see
+    /// [`diagnostic`](crate::diagnostic).
+    ///
+    /// `None` when the last line succeeded, or failed for a reason other
+    /// than its syntax or arguments (a `+CME ERROR`, a final result code).
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use at_parser_rs::parser::AtParser;
+    /// # use at_parser_rs::context::AtContext;
+    /// use at_parser_rs::diagnostic::Reason;
+    /// # const SIZE: usize = 64;
+    /// # struct MyHandler; impl AtContext<SIZE> for MyHandler {}
+    ///
+    /// let mut parser: AtParser<MyHandler, SIZE> = AtParser::new();
+    /// assert!(parser.execute("E0").is_err());
+    /// assert_eq!(parser.diagnostic().map(|d| d.reason), Some(Reason::Expected("AT")));
+    /// ```
+    pub fn diagnostic(&self) -> Option<Diagnostic> {
+        self.diagnostic
+    }
+
+    /// Set the writer used to send characters back to the DTE.
+    ///
+    /// Received characters are echoed to this writer when echo is enabled
+    /// (see [`set_echo`](AtParser::set_echo)).
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// # use at_parser_rs::parser::AtParser;
+    /// # use at_parser_rs::context::AtContext;
+    /// # const SIZE: usize = 64;
+    /// # struct MyHandler; impl AtContext<SIZE> for MyHandler {}
+    /// struct Uart;
+    /// impl core::fmt::Write for Uart {
+    ///     fn write_str(&mut self, s: &str) -> core::fmt::Result {
+    ///         // push `s` to the UART TX FIFO
+    ///         Ok(())
+    ///     }
+    /// }
+    ///
+    /// let mut uart = Uart;
+    /// let mut parser: AtParser<MyHandler, SIZE> = AtParser::new();
+    /// parser.set_output(&mut uart);
+    /// parser.set_echo(true);
+    /// ```
+    pub fn set_output(&mut self, output: &'a mut dyn Write) {
+        self.output.writer = Some(output);
+    }
+
+    /// Remove the output writer.
+    pub fn clear_output(&mut self) {
+        self.output.writer = None;
+    }
+
+    /// Drive the direction of a half-duplex link around the output, e.g.
+    /// the DE pin of an RS-485 transceiver, see [`DirectionControl`].
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use at_parser_rs::parser::AtParser;
+    /// # use at_parser_rs::context::AtContext;
+    /// # use at_parser_rs::direction::DirectionControl;
+    /// # const SIZE: usize = 64;
+    /// # struct MyHandler; impl AtContext<SIZE> for MyHandler {}
+    /// struct De(u32);
+    /// impl DirectionControl for De {
+    ///     fn pre_transmit(&mut self) { self.0 += 1; }
+    /// }
+    ///
+    /// let mut out = String::new();
+    /// let mut de = De(0);
+    /// let mut parser: AtParser<MyHandler, SIZE> = AtParser::new();
+    /// parser.set_output(&mut out);
+    /// parser.set_direction(&mut de);
+    /// parser.set_response_framing(true);
+    /// parser.feed_slice(b"ATE0\r");
+    /// parser.clear_direction();
+    /// assert_eq!(de.0, 1);
+    /// ```
+    pub fn set_direction(&mut self, direction: &'a mut dyn DirectionControl) {
+        self.output.direction = Some(direction);
+    }
+
+    /// Remove the direction control.
+    pub fn clear_direction(&mut self) {
+        self.output.direction = None;
+    }
+
+    /// Enable or disable the echo of received characters (`ATE1` / `ATE0`).
+    ///
+    /// When enabled, every character passed to [`feed`](AtParser::feed) or
+    /// [`feed_slice`](AtParser::feed_slice) is written to the output writer
+    /// before being parsed. Only ASCII characters are echoed. Disabled by
+    /// default.
+    ///
+    /// Unless a handler is registered for `"ATE"`, the parser serves `ATE`,
+    /// `ATE0` and `ATE1` itself by toggling this setting, and `ATE?` reports
+    /// it as `0` or `1`.
+    pub fn set_echo(&mut self, enabled: bool) {
+        self.echo = enabled;
+    }
+
+    /// Whether received characters are echoed back.
+    pub fn echo(&self) -> bool {
+        self.echo
+    }
+
+    /// Enable or disable the response framing layer.
+    ///
+    /// When enabled, the result of every line completed by
+    /// [`feed`](AtParser::feed) or [`feed_slice`](AtParser::feed_slice) is
+    /// written to the output writer as a full V.250 response (information
+    /// text followed by `OK` / `ERROR`, see [`Framing`]), before being
+    /// returned. Disabled by default.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// # use at_parser_rs::parser::AtParser;
+    /// # use at_parser_rs::context::AtContext;
+    /// # const SIZE: usize = 64;
+    /// # struct MyHandler; impl AtContext<SIZE> for MyHandler {}
+    /// # struct Uart; impl core::fmt::Write for Uart { fn write_str(&mut self, _: &str) -> core::fmt::Result { Ok(()) } }
+    /// # let mut uart = Uart;
+    /// let mut parser: AtParser<MyHandler, SIZE> = AtParser::new();
+    /// parser.set_output(&mut uart);
+    /// parser.set_response_framing(true);
+    /// parser.feed_slice(b"AT\r");   // writes "\r\nOK\r\n"
+    /// ```
+    pub fn set_response_framing(&mut self, enabled: bool) {
+        self.framed = enabled;
+    }
+
+    /// Whether the response framing layer is enabled.
+    pub fn response_framing(&self) -> bool {
+        self.framed
+    }
+
+    /// Current response framing settings.
+    ///
+    /// The line ending is the [`response_ending`](AtParser::response_ending)
+    /// and the result code modes follow `ATV0` / `ATV1` and `ATQ0` / `ATQ1`:
+    /// unless a handler is registered for `"ATV"` (resp. `"ATQ"`), the parser
+    /// serves `ATV`, `ATV0` and `ATV1` itself, and `ATV?` reports the mode.
+    /// Likewise `AT+CMEE=<n>` selects how [`AtError::Cme`] errors are
+    /// reported (see [`cme`](crate::cme)).
+    /// Use it to frame the results of [`execute`](AtParser::execute) yourself:
+    ///
+    /// ```rust,no_run
+    /// # use at_parser_rs::parser::AtParser;
+    /// # use at_parser_rs::context::AtContext;
+    /// # const SIZE: usize = 64;
+    /// # struct MyHandler; impl AtContext<SIZE> for MyHandler {}
+    /// # let mut parser: AtParser<MyHandler, SIZE> = AtParser::new();
+    /// # struct Uart; impl core::fmt::Write for Uart { fn write_str(&mut self, _: &str) -> core::fmt::Result { Ok(()) } }
+    /// # let mut uart = Uart;
+    /// let framing = parser.framing();
+    /// let result = parser.execute("AT+VAL?");
+    /// framing.write_result(&mut uart, &result).ok();
+    /// ```
+    pub fn framing(&self) -> Framing {
+        response_framing(self.framing, self.registers.as_ref())
+    }
+
+    /// Mutable access to the response framing settings.
+    ///
+    /// The line ending is overridden by `S3` / `S4` when S-registers are
+    /// enabled.
+    pub fn framing_mut(&mut self) -> &mut Framing {
+        &mut self.framing
+    }
+
+    /// Set the buffer handlers format their response into.
+    ///
+    /// Handlers receive it as a [`Responder`] in the
+    /// [`AtContext::exec_with`] family of methods; when a handler returns an
+    /// empty payload, the text it wrote becomes the payload. Without a buffer
+    /// every write to the responder fails.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// # use at_parser_rs::parser::AtParser;
+    /// # use at_parser_rs::context::AtContext;
+    /// # const SIZE: usize = 64;
+    /// # struct MyHandler; impl AtContext<SIZE> for MyHandler {}
+    /// let mut response = [0u8; SIZE];
+    /// let mut parser: AtParser<MyHandler, SIZE> = AtParser::new();
+    /// parser.set_response_buffer(&mut response);
+    /// ```
+    pub fn set_response_buffer(&mut self, buffer: &'a mut [u8]) {
+        self.response_buffer = buffer;
     }
-}
\ No newline at end of file
+
+    /// Install the user state shared by every handler.
+    ///
+    /// Handlers reach it through [`Responder::state`] in the
+    /// [`AtContext::exec_with`] family of methods, for lines dispatched by
+    /// [`execute`](AtParser::execute), [`execute_line`](AtParser::execute_line)
+    /// and [`feed`](AtParser::feed) alike. Use
+    /// [`execute_with_state`](AtParser::execute_with_state) instead when the
+    /// state cannot stay borrowed by the parser.
+    pub fn set_state(&mut self, state: &'a mut S) {
+        self.state = Some(state);
+    }
+
+    /// Share `abort` with the receive path, so that a character received
+    /// while a handler executes aborts it (V.250 command abortion).
+    ///
+    /// The parser marks the execution of every handler on the flag; the
+    /// receive path calls [`Abort::input`] for each character and drops those
+    /// it consumes, since the parser cannot be fed while it executes.
+    /// Abortable handlers poll [`Responder::is_aborted`] and return
+    /// [`AtError::Aborted`].
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// # use at_parser_rs::parser::AtParser;
+    /// # use at_parser_rs::context::AtContext;
+    /// # const SIZE: usize = 64;
+    /// # struct MyHandler; impl AtContext<SIZE> for MyHandler {}
+    /// use at_parser_rs::abort::Abort;
+    ///
+    /// static ABORT: Abort = Abort::new();
+    ///
+    /// fn on_uart_rx(byte: u8, queue: &mut impl FnMut(u8)) {
+    ///     if !ABORT.input() {
+    ///         queue(byte);
+    ///     }
+    /// }
+    ///
+    /// let mut parser: AtParser<MyHandler, SIZE> = AtParser::new();
+    /// parser.set_abort(&ABORT);
+    /// ```
+    pub fn set_abort(&mut self, abort: &'a Abort) {
+        self.abort = Some(abort);
+    }
+
+    /// Install the help table returned to handlers by [`Responder::help`],
+    /// as built by [`at_modules!`](crate::at_modules) with `help;` (see
+    /// [`help`](crate::help)).
+    pub fn set_help(&mut self, help: &'a Help) {
+        self.help = Some(help);
+    }
+
+    /// Remove the user state installed with [`set_state`](AtParser::set_state).
+    pub fn clear_state(&mut self) {
+        self.state = None;
+    }
+
+    /// Whether a handler returned [`AtResponse::Continue`] and receives the bytes
+    /// fed to the parser (see [`AtContext::input`]) instead of the command line
+    /// parser.
+    pub fn is_awaiting_input(&self) -> bool {
+        self.awaiting.is_some()
+    }
+
+    /// Leave the input mode of a handler without completing its command, e.g.
+    /// on a timeout, and resume normal command line parsing.
+    pub fn cancel_input(&mut self) {
+        if self.awaiting.take().is_some_and(|target| target.payload.is_some()) {
+            self.line.clear();
+        }
+        self.transition();
+    }
+
+    /// Collect the payloads of [`Responder::prompt_payload`] and
+    /// [`Responder::expect_bytes`] into `buffer` instead of the line buffer,
+    /// e.g. to receive a whole TCP segment (`AT+CIPSEND=1460`) with a short
+    /// command line.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use at_parser_rs::parser::AtParser;
+    /// # use at_parser_rs::context::AtContext;
+    /// # use at_parser_rs::response::Responder;
+    /// # use at_parser_rs::{Args, AtError, AtResponse, AtResult, at_response};
+    /// # const SIZE: usize = 64;
+    /// struct Socket;
+    /// impl AtContext<SIZE> for Socket {
+    ///     fn set_with(&mut self, at_response: &'static str, args: Args, responder: &mut Responder<'_>) -> AtResult<'_, SIZE> {
+    ///         let len = args.get_u16(0).map_err(|_| (at_response, AtError::InvalidArgs))?;
+    ///         responder.prompt("> ").map_err(|_| (at_response, AtError::InvalidArgs))?;
+    ///         responder.expect_bytes(usize::from(len));
+    ///         Ok((at_response, AtResponse::Continue))
+    ///     }
+    ///
+    ///     fn payload(&mut self, at_response: &'static str, payload: &[u8], _responder: &mut Responder<'_>) -> AtResult<'_, SIZE> {
+    ///         // send `payload` over the socket
+    ///         Ok(at_response!(SIZE, at_response; payload.len()))
+    ///     }
+    /// }
+    ///
+    /// let mut segment = [0u8; 1460];
+    /// let mut socket = Socket;
+    /// let commands: &mut [(&str, &str, &mut Socket)] = &mut [("AT+CIPSEND", "+CIPSEND: ", &mut socket)];
+    /// let mut parser: AtParser<Socket, SIZE> = AtParser::new();
+    /// parser.set_commands(commands);
+    /// parser.set_payload_buffer(&mut segment);
+    ///
+    /// parser.feed_slice(b"AT+CIPSEND=200\r");
+    /// assert!(parser.feed_slice(&[0x1a; 199]).1.is_none());   // 8-bit clean
+    /// assert!(parser.feed(0x1b).unwrap().is_ok());
+    /// ```
+    pub fn set_payload_buffer(&mut self, buffer: &'a mut [u8]) {
+        self.payload_buffer = Some(buffer);
+    }
+
+    /// Collect the payloads into the line buffer again.
+    pub fn clear_payload_buffer(&mut self) {
+        self.payload_buffer = None;
+    }
+
+    /// Enter the online data state whenever a command fed to the parser
+    /// ends with `CONNECT`, handing the bytes fed afterwards to `sink`
+    /// until the escape sequence or [`hang_up`](AtParser::hang_up), see
+    /// [`online`](crate::online).
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use at_parser_rs::parser::AtParser;
+    /// # use at_parser_rs::context::AtContext;
+    /// # use at_parser_rs::online::{DataSink, OnlineState};
+    /// # use at_parser_rs::response::ResultCode;
+    /// # use at_parser_rs::{Args, AtResponse, AtResult};
+    /// # const SIZE: usize = 64;
+    /// struct Dial;
+    /// impl AtContext<SIZE> for Dial {
+    ///     fn set(&mut self, at_response: &'static str, _args: Args) -> AtResult<'_, SIZE> {
+    ///         Ok((at_response, AtResponse::FinalCode(ResultCode::Connect(None))))
+    ///     }
+    /// }
+    ///
+    /// struct Modem(usize);
+    /// impl DataSink for Modem {
+    ///     fn data(&mut self, bytes: &[u8]) { self.0 += bytes.len(); }
+    /// }
+    ///
+    /// let mut dial = Dial;
+    /// let mut modem = Modem(0);
+    /// let prefixes: &mut [(&str, &str, &mut Dial)] = &mut [("ATD", "", &mut dial)];
+    /// let mut parser: AtParser<Dial, SIZE> = AtParser::new();
+    /// parser.set_prefix_commands(prefixes);
+    /// parser.set_data_sink(&mut modem);
+    ///
+    /// parser.feed_slice(b"ATD5551234\r");
+    /// assert_eq!(parser.online_state(), OnlineState::Data);
+    /// parser.feed_slice(b"AT+GMR\r");      // data, not a command
+    /// parser.hang_up();
+    /// parser.clear_data_sink();
+    /// assert_eq!(modem.0, 7);
+    /// ```
+    pub fn set_data_sink(&mut self, sink: &'a mut dyn DataSink) {
+        self.sink = Some(sink);
+    }
+
+    /// Remove the data sink; the parser no longer enters the online data
+    /// state and returns to the command state.
+    pub fn clear_data_sink(&mut self) {
+        self.sink = None;
+        self.online = OnlineState::Command;
+        self.transparent = false;
+        self.transition();
+    }
+
+    /// Time the guard times of the escape sequence with `clock`.
+    ///
+    /// Without a clock the escape sequence is any three escape characters
+    /// in a row, completed at the next [`poll_escape`](AtParser::poll_escape).
+    pub fn set_clock(&mut self, clock: &'a dyn Clock) {
+        self.clock = Some(clock);
+    }
+
+    /// Let handlers hand the stream of their connection to an external stack
+    /// through `handoff`, see [`Handoff`].
+    ///
+    /// While the stream is claimed, every byte fed goes to the data sink; once
+    /// the stack releases it, the next byte fed or
+    /// [`poll_escape`](AtParser::poll_escape) returns to the command state
+    /// with `NO CARRIER`.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use at_parser_rs::parser::AtParser;
+    /// # use at_parser_rs::context::AtContext;
+    /// # use at_parser_rs::online::{DataSink, Handoff, OnlineState};
+    /// # use at_parser_rs::response::ResultCode;
+    /// # use at_parser_rs::{Args, AtResponse, AtResult};
+    /// # const SIZE: usize = 64;
+    /// static PPP: Handoff = Handoff::new();
+    ///
+    /// struct Dial;
+    /// impl AtContext<SIZE> for Dial {
+    ///     fn set(&mut self, at_response: &'static str, args: Args) -> AtResult<'_, SIZE> {
+    ///         if args.raw() == "*99#" {
+    ///             PPP.claim();
+    ///         }
+    ///         Ok((at_response, AtResponse::FinalCode(ResultCode::Connect(None))))
+    ///     }
+    /// }
+    /// # struct Ppp; impl DataSink for Ppp { fn data(&mut self, _bytes: &[u8]) {} }
+    ///
+    /// let mut dial = Dial;
+    /// let mut ppp = Ppp;
+    /// let prefixes: &mut [(&str, &str, &mut Dial)] = &mut [("ATD", "", &mut dial)];
+    /// let mut parser: AtParser<Dial, SIZE> = AtParser::new();
+    /// parser.set_prefix_commands(prefixes);
+    /// parser.set_data_sink(&mut ppp);
+    /// parser.set_handoff(&PPP);
+    ///
+    /// parser.feed_slice(b"ATD*99#\r");
+    /// parser.feed_slice(b"\x7e\xff+++");        // PPP frames, no escape
+    /// PPP.release();                             // LCP terminated
+    /// assert!(parser.poll_escape().is_some());   // NO CARRIER
+    /// assert_eq!(parser.online_state(), OnlineState::Command);
+    /// ```
+    pub fn set_handoff(&mut self, handoff: &'a Handoff) {
+        self.handoff = Some(handoff);
+    }
+
+    /// Remove the handoff.
+    pub fn clear_handoff(&mut self) {
+        self.handoff = None;
+    }
+
+    /// Current connection state.
+    pub fn online_state(&self) -> OnlineState {
+        self.online
+    }
+
+    /// Current mode of the parser.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use at_parser_rs::parser::AtParser;
+    /// # use at_parser_rs::context::AtContext;
+    /// # use at_parser_rs::mode::{Mode, ModeListener};
+    /// # const SIZE: usize = 64;
+    /// # struct MyHandler; impl AtContext<SIZE> for MyHandler {}
+    /// struct Dma;
+    /// impl ModeListener for Dma {
+    ///     fn on_mode(&mut self, _from: Mode, to: Mode) {
+    ///         // idle-line interrupts for command lines, full buffers for data
+    ///     }
+    /// }
+    ///
+    /// let mut dma = Dma;
+    /// let mut parser: AtParser<MyHandler, SIZE> = AtParser::new();
+    /// parser.set_mode_listener(&mut dma);
+    /// assert_eq!(parser.mode(), Mode::Command);
+    /// ```
+    pub fn mode(&self) -> Mode {
+        current_mode(self.online, self.awaiting.is_some())
+    }
+
+    /// Tell `listener` about every change of [`mode`](AtParser::mode).
+    ///
+    /// Changes made by [`execute`](AtParser::execute) and its variants are
+    /// reported at the next change made by the byte stream or the methods of
+    /// the parser.
+    pub fn set_mode_listener(&mut self, listener: &'a mut dyn ModeListener) {
+        self.listener = Some(listener);
+    }
+
+    /// Remove the mode listener.
+    pub fn clear_mode_listener(&mut self) {
+        self.listener = None;
+    }
+
+    /// Enter the online data state on a connection established without a
+    /// command answering `CONNECT`, e.g. an incoming call answered by the
+    /// application, or resume it from the online command state.
+    ///
+    /// # Returns
+    ///
+    /// `false`, leaving the mode unchanged, without a data sink or while a
+    /// handler awaits input.
+    pub fn enter_online_data(&mut self) -> bool {
+        if self.sink.is_none() || self.awaiting.is_some() {
+            return false;
+        }
+
+        self.online = OnlineState::Data;
+        self.escape = Escape::new(self.clock.map(Clock::now));
+        self.transparent = self.handoff.is_some_and(Handoff::take_claim);
+        self.transition();
+        true
+    }
+
+    /// Leave the online data state for the online command state with `OK`
+    /// as the escape sequence does, e.g. when DTR drops with `AT&D1`.
+    ///
+    /// # Returns
+    ///
+    /// `Some(Ok(("", AtResponse::Ok)))`, also written to the output when
+    /// framing is enabled, unless the parser was not in the online data
+    /// state.
+    pub fn enter_online_command(&mut self) -> Option<AtResult<'static, SIZE, E>> {
+        if self.online != OnlineState::Data {
+            return None;
+        }
+
+        self.online = OnlineState::OnlineCommand;
+        let result = Ok(("", AtResponse::Ok));
+        self.write_response(&result);
+        Some(result)
+    }
+
+    /// Complete the escape sequence once its final guard time has passed,
+    /// returning to the online command state with `OK`, or end a stream
+    /// released by its external stack with `NO CARRIER` (see
+    /// [`set_handoff`](AtParser::set_handoff)).
+    ///
+    /// Call it periodically in the online data state, e.g. from the receive
+    /// timeout of the UART.
+    ///
+    /// # Returns
+    ///
+    /// `Some(Ok(("", AtResponse::Ok)))` or
+    /// `Some(Err(("", AtError::Final(ResultCode::NoCarrier))))`, also written
+    /// to the output when framing is enabled, when the online data state
+    /// ended.
+    pub fn poll_escape(&mut self) -> Option<AtResult<'static, SIZE, E>> {
+        if self.transparent {
+            return self.released();
+        }
+
+        let now = self.clock.map(Clock::now);
+        if self.online != OnlineState::Data || !self.escape.escaped(now, self.guard_time()) {
+            return None;
+        }
+
+        self.enter_online_command()
+    }
+
+    /// Report the loss of the connection, returning to the command state with
+    /// `NO CARRIER`.
+    ///
+    /// # Returns
+    ///
+    /// `Some(Err(("", AtError::Final(ResultCode::NoCarrier))))`, also written
+    /// to the output when framing is enabled, unless there was no connection.
+    pub fn hang_up(&mut self) -> Option<AtResult<'static, SIZE, E>> {
+        if self.online == OnlineState::Command {
+            return None;
+        }
+
+        self.online = OnlineState::Command;
+        self.transparent = false;
+        let result = Err(("", AtError::Final(ResultCode::NoCarrier)));
+        self.write_response(&result);
+        Some(result)
+    }
+
+    /// End the online data state with `NO CARRIER` if the external stack
+    /// released the stream.
+    fn released(&mut self) -> Option<AtResult<'static, SIZE, E>> {
+        if self.online == OnlineState::Data && self.handoff.is_some_and(Handoff::take_release) {
+            self.hang_up()
+        } else {
+            None
+        }
+    }
+
+    /// Hand a byte received in the online data state to the sink, unless it
+    /// may be part of the escape sequence.
+    fn data(&mut self, byte: u8) {
+        if self.transparent {
+            if let Some(sink) = self.sink.as_deref_mut() {
+                sink.data(&[byte]);
+            }
+            return;
+        }
+
+        let escape = self.registers.as_ref().and_then(|registers| registers.get(sreg::S2)).unwrap_or(b'+');
+        let guard = self.guard_time();
+        let (held, forward) = self.escape.byte(byte, self.clock.map(Clock::now), escape, guard);
+        if let Some(sink) = self.sink.as_deref_mut() {
+            if held > 0 {
+                sink.data(&[escape; 3][..usize::from(held)]);
+            }
+            if forward {
+                sink.data(&[byte]);
+            }
+        }
+    }
+
+    /// Report a change of mode to the listener.
+    fn transition(&mut self) {
+        self.reply().transition();
+    }
+
+    /// Escape guard time, from `S12`.
+    fn guard_time(&self) -> Duration {
+        let s12 = self.registers.as_ref().and_then(|registers| registers.get(sreg::S12));
+        online::guard_time(s12.unwrap_or(sreg::SRegisters::DEFAULTS[sreg::S12]))
+    }
+
+    /// Enter the online data state when `result` is `CONNECT` and a sink is
+    /// installed.
+    fn connect(&mut self, result: &AtResult<'_, SIZE, E>) {
+        self.reply().connect(result);
+    }
+
+    /// User state installed with [`set_state`](AtParser::set_state), if any.
+    pub fn state_mut(&mut self) -> Option<&mut S> {
+        self.state.as_deref_mut()
+    }
+
+    /// Send an unsolicited result code (e.g. `+CREG: 1`) to the output.
+    ///
+    /// The text is framed like information text with the current
+    /// [`framing`](AtParser::framing) settings. So as not to garble what the
+    /// DTE is typing, it is held back while a command line is being received
+    /// and sent right after the response to that line. Up to `SIZE` bytes of
+    /// framed URCs can be held back; beyond that an error is returned and the
+    /// URC is dropped. Discarded when no output is set.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// # use at_parser_rs::parser::AtParser;
+    /// # use at_parser_rs::context::AtContext;
+    /// # const SIZE: usize = 64;
+    /// # struct MyHandler; impl AtContext<SIZE> for MyHandler {}
+    /// # struct Uart; impl core::fmt::Write for Uart { fn write_str(&mut self, _: &str) -> core::fmt::Result { Ok(()) } }
+    /// # let mut uart = Uart;
+    /// let mut parser: AtParser<MyHandler, SIZE> = AtParser::new();
+    /// parser.set_output(&mut uart);
+    ///
+    /// // network registration changed
+    /// parser.emit_urc(format_args!("+CREG: {}", 1)).ok();   // "\r\n+CREG: 1\r\n"
+    /// ```
+    pub fn emit_urc(&mut self, text: impl core::fmt::Display) -> core::fmt::Result {
+        let framing = self.framing();
+        let ending = framing.ending().as_str();
+        if framing.verbose() {
+            self.send_urc(format_args!("{ending}{text}{ending}"))
+        } else {
+            self.send_urc(format_args!("{text}{ending}"))
+        }
+    }
+
+    /// Send an unsolicited result code such as [`ResultCode::Ring`].
+    ///
+    /// Follows the V.250 result code settings (verbose or numeric, suppressed
+    /// in quiet mode) and the same holding rules as
+    /// [`emit_urc`](AtParser::emit_urc).
+    pub fn emit_urc_code(&mut self, code: ResultCode) -> core::fmt::Result {
+        let framing = self.framing();
+        let mut pending = Pending::<32>::new();
+        framing.write_code(&mut pending, code)?;
+        self.send_urc(format_args!("{}", pending.as_str()))
+    }
+
+    /// Send the final response of a command that returned
+    /// [`AtResponse::Deferred`].
+    ///
+    /// `result` is framed like the result of a fed line and written to the
+    /// output; nothing is sent when framing is disabled or no output is set.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// # use at_parser_rs::parser::AtParser;
+    /// # use at_parser_rs::context::AtContext;
+    /// # use at_parser_rs::response::ResultCode;
+    /// # use at_parser_rs::{AtError, AtResponse};
+    /// # const SIZE: usize = 64;
+    /// # struct MyHandler; impl AtContext<SIZE> for MyHandler {}
+    /// # let mut parser: AtParser<MyHandler, SIZE> = AtParser::new();
+    /// // the dial handler returned Deferred; the call is now answered
+    /// parser.complete(&Ok(("", AtResponse::FinalCode(ResultCode::Connect(None)))));
+    /// // or could not be placed
+    /// parser.complete(&Err(("", AtError::Final(ResultCode::NoAnswer))));
+    /// ```
+    pub fn complete(&mut self, result: &AtResult<'_, SIZE, E>) -> core::fmt::Result {
+        observe_result_code(&mut self.observer.as_deref_mut(), "", result);
+        self.connect(result);
+        self.transition();
+        let framing = self.framing();
+        match self.output.transmission() {
+            Some(mut output) if self.framed => framing.write_result(&mut output, result),
+            _ => Ok(()),
+        }
+    }
+
+    /// Send the outcome of a dial or answer command that returned
+    /// [`AtResponse::Deferred`].
+    ///
+    /// Same as [`complete`](AtParser::complete) with
+    /// [`CallProgress::result`]: `CONNECT` enters online data mode, `BUSY`,
+    /// `NO ANSWER`, `NO DIALTONE` and `NO CARRIER` go through the error map
+    /// as [`AtError::Final`] failures.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// # use at_parser_rs::parser::AtParser;
+    /// # use at_parser_rs::context::AtContext;
+    /// # use at_parser_rs::response::CallProgress;
+    /// # const SIZE: usize = 64;
+    /// # struct MyHandler; impl AtContext<SIZE> for MyHandler {}
+    /// # let mut parser: AtParser<MyHandler, SIZE> = AtParser::new();
+    /// // the line is engaged
+    /// parser.complete_call(CallProgress::Busy);
+    /// ```
+    pub fn complete_call(&mut self, progress: CallProgress) -> core::fmt::Result {
+        self.complete(&progress.result(""))
+    }
+
+    /// Parse and execute an AT command string.
+    ///
+    /// Leading and trailing whitespace is stripped before parsing.
+    /// The command name is matched against the registered commands; if found,
+    /// the appropriate handler method is called based on the command form
+    /// detected from the suffix.
+    ///
+    /// Basic commands (`ATE0`, `ATI`, `AT&F`) are registered with their full
+    /// name (`"ATE"`, `"ATI"`, `"AT&F"`); the optional numeric parameter is
+    /// passed to [`set`](crate::context::AtContext::set) as the only argument.
+    /// When the line holds several commands (`ATE0V1`, `AT+A;+B`) all of them
+    /// are executed in order and the response of the last one is returned;
+    /// use [`execute_line`](AtParser::execute_line) to receive every response.
+    ///
+    /// | Input suffix | Dispatches to |
+    /// |---|---|
+    /// | *(none)* | [`exec`](crate::context::AtContext::exec) |
+    /// | `?` | [`query`](crate::context::AtContext::query) |
+    /// | `=?` | [`test`](crate::context::AtContext::test) |
+    /// | `=<args>` | [`set`](crate::context::AtContext::set) |
+    ///
+    /// # Arguments
+    ///
+    /// * `input` — raw AT command string (e.g. `"AT+CMD?"`, `"AT+CMD=1,2"`)
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(Bytes<SIZE>)` — response buffer returned by the matched handler
+    /// * `Err(AtError::UnknownCommand)` — no handler found for the command name
+    /// * `Err(AtError::NotSupported)` — handler found but the requested form is not implemented
+    /// * `Err(AtError::InvalidArgs)` — handler returned an argument error
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// # use at_parser_rs::parser::AtParser;
+    /// # use at_parser_rs::context::AtContext;
+    /// # use at_parser_rs::{Args, AtResult, AtError, at_response};
+    /// # const SIZE: usize = 64;
+    /// struct EchoModule { enabled: bool }
+    /// impl AtContext<SIZE> for EchoModule {
+    ///     fn query(&mut self, at_response: &'static str) -> AtResult<'_, SIZE> {
+    ///         Ok(at_response!(SIZE, at_response; if self.enabled { 1u8 } else { 0u8 }))
+    ///     }
+    ///     fn set(&mut self, at_response: &'static str, args: Args) -> AtResult<'_, SIZE> {
+    ///         let value = args.get(0).ok_or((at_response, AtError::InvalidArgs))?;
+    ///         match value.as_ref() {
+    ///             "0" => { self.enabled = false; Ok(at_response!(SIZE, at_response; "OK")) }
+    ///             "1" => { self.enabled = true;  Ok(at_response!(SIZE, at_response; "OK")) }
+    ///             _ => Err((at_response, AtError::InvalidArgs)),
+    ///         }
+    ///     }
+    /// }
+    ///
+    /// let mut echo = EchoModule { enabled: false };
+    /// let mut parser: AtParser<EchoModule, SIZE> = AtParser::new();
+    /// let commands: &mut [(&str, &str, &mut EchoModule)] = &mut [
+    ///     ("AT+ECHO", "+ECHO: ", &mut echo)
+    /// ];
+    /// parser.set_commands(commands);
+    ///
+    /// assert!(parser.execute("AT+ECHO=1").is_ok());    // Ok(("+ECHO: ", "OK"))
+    /// assert!(parser.execute("AT+ECHO?").is_ok());     // Ok(("+ECHO: ", "1"))
+    /// assert!(parser.execute("AT+UNKNOWN").is_err());  // Err(("", UnknownCommand))
+    /// assert!(parser.execute("AT+ECHO=9").is_err());   // Err(("+ECHO: ", InvalidArgs))
+    /// ```
+    pub fn execute<'b>(&'b mut self, input: &'b str) -> AtResult<'b, SIZE, E> {
+        self.dispatcher().run(input)
+    }
+
+    /// Parse and execute a command line received as bytes, like
+    /// [`execute`](AtParser::execute).
+    ///
+    /// For receive paths that hand over `&[u8]`: the line is parsed in place
+    /// without a copy. Noise around the command (`0xFF` after a reset,
+    /// control characters) is skipped, so only the bytes between the first
+    /// and the last printable ASCII character have to be valid UTF-8; they
+    /// are looked at as `&str` once checked.
+    ///
+    /// # Returns
+    ///
+    /// As [`execute`](AtParser::execute), or `Err(("", AtError::InvalidArgs))`
+    /// when the command itself is not valid UTF-8, with a
+    /// [`diagnostic`](AtParser::diagnostic) at the first invalid byte.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use at_parser_rs::parser::AtParser;
+    /// # use at_parser_rs::context::AtContext;
+    /// # use at_parser_rs::AtResponse;
+    /// # const SIZE: usize = 64;
+    /// # struct MyHandler; impl AtContext<SIZE> for MyHandler {}
+    /// let mut parser: AtParser<MyHandler, SIZE> = AtParser::new();
+    ///
+    /// let rx: &[u8] = b"\xff\xffATE0\r\n";
+    /// assert!(matches!(parser.execute_bytes(rx), Ok((_, AtResponse::Ok))));
+    /// ```
+    pub fn execute_bytes<'b>(&'b mut self, input: &'b [u8]) -> AtResult<'b, SIZE, E> {
+        let dispatcher = self.dispatcher();
+        match command_text(input) {
+            Ok(line) => dispatcher.run(line),
+            Err(valid_up_to) => dispatcher.invalid_line(valid_up_to),
+        }
+    }
+
+    /// Execute every complete command line of `buf`, writing the framed
+    /// response of each one to `out`.
+    ///
+    /// For receive paths handing over many lines at once (a large DMA
+    /// transfer): lines end with the [configured terminator](AtParser::set_line_ending)
+    /// and are executed in place, as with [`execute_bytes`](AtParser::execute_bytes);
+    /// the discarded bytes following a terminator (`\n` after `\r`) are
+    /// consumed with it, and lines holding nothing but noise are skipped.
+    /// Each response is framed with the settings its line leaves in effect,
+    /// and the URCs held back meanwhile are sent to the output after it, as
+    /// by [`feed`](AtParser::feed); `ATS3` applies from the next line on.
+    /// The bytes after the last terminator are left alone, and so are those
+    /// after a command expecting further input or entering the online data
+    /// state, which belong to [`feed`](AtParser::feed).
+    ///
+    /// # Returns
+    ///
+    /// The number of bytes consumed; the rest of `buf`, a partial line, is to
+    /// be kept and handed over again with the bytes received next.
+    ///
+    /// # Errors
+    ///
+    /// The first failure of `out`; the line whose response failed is
+    /// consumed.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use at_parser_rs::parser::AtParser;
+    /// # use at_parser_rs::context::AtContext;
+    /// # const SIZE: usize = 64;
+    /// # struct MyHandler; impl AtContext<SIZE> for MyHandler {}
+    /// let mut parser: AtParser<MyHandler, SIZE> = AtParser::new();
+    /// let mut out = String::new();
+    ///
+    /// let rx: &[u8] = b"ATE0\r\nATV1\r\nATV";
+    /// let consumed = parser.execute_all(rx, &mut out)?;
+    /// assert_eq!(&rx[consumed..], b"ATV");
+    /// assert_eq!(out, "\r\nOK\r\n\r\nOK\r\n");
+    /// # Ok::<(), core::fmt::Error>(())
+    /// ```
+    pub fn execute_all(&mut self, buf: &[u8], out: &mut impl Write) -> Result<usize, fmt::Error> {
+        let mut consumed = 0;
+        while !matches!(self.mode(), Mode::OnlineData | Mode::DataEntry) {
+            self.sync_line();
+            let ending = self.line.ending();
+            let Some(len) = buf[consumed..].iter().position(|&byte| ending.terminates(byte)) else {
+                break;
+            };
+            let line = &buf[consumed..consumed + len];
+            consumed += len + 1;
+            while buf.get(consumed).is_some_and(|&byte| ending.discards(byte)) {
+                consumed += 1;
+            }
+            if line.iter().any(u8::is_ascii_graphic) {
+                self.respond(Some(line), Some(out)).1?;
+            }
+        }
+        Ok(consumed)
+    }
+
+    /// Parse and execute a command line like [`execute`](AtParser::execute),
+    /// awaiting [`AsyncAtContext`] handlers.
+    ///
+    /// Lets handlers await flash writes or radio transactions from an async
+    /// executor such as Embassy, without blocking. Basic commands, S-registers
+    /// and the fallback are served as in [`execute`](AtParser::execute).
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// # use at_parser_rs::parser::AtParser;
+    /// # use at_parser_rs::context::{AsyncAtContext, AtContext};
+    /// # const SIZE: usize = 64;
+    /// # struct Flash; impl AtContext<SIZE> for Flash {} impl AsyncAtContext<SIZE> for Flash {}
+    /// # async fn task(parser: &mut AtParser<'_, Flash, SIZE>) {
+    /// let result = parser.execute_async("AT+SAVE").await;
+    /// # }
+    /// ```
+    #[cfg(feature = "async")]
+    pub async fn execute_async<'b>(&'b mut self, input: &'b str) -> AtResult<'b, SIZE, E>
+    where
+        T: AsyncAtContext<SIZE, S, E> {
+        self.dispatcher().run_async(input).await
+    }
+
+    /// Parse and execute a command line like [`execute`](AtParser::execute),
+    /// handing `state` to every handler.
+    ///
+    /// Handlers receive it through [`Responder::state`], so commands can share
+    /// mutable device state (radio, flash, configuration) without globals. It
+    /// takes the place of any state installed with
+    /// [`set_state`](AtParser::set_state) for this line.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// # use at_parser_rs::parser::AtParser;
+    /// # use at_parser_rs::context::AtContext;
+    /// # const SIZE: usize = 64;
+    /// struct Driver { resets: u32 }
+    /// # struct ResetModule; impl AtContext<SIZE, Driver> for ResetModule {}
+    ///
+    /// let mut driver = Driver { resets: 0 };
+    /// let mut parser: AtParser<ResetModule, SIZE, Driver> = AtParser::new();
+    /// let result = parser.execute_with_state("AT+RST", &mut driver);
+    /// ```
+    pub fn execute_with_state<'b>(&'b mut self, input: &'b str, state: &'b mut S) -> AtResult<'b, SIZE, E> {
+        let mut dispatcher = self.dispatcher();
+        dispatcher.state = Some(state);
+        dispatcher.run(input)
+    }
+
+    /// Parse and execute a command line that may hold several commands
+    /// concatenated with `;` (e.g. `AT+CMD1;+CMD2?;+CMD3=1`).
+    ///
+    /// Following V.250 concatenation rules, only the first command carries the
+    /// `AT` prefix; the following ones start directly with the command name
+    /// (`+CMD2`) and are matched against the commands registered as `AT+CMD2`.
+    /// Semicolons inside double-quoted arguments do not split the line, and
+    /// empty commands (e.g. a trailing `;`) are skipped. Basic commands need no
+    /// separator: `ATE0V1+CMEE=1` holds three commands.
+    ///
+    /// Commands are dispatched in order and every successful response is passed
+    /// to `on_response`. Execution stops at the first failing command: the
+    /// remaining commands are not executed and the error is returned, so the
+    /// caller can emit the single final result code for the whole line.
+    ///
+    /// # Arguments
+    ///
+    /// * `line` — raw command line
+    /// * `on_response` — called with `(at_response, bytes)` for each successful command
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(())` — every command succeeded
+    /// * `Err((at_response, error))` — the first failure; borrowed
+    ///   [`AtError::Unhandled`] descriptions are converted into
+    ///   [`AtError::UnhandledOwned`] (see [`AtError::into_owned`])
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// # use at_parser_rs::parser::AtParser;
+    /// # use at_parser_rs::context::AtContext;
+    /// # const SIZE: usize = 64;
+    /// # struct MyHandler; impl AtContext<SIZE> for MyHandler {}
+    /// let mut parser: AtParser<MyHandler, SIZE> = AtParser::new();
+    ///
+    /// let result = parser.execute_line("AT+ECHO=1;+ECHO?", |at_response, bytes| {
+    ///     // information text of each command: ("+ECHO: ", "OK"), ("+ECHO: ", "1")
+    /// });
+    /// // result: Ok(()) → final result code OK
+    /// ```
+    pub fn execute_line<F>(&mut self, line: &str, mut on_response: F) -> Result<(), (&'static str, AtError<'static, E>)>
+    where
+        F: FnMut(&'static str, AtResponse<'_, SIZE>) {
+        let mut dispatcher = self.dispatcher();
+        let mut observer = dispatcher.observer.take();
+        let mut diagnostic = dispatcher.diagnostic.take();
+        clear_diagnostic(&mut diagnostic);
+
+        if dispatcher.needs_fallback(line)
+            && let Some(fallback) = dispatcher.fallback.as_deref_mut() {
+            let result = fallback.unknown(line.trim());
+            observe_response(&mut observer, &result);
+            observe_result_code(&mut observer, line.trim(), &result);
+            return match result {
+                Ok((at_response, bytes)) => {
+                    on_response(at_response, bytes);
+                    Ok(())
+                }
+                Err((at_response, e)) => Err((at_response, e.into_owned())),
+            };
+        }
+
+        let raw = line;
+        let mut line = CommandLine::new(line, dispatcher.case_insensitive, dispatcher.strict);
+
+        let mut first = true;
+        let mut last = ("", Some(Ok(ResultCode::Ok)));
+        while let Some(step) = line.next_command(|rest| dispatcher.match_prefix(rest)) {
+            let step = step.map_err(|e| parse_error(&mut observer, e.into_owned(), raw))?;
+
+            dispatcher.observe_command(&mut observer, &step);
+            let locus = step.locus();
+            let result = dispatcher.reborrow().execute(step);
+            observe_response(&mut observer, &result);
+            diagnose(&mut diagnostic, raw, locus, &result, first);
+            first = false;
+            if result.is_err() {
+                observe_result_code(&mut observer, locus.0, &result);
+            }
+            last = (locus.0, result_code(&result));
+            match result {
+                Ok((at_response, AtResponse::Continue)) => {
+                    on_response(at_response, AtResponse::Continue);
+                    return Ok(());
+                }
+                Ok((at_response, bytes)) => on_response(at_response, bytes),
+                Err((at_response, e)) => return Err((at_response, e.into_owned())),
+            }
+        }
+
+        if let (name, Some(code)) = last
+            && let Some(observer) = observer {
+            observer.on_result_code(name, code);
+        }
+        Ok(())
+    }
+
+    /// Feed a single received byte into the parser.
+    ///
+    /// Bytes are accumulated into an internal line buffer of `SIZE` bytes until
+    /// the configured line terminator arrives (see
+    /// [`set_line_ending`](AtParser::set_line_ending), `\r` by default); the
+    /// buffered line is then dispatched exactly like [`execute`](AtParser::execute)
+    /// and the handler response is returned. Empty lines (e.g. the `\n` of a
+    /// `\r\n` pair) are ignored. Bytes that do not fit into the line buffer are
+    /// discarded.
+    ///
+    /// While a handler awaits input (see [`AtContext::input`]) bytes go to it
+    /// instead, and its result is returned once the command completes.
+    ///
+    /// # Arguments
+    ///
+    /// * `byte` — next byte received from the transport (UART, USB, ...)
+    ///
+    /// # Returns
+    ///
+    /// * `None` — the line is not complete yet
+    /// * `Some(AtResult)` — a line was completed and dispatched
+    /// * `Some(Err(("", AtError::InvalidArgs)))` — the completed line is not valid UTF-8
+    /// * `Some(Err(("", AtError::LineTooLong)))` — the completed line exceeded the
+    ///   [limit](AtParser::set_line_limit) and was discarded
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// # use at_parser_rs::parser::AtParser;
+    /// # use at_parser_rs::context::AtContext;
+    /// # const SIZE: usize = 64;
+    /// # struct MyHandler; impl AtContext<SIZE> for MyHandler {}
+    /// let mut parser: AtParser<MyHandler, SIZE> = AtParser::new();
+    ///
+    /// for &byte in b"AT+PING\r" {
+    ///     if let Some(result) = parser.feed(byte) {
+    ///         // send result back to the host
+    ///     }
+    /// }
+    /// ```
+    pub fn feed(&mut self, byte: u8) -> Option<AtResult<'_, SIZE, E>> {
+        self.feed_to(byte, None).map(|(result, _)| result)
+    }
+
+    /// Feed `byte` like [`feed`](AtParser::feed), sending the response of
+    /// the completed line to `out`, or to the output when `None`.
+    pub(crate) fn feed_to(&mut self, byte: u8, out: Option<&mut (dyn Write + '_)>) -> Option<(AtResult<'_, SIZE, E>, fmt::Result)> {
+        if self.transparent {
+            self.released();
+        }
+        if self.online == OnlineState::Data {
+            self.data(byte);
+            None
+        } else if self.awaiting.is_some() {
+            self.input(byte, out)
+        } else if self.push(byte) {
+            Some(self.respond(None, out))
+        } else {
+            None
+        }
+    }
+
+    /// Feed a chunk of received bytes into the parser.
+    ///
+    /// Bytes are consumed in order until a line is completed and dispatched, or
+    /// until the chunk is exhausted. Since a chunk (e.g. a DMA transfer) may
+    /// contain more than one line, the number of consumed bytes is returned so
+    /// that the caller can feed the remainder again.
+    ///
+    /// # Arguments
+    ///
+    /// * `bytes` — chunk of received bytes
+    ///
+    /// # Returns
+    ///
+    /// `(consumed, result)` where `consumed` is the number of bytes taken from
+    /// `bytes` and `result` is the response of the dispatched line, if any.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// # use at_parser_rs::parser::AtParser;
+    /// # use at_parser_rs::context::AtContext;
+    /// # const SIZE: usize = 64;
+    /// # struct MyHandler; impl AtContext<SIZE> for MyHandler {}
+    /// let mut parser: AtParser<MyHandler, SIZE> = AtParser::new();
+    ///
+    /// let mut chunk: &[u8] = b"AT+ECHO=1\rAT+ECHO?\r";
+    /// while !chunk.is_empty() {
+    ///     let (consumed, result) = parser.feed_slice(chunk);
+    ///     if let Some(result) = result {
+    ///         // send result back to the host
+    ///     }
+    ///     chunk = &chunk[consumed..];
+    /// }
+    /// ```
+    pub fn feed_slice(&mut self, bytes: &[u8]) -> (usize, Option<AtResult<'_, SIZE, E>>) {
+        let (consumed, response) = self.feed_slice_to(bytes, None);
+        (consumed, response.map(|(result, _)| result))
+    }
+
+    /// Feed `bytes` like [`feed_slice`](AtParser::feed_slice), sending the
+    /// response of the completed line to `out`, or to the output when `None`.
+    pub(crate) fn feed_slice_to(&mut self, bytes: &[u8], mut out: Option<&mut (dyn Write + '_)>) -> (usize, Option<(AtResult<'_, SIZE, E>, fmt::Result)>) {
+        for (i, &byte) in bytes.iter().enumerate() {
+            if self.transparent {
+                self.released();
+            }
+            if self.online == OnlineState::Data {
+                self.data(byte);
+            } else if self.awaiting.is_some() {
+                if let Some(response) = self.input(byte, out.as_deref_mut()) {
+                    return (i + 1, Some(response));
+                }
+            } else if self.push(byte) {
+                return (i + 1, Some(self.respond(None, out)));
+            }
+        }
+        (bytes.len(), None)
+    }
+
+    /// Set the command line termination convention used by
+    /// [`feed`](AtParser::feed) and [`feed_slice`](AtParser::feed_slice)
+    /// (V.250 `S3`).
+    ///
+    /// | Ending | Line completed by | Discarded |
+    /// |---|---|---|
+    /// | [`LineEnding::Cr`] (default) | `\r` | `\n` |
+    /// | [`LineEnding::Lf`] | `\n` | `\r` |
+    /// | [`LineEnding::CrLf`] | `\n` | `\r` |
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// # use at_parser_rs::parser::AtParser;
+    /// # use at_parser_rs::context::AtContext;
+    /// # use at_parser_rs::line::LineEnding;
+    /// # const SIZE: usize = 64;
+    /// # struct MyHandler; impl AtContext<SIZE> for MyHandler {}
+    /// let mut parser: AtParser<MyHandler, SIZE> = AtParser::new();
+    /// parser.set_line_ending(LineEnding::Lf);   // terminal sends "\n"
+    /// ```
+    pub fn set_line_ending(&mut self, ending: LineEnding) {
+        self.line.set_ending(ending);
+    }
+
+    /// Current command line termination convention.
+    pub fn line_ending(&self) -> LineEnding {
+        self.line.ending()
+    }
+
+    /// Set the maximum length of a command line received by
+    /// [`feed`](AtParser::feed) and [`feed_slice`](AtParser::feed_slice),
+    /// terminator excluded.
+    ///
+    /// `limit` is capped at `SIZE`, the default. A longer line is discarded
+    /// up to its terminator, which then yields
+    /// `Err(("", AtError::LineTooLong))`, framed as `ERROR` unless the
+    /// [error map](Framing::set_error_map) says otherwise.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use at_parser_rs::parser::AtParser;
+    /// # use at_parser_rs::context::AtContext;
+    /// # use at_parser_rs::AtError;
+    /// # const SIZE: usize = 64;
+    /// # struct MyHandler; impl AtContext<SIZE> for MyHandler {}
+    /// let mut parser: AtParser<MyHandler, SIZE> = AtParser::new();
+    /// parser.set_line_limit(8);
+    ///
+    /// let (_, result) = parser.feed_slice(b"AT+CGDCONT=1\r");
+    /// assert!(matches!(result, Some(Err(("", AtError::LineTooLong)))));
+    /// ```
+    pub fn set_line_limit(&mut self, limit: usize) {
+        self.line.set_limit(limit);
+    }
+
+    /// Current maximum length of a command line.
+    pub fn line_limit(&self) -> usize {
+        self.line.limit()
+    }
+
+    /// Enable or disable resynchronization on the `AT` prefix in
+    /// [`feed`](AtParser::feed) and [`feed_slice`](AtParser::feed_slice).
+    ///
+    /// As V.250 requires, everything received before `AT` or `A/` (line
+    /// noise, partial characters after a reset) is then ignored instead of
+    /// failing the whole line, see [`LineBuffer::set_resync`]. Lines without
+    /// the `AT` prefix are ignored as well, so leave it disabled (the
+    /// default) for hosts that send bare `+CMD` lines.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use at_parser_rs::parser::AtParser;
+    /// # use at_parser_rs::context::AtContext;
+    /// # use at_parser_rs::AtResponse;
+    /// # const SIZE: usize = 64;
+    /// # struct MyHandler; impl AtContext<SIZE> for MyHandler {}
+    /// let mut parser: AtParser<MyHandler, SIZE> = AtParser::new();
+    /// parser.set_resync(true);
+    ///
+    /// let (_, result) = parser.feed_slice(b"\x00\xfe~ATE0\r");
+    /// assert!(matches!(result, Some(Ok((_, AtResponse::Ok)))));
+    /// ```
+    pub fn set_resync(&mut self, enabled: bool) {
+        self.line.set_resync(enabled);
+    }
+
+    /// Whether lines resynchronize on the `AT` prefix.
+    pub fn is_resync(&self) -> bool {
+        self.line.resync()
+    }
+
+    /// Set the characters used to format response lines (V.250 `S3` + `S4`).
+    ///
+    /// Used by the response framing layer (see
+    /// [`set_response_framing`](AtParser::set_response_framing)) and exposed
+    /// through [`response_ending`](AtParser::response_ending) for applications
+    /// writing responses themselves. Defaults to [`LineEnding::CrLf`].
+    pub fn set_response_ending(&mut self, ending: LineEnding) {
+        self.framing.set_ending(ending);
+    }
+
+    /// Characters used to format response lines.
+    ///
+    /// When S-registers are enabled the value is derived from `S3` and `S4`.
+    pub fn response_ending(&self) -> LineEnding {
+        match &self.registers {
+            Some(registers) => registers.response_ending(),
+            None => self.framing.ending(),
+        }
+    }
+
+    /// Enable the built-in S-register subsystem.
+    ///
+    /// Once enabled, `ATS<n>=<value>`, `ATS<n>?` and `ATS<n>=?` are served by
+    /// the parser itself (query responses are 3-digit decimal values, e.g.
+    /// `013`) instead of being looked up among the registered commands. `S3`,
+    /// `S4` and `S5` then drive the line termination, the response line ending
+    /// and the backspace character, overriding
+    /// [`set_line_ending`](AtParser::set_line_ending) and
+    /// [`set_response_ending`](AtParser::set_response_ending).
+    ///
+    /// The registers start from the V.250 defaults; calling this method again
+    /// keeps the current values.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// # use at_parser_rs::parser::AtParser;
+    /// # use at_parser_rs::context::AtContext;
+    /// # const SIZE: usize = 64;
+    /// # struct MyHandler; impl AtContext<SIZE> for MyHandler {}
+    /// let mut parser: AtParser<MyHandler, SIZE> = AtParser::new();
+    /// parser.enable_s_registers();
+    ///
+    /// parser.execute("ATS7=60");   // Ok(("", ""))
+    /// parser.execute("ATS7?");     // Ok(("", "060"))
+    /// parser.execute("ATS3=10");   // command lines are now terminated by "\n"
+    /// ```
+    pub fn enable_s_registers(&mut self) {
+        if self.registers.is_none() {
+            self.registers = Some(SRegisters::new());
+        }
+    }
+
+    /// S-registers, if enabled.
+    pub fn s_registers(&self) -> Option<&SRegisters> {
+        self.registers.as_ref()
+    }
+
+    /// Mutable access to the S-registers, if enabled.
+    pub fn s_registers_mut(&mut self) -> Option<&mut SRegisters> {
+        self.registers.as_mut()
+    }
+
+    /// Install `store` to keep the profile in, serving the built-in `AT&W`,
+    /// `ATZ` and `AT&F` commands (see [`profile`](crate::profile)).
+    ///
+    /// Call [`load_profile`](AtParser::load_profile) at boot to restore the
+    /// stored profile.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use at_parser_rs::AtError;
+    /// # use at_parser_rs::parser::AtParser;
+    /// # use at_parser_rs::context::AtContext;
+    /// # use at_parser_rs::profile::SettingsStore;
+    /// # const SIZE: usize = 64;
+    /// # struct MyHandler; impl AtContext<SIZE> for MyHandler {}
+    /// # struct Flash;
+    /// # impl SettingsStore for Flash {
+    /// #     fn read(&mut self, _buf: &mut [u8]) -> Option<usize> { None }
+    /// #     fn write(&mut self, _profile: &[u8]) -> Result<(), AtError<'static>> { Ok(()) }
+    /// # }
+    /// let mut flash = Flash;
+    /// let mut parser: AtParser<MyHandler, SIZE> = AtParser::new();
+    /// parser.enable_s_registers();
+    /// parser.set_settings_store(&mut flash);
+    ///
+    /// // nothing stored yet: the parser keeps its defaults
+    /// assert!(!parser.load_profile());
+    /// assert!(parser.execute("ATS7=60&W").is_ok());
+    /// ```
+    pub fn set_settings_store(&mut self, store: &'a mut dyn SettingsStore) {
+        self.store = Some(store);
+    }
+
+    /// Remove the profile store; `AT&W`, `ATZ` and `AT&F` are unknown again
+    /// unless handlers are registered for them.
+    pub fn clear_settings_store(&mut self) {
+        self.store = None;
+    }
+
+    /// Install the settings of the application listed by the built-in
+    /// `AT&V`, after those of the parser.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use core::fmt;
+    /// # use at_parser_rs::parser::AtParser;
+    /// # use at_parser_rs::context::AtContext;
+    /// # use at_parser_rs::profile::{SettingsView, View};
+    /// # const SIZE: usize = 64;
+    /// # struct MyHandler; impl AtContext<SIZE> for MyHandler {}
+    /// struct Uart;
+    /// impl SettingsView for Uart {
+    ///     fn view(&mut self, view: &mut View<'_>) -> fmt::Result {
+    ///         view.line("+IPR: 115200")
+    ///     }
+    /// }
+    ///
+    /// let mut uart = Uart;
+    /// let views: &mut [&mut dyn SettingsView] = &mut [&mut uart];
+    /// let mut parser: AtParser<MyHandler, SIZE> = AtParser::new();
+    /// parser.set_settings_views(views);
+    ///
+    /// let (_, response) = parser.execute("AT&V").unwrap();
+    /// assert_eq!(format!("{response}"), "ACTIVE PROFILE:\r\nE0 Q0 V1 +CMEE=0\r\n+IPR: 115200");
+    /// ```
+    pub fn set_settings_views(&mut self, views: &'a mut [&'a mut dyn SettingsView]) {
+        self.views = views;
+    }
+
+    /// Restore the stored profile, as `ATZ` does.
+    ///
+    /// # Returns
+    ///
+    /// `false`, leaving the settings untouched, when no store is installed
+    /// or it holds no valid profile.
+    pub fn load_profile(&mut self) -> bool {
+        let Some(store) = self.store.as_deref_mut() else {
+            return false;
+        };
+        Settings { echo: &mut self.echo, framing: &mut self.framing, registers: self.registers.as_mut() }.load(store)
+    }
+
+    /// Save the active settings as the stored profile, as `AT&W` does.
+    ///
+    /// Does nothing when no store is installed.
+    pub fn save_profile(&mut self) -> Result<(), AtError<'static>> {
+        let Some(store) = self.store.as_deref_mut() else {
+            return Ok(());
+        };
+        Settings { echo: &mut self.echo, framing: &mut self.framing, registers: self.registers.as_mut() }.save(store)
+    }
+
+    /// Enable or disable case-insensitive command matching.
+    ///
+    /// V.250 allows the DTE to send commands in lower case. When enabled, the
+    /// `AT` prefix and command names are compared ignoring ASCII case, so
+    /// `at+gmr`, `AT+GmR` and `AT+GMR` all resolve to the handler registered
+    /// as `"AT+GMR"`. The comparison is done in place, without allocating.
+    /// Disabled by default.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// # use at_parser_rs::parser::AtParser;
+    /// # use at_parser_rs::context::AtContext;
+    /// # const SIZE: usize = 64;
+    /// # struct MyHandler; impl AtContext<SIZE> for MyHandler {}
+    /// let mut parser: AtParser<MyHandler, SIZE> = AtParser::new();
+    /// parser.set_case_insensitive(true);
+    /// // parser.execute("at+gmr") now dispatches to "AT+GMR"
+    /// ```
+    pub fn set_case_insensitive(&mut self, enabled: bool) {
+        self.case_insensitive = enabled;
+    }
+
+    /// Whether case-insensitive command matching is enabled.
+    pub fn is_case_insensitive(&self) -> bool {
+        self.case_insensitive
+    }
+
+    /// Set the maximum number of parameters a command may carry.
+    ///
+    /// A command with more parameters fails with
+    /// [`AtError::InvalidArgs`] before its handler runs, so a line such as
+    /// `AT+X=1,1,1,...` cannot make a handler walk thousands of parameters
+    /// or overrun a fixed-size array. Parameters are counted only up to the
+    /// limit. Unlimited by default.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use at_parser_rs::parser::AtParser;
+    /// # use at_parser_rs::context::AtContext;
+    /// # use at_parser_rs::{AtError, AtResult, Args, AtResponse};
+    /// # const SIZE: usize = 64;
+    /// struct Ping;
+    /// impl AtContext<SIZE> for Ping {
+    ///     fn set(&mut self, at_response: &'static str, _args: Args) -> AtResult<'_, SIZE> {
+    ///         Ok((at_response, AtResponse::Ok))
+    ///     }
+    /// }
+    ///
+    /// let mut ping = Ping;
+    /// let commands: &mut [(&str, &str, &mut Ping)] = &mut [("AT+PING", "+PING: ", &mut ping)];
+    /// let mut parser: AtParser<Ping, SIZE> = AtParser::new();
+    /// parser.set_commands(commands);
+    /// parser.set_arg_limit(2);
+    ///
+    /// assert!(parser.execute("AT+PING=1,2").is_ok());
+    /// assert!(matches!(parser.execute("AT+PING=1,2,3"), Err(("+PING: ", AtError::InvalidArgs))));
+    /// ```
+    pub fn set_arg_limit(&mut self, limit: usize) {
+        self.arg_limit = limit;
+    }
+
+    /// Current maximum number of parameters of a command.
+    pub fn arg_limit(&self) -> usize {
+        self.arg_limit
+    }
+
+    /// Enable or disable strict parsing of extended commands.
+    ///
+    /// In strict mode text following the `?` of a query or the `=?` of a
+    /// test command (`AT+CMD?extra`, `AT+CMD=?junk`) fails the line with
+    /// [`AtError::InvalidArgs`]. Disabled by default, since some legacy hosts
+    /// send such lines: `AT+CMD=?junk` is then a set command with the
+    /// arguments `?junk`, and `AT+CMD?extra` names no known command.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// # use at_parser_rs::parser::AtParser;
+    /// # use at_parser_rs::context::AtContext;
+    /// # const SIZE: usize = 64;
+    /// # struct MyHandler; impl AtContext<SIZE> for MyHandler {}
+    /// let mut parser: AtParser<MyHandler, SIZE> = AtParser::new();
+    /// parser.set_strict(true);
+    /// // parser.execute("AT+CSQ?x") now fails with InvalidArgs
+    /// ```
+    pub fn set_strict(&mut self, enabled: bool) {
+        self.strict = enabled;
+    }
+
+    /// Whether strict parsing of extended commands is enabled.
+    pub fn is_strict(&self) -> bool {
+        self.strict
+    }
+
+    /// Set the extended command prefix characters (default `"+"`).
+    ///
+    /// Vendors put their commands in their own namespace, such as `AT#` (Telit)
+    /// or `AT$` (some GNSS modules). A command registered with any of these
+    /// prefixes also answers when invoked with any other of them, and the
+    /// handler is told which prefix was actually used through
+    /// [`AtContext::prefix`] right before its command form is called. An exact
+    /// registration always takes precedence.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// # use at_parser_rs::parser::AtParser;
+    /// # use at_parser_rs::context::AtContext;
+    /// # const SIZE: usize = 64;
+    /// # struct MyHandler; impl AtContext<SIZE> for MyHandler {}
+    /// let mut parser: AtParser<MyHandler, SIZE> = AtParser::new();
+    /// parser.set_extended_prefixes("+#$");
+    /// // "AT+GPSPWR", "AT#GPSPWR" and "AT$GPSPWR" reach the handler registered as "AT+GPSPWR"
+    /// ```
+    pub fn set_extended_prefixes(&mut self, prefixes: &'static str) {
+        self.prefixes = prefixes;
+    }
+
+    /// Extended command prefix characters.
+    pub fn extended_prefixes(&self) -> &'static str {
+        self.prefixes
+    }
+
+    /// Borrow the state needed to dispatch commands.
+    fn dispatcher(&mut self) -> Dispatcher<'_, '_, 'a, T, SIZE, S, E> {
+        dispatcher!(self, self.registers.as_mut(), self.output.transmission(), self.response_buffer, Some(&mut self.diagnostic))
+    }
+
+    /// Append `byte` to the line buffer, returning `true` when a non-empty
+    /// line has been terminated and is ready to be dispatched (including the
+    /// `A/` repeat command, see [`LineBuffer::push`]).
+    fn push(&mut self, byte: u8) -> bool {
+        self.echo_byte(byte);
+        self.sync_line();
+        self.line.push(byte)
+    }
+
+    /// Apply the line ending and backspace of the S-registers, if enabled,
+    /// to the line buffer.
+    fn sync_line(&mut self) {
+        if let Some(registers) = &self.registers {
+            self.line.set_ending(registers.line_ending());
+            self.line.set_backspace(registers.backspace());
+        }
+    }
+
+    /// Echo `byte` back to the DTE when echo is enabled.
+    fn echo_byte(&mut self, byte: u8) {
+        if self.echo
+            && byte.is_ascii()
+            && let Some(mut output) = self.output.transmission() {
+            let _ = output.write_char(char::from(byte));
+        }
+    }
+
+    /// Hand `byte` to the handler awaiting input, returning its result once
+    /// the command completes.
+    fn input(&mut self, byte: u8, out: Option<&mut (dyn Write + '_)>) -> Option<(AtResult<'static, SIZE, E>, fmt::Result)> {
+        let mut target = self.awaiting?;
+        // Counted payloads are binary
+        if !matches!(target.payload, Some(Payload::Length(_))) {
+            self.echo_byte(byte);
+        }
+
+        if let Some(payload) = target.payload
+            && !(payload == Payload::Terminated && matches!(byte, CTRL_Z | ESC)) {
+            target = self.collect(target, byte);
+            // Only a counted payload completes on a data byte
+            if payload != Payload::Length(target.received) {
+                self.awaiting = Some(target);
+                return None;
+            }
+        }
+
+        let framing = self.framing();
+        let commands = if target.prefixed { &mut *self.prefix_commands } else { &mut *self.commands };
+        let (command, at_response, module) = &mut commands[target.index];
+        let buffer = &mut *self.response_buffer;
+        let mut output = self.output.transmission();
+        let mut responder = responder(&mut *buffer, writer(&mut output), self.framed, framing, self.state.as_deref_mut())
+            .with_command(command, target.form)
+            .with_abort(self.abort)
+            .with_help(self.help);
+        let result = match target.payload {
+            None => module.input(at_response, byte, &mut responder)?,
+            // Cancelled: the payload is dropped
+            Some(Payload::Terminated) if byte == ESC => Ok((*at_response, AtResponse::Ok)),
+            Some(_) if target.overflow => Err((*at_response, AtError::InvalidArgs)),
+            Some(_) => {
+                let payload = match self.payload_buffer.as_deref() {
+                    Some(buffer) => &buffer[..target.received],
+                    None => self.line.as_bytes(),
+                };
+                module.payload(at_response, payload, &mut responder)
+            }
+        };
+        let len = responder.len();
+        // The bus stays taken for the response
+        drop(output.map(Transmission::into_part));
+        let result = with_written(result, written(buffer, len));
+        observe_result_code(&mut self.observer.as_deref_mut(), command, &result);
+
+        if target.payload.is_some() {
+            self.line.clear();
+            if is_continue(&result) {
+                self.awaiting = Some(Target { received: 0, overflow: false, ..target });
+            }
+        }
+        if !is_continue(&result) {
+            self.awaiting = None;
+        }
+        let sent = reply!(self).send(&result, out);
+        // Sent in full before being copied
+        Some((into_owned(result), sent))
+    }
+
+    /// Store a payload byte in the payload buffer, or the line buffer without
+    /// one.
+    fn collect(&mut self, target: Target, byte: u8) -> Target {
+        let stored = match self.payload_buffer.as_deref_mut() {
+            Some(buffer) => buffer.get_mut(target.received).map(|slot| *slot = byte).is_some(),
+            None => self.line.push_raw(byte),
+        };
+        Target { received: target.received + 1, overflow: target.overflow || !stored, ..target }
+    }
+
+    /// Write the framed response of a dispatched line, then the URCs held
+    /// back meanwhile unless a handler still awaits input.
+    fn write_response(&mut self, result: &AtResult<'_, SIZE, E>) {
+        let _ = self.reply().send(result, None);
+    }
+
+    /// Borrow the state needed to send a response.
+    fn reply(&mut self) -> Reply<'_, 'a, SIZE> {
+        reply!(self)
+    }
+
+    /// Write a framed URC, or hold it back while a line is being received.
+    fn send_urc(&mut self, urc: core::fmt::Arguments<'_>) -> core::fmt::Result {
+        let Some(mut output) = self.output.transmission() else {
+            return Ok(());
+        };
+
+        if !self.line.is_empty() || self.awaiting.is_some() {
+            return self.urcs.push(urc);
+        }
+
+        output.write_str(self.urcs.as_str())?;
+        self.urcs.clear();
+        output.write_fmt(urc)
+    }
+
+    /// Dispatch `line`, or the buffered line when `None`, and send its
+    /// response to `out`, or to the output when `None`.
+    ///
+    /// The response is sent with the framing the line leaves in effect
+    /// (`ATV0`, `ATS4`), followed by the URCs held back meanwhile.
+    pub(crate) fn respond<'l>(&'l mut self, line: Option<&'l [u8]>, out: Option<&mut (dyn Write + '_)>) -> (AtResult<'l, SIZE, E>, fmt::Result) {
+        let dispatcher = dispatcher!(
+            self,
+            self.registers.as_mut(),
+            self.output.transmission().map(Transmission::into_part),
+            self.response_buffer,
+            Some(&mut self.diagnostic)
+        );
+
+        let result = match line {
+            Some(line) => match command_text(line) {
+                Ok(line) => dispatcher.run(line),
+                Err(valid_up_to) => dispatcher.invalid_line(valid_up_to),
+            },
+            None if self.line.overflowed() => {
+                let limit = self.line.limit();
+                self.line.clear();
+                dispatcher.line_too_long(limit)
+            }
+            None => match core::str::from_utf8(self.line.take()) {
+                Ok(line) => dispatcher.run(line),
+                Err(e) => dispatcher.invalid_line(e.valid_up_to()),
+            },
+        };
+
+        let sent = reply!(self).send(&result, out);
+        (result, sent)
+    }
+}
+
+impl<'a, T, const SIZE: usize, S, E> Default for AtParser<'a, T, SIZE, S, E>
+where
+    T: AtContext<SIZE, S, E> + ?Sized,
+    E: UserError {
+
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Borrowed view of the parser state needed to dispatch commands.
+///
+/// Results borrow no more than the command tables, the fallback and the
+/// response buffer (`'r`), so the rest of the state may be used to send them.
+struct Dispatcher<'r, 'b, 'a, T, const SIZE: usize, S, E>
+where
+    T: AtContext<SIZE, S, E> + ?Sized {
+    /// Registered commands
+    commands: &'r mut [(&'static str, &'static str, &'a mut T)],
+    /// Whether the registered commands are sorted by name
+    sorted: bool,
+    /// Index of the last command matched by name, tried first
+    last_command: &'b Cell<Option<usize>>,
+    /// Identifiers of the registered names, compared before the names
+    ids: Option<&'a [u32]>,
+    /// Registered names in a dense array, in table order
+    names: Option<&'a [&'static str]>,
+    /// Perfect hash over the names of the registered commands
+    #[cfg(feature = "perfect-hash")]
+    hash: Option<HashIndex<'a>>,
+    /// Commands matched on a prefix
+    prefix_commands: &'r mut [(&'static str, &'static str, &'a mut T)],
+    /// S-registers, when enabled
+    registers: Option<&'b mut SRegisters>,
+    /// Whether command names are matched ignoring ASCII case
+    case_insensitive: bool,
+    /// Maximum number of parameters handed to a handler
+    arg_limit: usize,
+    /// Whether text after the `?` / `=?` of an extended command is rejected
+    strict: bool,
+    /// Extended command prefix characters served by a single registration
+    prefixes: &'static str,
+    /// Catch-all handler for unknown commands
+    fallback: Option<&'r mut (dyn AtFallback<SIZE, E> + 'a)>,
+    /// Hook observing the dispatched commands
+    observer: Option<&'b mut (dyn AtObserver<SIZE, E> + 'a)>,
+    /// Echo setting served by the built-in `ATE`
+    echo: &'b mut bool,
+    /// Framing settings served by the built-in `ATV`
+    framing: &'b mut Framing,
+    /// Writer towards the DTE, handed to handlers through a [`Responder`]
+    output: Option<Transmission<'b>>,
+    /// Whether the response framing layer is enabled
+    framed: bool,
+    /// Buffer handlers write their response into
+    buffer: &'r mut [u8],
+    /// User state handed to handlers through a [`Responder`]
+    state: Option<&'b mut S>,
+    /// Flag marking handler execution, polled through a [`Responder`]
+    abort: Option<&'a Abort>,
+    /// Help table handed to handlers through a [`Responder`]
+    help: Option<&'a Help>,
+    /// Set to the handler that returned [`AtResponse::Continue`]
+    awaiting: &'b mut Option<Target>,
+    /// Storage of the profile served by `AT&W` and `ATZ`
+    store: Option<&'b mut (dyn SettingsStore + 'a)>,
+    /// Settings of the application listed by `AT&V`
+    views: &'b mut [&'a mut dyn SettingsView],
+    /// Set to the diagnostic of a rejected line
+    diagnostic: Option<&'b mut Option<Diagnostic>>,
+}
+
+impl<'r, 'b, 'a, T, const SIZE: usize, S, E> Dispatcher<'r, 'b, 'a, T, SIZE, S, E>
+where
+    T: AtContext<SIZE, S, E> + ?Sized,
+    E: UserError {
+
+    /// Reborrow for a single dispatch, keeping `self` usable afterwards.
+    fn reborrow(&mut self) -> Dispatcher<'_, '_, 'a, T, SIZE, S, E> {
+        dispatcher!(
+            self,
+            self.registers.as_deref_mut(),
+            self.output.as_mut().map(Transmission::part),
+            &mut *self.buffer,
+            self.diagnostic.as_deref_mut()
+        )
+    }
+
+    /// Execute every command of `line`, returning the response of the last
+    /// one or the first error.
+    fn run(mut self, line: &'r str) -> AtResult<'r, SIZE, E> {
+        let mut observer = self.observer.take();
+        let mut diagnostic = self.diagnostic.take();
+        clear_diagnostic(&mut diagnostic);
+        if self.needs_fallback(line)
+            && let Some(fallback) = self.fallback {
+            let result = fallback.unknown(line.trim());
+            observe_response(&mut observer, &result);
+            observe_result_code(&mut observer, line.trim(), &result);
+            return result;
+        }
+
+        let raw = line;
+        let mut line = CommandLine::new(line, self.case_insensitive, self.strict);
+        let mut step = line
+            .next_command(|rest| self.match_prefix(rest))
+            .unwrap_or(Ok(Step::Command("", AtForm::Exec)))
+            .map_err(|e| parse_error(&mut observer, e, raw))?;
+
+        let mut first = true;
+        while let Some(next) = line.next_command(|rest| self.match_prefix(rest)) {
+            self.observe_command(&mut observer, &step);
+            let locus = step.locus();
+            let result = self.reborrow().execute(step);
+            observe_response(&mut observer, &result);
+            diagnose(&mut diagnostic, raw, locus, &result, first);
+            first = false;
+            if result.is_err() {
+                observe_result_code(&mut observer, locus.0, &result);
+            }
+            match result {
+                Err((at_response, e)) => return Err((at_response, e.into_owned())),
+                // The rest of the line is not executed while input is awaited
+                Ok((at_response, AtResponse::Continue)) => return Ok((at_response, AtResponse::Continue)),
+                Ok(_) => {}
+            }
+            step = next.map_err(|e| parse_error(&mut observer, e, raw))?;
+        }
+
+        self.observe_command(&mut observer, &step);
+        let locus = step.locus();
+        let result = self.execute(step);
+        observe_response(&mut observer, &result);
+        diagnose(&mut diagnostic, raw, locus, &result, first);
+        observe_result_code(&mut observer, locus.0, &result);
+        result
+    }
+
+    /// Fail a line that is valid UTF-8 only up to `valid_up_to`.
+    fn invalid_line(mut self, valid_up_to: usize) -> AtResult<'r, SIZE, E> {
+        if let Some(diagnostic) = self.diagnostic.take() {
+            *diagnostic = Some(Diagnostic::invalid_utf8(valid_up_to));
+        }
+        let mut observer = self.observer.take();
+        Err(parse_error(&mut observer, AtError::InvalidArgs, ""))
+    }
+
+    /// Reject a line discarded for exceeding `limit` bytes.
+    fn line_too_long(mut self, limit: usize) -> AtResult<'r, SIZE, E> {
+        if let Some(diagnostic) = self.diagnostic.take() {
+            *diagnostic = Some(Diagnostic::too_long(limit));
+        }
+        let mut observer = self.observer.take();
+        Err(parse_error(&mut observer, AtError::LineTooLong, ""))
+    }
+
+    /// Tell the observer that `step` is about to be executed.
+    fn observe_command(&self, observer: &mut Option<&mut (dyn AtObserver<SIZE, E> + 'a)>, step: &Step<'_>) {
+        let Some(observer) = observer.as_deref_mut() else {
+            return;
+        };
+        match step {
+            Step::Command(name, form) => observer.on_command(name, form.kind()),
+            Step::Prefix(index, args) => {
+                let form = if args.is_empty() { Form::Exec } else { Form::Set };
+                observer.on_command(self.prefix_commands[*index].0, form);
+            }
+        }
+    }
+
+    /// Whether a fallback handler is installed and `line` holds a command
+    /// that cannot be resolved.
+    fn needs_fallback(&self, line: &str) -> bool {
+        if self.fallback.is_none() {
+            return false;
+        }
+
+        let mut commands = CommandLine::new(line, self.case_insensitive, self.strict);
+        while let Some(step) = commands.next_command(|rest| self.match_prefix(rest)) {
+            if let Ok(Step::Command(name, _)) = step
+                && !self.resolves(name) {
+                return true;
+            }
+        }
+        false
+    }
+
+    /// Whether a command named `name` would be dispatched to a handler.
+    fn resolves(&self, name: &str) -> bool {
+        if self.registers.is_some() && sreg::index(name).is_some() {
+            return true;
+        }
+
+        self.lookup(name).is_some()
+            || Builtin::find(strip_at(name, self.case_insensitive), self.case_insensitive)
+                .is_some_and(|builtin| self.store.is_some() || !builtin.is_profile())
+    }
+
+    /// Index of the command registered for `name`.
+    ///
+    /// An exact match wins; otherwise an extended command may be served by a
+    /// registration differing only in its prefix character, when both prefixes
+    /// are part of the configured set. The exact match is binary searched in
+    /// sorted tables, and hashed in hashed ones, unless it is the command
+    /// matched last: hosts polling `AT+CSQ?` find it without a search.
+    fn lookup(&self, name: &str) -> Option<usize> {
+        let case_insensitive = self.case_insensitive;
+        let name = strip_at(name, case_insensitive);
+
+        if let Some(last) = self.last_command.get()
+            && self.commands.get(last).is_some_and(|(n, _, _)| names_match(strip_at(n, case_insensitive), name, case_insensitive)) {
+            return Some(last);
+        }
+
+        #[cfg(feature = "perfect-hash")]
+        let hashed = self.hash.map(|hash| {
+            hash.candidate(name)
+                .filter(|&i| self.commands.get(i).is_some_and(|(n, _, _)| names_match(strip_at(n, case_insensitive), name, case_insensitive)))
+        });
+        #[cfg(not(feature = "perfect-hash"))]
+        let hashed: Option<Option<usize>> = None;
+
+        let exact = if let Some(exact) = hashed {
+            exact
+        } else if self.sorted {
+            self.commands
+                .binary_search_by(|(n, _, _)| search_order(strip_at(n, case_insensitive), name, case_insensitive))
+                .ok()
+        } else if let Some(ids) = self.ids {
+            let id = fold_id(name.as_bytes());
+            self.commands
+                .iter()
+                .zip(ids)
+                .position(|((n, _, _), &other)| other == id && names_match(strip_at(n, case_insensitive), name, case_insensitive))
+        } else if let Some(names) = self.names {
+            names.iter().take(self.commands.len()).position(|n| names_match(strip_at(n, case_insensitive), name, case_insensitive))
+        } else {
+            self.commands
+                .iter()
+                .position(|(n, _, _)| names_match(strip_at(n, case_insensitive), name, case_insensitive))
+        };
+        if exact.is_some() {
+            self.last_command.set(exact);
+        }
+        exact.or_else(|| {
+            let (_, name) = split_prefix(name, self.prefixes)?;
+            self.commands.iter().position(|(n, _, _)| {
+                split_prefix(strip_at(n, case_insensitive), self.prefixes)
+                    .is_some_and(|(_, n)| names_match(n, name, case_insensitive))
+            })
+        })
+    }
+
+    /// Find the prefix command `rest` starts with.
+    ///
+    /// Returns the index in the prefix table and the prefix length.
+    fn match_prefix(&self, rest: &str) -> Option<(usize, usize)> {
+        let case_insensitive = self.case_insensitive;
+
+        self.prefix_commands.iter().enumerate().find_map(|(index, (n, _, _))| {
+            let prefix = strip_at(n, case_insensitive);
+            let head = rest.as_bytes().get(..prefix.len())?;
+            let matched = if case_insensitive {
+                head.eq_ignore_ascii_case(prefix.as_bytes())
+            } else {
+                head == prefix.as_bytes()
+            };
+            (matched && !prefix.is_empty()).then_some((index, prefix.len()))
+        })
+    }
+
+    /// Framing settings in effect, with the line ending from `S3` / `S4`.
+    fn response_framing(&self) -> Framing {
+        response_framing(*self.framing, self.registers.as_deref())
+    }
+
+    /// Execute a single step of a command line.
+    fn execute(mut self, step: Step<'r>) -> AtResult<'r, SIZE, E> {
+        match step {
+            Step::Command(name, form) => self.dispatch(name, form),
+            Step::Prefix(index, args) if too_many_args(&Args::new(args), self.arg_limit) => Err((self.prefix_commands[index].1, AtError::InvalidArgs)),
+            Step::Prefix(index, args) => {
+                let framing = self.response_framing();
+                let form = if args.is_empty() { Form::Exec } else { Form::Set };
+                let buffer = self.buffer;
+                let mut responder = responder(&mut *buffer, writer(&mut self.output), self.framed, framing, self.state)
+                    .with_command(self.prefix_commands[index].0, form)
+                    .with_abort(self.abort)
+                    .with_help(self.help);
+                let (_, at_response, module) = &mut self.prefix_commands[index];
+                if let Some(abort) = self.abort {
+                    abort.start();
+                }
+                let result = if args.is_empty() {
+                    module.exec_with(at_response, &mut responder)
+                } else {
+                    module.set_with(at_response, Args::new(args), &mut responder)
+                };
+                if let Some(abort) = self.abort {
+                    abort.finish();
+                }
+                if is_continue(&result) {
+                    *self.awaiting = Some(Target { prefixed: true, index, form, payload: responder.collects_payload(), received: 0, overflow: false });
+                }
+                let len = responder.len();
+                with_written(result, written(buffer, len))
+            }
+        }
+    }
+
+    /// Serve `name` from the S-registers or the built-in commands, or find
+    /// the handler registered for it and notify it of the command prefix.
+    fn route(&mut self, name: &str, form: AtForm<'r>) -> Route<'r, SIZE, E> {
+        if let Some(index) = sreg::index(name)
+            && let Some(registers) = self.registers.as_deref_mut() {
+            return Route::Done(s_register(registers, index, form));
+        }
+
+        // Find the command handler
+        let Some(index) = self.lookup(name) else {
+            return Route::Done(match Builtin::find(strip_at(name, self.case_insensitive), self.case_insensitive) {
+                Some(Builtin::Echo) => flag(self.echo, form),
+                Some(Builtin::Verbose) => {
+                    let mut verbose = self.framing.verbose();
+                    let result = flag(&mut verbose, form);
+                    self.framing.set_verbose(verbose);
+                    result
+                }
+                Some(Builtin::Quiet) => {
+                    let mut quiet = self.framing.quiet();
+                    let result = flag(&mut quiet, form);
+                    self.framing.set_quiet(quiet);
+                    result
+                }
+                Some(Builtin::Cmee) => cmee(self.framing, form),
+                Some(Builtin::View) => {
+                    let framing = self.response_framing();
+                    let mut buffer = [0; SIZE];
+                    let responder = responder(&mut buffer, writer(&mut self.output), self.framed, framing, None::<&mut ()>);
+                    let settings = Settings { echo: self.echo, framing: self.framing, registers: self.registers.as_deref_mut() };
+                    view(&settings, self.views, responder, form)
+                }
+                Some(builtin) => {
+                    let Some(store) = self.store.as_deref_mut() else {
+                        return Route::Done(Err(("", AtError::UnknownCommand)));
+                    };
+                    let reset = matches!(builtin, Builtin::Reset);
+                    let settings = Settings { echo: self.echo, framing: self.framing, registers: self.registers.as_deref_mut() };
+                    let result = profile(builtin, settings, store, form);
+                    if reset && result.is_ok() {
+                        self.reset_handlers();
+                    }
+                    result
+                }
+                None => Err(("", AtError::UnknownCommand)),
+            });
+        };
+
+        if let Some((prefix, _)) = split_prefix(strip_at(name, self.case_insensitive), self.prefixes) {
+            self.commands[index].2.prefix(prefix);
+        }
+        Route::Handler(index, form)
+    }
+
+    /// Notify every registered handler of `ATZ`, see [`AtContext::on_reset`].
+    fn reset_handlers(&mut self) {
+        for (_, _, handler) in self.commands.iter_mut().chain(self.prefix_commands.iter_mut()) {
+            handler.on_reset();
+        }
+    }
+
+    /// Look up the handler registered for `name` and call the method matching
+    /// the command form.
+    fn dispatch(mut self, name: &str, form: AtForm<'r>) -> AtResult<'r, SIZE, E> {
+        let (index, form) = match self.route(name, form) {
+            Route::Done(result) => return result,
+            Route::Handler(index, form) => (index, form),
+        };
+        if let AtForm::Set(args) = &form
+            && too_many_args(args, self.arg_limit) {
+            return Err((self.commands[index].1, AtError::InvalidArgs));
+        }
+        let framing = self.response_framing();
+        let buffer = self.buffer;
+        let mut responder = responder(&mut *buffer, writer(&mut self.output), self.framed, framing, self.state)
+            .with_command(self.commands[index].0, form.kind())
+            .with_abort(self.abort)
+            .with_help(self.help);
+        let (_, at_response, module) = &mut self.commands[index];
+
+        // Dispatch to the appropriate handler method
+        if let Some(abort) = self.abort {
+            abort.start();
+        }
+        let result = match form {
+            AtForm::Exec => module.exec_with(at_response, &mut responder),
+            AtForm::Query => module.query_with(at_response, &mut responder),
+            AtForm::Test => module.test_with(at_response, &mut responder),
+            AtForm::Set(args) => module.set_with(at_response, args, &mut responder),
+        };
+        if let Some(abort) = self.abort {
+            abort.finish();
+        }
+        if is_continue(&result) {
+            *self.awaiting = Some(Target { prefixed: false, index, form: responder.form(), payload: responder.collects_payload(), received: 0, overflow: false });
+        }
+        let len = responder.len();
+        with_written(result, written(buffer, len))
+    }
+}
+
+#[cfg(feature = "async")]
+impl<'r, 'b, 'a, T, const SIZE: usize, S, E> Dispatcher<'r, 'b, 'a, T, SIZE, S, E>
+where
+    T: AsyncAtContext<SIZE, S, E> + ?Sized,
+    E: UserError {
+
+    /// Async counterpart of [`run`](Dispatcher::run).
+    async fn run_async(mut self, line: &'r str) -> AtResult<'r, SIZE, E> {
+        let mut observer = self.observer.take();
+        let mut diagnostic = self.diagnostic.take();
+        clear_diagnostic(&mut diagnostic);
+        if self.needs_fallback(line)
+            && let Some(fallback) = self.fallback {
+            let result = fallback.unknown(line.trim());
+            observe_response(&mut observer, &result);
+            observe_result_code(&mut observer, line.trim(), &result);
+            return result;
+        }
+
+        let raw = line;
+        let mut line = CommandLine::new(line, self.case_insensitive, self.strict);
+        let mut step = line
+            .next_command(|rest| self.match_prefix(rest))
+            .unwrap_or(Ok(Step::Command("", AtForm::Exec)))
+            .map_err(|e| parse_error(&mut observer, e, raw))?;
+
+        let mut first = true;
+        while let Some(next) = line.next_command(|rest| self.match_prefix(rest)) {
+            self.observe_command(&mut observer, &step);
+            let locus = step.locus();
+            let result = self.reborrow().execute_async(step).await;
+            observe_response(&mut observer, &result);
+            diagnose(&mut diagnostic, raw, locus, &result, first);
+            first = false;
+            if result.is_err() {
+                observe_result_code(&mut observer, locus.0, &result);
+            }
+            match result {
+                Err((at_response, e)) => return Err((at_response, e.into_owned())),
+                Ok((at_response, AtResponse::Continue)) => return Ok((at_response, AtResponse::Continue)),
+                Ok(_) => {}
+            }
+            step = next.map_err(|e| parse_error(&mut observer, e, raw))?;
+        }
+
+        self.observe_command(&mut observer, &step);
+        let locus = step.locus();
+        let result = self.execute_async(step).await;
+        observe_response(&mut observer, &result);
+        diagnose(&mut diagnostic, raw, locus, &result, first);
+        observe_result_code(&mut observer, locus.0, &result);
+        result
+    }
+
+    /// Async counterpart of [`execute`](Dispatcher::execute).
+    async fn execute_async(mut self, step: Step<'r>) -> AtResult<'r, SIZE, E> {
+        let (prefixed, index, form) = match step {
+            Step::Command(name, form) => match self.route(name, form) {
+                Route::Done(result) => return result,
+                Route::Handler(index, form) => (false, index, form),
+            },
+            Step::Prefix(index, "") => (true, index, AtForm::Exec),
+            Step::Prefix(index, args) => (true, index, AtForm::Set(Args::new(args))),
+        };
+        let framing = self.response_framing();
+        let commands = if prefixed { self.prefix_commands } else { self.commands };
+        if let AtForm::Set(args) = &form
+            && too_many_args(args, self.arg_limit) {
+            return Err((commands[index].1, AtError::InvalidArgs));
+        }
+        let buffer = self.buffer;
+        let mut responder = responder(&mut *buffer, writer(&mut self.output), self.framed, framing, self.state)
+            .with_command(commands[index].0, form.kind())
+            .with_abort(self.abort)
+            .with_help(self.help);
+        let (_, at_response, module) = &mut commands[index];
+
+        if let Some(abort) = self.abort {
+            abort.start();
+        }
+
+        let result = match form {
+            AtForm::Exec => AsyncAtContext::exec(&mut **module, at_response, &mut responder).await,
+            AtForm::Query => AsyncAtContext::query(&mut **module, at_response, &mut responder).await,
+            AtForm::Test => AsyncAtContext::test(&mut **module, at_response, &mut responder).await,
+            AtForm::Set(args) => AsyncAtContext::set(&mut **module, at_response, args, &mut responder).await,
+        };
+        if let Some(abort) = self.abort {
+            abort.finish();
+        }
+        if is_continue(&result) {
+            *self.awaiting = Some(Target { prefixed, index, form: responder.form(), payload: responder.collects_payload(), received: 0, overflow: false });
+        }
+        let len = responder.len();
+        with_written(result, written(buffer, len))
+    }
+}
+
+/// Borrowed view of the parser state needed to send the response of a line.
+struct Reply<'p, 'a, const SIZE: usize> {
+    /// Framing settings in effect after the line
+    framing: Framing,
+    /// Writer towards the DTE
+    output: &'p mut Output<'a>,
+    /// Whether the response framing layer is enabled
+    framed: bool,
+    /// URCs held back while the line was received
+    urcs: &'p mut Pending<SIZE>,
+    /// Whether a handler awaits further input
+    awaiting: bool,
+    /// Whether a sink is installed for the online data state
+    sink: bool,
+    /// Online state
+    online: &'p mut OnlineState,
+    /// Detector of the escape sequence
+    escape: &'p mut Escape,
+    /// Time source of the escape guard times
+    clock: Option<&'a dyn Clock>,
+    /// Handoff of the online data state to an external stack
+    handoff: Option<&'a Handoff>,
+    /// Whether the external stack has claimed the stream
+    transparent: &'p mut bool,
+    /// Mode last reported to the listener
+    mode: &'p mut Mode,
+    /// Listener of the changes of mode
+    listener: Option<&'p mut (dyn ModeListener + 'a)>,
+}
+
+impl<const SIZE: usize> Reply<'_, '_, SIZE> {
+    /// Enter the online data state when `result` is `CONNECT` and a sink is
+    /// installed.
+    fn connect<E>(&mut self, result: &AtResult<'_, SIZE, E>) {
+        if self.sink
+            && matches!(result, Ok((_, AtResponse::FinalCode(ResultCode::Connect(_))))) {
+            *self.online = OnlineState::Data;
+            *self.escape = Escape::new(self.clock.map(Clock::now));
+            *self.transparent = self.handoff.is_some_and(Handoff::take_claim);
+        }
+    }
+
+    /// Report a change of mode to the listener.
+    fn transition(&mut self) {
+        let (from, to) = (*self.mode, current_mode(*self.online, self.awaiting));
+        if from != to {
+            *self.mode = to;
+            if let Some(listener) = self.listener.as_deref_mut() {
+                listener.on_mode(from, to);
+            }
+        }
+    }
+
+    /// Send the framed `result` to `out`, or to the output when `None` and
+    /// framing is enabled, then the URCs held back meanwhile to the output
+    /// unless a handler still awaits input.
+    fn send<E: UserError>(mut self, result: &AtResult<'_, SIZE, E>, out: Option<&mut (dyn Write + '_)>) -> fmt::Result {
+        self.connect(result);
+        self.transition();
+        let framed = out.is_none() && self.framed;
+        let mut sent = out.map_or(Ok(()), |out| self.framing.write_result(out, result));
+        if let Some(mut output) = self.output.transmission() {
+            if framed {
+                sent = self.framing.write_result(&mut output, result);
+            }
+            if !self.awaiting {
+                sent = sent.and(output.write_str(self.urcs.as_str()));
+            }
+        }
+        if !self.awaiting {
+            self.urcs.clear();
+        }
+        sent
+    }
+}
+
+/// Framing settings `framing` with the response line ending of `registers`,
+/// when enabled.
+fn response_framing(mut framing: Framing, registers: Option<&SRegisters>) -> Framing {
+    if let Some(registers) = registers {
+        framing.set_ending(registers.response_ending());
+    }
+    framing
+}
+
+/// Mode of a parser in the `online` state, `awaiting` input or not.
+fn current_mode(online: OnlineState, awaiting: bool) -> Mode {
+    match online {
+        _ if awaiting => Mode::DataEntry,
+        OnlineState::Command => Mode::Command,
+        OnlineState::Data => Mode::OnlineData,
+        OnlineState::OnlineCommand => Mode::OnlineCommand,
+    }
+}
+
+/// Ends the payload of a command in data entry mode
+const CTRL_Z: u8 = 0x1a;
+/// Cancels the payload of a command in data entry mode
+const ESC: u8 = 0x1b;
+
+/// Handler of the command table receiving the input of a multi-step command.
+#[derive(Clone, Copy)]
+struct Target {
+    /// Whether the handler is in the table of prefix commands
+    prefixed: bool,
+    /// Index of the handler in its table
+    index: usize,
+    /// Form of the command that returned [`AtResponse::Continue`]
+    form: Form,
+    /// Payload collected for [`AtContext::payload`], if the input is not
+    /// handed to [`AtContext::input`] byte by byte
+    payload: Option<Payload>,
+    /// Number of payload bytes received
+    received: usize,
+    /// Whether the payload collected did not fit in its buffer
+    overflow: bool,
+}
+
+/// Tell the observer about the result of a command.
+fn observe_response<const SIZE: usize, E>(observer: &mut Option<&mut (dyn AtObserver<SIZE, E> + '_)>, result: &AtResult<'_, SIZE, E>) {
+    if let Some(observer) = observer.as_deref_mut() {
+        observer.on_response(result);
+    }
+}
+
+/// Tell the observer that the line ended with the final result code of
+/// `result`, sent for the command `name`.
+fn observe_result_code<const SIZE: usize, E: UserError>(observer: &mut Option<&mut (dyn AtObserver<SIZE, E> + '_)>, name: &str, result: &AtResult<'_, SIZE, E>) {
+    if let Some(observer) = observer.as_deref_mut()
+        && let Some(code) = result_code(result) {
+        observer.on_result_code(name, code);
+    }
+}
+
+/// Final result code of `result`, `None` while the command has not ended.
+fn result_code<const SIZE: usize, E: UserError>(result: &AtResult<'_, SIZE, E>) -> Option<Result<ResultCode, ErrorKind>> {
+    match result {
+        Ok((_, AtResponse::Continue | AtResponse::Deferred)) => None,
+        Ok((_, AtResponse::FinalCode(code))) => Some(Ok(*code)),
+        Ok(_) => Some(Ok(ResultCode::Ok)),
+        Err((_, error)) => Some(Err(ErrorKind::reported(error))),
+    }
+}
+
+/// Tell the observer that `raw` failed to parse, widening the error for the
+/// caller.
+fn parse_error<'r, const SIZE: usize, E>(observer: &mut Option<&mut (dyn AtObserver<SIZE, E> + '_)>, error: AtError<'r>, raw: &str) -> (&'static str, AtError<'r, E>) {
+    if let Some(observer) = observer.as_deref_mut() {
+        observer.on_parse_error(&error, raw);
+        observer.on_result_code("", Err(ErrorKind::reported(&error)));
+    }
+    ("", error.widen())
+}
+
+/// Text of the command line `bytes` without the noise around it: bytes that
+/// are not printable ASCII. `Err` with the offset of the first byte that is
+/// not valid UTF-8 in between.
+fn command_text(bytes: &[u8]) -> Result<&str, usize> {
+    let start = bytes.iter().position(u8::is_ascii_graphic).unwrap_or(bytes.len());
+    let end = bytes.iter().rposition(u8::is_ascii_graphic).map_or(start, |last| last + 1);
+    core::str::from_utf8(&bytes[start..end]).map_err(|e| start + e.valid_up_to())
+}
+
+/// Whether the raw arguments `args` hold more than `limit` parameters,
+/// counting no further than the limit.
+fn too_many_args(args: &Args, limit: usize) -> bool {
+    args.param(limit).is_some()
+}
+
+/// Forget the diagnostic of the previous line.
+fn clear_diagnostic(diagnostic: &mut Option<&mut Option<Diagnostic>>) {
+    if let Some(diagnostic) = diagnostic.as_deref_mut() {
+        *diagnostic = None;
+    }
+}
+
+/// Record the diagnostic of the command at `locus` of `line` when it failed
+/// with `result`.
+fn diagnose<const SIZE: usize, E>(
+    diagnostic: &mut Option<&mut Option<Diagnostic>>,
+    line: &str,
+    (name, args): (&str, Option<&str>),
+    result: &AtResult<'_, SIZE, E>,
+    first: bool,
+) {
+    if let (Some(diagnostic), Err((_, error))) = (diagnostic.as_deref_mut(), result) {
+        *diagnostic = Diagnostic::of(line, name, args, error, first);
+    }
+}
+
+/// Whether `result` asks for more input.
+fn is_continue<const SIZE: usize, E>(result: &AtResult<'_, SIZE, E>) -> bool {
+    matches!(result, Ok((_, AtResponse::Continue)))
+}
+
+/// Where a command of a line is served.
+enum Route<'b, const SIZE: usize, E> {
+    /// Served by the parser itself (S-registers, built-ins) or unknown
+    Done(AtResult<'b, SIZE, E>),
+    /// Served by the handler at this index of the command table
+    Handler(usize, AtForm<'b>),
+}
+
+/// Writer of `output` handed to a responder.
+fn writer<'w>(output: &'w mut Option<Transmission<'_>>) -> Option<&'w mut (dyn Write + 'w)> {
+    output.as_mut().map(|output| output as &mut dyn Write)
+}
+
+/// Responder handed to handlers: connected to `output` only when framing is
+/// enabled.
+fn responder<'r, S>(buffer: &'r mut [u8], output: Option<&'r mut (dyn Write + '_)>, framed: bool, framing: Framing, state: Option<&'r mut S>) -> Responder<'r, S> {
+    let output = output.filter(|_| framed).map(|output| output as &mut dyn Write);
+    Responder::from_parts(buffer, output, framing, state)
+}
+
+/// Use the text `written` to a responder as the information text of a
+/// result that carries none, borrowing it from the response buffer.
+fn with_written<'r, const SIZE: usize, E>(result: AtResult<'r, SIZE, E>, written: &'r str) -> AtResult<'r, SIZE, E> {
+    match result {
+        Ok((at_response, AtResponse::Ok | AtResponse::InfoText(_))) if !written.is_empty() && !result_has_text(&result) => {
+            Ok((at_response, AtResponse::Text(written)))
+        }
+        result => result,
+    }
+}
+
+/// Text written to the first `len` bytes of the response `buffer`.
+fn written(buffer: &[u8], len: usize) -> &str {
+    core::str::from_utf8(&buffer[..len]).unwrap_or("")
+}
+
+/// Whether a successful `result` carries information text.
+fn result_has_text<const SIZE: usize, E>(result: &AtResult<'_, SIZE, E>) -> bool {
+    matches!(result, Ok((_, response)) if response.text().is_some())
+}
+
+/// Basic commands served by the parser when no handler is registered for them.
+enum Builtin {
+    /// `ATE` — command echo
+    Echo,
+    /// `ATV` — verbose or numeric result codes
+    Verbose,
+    /// `ATQ` — result code suppression
+    Quiet,
+    /// `AT+CMEE` — mobile equipment error reporting
+    Cmee,
+    /// `AT&W` — save the stored profile, with a [`SettingsStore`]
+    Save,
+    /// `ATZ` — restore the stored profile, with a [`SettingsStore`]
+    Reset,
+    /// `AT&F` — restore the factory defaults, with a [`SettingsStore`]
+    Factory,
+    /// `AT&V` — view the active settings
+    View,
+}
+
+impl Builtin {
+    /// Built-in command named `name`, if any.
+    fn find(name: &str, case_insensitive: bool) -> Option<Self> {
+        if names_match("E", name, case_insensitive) {
+            Some(Builtin::Echo)
+        } else if names_match("V", name, case_insensitive) {
+            Some(Builtin::Verbose)
+        } else if names_match("Q", name, case_insensitive) {
+            Some(Builtin::Quiet)
+        } else if names_match("+CMEE", name, case_insensitive) {
+            Some(Builtin::Cmee)
+        } else if names_match("&W", name, case_insensitive) {
+            Some(Builtin::Save)
+        } else if names_match("Z", name, case_insensitive) {
+            Some(Builtin::Reset)
+        } else if names_match("&F", name, case_insensitive) {
+            Some(Builtin::Factory)
+        } else if names_match("&V", name, case_insensitive) {
+            Some(Builtin::View)
+        } else {
+            None
+        }
+    }
+
+    /// Whether the command is only served with a [`SettingsStore`].
+    fn is_profile(&self) -> bool {
+        matches!(self, Builtin::Save | Builtin::Reset | Builtin::Factory)
+    }
+}
+
+/// Serve a built-in on/off basic command such as `ATE`, `ATV` or `ATQ`.
+///
+/// A missing value means `0`, as specified by V.250.
+fn flag<const SIZE: usize, E>(value: &mut bool, form: AtForm<'_>) -> AtResult<'static, SIZE, E> {
+    let mut response = Bytes::<SIZE>::new();
+
+    match form {
+        AtForm::Exec => *value = false,
+        AtForm::Set(args) => match args.raw() {
+            "" | "0" => *value = false,
+            "1" => *value = true,
+            _ => return Err(("", AtError::InvalidArgs)),
+        },
+        AtForm::Query => {
+            response.format(format_args!("{}", u8::from(*value)));
+            return Ok(("", response.into()));
+        }
+        AtForm::Test => {
+            response.format(format_args!("(0,1)"));
+            return Ok(("", response.into()));
+        }
+    }
+
+    Ok(("", AtResponse::Ok))
+}
+
+/// Serve the built-in `AT&W`, `ATZ` and `AT&F` commands.
+///
+/// Only profile `0` exists; `ATZ` falls back to the factory defaults when
+/// no valid profile is stored.
+fn profile<const SIZE: usize, E>(
+    builtin: Builtin,
+    mut settings: Settings<'_>,
+    store: &mut dyn SettingsStore,
+    form: AtForm<'_>,
+) -> AtResult<'static, SIZE, E> {
+    match form {
+        AtForm::Exec => {}
+        AtForm::Set(args) if args.raw().trim() == "0" => {}
+        _ => return Err(("", AtError::InvalidArgs)),
+    }
+
+    match builtin {
+        Builtin::Save => settings.save(store).map_err(|e| ("", e.widen()))?,
+        Builtin::Reset => {
+            if !settings.load(store) {
+                settings.factory(store);
+            }
+        }
+        _ => settings.factory(store),
+    }
+    Ok(("", AtResponse::Ok))
+}
+
+/// Serve the built-in `AT&V` command, listing the settings of the parser
+/// and then `views`.
+///
+/// Lines that do not fit the response are dropped when the response framing
+/// layer is disabled.
+fn view<const SIZE: usize, E>(
+    settings: &Settings<'_>,
+    views: &mut [&mut dyn SettingsView],
+    mut responder: Responder<'_>,
+    form: AtForm<'_>,
+) -> AtResult<'static, SIZE, E> {
+    match form {
+        AtForm::Exec => {}
+        AtForm::Set(args) if args.raw().trim() == "0" => {}
+        _ => return Err(("", AtError::InvalidArgs)),
+    }
+
+    let ending = responder.framing().ending().as_str();
+    let mut line = |text: fmt::Arguments<'_>| {
+        if responder.is_connected() {
+            return responder.info(text);
+        }
+        if !responder.is_empty() {
+            responder.write_str(ending)?;
+        }
+        responder.write_fmt(text)
+    };
+    let mut lines = View::new(&mut line);
+    let _ = settings.view(&mut lines).and_then(|()| views.iter_mut().try_for_each(|view| view.view(&mut lines)));
+
+    into_owned(with_written(Ok(("", AtResponse::Ok)), responder.as_str()))
+}
+
+/// Serve the built-in `AT+CMEE` command.
+///
+/// `AT+CMEE=` without a value selects the default mode `0`.
+fn cmee<const SIZE: usize, E>(framing: &mut Framing, form: AtForm<'_>) -> AtResult<'static, SIZE, E> {
+    let mut response = Bytes::<SIZE>::new();
+
+    match form {
+        AtForm::Exec => framing.set_cme_mode(CmeMode::Disabled),
+        AtForm::Set(args) => {
+            let value = match args.raw().trim() {
+                "" => 0,
+                value => value.parse().map_err(|_| ("+CMEE: ", AtError::InvalidArgs))?,
+            };
+            let mode = CmeMode::from_value(value).ok_or(("+CMEE: ", AtError::InvalidArgs))?;
+            framing.set_cme_mode(mode);
+        }
+        AtForm::Query => {
+            response.format(format_args!("{}", framing.cme_mode().value()));
+            return Ok(("+CMEE: ", response.into()));
+        }
+        AtForm::Test => {
+            response.format(format_args!("(0-2)"));
+            return Ok(("+CMEE: ", response.into()));
+        }
+    }
+
+    Ok(("", AtResponse::Ok))
+}
+
+/// Serve an `ATS<n>` command from the S-register file.
+fn s_register<const SIZE: usize, E>(registers: &mut SRegisters, index: usize, form: AtForm<'_>) -> AtResult<'static, SIZE, E> {
+    let mut response = Bytes::<SIZE>::new();
+
+    match form {
+        AtForm::Query => {
+            let value = registers.get(index).ok_or(("", AtError::InvalidArgs))?;
+            response.format(format_args!("{:03}", value));
+        }
+        AtForm::Test => response.format(format_args!("(0-255)")),
+        AtForm::Set(args) => {
+            let value = args.raw().parse().map_err(|_| ("", AtError::InvalidArgs))?;
+            registers.set(index, value).map_err(|_| ("", AtError::InvalidArgs))?;
+            return Ok(("", AtResponse::Ok));
+        }
+        AtForm::Exec => return Err(("", AtError::NotSupported)),
+    }
+
+    Ok(("", response.into()))
+}
+
+/// Remove the leading `AT` prefix so that the first command of a line
+/// (`AT+CMD`) and a concatenated one (`+CMD`) resolve to the same name.
+fn strip_at(name: &str, case_insensitive: bool) -> &str {
+    match name.as_bytes().get(..2) {
+        Some(b"AT") => &name[2..],
+        Some(prefix) if case_insensitive && prefix.eq_ignore_ascii_case(b"AT") => &name[2..],
+        _ => name,
+    }
+}
+
+/// Split an extended command name (`+CMD`, `#CMD`) into its prefix character
+/// and the rest, when the prefix is one of `prefixes`.
+fn split_prefix<'n>(name: &'n str, prefixes: &str) -> Option<(char, &'n str)> {
+    let prefix = name.chars().next()?;
+    prefixes.contains(prefix).then(|| (prefix, &name[prefix.len_utf8()..]))
+}
+
+/// Order of the commands of a table registered with
+/// [`set_sorted_commands`](AtParser::set_sorted_commands).
+///
+/// Names are compared without their `AT` prefix, ignoring ASCII case, then
+/// byte by byte, so that the same order serves case-sensitive and
+/// case-insensitive matching.
+pub const fn command_order(a: &str, b: &str) -> Ordering {
+    let (a, b) = (strip_at_prefix(a.as_bytes()), strip_at_prefix(b.as_bytes()));
+    match compare_names(a, b, true) {
+        Ordering::Equal => compare_names(a, b, false),
+        order => order,
+    }
+}
+
+/// Whether `names` are strictly ordered by [`command_order`].
+///
+/// Usable in constant expressions, as done by
+/// [`at_sorted_modules!`](crate::at_sorted_modules).
+pub const fn is_sorted(names: &[&str]) -> bool {
+    let mut i = 1;
+    while i < names.len() {
+        if !command_order(names[i - 1], names[i]).is_lt() {
+            return false;
+        }
+        i += 1;
+    }
+    true
+}
+
+/// Sort a command table built at run time by [`command_order`], for
+/// [`set_sorted_commands`](AtParser::set_sorted_commands).
+pub fn sort_commands<T: ?Sized>(commands: &mut [(&'static str, &'static str, &mut T)]) {
+    commands.sort_unstable_by(|a, b| command_order(a.0, b.0));
+}
+
+/// Number of entries of `names` naming the same command as `name`: equal
+/// without their `AT` prefix, ignoring ASCII case, so that neither
+/// case-sensitive nor case-insensitive matching can tell them apart.
+///
+/// Usable in constant expressions, as done by [`at_modules!`](crate::at_modules)
+/// to reject a command registered twice.
+pub const fn count_command(names: &[&str], name: &str) -> usize {
+    let name = strip_at_prefix(name.as_bytes());
+    let (mut i, mut count) = (0, 0);
+    while i < names.len() {
+        if compare_names(strip_at_prefix(names[i].as_bytes()), name, true).is_eq() {
+            count += 1;
+        }
+        i += 1;
+    }
+    count
+}
+
+/// Debug-assert that no command of a table is registered twice, as the
+/// second one would never be dispatched.
+pub(crate) fn debug_assert_unique<T: ?Sized>(commands: &[(&'static str, &'static str, &mut T)]) {
+    if cfg!(debug_assertions) {
+        for (i, (name, _, _)) in commands.iter().enumerate() {
+            let name = strip_at_prefix(name.as_bytes());
+            assert!(
+                !commands[..i].iter().any(|(other, _, _)| compare_names(strip_at_prefix(other.as_bytes()), name, true).is_eq()),
+                "command {} is registered more than once",
+                commands[i].0
+            );
+        }
+    }
+}
+
+/// Error of [`AtParser::set_interned_commands`]: the identifier buffer is
+/// shorter than the command table.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TooFewIds;
+
+/// Error of [`AtParser::set_split_commands`]: the names do not list those of
+/// the command table in order.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NamesMismatch;
+
+/// Identifier of a command name, as interned by
+/// [`AtParser::set_interned_commands`].
+///
+/// A 32-bit FNV-1a hash of the name without its `AT` prefix, folded to upper
+/// case, so that the spellings a case-insensitive parser matches share one
+/// identifier. Distinct names may collide: the parser still compares the
+/// names of the entries whose identifier matches.
+pub const fn command_id(name: &str) -> u32 {
+    fold_id(strip_at_prefix(name.as_bytes()))
+}
+
+/// [`command_id`] of a name already stripped of its `AT` prefix.
+const fn fold_id(name: &[u8]) -> u32 {
+    let mut id = 0x811c_9dc5_u32;
+    let mut i = 0;
+    while i < name.len() {
+        id ^= name[i].to_ascii_uppercase() as u32;
+        id = id.wrapping_mul(0x0100_0193);
+        i += 1;
+    }
+    id
+}
+
+/// [`strip_at`] usable in constant expressions, ignoring case.
+pub(crate) const fn strip_at_prefix(name: &[u8]) -> &[u8] {
+    if name.len() >= 2 && name[0].eq_ignore_ascii_case(&b'A') && name[1].eq_ignore_ascii_case(&b'T') {
+        name.split_at(2).1
+    } else {
+        name
+    }
+}
+
+/// Compare two command names byte by byte, folding ASCII case when
+/// `ignore_case` is set.
+pub(crate) const fn compare_names(a: &[u8], b: &[u8], ignore_case: bool) -> Ordering {
+    let mut i = 0;
+    while i < a.len() && i < b.len() {
+        let (x, y) = if ignore_case { (a[i].to_ascii_uppercase(), b[i].to_ascii_uppercase()) } else { (a[i], b[i]) };
+        if x != y {
+            return if x < y { Ordering::Less } else { Ordering::Greater };
+        }
+        i += 1;
+    }
+    if a.len() < b.len() {
+        Ordering::Less
+    } else if a.len() > b.len() {
+        Ordering::Greater
+    } else {
+        Ordering::Equal
+    }
+}
+
+/// Position of a registered command name relative to a parsed one in a table
+/// sorted by [`command_order`]; case-insensitive matching stops at the folded
+/// comparison, so that any spelling of the name is found.
+fn search_order(registered: &str, name: &str, case_insensitive: bool) -> Ordering {
+    match compare_names(registered.as_bytes(), name.as_bytes(), true) {
+        Ordering::Equal if !case_insensitive => compare_names(registered.as_bytes(), name.as_bytes(), false),
+        order => order,
+    }
+}
+
+/// Compare a registered command name with a parsed one.
+fn names_match(registered: &str, name: &str, case_insensitive: bool) -> bool {
+    if case_insensitive {
+        registered.eq_ignore_ascii_case(name)
+    } else {
+        registered == name
+    }
+}
+
+/// A command split off a command line.
+enum Step<'a> {
+    /// Command looked up by name, with its form
+    Command(&'a str, AtForm<'a>),
+    /// Prefix command at the given index of the prefix table, with the
+    /// remainder of the line as raw arguments
+    Prefix(usize, &'a str),
+}
+
+impl<'a> Step<'a> {
+    /// Name and raw arguments of the command, for its diagnostic.
+    ///
+    /// Prefix commands are located by their arguments.
+    fn locus(&self) -> (&'a str, Option<&'a str>) {
+        match self {
+            Step::Command(name, AtForm::Set(args)) => (name, Some(args.raw())),
+            Step::Command(name, _) => (name, None),
+            Step::Prefix(_, args) => (args, Some(args)),
+        }
+    }
+}
+
+/// Splits one command line into its commands.
+///
+/// A line starting with `AT` follows the V.250 grammar: a sequence of basic
+/// commands (`E0`, `V1`, `&F`) with no separator, and extended commands
+/// (`+CMD=...`) that extend up to the next `;`. Lines without the `AT` prefix
+/// are split on `;` only and every piece is parsed as an extended command.
+///
+/// Yielded names do not include the `AT` prefix (`""` for a bare `AT`).
+/// At basic command positions, prefix commands (`D5551234;`) take the rest of
+/// the line.
+struct CommandLine<'a> {
+    /// Part of the line not parsed yet
+    rest: &'a str,
+    /// Whether basic commands are recognized (the line starts with `AT`)
+    basic: bool,
+    /// Whether a bare `AT` must still be yielded
+    bare: bool,
+    /// Whether text after `?` / `=?` fails the command
+    strict: bool,
+}
+
+impl<'a> CommandLine<'a> {
+    fn new(line: &'a str, case_insensitive: bool, strict: bool) -> Self {
+        let line = line.trim();
+        let rest = strip_at(line, case_insensitive);
+
+        if rest.len() < line.len() {
+            Self { rest, basic: true, bare: rest.trim().is_empty(), strict }
+        } else {
+            Self { rest: line, basic: false, bare: false, strict }
+        }
+    }
+}
+
+impl<'a> CommandLine<'a> {
+    /// Split off the next command.
+    ///
+    /// `prefix` is consulted at basic command positions and returns the index
+    /// and length of the prefix command the text starts with, if any.
+    fn next_command<F>(&mut self, prefix: F) -> Option<Result<Step<'a>, AtError<'a>>>
+    where
+        F: Fn(&str) -> Option<(usize, usize)> {
+        loop {
+            let rest = self.rest.trim_start();
+
+            if rest.is_empty() {
+                let bare = self.bare;
+                self.bare = false;
+                return bare.then_some(Ok(Step::Command("", AtForm::Exec)));
+            }
+
+            if let Some(rest) = rest.strip_prefix(';') {
+                self.rest = rest;
+                continue;
+            }
+
+            if self.basic && let Some((index, len)) = prefix(rest) {
+                self.rest = "";
+                return Some(Ok(Step::Prefix(index, &rest[len..])));
+            }
+
+            let first = rest.as_bytes()[0];
+            if self.basic && (first.is_ascii_alphabetic() || first == b'&') {
+                let (name, form, rest) = parse_basic(rest);
+                self.rest = rest;
+                if !served(&form) {
+                    return Some(Err(AtError::NotSupported));
+                }
+                return Some(Ok(Step::Command(name, form)));
+            }
+
+            let (command, rest) = split_command(rest);
+            self.rest = rest.unwrap_or("");
+            if self.strict && has_trailing_text(command) {
+                return Some(Err(AtError::InvalidArgs));
+            }
+            let (name, form) = parse(command);
+            if !served(&form) {
+                return Some(Err(AtError::NotSupported));
+            }
+            return Some(Ok(Step::Command(name, form)));
+        }
+    }
+}
+
+/// Whether `form` is served by this build: the `exec-set-only` feature
+/// leaves the query and test forms out.
+const fn served(form: &AtForm<'_>) -> bool {
+    !cfg!(feature = "exec-set-only") || matches!(form, AtForm::Exec | AtForm::Set(_))
+}
+
+/// Parse the basic command at the start of `input`.
+///
+/// A basic command is a single letter, or `&` followed by a letter, with an
+/// optional numeric parameter (`E0`, `V1`, `&F`, `I`). The parameter is
+/// passed as [`AtForm::Set`] arguments; `?` and `=?` select the query and test
+/// forms. For S-registers the register number belongs to the name (`S3=13`
+/// is named `S3` with argument `13`).
+///
+/// # Returns
+///
+/// `(name, form, rest)` where `rest` is the part of `input` after the command.
+fn parse_basic(input: &str) -> (&str, AtForm<'_>, &str) {
+    let bytes = input.as_bytes();
+    let len = match bytes[0] {
+        b'&' if bytes.get(1).is_some_and(u8::is_ascii_alphabetic) => 2,
+        // S-register number is part of the name: S3=13, S7?
+        b'S' | b's' => 1 + bytes[1..].iter().take_while(|b| b.is_ascii_digit()).count(),
+        _ => 1,
+    };
+    let (name, rest) = input.split_at(len);
+
+    if let Some(rest) = rest.strip_prefix("=?") {
+        return (name, AtForm::Test, rest);
+    }
+    if let Some(rest) = rest.strip_prefix('?') {
+        return (name, AtForm::Query, rest);
+    }
+
+    let (assigned, rest) = match rest.strip_prefix('=') {
+        Some(rest) => (true, rest),
+        None => (false, rest),
+    };
+    let digits = rest.bytes().take_while(u8::is_ascii_digit).count();
+    let (value, rest) = rest.split_at(digits);
+
+    if assigned || digits > 0 {
+        (name, AtForm::Set(Args::new(value)), rest)
+    } else {
+        (name, AtForm::Exec, rest)
+    }
+}
+
+/// Split the first command off a `;`-concatenated command line.
+///
+/// Returns the first command (trimmed) and the remainder after the `;`, if
+/// any. Semicolons inside double-quoted strings are not separators.
+fn split_command(line: &str) -> (&str, Option<&str>) {
+    let mut in_quotes = false;
+    let mut escaped = false;
+
+    for (offset, ch) in line.char_indices() {
+        if escaped {
+            escaped = false;
+            continue;
+        }
+
+        match ch {
+            '\\' if in_quotes => escaped = true,
+            '"' => in_quotes = !in_quotes,
+            ';' if !in_quotes => return (line[..offset].trim(), Some(&line[offset + 1..])),
+            _ => {}
+        }
+    }
+
+    (line.trim(), None)
+}
+
+/// Whether the extended command `input` holds text after the `?` of a
+/// query or the `=?` of a test form.
+fn has_trailing_text(input: &str) -> bool {
+    let (name, args) = match input.split_once('=') {
+        Some((name, args)) => (name, Some(args)),
+        None => (input, None),
+    };
+    name.find('?').is_some_and(|query| query + 1 < input.len())
+        || args.is_some_and(|args| args.len() > 1 && args.starts_with('?'))
+}
+
+/// Parse a single extended command into its name, form and arguments.
+///
+/// `input` is one trimmed command such as `AT+CSQ?` or `+CWJAP="home",1`,
+/// without a `;` separator; the name keeps the `AT` prefix when present.
+///
+/// | Suffix | Form | Arguments |
+/// |---|---|---|
+/// | `=?` | [`Form::Test`] | empty |
+/// | `?` | [`Form::Query`] | empty |
+/// | `=<args>` | [`Form::Set`] | the text after the first `=` |
+/// | *(none)* | [`Form::Exec`] | empty |
+///
+/// This is the grammar [`AtParser::execute`] applies to extended commands.
+/// It is a `const fn` over the bytes of `input`, with no allocation and no
+/// reachable panic, so command grammars can be checked and test tables
+/// built at compile time.
+///
+/// # Example
+///
+/// ```rust
+/// use at_parser_rs::context::Form;
+/// use at_parser_rs::parser::parse_command;
+///
+/// const CWJAP: (&str, Form, at_parser_rs::Args<'_>) = parse_command("AT+CWJAP=\"home\",1");
+/// const _: () = assert!(matches!(CWJAP.1, Form::Set));
+/// assert_eq!(CWJAP.0, "AT+CWJAP");
+/// assert_eq!(CWJAP.2.raw(), "\"home\",1");
+/// assert_eq!(parse_command("AT+CSQ=?").1, Form::Test);
+/// ```
+pub const fn parse_command(input: &str) -> (&str, Form, Args<'_>) {
+    match parse(input) {
+        (name, AtForm::Set(args)) => (name, Form::Set, args),
+        (name, form) => (name, form.kind(), Args::new("")),
+    }
+}
+
+/// Parse an AT command string into its name and form.
+///
+/// Examines the suffix of `input` to determine which AT command form was
+/// requested, then returns the bare command name together with the
+/// detected [`AtForm`], see [`parse_command`].
+///
+/// # Arguments
+///
+/// * `input` — AT command string, already trimmed by [`split_command`]
+///
+/// # Returns
+///
+/// `(command_name, form)` where `command_name` is a slice of `input` with
+/// the suffix removed.
+const fn parse(input: &str) -> (&str, AtForm<'_>) {
+    let bytes = input.as_bytes();
+    let len = bytes.len();
+
+    // Check suffixes to determine command form
+    if len >= 2 && bytes[len - 2] == b'=' && bytes[len - 1] == b'?' {
+        return (head(input, len - 2), AtForm::Test);
+    }
+    if len >= 1 && bytes[len - 1] == b'?' {
+        return (head(input, len - 1), AtForm::Query);
+    }
+
+    let mut i = 0;
+    while i < len {
+        if bytes[i] == b'=' {
+            return match input.split_at_checked(i + 1) {
+                Some((assigned, args)) => (head(assigned, i), AtForm::Set(Args::new(args))),
+                None => (input, AtForm::Exec),
+            };
+        }
+        i += 1;
+    }
+    (input, AtForm::Exec)
+}
+
+/// First `mid` bytes of `input`, which end on an ASCII byte.
+const fn head(input: &str, mid: usize) -> &str {
+    match input.split_at_checked(mid) {
+        Some((head, _)) => head,
+        None => input,
+    }
+}
+
+#[cfg(test)]
+// Some fixtures serve only the tests left out of the reduced builds
+#[cfg_attr(any(feature = "exec-set-only", feature = "basic-errors", feature = "no-error-text"), allow(unused_imports, dead_code))]
+mod tests {
+    use alloc::format;
+    use alloc::string::String;
+    use alloc::vec::Vec;
+    use core::cell::Cell;
+    use core::fmt::Write;
+
+    use super::{AtParser, DynAtParser, NamesMismatch, TooFewIds, command_id, command_order, count_command, is_sorted, parse_command, sort_commands, split_command};
+    use crate::abort::Abort;
+    use crate::context::{AtContext, AtFallback, AtObserver, Form};
+    use crate::diagnostic::{Diagnostic, Reason};
+    use crate::line::LineEnding;
+    use crate::mode::Mode;
+    use crate::online::DataSink;
+    use crate::response::{CallProgress, ErrorKind, Responder, ResultCode};
+    use crate::{Args, AtError, AtResponse, AtResult, UserError, at_response};
+
+    const SIZE: usize = 64;
+
+    struct Value {
+        value: u32,
+    }
+
+    impl AtContext<SIZE> for Value {
+        fn exec(&mut self, at_response: &'static str) -> AtResult<'_, SIZE> {
+            Ok(at_response!(SIZE, at_response; "executed"))
+        }
+
+        fn query(&mut self, at_response: &'static str) -> AtResult<'_, SIZE> {
+            Ok(at_response!(SIZE, at_response; self.value))
+        }
+
+        fn set(&mut self, at_response: &'static str, args: Args) -> AtResult<'_, SIZE> {
+            let value = args.get(0).ok_or((at_response, AtError::InvalidArgs))?;
+            self.value = value.parse().map_err(|_| (at_response, AtError::InvalidArgs))?;
+            Ok(at_response!(SIZE, at_response; "OK"))
+        }
+    }
+
+    fn ok(result: Option<AtResult<'_, SIZE>>) -> String {
+        match result {
+            Some(Ok((prefix, bytes))) => format!("{}{}", prefix, bytes),
+            Some(Err((_, e))) => panic!("unexpected error: {:?}", e),
+            None => panic!("no response"),
+        }
+    }
+
+    #[cfg(not(feature = "exec-set-only"))]
+    #[test]
+    fn feed_dispatches_on_terminator() {
+        let mut value = Value { value: 7 };
+        let commands: &mut [(&str, &str, &mut Value)] = &mut [("AT+VAL", "+VAL: ", &mut value)];
+        let mut parser: AtParser<Value, SIZE> = AtParser::new();
+        parser.set_commands(commands);
+
+        for &byte in b"AT+VAL?" {
+            assert!(parser.feed(byte).is_none());
+        }
+        assert_eq!(ok(parser.feed(b'\r')), "+VAL: 7");
+        // the LF of a CR+LF pair is an empty line
+        assert!(parser.feed(b'\n').is_none());
+    }
+
+    #[cfg(not(feature = "exec-set-only"))]
+    #[test]
+    fn execute_line_dispatches_concatenated_commands() {
+        let mut first = Value { value: 1 };
+        let mut second = Value { value: 2 };
+        let commands: &mut [(&str, &str, &mut Value)] = &mut [
+            ("AT+A", "+A: ", &mut first),
+            ("AT+B", "+B: ", &mut second),
+        ];
+        let mut parser: AtParser<Value, SIZE> = AtParser::new();
+        parser.set_commands(commands);
+
+        let mut responses = Vec::new();
+        let result = parser.execute_line("AT+A=5;+B?;+A?;", |prefix, bytes| {
+            responses.push(format!("{}{}", prefix, bytes));
+        });
+        assert!(result.is_ok());
+        assert_eq!(responses, ["+A: OK", "+B: 2", "+A: 5"]);
+    }
+
+    #[cfg(not(feature = "exec-set-only"))]
+    #[test]
+    fn execute_line_stops_at_first_error() {
+        let mut first = Value { value: 1 };
+        let commands: &mut [(&str, &str, &mut Value)] = &mut [("AT+A", "+A: ", &mut first)];
+        let mut parser: AtParser<Value, SIZE> = AtParser::new();
+        parser.set_commands(commands);
+
+        let mut responses = Vec::new();
+        let result = parser.execute_line("AT+A?;+A=x;+A=9", |prefix, bytes| {
+            responses.push(format!("{}{}", prefix, bytes));
+        });
+        assert!(matches!(result, Err(("+A: ", AtError::InvalidArgs))));
+        assert_eq!(responses, ["+A: 1"]);
+        assert_eq!(ok(Some(parser.execute("AT+A?"))), "+A: 1");
+    }
+
+    #[cfg(not(feature = "exec-set-only"))]
+    #[test]
+    fn basic_commands_are_dispatched() {
+        let mut echo = Value { value: 1 };
+        let mut verbose = Value { value: 1 };
+        let mut factory = Value { value: 0 };
+        let mut extended = Value { value: 9 };
+        let commands: &mut [(&str, &str, &mut Value)] = &mut [
+            ("ATE", "", &mut echo),
+            ("ATV", "", &mut verbose),
+            ("AT&F", "", &mut factory),
+            ("AT+A", "+A: ", &mut extended),
+        ];
+        let mut parser: AtParser<Value, SIZE> = AtParser::new();
+        parser.set_commands(commands);
+
+        let mut responses = Vec::new();
+        let result = parser.execute_line("ATE0V0&F+A?", |prefix, bytes| {
+            responses.push(format!("{}{}", prefix, bytes));
+        });
+        assert!(result.is_ok());
+        assert_eq!(responses, ["OK", "OK", "executed", "+A: 9"]);
+        assert_eq!(ok(Some(parser.execute("ATE?"))), "0");
+        assert_eq!(ok(Some(parser.execute("ATV1E?"))), "0");
+        assert_eq!(ok(Some(parser.execute("ATV?"))), "1");
+    }
+
+    #[cfg(not(feature = "exec-set-only"))]
+    #[test]
+    fn s_registers_are_served_and_drive_the_line() {
+        let mut value = Value { value: 5 };
+        let commands: &mut [(&str, &str, &mut Value)] = &mut [("AT+VAL", "+VAL: ", &mut value)];
+        let mut parser: AtParser<Value, SIZE> = AtParser::new();
+        parser.set_commands(commands);
+        parser.enable_s_registers();
+
+        assert_eq!(ok(Some(parser.execute("ATS7=60"))), "");
+        assert_eq!(ok(Some(parser.execute("ATS7?"))), "060");
+        assert_eq!(ok(Some(parser.execute("ATS3?"))), "013");
+        assert!(matches!(parser.execute("ATS3=65"), Err(("", AtError::InvalidArgs))));
+        assert!(matches!(parser.execute("ATS99=1"), Err(("", AtError::InvalidArgs))));
+
+        assert_eq!(ok(Some(parser.execute("ATS3=10"))), "");
+        assert_eq!(parser.response_ending(), LineEnding::Lf);
+        let (_, result) = parser.feed_slice(b"AT+VAL?\r\n");
+        assert_eq!(ok(result), "+VAL: 5");
+    }
+
+    #[cfg(not(feature = "exec-set-only"))]
+    #[test]
+    fn case_insensitive_matching_is_opt_in() {
+        let mut value = Value { value: 8 };
+        let mut echo = Value { value: 1 };
+        let commands: &mut [(&str, &str, &mut Value)] = &mut [
+            ("AT+GMR", "+GMR: ", &mut value),
+            ("ATE", "", &mut echo),
+        ];
+        let mut parser: AtParser<Value, SIZE> = AtParser::new();
+        parser.set_commands(commands);
+
+        assert!(matches!(parser.execute("at+gmr?"), Err(("", AtError::UnknownCommand))));
+
+        parser.set_case_insensitive(true);
+        assert_eq!(ok(Some(parser.execute("at+gmr?"))), "+GMR: 8");
+        assert_eq!(ok(Some(parser.execute("AT+GmR?"))), "+GMR: 8");
+        assert_eq!(ok(Some(parser.execute("ate0;+gmr?"))), "+GMR: 8");
+        assert_eq!(ok(Some(parser.execute("AtE?"))), "0");
+    }
+
+    #[cfg(not(feature = "exec-set-only"))]
+    #[test]
+    fn prefix_commands_take_the_rest_of_the_line() {
+        let mut dial = Value { value: 0 };
+        let mut echo = Value { value: 1 };
+        let prefixes: &mut [(&str, &str, &mut Value)] = &mut [("ATD", "", &mut dial)];
+        let commands: &mut [(&str, &str, &mut Value)] = &mut [("ATE", "", &mut echo)];
+        let mut parser: AtParser<Value, SIZE> = AtParser::new();
+        parser.set_commands(commands);
+        parser.set_prefix_commands(prefixes);
+
+        // the dial string is not a number, so Value::set rejects it...
+        assert!(matches!(parser.execute("ATE0D5551234;"), Err(("", AtError::InvalidArgs))));
+        assert_eq!(ok(Some(parser.execute("ATE?"))), "0");
+        // ...while plain digits are accepted and an empty dial string calls exec
+        assert_eq!(ok(Some(parser.execute("ATD42"))), "OK");
+        assert_eq!(ok(Some(parser.execute("ATD"))), "executed");
+    }
+
+    #[cfg(not(feature = "exec-set-only"))]
+    #[test]
+    fn fallback_receives_lines_with_unknown_commands() {
+        struct Passthrough {
+            lines: Vec<String>,
+        }
+
+        impl AtFallback<SIZE> for Passthrough {
+            fn unknown(&mut self, line: &str) -> AtResult<'_, SIZE> {
+                self.lines.push(String::from(line));
+                Ok(at_response!(SIZE, ""; "forwarded"))
+            }
+        }
+
+        let mut value = Value { value: 1 };
+        let mut passthrough = Passthrough { lines: Vec::new() };
+        let commands: &mut [(&str, &str, &mut Value)] = &mut [("AT+VAL", "+VAL: ", &mut value)];
+        let mut parser: AtParser<Value, SIZE> = AtParser::new();
+        parser.set_commands(commands);
+        parser.set_fallback(&mut passthrough);
+
+        assert_eq!(ok(Some(parser.execute(" AT+VAL=2;+OTHER? "))), "forwarded");
+        assert_eq!(ok(Some(parser.execute("AT+VAL?"))), "+VAL: 1");
+        parser.clear_fallback();
+        assert!(matches!(parser.execute("AT+OTHER"), Err(("", AtError::UnknownCommand))));
+
+        assert_eq!(passthrough.lines, ["AT+VAL=2;+OTHER?"]);
+    }
+
+    #[cfg(not(feature = "exec-set-only"))]
+    #[test]
+    fn builtin_echo_writes_received_characters() {
+        let mut value = Value { value: 2 };
+        let mut out = String::new();
+        let commands: &mut [(&str, &str, &mut Value)] = &mut [("AT+VAL", "+VAL: ", &mut value)];
+        let mut parser: AtParser<Value, SIZE> = AtParser::new();
+        parser.set_commands(commands);
+        parser.set_output(&mut out);
+
+        let (_, result) = parser.feed_slice(b"ATE1\r");
+        assert_eq!(ok(result), "");
+        assert!(parser.echo());
+        let (_, result) = parser.feed_slice(b"AT+VAL?\r");
+        assert_eq!(ok(result), "+VAL: 2");
+        assert_eq!(ok(Some(parser.execute("ATE?"))), "1");
+        let (_, result) = parser.feed_slice(b"ATE0\r");
+        assert_eq!(ok(result), "");
+        let (_, result) = parser.feed_slice(b"AT+VAL?\r");
+        assert_eq!(ok(result), "+VAL: 2");
+
+        assert_eq!(out, "AT+VAL?\rATE0\r");
+    }
+
+    struct Namespaced {
+        prefix: char,
+    }
+
+    impl AtContext<SIZE> for Namespaced {
+        fn prefix(&mut self, prefix: char) {
+            self.prefix = prefix;
+        }
+
+        fn exec(&mut self, at_response: &'static str) -> AtResult<'_, SIZE> {
+            Ok(at_response!(SIZE, at_response; self.prefix))
+        }
+    }
+
+    #[test]
+    fn extended_prefixes_share_one_registration() {
+        let mut power = Namespaced { prefix: ' ' };
+        let mut exact = Namespaced { prefix: ' ' };
+        let commands: &mut [(&str, &str, &mut Namespaced)] = &mut [
+            ("AT+PWR", "PWR: ", &mut power),
+            ("AT%PWR", "VENDOR: ", &mut exact),
+        ];
+        let mut parser: AtParser<Namespaced, SIZE> = AtParser::new();
+        parser.set_commands(commands);
+
+        assert_eq!(ok(Some(parser.execute("AT+PWR"))), "PWR: +");
+        assert!(matches!(parser.execute("AT#PWR"), Err((_, AtError::UnknownCommand))));
+
+        parser.set_extended_prefixes("+#$%");
+        assert_eq!(parser.extended_prefixes(), "+#$%");
+        assert_eq!(ok(Some(parser.execute("AT#PWR"))), "PWR: #");
+        assert_eq!(ok(Some(parser.execute("AT$PWR"))), "PWR: $");
+        assert_eq!(ok(Some(parser.execute("AT%PWR"))), "VENDOR: %");
+        assert!(matches!(parser.execute("AT*PWR"), Err((_, AtError::UnknownCommand))));
+    }
+
+    #[cfg(not(feature = "exec-set-only"))]
+    #[test]
+    fn framing_writes_fed_results_to_output() {
+        let mut value = Value { value: 2 };
+        let mut out = String::new();
+        let commands: &mut [(&str, &str, &mut Value)] = &mut [("AT+VAL", "+VAL: ", &mut value)];
+        let mut parser: AtParser<Value, SIZE> = AtParser::new();
+        parser.set_commands(commands);
+        parser.set_output(&mut out);
+
+        parser.feed_slice(b"AT+VAL?\r");
+        parser.set_response_framing(true);
+        assert!(parser.response_framing());
+        let (_, result) = parser.feed_slice(b"AT+VAL?\r");
+        assert_eq!(ok(result), "+VAL: 2");
+        parser.feed_slice(b"AT+VAL=1\r");
+        parser.feed_slice(b"AT+NOPE\r");
+
+        assert_eq!(out, "\r\n+VAL: 2\r\n\r\nOK\r\n\r\n+VAL: OK\r\n\r\nOK\r\n\r\nERROR\r\n");
+    }
+
+    #[cfg(not(feature = "exec-set-only"))]
+    #[test]
+    fn builtin_verbose_selects_numeric_result_codes() {
+        let mut value = Value { value: 2 };
+        let mut out = String::new();
+        let commands: &mut [(&str, &str, &mut Value)] = &mut [("AT+VAL", "+VAL: ", &mut value)];
+        let mut parser: AtParser<Value, SIZE> = AtParser::new();
+        parser.set_commands(commands);
+        parser.set_output(&mut out);
+        parser.set_response_framing(true);
+
+        parser.feed_slice(b"ATV0\r");
+        assert!(!parser.framing().verbose());
+        parser.feed_slice(b"AT+VAL?\r");
+        parser.feed_slice(b"AT+NOPE\r");
+        parser.feed_slice(b"ATV1\r");
+        assert_eq!(ok(Some(parser.execute("ATV?"))), "1");
+
+        assert_eq!(out, "0\r+VAL: 2\r\n0\r4\r\r\nOK\r\n");
+    }
+
+    struct NoSim;
+
+    impl AtContext<SIZE> for NoSim {
+        fn query(&mut self, at_response: &'static str) -> AtResult<'_, SIZE> {
+            Err((at_response, AtError::Cme(crate::cme::SIM_NOT_INSERTED)))
+        }
+    }
+
+    #[cfg(not(any(feature = "exec-set-only", feature = "basic-errors", feature = "no-error-text")))]
+    #[test]
+    fn builtin_cmee_selects_cme_error_reporting() {
+        let mut sim = NoSim;
+        let mut out = String::new();
+        let commands: &mut [(&str, &str, &mut NoSim)] = &mut [("AT+CPIN", "+CPIN: ", &mut sim)];
+        let mut parser: AtParser<NoSim, SIZE> = AtParser::new();
+        parser.set_commands(commands);
+        parser.set_output(&mut out);
+        parser.set_response_framing(true);
+
+        parser.feed_slice(b"AT+CPIN?\r");
+        parser.feed_slice(b"AT+CMEE=1\r");
+        parser.feed_slice(b"AT+CPIN?\r");
+        parser.feed_slice(b"AT+CMEE=2\r");
+        parser.feed_slice(b"AT+CPIN?\r");
+        parser.feed_slice(b"AT+CMEE=3\r");
+        assert_eq!(ok(Some(parser.execute("AT+CMEE?"))), "+CMEE: 2");
+        assert_eq!(ok(Some(parser.execute("AT+CMEE=?"))), "+CMEE: (0-2)");
+
+        assert_eq!(out, "\r\nERROR\r\n\r\nOK\r\n\r\n+CME ERROR: 10\r\n\r\nOK\r\n\r\n+CME ERROR: SIM not inserted\r\n\r\nERROR\r\n");
+    }
+
+    struct Signal;
+
+    impl AtContext<SIZE> for Signal {
+        fn exec_with(&mut self, at_response: &'static str, responder: &mut Responder<'_>) -> AtResult<'_, SIZE> {
+            write!(responder, "{},{}", 23, 0).map_err(|_| (at_response, AtError::InvalidArgs))?;
+            Ok((at_response, AtResponse::Ok))
+        }
+    }
+
+    #[test]
+    fn handlers_format_into_the_response_buffer() {
+        let mut signal = Signal;
+        let mut buffer = [0u8; 4];
+        let commands: &mut [(&str, &str, &mut Signal)] = &mut [("AT+CSQ", "+CSQ: ", &mut signal)];
+        let mut parser: AtParser<Signal, SIZE> = AtParser::new();
+        parser.set_commands(commands);
+
+        // no buffer: the write fails
+        assert!(matches!(parser.execute("AT+CSQ"), Err(("+CSQ: ", AtError::InvalidArgs))));
+        parser.set_response_buffer(&mut buffer);
+        assert_eq!(ok(Some(parser.execute("AT+CSQ"))), "+CSQ: 23,0");
+    }
+
+    struct Dialer;
+
+    impl AtContext<SIZE> for Dialer {
+        fn set_with(&mut self, at_response: &'static str, args: Args, responder: &mut Responder<'_>) -> AtResult<'_, SIZE> {
+            if args.raw() == "5551234" {
+                responder.intermediate(ResultCode::Connect(Some(9600))).unwrap();
+                Err((at_response, AtError::Final(ResultCode::NoCarrier)))
+            } else {
+                Err((at_response, AtError::Final(ResultCode::Busy)))
+            }
+        }
+    }
+
+    #[test]
+    fn handlers_emit_intermediate_result_codes() {
+        let mut dialer = Dialer;
+        let mut out = String::new();
+        let prefixes: &mut [(&str, &str, &mut Dialer)] = &mut [("ATD", "", &mut dialer)];
+        let mut parser: AtParser<Dialer, SIZE> = AtParser::new();
+        parser.set_prefix_commands(prefixes);
+        parser.set_output(&mut out);
+
+        // the responder is disconnected until framing is enabled
+        parser.feed_slice(b"ATD5551234\r");
+        parser.set_response_framing(true);
+        parser.feed_slice(b"ATD5551234\r");
+        parser.feed_slice(b"ATD5550000\r");
+
+        assert_eq!(out, "\r\nCONNECT 9600\r\n\r\nNO CARRIER\r\n\r\nBUSY\r\n");
+    }
+
+    struct Listing;
+
+    impl AtContext<SIZE> for Listing {
+        fn test_with(&mut self, at_response: &'static str, responder: &mut Responder<'_>) -> AtResult<'_, SIZE> {
+            responder.info(format_args!("{at_response}(2,\"A\")")).unwrap();
+            responder.info(format_args!("{at_response}(1,\"B\")")).unwrap();
+            Ok((at_response, AtResponse::Ok))
+        }
+    }
+
+    #[cfg(not(feature = "exec-set-only"))]
+    #[test]
+    fn handlers_emit_multi_line_information_text() {
+        let mut listing = Listing;
+        let mut out = String::new();
+        let commands: &mut [(&str, &str, &mut Listing)] = &mut [("AT+COPS", "+COPS: ", &mut listing)];
+        let mut parser: AtParser<Listing, SIZE> = AtParser::new();
+        parser.set_commands(commands);
+        parser.set_output(&mut out);
+        parser.set_response_framing(true);
+
+        parser.feed_slice(b"AT+COPS=?\r");
+
+        assert_eq!(out, "\r\n+COPS: (2,\"A\")\r\n+COPS: (1,\"B\")\r\n\r\nOK\r\n");
+    }
+
+    #[cfg(not(feature = "exec-set-only"))]
+    #[test]
+    fn urcs_are_held_back_while_a_line_is_received() {
+        let mut value = Value { value: 2 };
+        let mut out = String::new();
+        let commands: &mut [(&str, &str, &mut Value)] = &mut [("AT+VAL", "+VAL: ", &mut value)];
+        let mut parser: AtParser<Value, SIZE> = AtParser::new();
+        parser.set_commands(commands);
+        parser.set_output(&mut out);
+        parser.set_response_framing(true);
+
+        parser.emit_urc("+CREG: 1").unwrap();
+        parser.feed_slice(b"AT+V");
+        parser.emit_urc_code(ResultCode::Ring).unwrap();
+        parser.emit_urc(format_args!("+CREG: {}", 5)).unwrap();
+        parser.feed_slice(b"AL?\r");
+        parser.framing_mut().set_verbose(false);
+        parser.emit_urc_code(ResultCode::Ring).unwrap();
+
+        assert_eq!(out, "\r\n+CREG: 1\r\n\r\n+VAL: 2\r\n\r\nOK\r\n\r\nRING\r\n\r\n+CREG: 5\r\n2\r");
+    }
+
+    #[cfg(not(feature = "exec-set-only"))]
+    #[test]
+    fn builtin_quiet_suppresses_result_codes() {
+        let mut value = Value { value: 2 };
+        let mut out = String::new();
+        let commands: &mut [(&str, &str, &mut Value)] = &mut [("AT+VAL", "+VAL: ", &mut value)];
+        let mut parser: AtParser<Value, SIZE> = AtParser::new();
+        parser.set_commands(commands);
+        parser.set_output(&mut out);
+        parser.set_response_framing(true);
+
+        parser.feed_slice(b"ATQ1\r");
+        assert!(parser.framing().quiet());
+        parser.feed_slice(b"AT+VAL?\r");
+        parser.feed_slice(b"AT+NOPE\r");
+        parser.feed_slice(b"ATQ0\r");
+
+        assert_eq!(out, "\r\n+VAL: 2\r\n\r\nOK\r\n");
+    }
+
+    #[cfg(not(feature = "exec-set-only"))]
+    #[test]
+    fn feed_repeats_last_line_on_a_slash() {
+        let mut value = Value { value: 4 };
+        let commands: &mut [(&str, &str, &mut Value)] = &mut [("AT+VAL", "+VAL: ", &mut value)];
+        let mut parser: AtParser<Value, SIZE> = AtParser::new();
+        parser.set_commands(commands);
+
+        let (_, result) = parser.feed_slice(b"AT+VAL?\r");
+        assert_eq!(ok(result), "+VAL: 4");
+        assert!(parser.feed(b'A').is_none());
+        assert_eq!(ok(parser.feed(b'/')), "+VAL: 4");
+    }
+
+    struct SendSms {
+        text: String,
+    }
+
+    impl AtContext<SIZE> for SendSms {
+        fn set_with(&mut self, at_response: &'static str, _args: Args, responder: &mut Responder<'_>) -> AtResult<'_, SIZE> {
+            self.text.clear();
+            responder.prompt("> ").map_err(|_| (at_response, AtError::InvalidArgs))?;
+            Ok((at_response, AtResponse::Continue))
+        }
+
+        fn input(&mut self, at_response: &'static str, byte: u8, _responder: &mut Responder<'_>) -> Option<AtResult<'_, SIZE>> {
+            match byte {
+                0x1a => Some(Ok(at_response!(SIZE, at_response; self.text.len()))),
+                byte => {
+                    self.text.push(char::from(byte));
+                    None
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn text_longer_than_size_is_sent_in_full() {
+        struct Banner(String);
+
+        impl AtContext<SIZE> for Banner {
+            fn exec(&mut self, at_response: &'static str) -> AtResult<'_, SIZE> {
+                Ok((at_response, AtResponse::Text(&self.0)))
+            }
+
+            fn set(&mut self, at_response: &'static str, _args: Args) -> AtResult<'_, SIZE> {
+                Ok((at_response, AtResponse::Continue))
+            }
+
+            fn input(&mut self, at_response: &'static str, _byte: u8, _responder: &mut Responder<'_>) -> Option<AtResult<'_, SIZE>> {
+                Some(Ok((at_response, AtResponse::Text(&self.0))))
+            }
+        }
+
+        let text = "0123456789".repeat(10);
+        let mut banner = Banner(text.clone());
+        let commands: &mut [(&str, &str, &mut Banner)] = &mut [("AT+BANNER", "", &mut banner)];
+        let mut out = String::new();
+        let mut parser: AtParser<Banner, SIZE> = AtParser::new();
+        parser.set_commands(commands);
+        parser.set_output(&mut out);
+        parser.set_response_framing(true);
+
+        let (_, result) = parser.feed_slice(b"AT+BANNER\r");
+        assert!(matches!(result, Some(Ok((_, AtResponse::Text(sent)))) if sent == text));
+        parser.feed_slice(b"AT+BANNER=1\r");
+        assert!(parser.feed(b'x').is_some());
+        assert_eq!(out, format!("\r\n{text}\r\n\r\nOK\r\n").repeat(2));
+    }
+
+    #[test]
+    fn continue_hands_the_following_input_to_the_handler() {
+        let mut sms = SendSms { text: String::new() };
+        let commands: &mut [(&str, &str, &mut SendSms)] = &mut [("AT+CMGS", "+CMGS: ", &mut sms)];
+        let mut out = String::new();
+        let mut parser: AtParser<SendSms, SIZE> = AtParser::new();
+        parser.set_commands(commands);
+        parser.set_output(&mut out);
+        parser.set_response_framing(true);
+
+        let input = b"AT+CMGS=\"123\";+CMGS=\"456\"\rAT\r\x1aAT+CMGS=1\r";
+        let (consumed, result) = parser.feed_slice(input);
+        assert!(matches!(result, Some(Ok(("+CMGS: ", AtResponse::Continue)))));
+        assert!(parser.is_awaiting_input());
+        parser.emit_urc(format_args!("RING")).unwrap();
+
+        let (more, result) = parser.feed_slice(&input[consumed..]);
+        assert_eq!(ok(result), "+CMGS: 3");
+        assert!(!parser.is_awaiting_input());
+
+        assert!(parser.feed_slice(&input[consumed + more..]).1.unwrap().is_ok());
+        parser.cancel_input();
+        assert!(parser.feed_slice(b"AT\r").1.unwrap().is_err());
+        assert_eq!(out, "\r\n> \r\n+CMGS: 3\r\n\r\nOK\r\n\r\nRING\r\n\r\n> \r\nERROR\r\n");
+    }
+
+    struct SendPdu {
+        sent: Vec<Vec<u8>>,
+    }
+
+    impl AtContext<SIZE> for SendPdu {
+        fn set_with(&mut self, at_response: &'static str, _args: Args, responder: &mut Responder<'_>) -> AtResult<'_, SIZE> {
+            responder.prompt_payload("> ").map_err(|_| (at_response, AtError::InvalidArgs))?;
+            Ok((at_response, AtResponse::Continue))
+        }
+
+        fn payload(&mut self, at_response: &'static str, payload: &[u8], _responder: &mut Responder<'_>) -> AtResult<'_, SIZE> {
+            self.sent.push(payload.to_vec());
+            Ok(at_response!(SIZE, at_response; self.sent.len()))
+        }
+    }
+
+    #[test]
+    fn payload_is_collected_up_to_ctrl_z_and_cancelled_by_esc() {
+        let mut pdu = SendPdu { sent: Vec::new() };
+        let commands: &mut [(&str, &str, &mut SendPdu)] = &mut [("AT+CMGS", "+CMGS: ", &mut pdu)];
+        let mut out = String::new();
+        let mut parser: AtParser<SendPdu, SIZE> = AtParser::new();
+        parser.set_commands(commands);
+        parser.set_output(&mut out);
+        parser.set_response_framing(true);
+
+        parser.feed_slice(b"AT+CMGS=9\r");
+        assert!(parser.feed_slice(b"hi\r\nthere").1.is_none());
+        assert_eq!(ok(parser.feed_slice(b"\x1a").1), "+CMGS: 1");
+
+        parser.feed_slice(b"AT+CMGS=3\r");
+        assert!(matches!(parser.feed_slice(b"abc\x1b").1, Some(Ok(("+CMGS: ", AtResponse::Ok)))));
+
+        parser.feed_slice(b"AT+CMGS=99\r");
+        parser.feed_slice(&[b'x'; SIZE + 1]);
+        assert!(matches!(parser.feed_slice(b"\x1a").1, Some(Err(("+CMGS: ", AtError::InvalidArgs)))));
+        assert!(!parser.is_awaiting_input());
+
+        assert!(parser.feed_slice(b"ATE0\r").1.unwrap().is_ok());
+        assert_eq!(out, "\r\n> \r\n+CMGS: 1\r\n\r\nOK\r\n\r\n> \r\nOK\r\n\r\n> \r\nERROR\r\n\r\nOK\r\n");
+        assert_eq!(pdu.sent, [b"hi\r\nthere".to_vec()]);
+    }
+
+    struct CipSend {
+        sent: Vec<Vec<u8>>,
+    }
+
+    impl AtContext<SIZE> for CipSend {
+        fn set_with(&mut self, at_response: &'static str, args: Args, responder: &mut Responder<'_>) -> AtResult<'_, SIZE> {
+            let len = args.get_u16(0).map_err(|e| (at_response, e.into()))?;
+            responder.prompt("> ").map_err(|_| (at_response, AtError::InvalidArgs))?;
+            responder.expect_bytes(usize::from(len));
+            Ok((at_response, AtResponse::Continue))
+        }
+
+        fn payload(&mut self, at_response: &'static str, payload: &[u8], _responder: &mut Responder<'_>) -> AtResult<'_, SIZE> {
+            self.sent.push(payload.to_vec());
+            Ok((at_response, AtResponse::Ok))
+        }
+    }
+
+    #[test]
+    fn counted_payload_is_binary_and_received_in_full() {
+        let mut socket = CipSend { sent: Vec::new() };
+        let commands: &mut [(&str, &str, &mut CipSend)] = &mut [("AT+CIPSEND", "", &mut socket)];
+        let mut out = String::new();
+        let mut buffer = [0u8; 4];
+        let mut parser: AtParser<CipSend, SIZE> = AtParser::new();
+        parser.set_commands(commands);
+        parser.set_output(&mut out);
+        parser.set_response_framing(true);
+        parser.set_payload_buffer(&mut buffer);
+
+        parser.feed_slice(b"ATE1\r");
+        parser.feed_slice(b"AT+CIPSEND=4\r");
+        assert!(parser.feed_slice(b"\x1b\r\x1a").1.is_none());
+        assert!(parser.feed(0).unwrap().is_ok());
+
+        // Too long for the buffer, but the command line resumes after it
+        parser.feed_slice(b"AT+CIPSEND=6\r");
+        let (consumed, result) = parser.feed_slice(b"abcdefATE0\r");
+        assert_eq!(consumed, 6);
+        assert!(matches!(result, Some(Err(("", AtError::InvalidArgs)))));
+        assert!(parser.feed_slice(b"ATE0\r").1.unwrap().is_ok());
+
+        assert_eq!(out, concat!(
+            "\r\nOK\r\n",
+            "AT+CIPSEND=4\r\r\n> \r\nOK\r\n",
+            "AT+CIPSEND=6\r\r\n> \r\nERROR\r\n",
+            "ATE0\r\r\nOK\r\n",
+        ));
+        assert_eq!(socket.sent, [b"\x1b\r\x1a\0".to_vec()]);
+    }
+
+    #[derive(Debug, PartialEq)]
+    enum ModemError {
+        SimNotReady,
+        FlashBusy,
+    }
+
+    impl UserError for ModemError {
+        fn report(&self) -> AtError<'static> {
+            match self {
+                ModemError::SimNotReady => AtError::Cme(10),
+                ModemError::FlashBusy => AtError::Final(ResultCode::Busy),
+            }
+        }
+    }
+
+    struct Sim;
+
+    impl AtContext<SIZE, (), ModemError> for Sim {
+        fn query(&mut self, at_response: &'static str) -> AtResult<'_, SIZE, ModemError> {
+            Err((at_response, AtError::User(ModemError::SimNotReady)))
+        }
+
+        fn set(&mut self, at_response: &'static str, _args: Args) -> AtResult<'_, SIZE, ModemError> {
+            Err((at_response, AtError::User(ModemError::FlashBusy)))
+        }
+    }
+
+    #[cfg(not(any(feature = "exec-set-only", feature = "basic-errors")))]
+    #[test]
+    fn user_errors_reach_the_caller_and_are_framed_as_reported() {
+        let mut sim = Sim;
+        let commands: &mut [(&str, &str, &mut Sim)] = &mut [("AT+CPIN", "+CPIN: ", &mut sim)];
+        let mut out = String::new();
+        let mut parser: AtParser<Sim, SIZE, (), ModemError> = AtParser::new();
+        parser.set_commands(commands);
+        parser.set_output(&mut out);
+        parser.set_response_framing(true);
+
+        assert!(matches!(parser.execute("AT+CPIN?"), Err(("+CPIN: ", AtError::User(ModemError::SimNotReady)))));
+        assert!(matches!(parser.execute("AT+NOPE"), Err(("", AtError::UnknownCommand))));
+        parser.feed_slice(b"AT+CMEE=1\r");
+        parser.feed_slice(b"AT+CPIN?\r");
+        parser.feed_slice(b"AT+CPIN=1\r");
+        assert_eq!(out, "\r\nOK\r\n\r\n+CME ERROR: 10\r\n\r\nBUSY\r\n");
+    }
+
+    struct Factory {
+        restored: u32,
+    }
+
+    impl AtContext<SIZE> for Factory {
+        fn exec(&mut self, at_response: &'static str) -> AtResult<'_, SIZE> {
+            self.restored += 1;
+            Ok((at_response, AtResponse::Ok))
+        }
+    }
+
+    #[test]
+    fn exec_form_updates_the_handler() {
+        let mut factory = Factory { restored: 0 };
+        {
+            let commands: &mut [(&str, &str, &mut Factory)] = &mut [("AT&F", "", &mut factory)];
+            let mut parser: AtParser<Factory, SIZE> = AtParser::new();
+            parser.set_commands(commands);
+
+            assert!(parser.execute("AT&F").is_ok());
+            assert!(parser.execute("AT&F").is_ok());
+        }
+        assert_eq!(factory.restored, 2);
+    }
+
+    struct Driver {
+        resets: u32,
+    }
+
+    struct Reset;
+
+    impl AtContext<SIZE, Driver> for Reset {
+        fn exec_with(&mut self, at_response: &'static str, responder: &mut Responder<'_, Driver>) -> AtResult<'_, SIZE> {
+            let driver = responder.state().ok_or((at_response, AtError::NotSupported))?;
+            driver.resets += 1;
+            Ok(at_response!(SIZE, at_response; driver.resets))
+        }
+    }
+
+    #[test]
+    fn handlers_share_user_state() {
+        let mut reset = Reset;
+        let mut other = Reset;
+        let mut driver = Driver { resets: 0 };
+        let commands: &mut [(&str, &str, &mut Reset)] = &mut [("AT+RST", "+RST: ", &mut reset), ("AT+RBT", "+RBT: ", &mut other)];
+        let mut parser: AtParser<Reset, SIZE, Driver> = AtParser::new();
+        parser.set_commands(commands);
+
+        assert!(matches!(parser.execute("AT+RST"), Err(("+RST: ", AtError::NotSupported))));
+        assert_eq!(ok(Some(parser.execute_with_state("AT+RST", &mut driver))), "+RST: 1");
+        assert_eq!(ok(Some(parser.execute_with_state("AT+RBT;+RST", &mut driver))), "+RST: 3");
+        assert_eq!(driver.resets, 3);
+
+        let mut installed = Driver { resets: 10 };
+        parser.set_state(&mut installed);
+        assert_eq!(ok(parser.feed_slice(b"AT+RBT\r").1), "+RBT: 11");
+        assert_eq!(parser.state_mut().map(|driver| driver.resets), Some(11));
+    }
+
+    struct Packet<'c> {
+        active: &'c Cell<u8>,
+        attached: &'c Cell<u8>,
+    }
+
+    impl AtContext<SIZE> for Packet<'_> {
+        fn query_with(&mut self, at_response: &'static str, responder: &mut Responder<'_>) -> AtResult<'_, SIZE> {
+            let value = match responder.command() {
+                "AT+CGACT" => self.active.get(),
+                _ => self.attached.get(),
+            };
+            Ok(at_response!(SIZE, at_response; value))
+        }
+
+        fn set_with(&mut self, at_response: &'static str, args: Args, responder: &mut Responder<'_>) -> AtResult<'_, SIZE> {
+            assert_eq!(responder.form(), Form::Set);
+            let value = args.get_u8(0).map_err(|e| (at_response, e.into()))?;
+            match responder.command() {
+                "AT+CGACT" => self.active.set(value),
+                _ => self.attached.set(value),
+            }
+            Ok((at_response, AtResponse::Ok))
+        }
+    }
+
+    #[cfg(not(feature = "exec-set-only"))]
+    #[test]
+    fn handlers_know_the_matched_command() {
+        let (active, attached) = (Cell::new(0), Cell::new(0));
+        let mut cgact = Packet { active: &active, attached: &attached };
+        let mut cgatt = Packet { active: &active, attached: &attached };
+        let commands: &mut [(&str, &str, &mut Packet)] = &mut [("AT+CGACT", "+CGACT: ", &mut cgact), ("AT+CGATT", "+CGATT: ", &mut cgatt)];
+        let mut parser: AtParser<Packet, SIZE> = AtParser::new();
+        parser.set_commands(commands);
+
+        assert!(parser.execute("AT+CGATT=1").is_ok());
+        assert_eq!(ok(Some(parser.execute("AT+CGACT?"))), "+CGACT: 0");
+        assert_eq!(ok(Some(parser.execute("AT+CGATT?"))), "+CGATT: 1");
+    }
+
+    struct Scan<'c> {
+        /// Stands for the receive path, which runs concurrently on a device
+        rx: &'c Abort,
+    }
+
+    impl AtContext<SIZE> for Scan<'_> {
+        fn test_with(&mut self, at_response: &'static str, responder: &mut Responder<'_>) -> AtResult<'_, SIZE> {
+            assert!(self.rx.input());
+            if responder.is_aborted() {
+                return Err((at_response, AtError::Aborted));
+            }
+            Ok((at_response, AtResponse::Ok))
+        }
+    }
+
+    #[cfg(not(feature = "exec-set-only"))]
+    #[test]
+    fn input_during_execution_aborts_the_handler() {
+        let abort = Abort::new();
+        let mut scan = Scan { rx: &abort };
+        let commands: &mut [(&str, &str, &mut Scan)] = &mut [("AT+COPS", "+COPS: ", &mut scan)];
+        let mut out = String::new();
+        let mut parser: AtParser<Scan, SIZE> = AtParser::new();
+        parser.set_commands(commands);
+        parser.set_output(&mut out);
+        parser.set_response_framing(true);
+        parser.set_abort(&abort);
+
+        assert!(matches!(parser.feed_slice(b"AT+COPS=?\r").1, Some(Err(("+COPS: ", AtError::Aborted)))));
+        assert!(!abort.is_executing());
+        assert!(!abort.input());
+        assert_eq!(out, "\r\nABORTED\r\n");
+    }
+
+    #[cfg(feature = "async")]
+    struct Flash {
+        saved: u32,
+    }
+
+    #[cfg(feature = "async")]
+    impl AtContext<SIZE> for Flash {
+        fn query(&mut self, at_response: &'static str) -> AtResult<'_, SIZE> {
+            Ok(at_response!(SIZE, at_response; self.saved))
+        }
+    }
+
+    #[cfg(feature = "async")]
+    impl crate::context::AsyncAtContext<SIZE> for Flash {
+        async fn set(&mut self, at_response: &'static str, args: Args<'_>, _responder: &mut Responder<'_>) -> AtResult<'_, SIZE> {
+            // a write that completes on the second poll
+            let mut polled = false;
+            core::future::poll_fn(|cx| {
+                if polled {
+                    core::task::Poll::Ready(())
+                } else {
+                    polled = true;
+                    cx.waker().wake_by_ref();
+                    core::task::Poll::Pending
+                }
+            })
+            .await;
+            self.saved = args.get_u32(0).map_err(|e| (at_response, e.into()))?;
+            Ok(at_response!(SIZE, at_response; "OK"))
+        }
+    }
+
+    #[cfg(feature = "async")]
+    fn block_on<F: core::future::Future>(future: F) -> F::Output {
+        let mut future = core::pin::pin!(future);
+        let mut cx = core::task::Context::from_waker(core::task::Waker::noop());
+        loop {
+            if let core::task::Poll::Ready(output) = future.as_mut().poll(&mut cx) {
+                return output;
+            }
+        }
+    }
+
+    #[cfg(all(feature = "async", not(feature = "exec-set-only")))]
+    #[test]
+    fn async_handlers_are_awaited() {
+        let mut flash = Flash { saved: 0 };
+        let commands: &mut [(&str, &str, &mut Flash)] = &mut [("AT+SAVE", "+SAVE: ", &mut flash)];
+        let mut parser: AtParser<Flash, SIZE> = AtParser::new();
+        parser.set_commands(commands);
+
+        assert_eq!(ok(Some(block_on(parser.execute_async("AT+SAVE=42;E0")))), "");
+        assert_eq!(ok(Some(block_on(parser.execute_async("AT+SAVE?")))), "+SAVE: 42");
+        assert!(matches!(block_on(parser.execute_async("AT+SAVE")), Err(("+SAVE: ", AtError::NotSupported))));
+    }
+
+    struct Charset;
+
+    impl AtContext<SIZE> for Charset {
+        fn supported(&self) -> &[crate::context::Supported] {
+            use crate::context::Supported;
+
+            &[Supported::Strings(&["GSM", "UCS2"]), Supported::Range(1, 65535), Supported::Values(&[0, 2])]
+        }
+    }
+
+    #[cfg(not(feature = "exec-set-only"))]
+    #[test]
+    fn test_form_is_answered_from_declared_parameters() {
+        let mut charset = Charset;
+        let mut value = Value { value: 0 };
+        let mut parser: AtParser<dyn AtContext<SIZE>, SIZE> = AtParser::new();
+        let commands: &mut [(&str, &str, &mut dyn AtContext<SIZE>)] = &mut [("AT+CSCS", "+CSCS: ", &mut charset), ("AT+VAL", "+VAL: ", &mut value)];
+        parser.set_commands(commands);
+
+        assert_eq!(ok(Some(parser.execute("AT+CSCS=?"))), "+CSCS: (\"GSM\",\"UCS2\"),(1-65535),(0,2)");
+        assert!(matches!(parser.execute("AT+VAL=?"), Err(("+VAL: ", AtError::NotSupported))));
+    }
+
+    #[cfg(not(feature = "exec-set-only"))]
+    #[test]
+    fn handlers_of_different_types_share_one_table() {
+        let (active, attached) = (Cell::new(1), Cell::new(0));
+        let mut packet = Packet { active: &active, attached: &attached };
+        let mut value = Value { value: 7 };
+        let commands: &mut [(&str, &str, &mut dyn AtContext<SIZE>)] = &mut [("AT+CGACT", "+CGACT: ", &mut packet), ("AT+VAL", "+VAL: ", &mut value)];
+        let mut parser: DynAtParser<SIZE> = DynAtParser::new();
+        parser.set_commands(commands);
+
+        assert_eq!(ok(Some(parser.execute("AT+CGACT?"))), "+CGACT: 1");
+        assert_eq!(ok(Some(parser.execute("AT+VAL?"))), "+VAL: 7");
+    }
+
+    #[cfg(not(feature = "exec-set-only"))]
+    #[test]
+    fn sorted_tables_are_binary_searched() {
+        let (mut gsn, mut gmi, mut gmr, mut gmm) = (Value { value: 1 }, Value { value: 2 }, Value { value: 3 }, Value { value: 4 });
+        let commands: &mut [(&str, &str, &mut Value)] = &mut [
+            ("AT+CGSN", "+CGSN: ", &mut gsn),
+            ("AT+CGMI", "+CGMI: ", &mut gmi),
+            ("AT+CGMR", "+CGMR: ", &mut gmr),
+            ("AT+CGMM", "+CGMM: ", &mut gmm),
+        ];
+        sort_commands(commands);
+        assert!(is_sorted(&commands.iter().map(|(name, _, _)| *name).collect::<Vec<_>>()));
+
+        let mut parser: AtParser<Value, SIZE> = AtParser::new();
+        parser.set_sorted_commands(commands);
+        assert_eq!(ok(Some(parser.execute("AT+CGSN?"))), "+CGSN: 1");
+        assert_eq!(ok(Some(parser.execute("AT+CGMI?"))), "+CGMI: 2");
+        assert_eq!(ok(Some(parser.execute("AT+CGMM?;+CGMR?"))), "+CGMR: 3");
+        assert!(matches!(parser.execute("AT+CGMX?"), Err((_, AtError::UnknownCommand))));
+        assert!(matches!(parser.execute("at+cgmm?"), Err((_, AtError::UnknownCommand))));
+
+        parser.set_case_insensitive(true);
+        assert_eq!(ok(Some(parser.execute("at+cgmm?"))), "+CGMM: 4");
+    }
+
+    #[cfg(feature = "exec-set-only")]
+    #[test]
+    fn query_and_test_forms_are_left_out() {
+        let mut value = Value { value: 7 };
+        let commands: &mut [(&str, &str, &mut Value)] = &mut [("AT+VAL", "+VAL: ", &mut value)];
+        let mut parser: AtParser<Value, SIZE> = AtParser::new();
+        parser.set_commands(commands);
+
+        assert_eq!(ok(Some(parser.execute("AT+VAL=3"))), "+VAL: OK");
+        assert!(matches!(parser.execute("AT+VAL?"), Err((_, AtError::NotSupported))));
+        assert!(matches!(parser.execute("AT+VAL=?"), Err((_, AtError::NotSupported))));
+        assert!(matches!(parser.execute("ATE?"), Err((_, AtError::NotSupported))));
+    }
+
+    #[cfg(not(feature = "exec-set-only"))]
+    #[test]
+    fn split_tables_are_looked_up_through_their_names() {
+        let (mut gmi, mut gmm) = (Value { value: 1 }, Value { value: 2 });
+        let commands: &mut [(&str, &str, &mut Value)] = &mut [("AT+CGMI", "+CGMI: ", &mut gmi), ("AT+CGMM", "+CGMM: ", &mut gmm)];
+        let mut parser: AtParser<Value, SIZE> = AtParser::new();
+        assert_eq!(parser.set_split_commands(commands, &["AT+CGMI", "AT+CGMM"]), Ok(()));
+
+        assert_eq!(ok(Some(parser.execute("AT+CGMM?"))), "+CGMM: 2");
+        assert_eq!(ok(Some(parser.execute("AT+CGMI?;+CGMM?"))), "+CGMM: 2");
+        assert!(matches!(parser.execute("AT+CGMR?"), Err((_, AtError::UnknownCommand))));
+
+        parser.set_case_insensitive(true);
+        assert_eq!(ok(Some(parser.execute("at+cgmi?"))), "+CGMI: 1");
+    }
+
+    #[test]
+    fn split_tables_with_other_names_are_rejected() {
+        let (mut gmi, mut gmm) = (Value { value: 1 }, Value { value: 2 });
+        let mut parser: AtParser<Value, SIZE> = AtParser::new();
+        let commands: &mut [(&str, &str, &mut Value)] = &mut [("AT+CGMI", "+CGMI: ", &mut gmi)];
+        assert_eq!(parser.set_split_commands(commands, &["AT+CGMI", "AT+CGMM"]), Err(NamesMismatch));
+        let commands: &mut [(&str, &str, &mut Value)] = &mut [("AT+CGMM", "+CGMM: ", &mut gmm)];
+        assert_eq!(parser.set_split_commands(commands, &["AT+CGMI"]), Err(NamesMismatch));
+        assert!(matches!(parser.execute("AT+CGMI"), Err((_, AtError::UnknownCommand))));
+    }
+
+    #[cfg(not(feature = "exec-set-only"))]
+    #[test]
+    fn interned_tables_compare_identifiers_first() {
+        const ID: u32 = command_id("AT+QCFGEXT");
+        assert_eq!(ID, command_id("+QCFGEXT"));
+        assert_eq!(ID, command_id("at+qcfgext"));
+        assert_ne!(ID, command_id("AT+QENGEXT"));
+
+        let (mut cfg, mut eng) = (Value { value: 1 }, Value { value: 2 });
+        let commands: &mut [(&str, &str, &mut Value)] = &mut [("AT+QCFGEXT", "+QCFGEXT: ", &mut cfg), ("AT+QENGEXT", "+QENGEXT: ", &mut eng)];
+        let mut ids = [0; 2];
+        let mut parser: AtParser<Value, SIZE> = AtParser::new();
+        assert_eq!(parser.set_interned_commands(commands, &mut ids), Ok(()));
+        assert_eq!(ok(Some(parser.execute("AT+QENGEXT?"))), "+QENGEXT: 2");
+        assert_eq!(ok(Some(parser.execute("AT+QCFGEXT?"))), "+QCFGEXT: 1");
+        assert!(matches!(parser.execute("AT+QCFG?"), Err((_, AtError::UnknownCommand))));
+
+        parser.set_case_insensitive(true);
+        assert_eq!(ok(Some(parser.execute("at+qengext?"))), "+QENGEXT: 2");
+    }
+
+    #[test]
+    fn interned_tables_need_an_identifier_per_command() {
+        let (mut cfg, mut eng) = (Value { value: 1 }, Value { value: 2 });
+        let commands: &mut [(&str, &str, &mut Value)] = &mut [("AT+QCFGEXT", "+QCFGEXT: ", &mut cfg), ("AT+QENGEXT", "+QENGEXT: ", &mut eng)];
+        let mut ids = [0; 1];
+        let mut parser: AtParser<Value, SIZE> = AtParser::new();
+        assert_eq!(parser.set_interned_commands(commands, &mut ids), Err(TooFewIds));
+        assert!(matches!(parser.execute("AT+QCFGEXT"), Err((_, AtError::UnknownCommand))));
+    }
+
+    #[cfg(all(feature = "perfect-hash", not(feature = "exec-set-only")))]
+    #[test]
+    fn hashed_tables_resolve_commands() {
+        let (mut gsn, mut gmi, mut gmr) = (Value { value: 1 }, Value { value: 2 }, Value { value: 3 });
+        let (commands, hash) = crate::at_hashed_modules! {
+            SIZE;
+            ("AT+CGSN", "+CGSN: ") => gsn,
+            ("AT+CGMI", "+CGMI: ") => gmi,
+            ("AT+CGMR", "+CGMR: ") => gmr,
+        };
+        let mut parser: DynAtParser<SIZE> = DynAtParser::new();
+        parser.set_hashed_commands(commands, hash);
+
+        assert_eq!(ok(Some(parser.execute("AT+CGSN?"))), "+CGSN: 1");
+        assert_eq!(ok(Some(parser.execute("AT+CGMI?;+CGMR?"))), "+CGMR: 3");
+        assert!(matches!(parser.execute("AT+CGMM?"), Err((_, AtError::UnknownCommand))));
+        assert!(matches!(parser.execute("at+cgmi?"), Err((_, AtError::UnknownCommand))));
+
+        parser.set_case_insensitive(true);
+        assert_eq!(ok(Some(parser.execute("at+cgmi?"))), "+CGMI: 2");
+    }
+
+    #[test]
+    fn command_order_ignores_the_at_prefix_and_case() {
+        assert!(command_order("AT+CGMI", "+CGMM").is_lt());
+        assert!(command_order("AT+abc", "AT+ABD").is_lt());
+        assert!(command_order("AT+ABC", "AT+abc").is_lt());
+        assert!(command_order("AT+CG", "AT+CGMI").is_lt());
+        assert!(command_order("AT+CGMI", "+CGMI").is_eq());
+        assert!(is_sorted(&["AT", "AT&F", "AT+CGMI", "ATE"]));
+        assert!(!is_sorted(&["AT+CGMI", "AT+CGMI"]));
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(expected = "command table is not sorted by name")]
+    fn unsorted_tables_are_rejected_in_debug_builds() {
+        let (mut b, mut a) = (Value { value: 1 }, Value { value: 2 });
+        let commands: &mut [(&str, &str, &mut Value)] = &mut [("AT+B", "+B: ", &mut b), ("AT+A", "+A: ", &mut a)];
+        let mut parser: AtParser<Value, SIZE> = AtParser::new();
+        parser.set_sorted_commands(commands);
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(expected = "command at+gmr is registered more than once")]
+    fn duplicate_commands_are_rejected_in_debug_builds() {
+        assert_eq!(count_command(&["AT+GMR", "AT+CGMR", "+gmr"], "AT+GMR"), 2);
+        let (mut a, mut b) = (Value { value: 1 }, Value { value: 2 });
+        let commands: &mut [(&str, &str, &mut Value)] = &mut [("AT+GMR", "+GMR: ", &mut a), ("at+gmr", "+GMR: ", &mut b)];
+        let mut parser: AtParser<Value, SIZE> = AtParser::new();
+        parser.set_commands(commands);
+    }
+
+    struct Call;
+
+    impl AtContext<SIZE> for Call {
+        fn exec(&mut self, at_response: &'static str) -> AtResult<'_, SIZE> {
+            Ok((at_response, AtResponse::FinalCode(ResultCode::Connect(Some(9600)))))
+        }
+
+        fn set(&mut self, at_response: &'static str, _args: Args) -> AtResult<'_, SIZE> {
+            Ok((at_response, AtResponse::Deferred))
+        }
+    }
+
+    #[test]
+    fn structured_responses_drive_the_final_result_code() {
+        let mut call = Call;
+        let mut out = String::new();
+        let commands: &mut [(&str, &str, &mut Call)] = &mut [("AT+CALL", "", &mut call)];
+        let mut parser: AtParser<Call, SIZE> = AtParser::new();
+        parser.set_commands(commands);
+        parser.set_output(&mut out);
+        parser.set_response_framing(true);
+
+        assert!(matches!(parser.feed_slice(b"AT+CALL\r").1, Some(Ok((_, AtResponse::FinalCode(_))))));
+        assert!(matches!(parser.feed_slice(b"AT+CALL=1\r").1, Some(Ok((_, AtResponse::Deferred)))));
+        parser.complete(&Err(("", AtError::Final(ResultCode::NoAnswer)))).unwrap();
+        assert!(matches!(parser.execute("ATE0"), Ok((_, AtResponse::Ok))));
+        assert!(matches!(parser.feed_slice(b"AT+CALL=2\r").1, Some(Ok((_, AtResponse::Deferred)))));
+        parser.complete_call(CallProgress::Busy).unwrap();
+        assert!(matches!(parser.feed_slice(b"AT+CALL=3\r").1, Some(Ok((_, AtResponse::Deferred)))));
+        assert!(matches!(parser.execute("ATV0"), Ok((_, AtResponse::Ok))));
+        parser.complete_call(CallProgress::NoDialtone).unwrap();
+
+        assert_eq!(out, "\r\nCONNECT 9600\r\n\r\nNO ANSWER\r\n\r\nBUSY\r\n6\r");
+    }
+
+    #[cfg(not(feature = "exec-set-only"))]
+    #[test]
+    fn strict_mode_rejects_text_after_query_and_test_suffixes() {
+        let mut a = Value { value: 3 };
+        let commands: &mut [(&str, &str, &mut Value)] = &mut [("AT+A", "+A: ", &mut a)];
+        let mut parser: AtParser<Value, SIZE> = AtParser::new();
+        parser.set_commands(commands);
+
+        assert!(matches!(parser.execute("AT+A?x"), Err(("", AtError::UnknownCommand))));
+        assert!(matches!(parser.execute("AT+A=?x"), Err(("+A: ", AtError::InvalidArgs))));
+
+        parser.set_strict(true);
+        assert!(matches!(parser.execute("AT+A?x"), Err(("", AtError::InvalidArgs))));
+        assert!(matches!(parser.execute("AT+A=?junk;+A?"), Err(("", AtError::InvalidArgs))));
+        assert_eq!(ok(Some(parser.execute("AT+A?"))), "+A: 3");
+        assert!(matches!(parser.execute("AT+A=?"), Err(("+A: ", AtError::NotSupported))));
+        assert!(parser.execute("ATE?").is_ok());
+    }
+
+    #[test]
+    fn surplus_parameters_are_rejected_before_the_handler() {
+        let (mut a, mut d) = (Value { value: 0 }, Value { value: 0 });
+        {
+            let commands: &mut [(&str, &str, &mut Value)] = &mut [("AT+A", "+A: ", &mut a)];
+            let prefixes: &mut [(&str, &str, &mut Value)] = &mut [("ATD", "", &mut d)];
+            let mut parser: AtParser<Value, SIZE> = AtParser::new();
+            parser.set_commands(commands);
+            parser.set_prefix_commands(prefixes);
+            parser.set_arg_limit(2);
+
+            assert!(parser.execute("AT+A=1,2").is_ok());
+            let hostile = format!("AT+A=7{}", ",1".repeat(1000));
+            assert!(matches!(parser.execute(&hostile), Err(("+A: ", AtError::InvalidArgs))));
+            assert!(matches!(parser.execute("ATD5,6,7"), Err(("", AtError::InvalidArgs))));
+        }
+        assert_eq!((a.value, d.value), (1, 0));
+    }
+
+    #[test]
+    fn overlong_lines_are_discarded_up_to_the_terminator() {
+        let mut out = String::new();
+        let mut parser: AtParser<Call, SIZE> = AtParser::new();
+        parser.set_output(&mut out);
+        parser.set_response_framing(true);
+        parser.set_line_limit(6);
+
+        assert!(matches!(parser.feed_slice(b"AT+TOOLONG\x08\x08\r").1, Some(Err(("", AtError::LineTooLong)))));
+        assert_eq!(parser.diagnostic(), Some(Diagnostic { offset: 6, reason: Reason::TooLong }));
+        assert!(matches!(parser.feed_slice(b"ATE0\r").1, Some(Ok((_, AtResponse::Ok)))));
+
+        assert_eq!(out, "\r\nERROR\r\n\r\nOK\r\n");
+    }
+
+    #[cfg(not(feature = "exec-set-only"))]
+    #[test]
+    fn last_matched_command_is_checked_against_the_current_table() {
+        let (mut a, mut b, mut c, mut d) = (Value { value: 1 }, Value { value: 2 }, Value { value: 3 }, Value { value: 4 });
+        let first: &mut [(&str, &str, &mut Value)] = &mut [("AT+A", "+A: ", &mut a), ("AT+B", "+B: ", &mut b)];
+        let second: &mut [(&str, &str, &mut Value)] = &mut [("AT+B", "+B: ", &mut c), ("AT+C", "+C: ", &mut d)];
+        let mut parser: AtParser<Value, SIZE> = AtParser::new();
+        parser.set_commands(first);
+
+        assert_eq!(ok(Some(parser.execute("AT+B?"))), "+B: 2");
+        assert_eq!(ok(Some(parser.execute("AT+B?"))), "+B: 2");
+        assert_eq!(ok(Some(parser.execute("AT+A?"))), "+A: 1");
+
+        parser.set_commands(second);
+        assert_eq!(ok(Some(parser.execute("AT+C?"))), "+C: 4");
+        assert_eq!(ok(Some(parser.execute("AT+B?"))), "+B: 3");
+        assert!(matches!(parser.execute("AT+A?"), Err(("", AtError::UnknownCommand))));
+    }
+
+    #[test]
+    fn split_command_ignores_quoted_semicolons() {
+        assert_eq!(split_command("AT+A=\"x;y\";+B"), ("AT+A=\"x;y\"", Some("+B")));
+        assert_eq!(split_command("+B"), ("+B", None));
+    }
+
+    #[test]
+    fn command_grammar_is_evaluated_at_compile_time() {
+        const TABLE: [(&str, Form, Args<'_>); 6] = [
+            parse_command("AT+CSQ"),
+            parse_command("AT+CSQ?"),
+            parse_command("+CSQ=?"),
+            parse_command("AT+X=a=b"),
+            parse_command("AT+X="),
+            parse_command(""),
+        ];
+
+        assert_eq!(TABLE, [
+            ("AT+CSQ", Form::Exec, Args::new("")),
+            ("AT+CSQ", Form::Query, Args::new("")),
+            ("+CSQ", Form::Test, Args::new("")),
+            ("AT+X", Form::Set, Args::new("a=b")),
+            ("AT+X", Form::Set, Args::new("")),
+            ("", Form::Exec, Args::new("")),
+        ]);
+    }
+
+    #[cfg(not(feature = "exec-set-only"))]
+    #[test]
+    fn feed_honours_configured_line_ending() {
+        let mut value = Value { value: 3 };
+        let commands: &mut [(&str, &str, &mut Value)] = &mut [("AT+VAL", "+VAL: ", &mut value)];
+        let mut parser: AtParser<Value, SIZE> = AtParser::new();
+        parser.set_commands(commands);
+        parser.set_line_ending(LineEnding::Lf);
+
+        for &byte in b"AT+VAL?\r" {
+            assert!(parser.feed(byte).is_none());
+        }
+        assert_eq!(ok(parser.feed(b'\n')), "+VAL: 3");
+    }
+
+    #[cfg(not(feature = "exec-set-only"))]
+    #[test]
+    fn byte_lines_skip_the_noise_around_the_command() {
+        let mut value = Value { value: 3 };
+        let commands: &mut [(&str, &str, &mut Value)] = &mut [("AT+VAL", "+VAL: ", &mut value)];
+        let mut parser: AtParser<Value, SIZE> = AtParser::new();
+        parser.set_commands(commands);
+
+        assert_eq!(ok(Some(parser.execute_bytes(b"\xff\x00AT+VAL=5\r\n\xfe"))), "+VAL: OK");
+        assert_eq!(ok(Some(parser.execute_bytes(b"AT+VAL?"))), "+VAL: 5");
+        assert!(matches!(parser.execute_bytes(b"\xffAT+VAL=\"\xff\""), Err(("", AtError::InvalidArgs))));
+        assert_eq!(parser.diagnostic(), Some(Diagnostic { offset: 9, reason: Reason::InvalidUtf8 }));
+    }
+
+    #[cfg(not(feature = "exec-set-only"))]
+    #[test]
+    fn execute_all_runs_every_complete_line_of_a_buffer() {
+        let mut value = Value { value: 0 };
+        let commands: &mut [(&str, &str, &mut Value)] = &mut [("AT+VAL", "+VAL: ", &mut value)];
+        let mut parser: AtParser<Value, SIZE> = AtParser::new();
+        parser.set_commands(commands);
+
+        let mut out = String::new();
+        let buf: &[u8] = b"AT+VAL=42\r\n\r\nAT+VAL?\r\nATV0\r\nAT+VAL?\r\nAT+V";
+        let consumed = parser.execute_all(buf, &mut out).unwrap();
+        assert_eq!(&buf[consumed..], b"AT+V");
+        assert_eq!(out, "\r\n+VAL: OK\r\n\r\nOK\r\n\r\n+VAL: 42\r\n\r\nOK\r\n0\r+VAL: 42\r\n0\r");
+
+        out.clear();
+        assert_eq!(parser.execute_all(b"AT+V", &mut out), Ok(0));
+        assert!(out.is_empty());
+    }
+
+    #[cfg(not(feature = "exec-set-only"))]
+    #[test]
+    fn execute_all_follows_the_line_endings_set_by_the_lines() {
+        let mut value = Value { value: 7 };
+        let commands: &mut [(&str, &str, &mut Value)] = &mut [("AT+VAL", "+VAL: ", &mut value)];
+        let mut parser: AtParser<Value, SIZE> = AtParser::new();
+        parser.set_commands(commands);
+        parser.enable_s_registers();
+
+        let mut out = String::new();
+        let buf: &[u8] = b"ATS4=13\rATS3=10\rAT+VAL?\nAT+VAL?\r";
+        let consumed = parser.execute_all(buf, &mut out).unwrap();
+        assert_eq!(&buf[consumed..], b"AT+VAL?\r");
+        assert_eq!(out, "\rOK\r\nOK\n\n+VAL: 7\n\nOK\n");
+    }
+
+    #[test]
+    fn execute_all_leaves_the_data_after_connect_to_feed() {
+        struct Dial;
+
+        impl AtContext<SIZE> for Dial {
+            fn exec(&mut self, at_response: &'static str) -> AtResult<'_, SIZE> {
+                Ok((at_response, AtResponse::FinalCode(ResultCode::Connect(None))))
+            }
+        }
+
+        struct Link(Vec<u8>);
+
+        impl DataSink for Link {
+            fn data(&mut self, bytes: &[u8]) {
+                self.0.extend_from_slice(bytes);
+            }
+        }
+
+        let mut dial = Dial;
+        let mut link = Link(Vec::new());
+        let commands: &mut [(&str, &str, &mut Dial)] = &mut [("AT+DIAL", "", &mut dial)];
+        let mut parser: AtParser<Dial, SIZE> = AtParser::new();
+        parser.set_commands(commands);
+        parser.set_data_sink(&mut link);
+
+        let mut out = String::new();
+        let buf: &[u8] = b"AT+DIAL\r\nATE0\r";
+        let consumed = parser.execute_all(buf, &mut out).unwrap();
+        assert_eq!(&buf[consumed..], b"ATE0\r");
+        assert_eq!(out, "\r\nCONNECT\r\n");
+        assert_eq!(parser.mode(), Mode::OnlineData);
+
+        parser.feed_slice(&buf[consumed..]);
+        assert_eq!(link.0, b"ATE0\r");
+    }
+
+    #[cfg(not(feature = "exec-set-only"))]
+    #[test]
+    fn execute_all_sends_the_held_urcs_after_the_response() {
+        let mut value = Value { value: 1 };
+        let mut output = String::new();
+        let commands: &mut [(&str, &str, &mut Value)] = &mut [("AT+VAL", "+VAL: ", &mut value)];
+        let mut parser: AtParser<Value, SIZE> = AtParser::new();
+        parser.set_commands(commands);
+        parser.set_output(&mut output);
+
+        parser.feed_slice(b"AT+V");
+        parser.emit_urc("+CREG: 1").unwrap();
+        let mut out = String::new();
+        assert_eq!(parser.execute_all(b"AT+VAL?\r", &mut out), Ok(8));
+        assert_eq!(out, "\r\n+VAL: 1\r\n\r\nOK\r\n");
+        assert_eq!(output, "\r\n+CREG: 1\r\n");
+    }
+
+    #[cfg(not(feature = "exec-set-only"))]
+    #[test]
+    fn feed_slice_reports_consumed_bytes() {
+        let mut value = Value { value: 0 };
+        let commands: &mut [(&str, &str, &mut Value)] = &mut [("AT+VAL", "+VAL: ", &mut value)];
+        let mut parser: AtParser<Value, SIZE> = AtParser::new();
+        parser.set_commands(commands);
+
+        let chunk: &[u8] = b"AT+VAL=42\r\nAT+VAL?\r\nAT+V";
+        let (consumed, result) = parser.feed_slice(chunk);
+        assert_eq!(consumed, 10);
+        assert_eq!(ok(result), "+VAL: OK");
+
+        let (consumed, result) = parser.feed_slice(&chunk[10..]);
+        assert_eq!(consumed, 9);
+        assert_eq!(ok(result), "+VAL: 42");
+
+        let (consumed, result) = parser.feed_slice(&chunk[19..]);
+        assert_eq!(consumed, 5);
+        assert!(result.is_none());
+
+        let (consumed, result) = parser.feed_slice(b"AL\r");
+        assert_eq!(consumed, 3);
+        assert_eq!(ok(result), "+VAL: executed");
+    }
+
+    #[cfg(not(feature = "exec-set-only"))]
+    #[test]
+    fn observer_sees_commands_results_and_parse_errors() {
+        #[derive(Default)]
+        struct Log(Vec<String>);
+
+        impl AtObserver<SIZE> for Log {
+            fn on_command(&mut self, name: &str, form: Form) {
+                self.0.push(format!("{} {:?}", name, form));
+            }
+
+            fn on_response(&mut self, result: &AtResult<'_, SIZE>) {
+                self.0.push(String::from(if result.is_ok() { "ok" } else { "error" }));
+            }
+
+            fn on_parse_error(&mut self, error: &AtError<'_>, raw: &str) {
+                self.0.push(format!("{:?} {:?}", error, raw));
+            }
+        }
+
+        let mut log = Log::default();
+        let mut value = Value { value: 0 };
+        let commands: &mut [(&str, &str, &mut Value)] = &mut [("AT+VAL", "+VAL: ", &mut value)];
+        let mut parser: AtParser<Value, SIZE> = AtParser::new();
+        parser.set_commands(commands);
+        parser.set_observer(&mut log);
+
+        assert!(parser.execute("AT+VAL=7;+VAL?").is_ok());
+        assert!(parser.execute("AT+NOPE").is_err());
+        assert!(matches!(parser.feed_slice(b"AT\xff\r").1, Some(Err(("", AtError::InvalidArgs)))));
+        parser.clear_observer();
+        assert!(parser.execute("AT+VAL").is_ok());
+
+        assert_eq!(log.0, [
+            "+VAL Set", "ok", "+VAL Query", "ok",
+            "+NOPE Exec", "error",
+            "InvalidArgs \"\"",
+        ]);
+    }
+
+    #[cfg(not(feature = "exec-set-only"))]
+    #[test]
+    fn observer_sees_one_final_result_code_per_line() {
+        #[derive(Default)]
+        struct Codes(Vec<String>);
+
+        impl AtObserver<SIZE> for Codes {
+            fn on_result_code(&mut self, name: &str, code: Result<ResultCode, ErrorKind>) {
+                self.0.push(format!("{name} {code:?}"));
+            }
+        }
+
+        let mut codes = Codes::default();
+        let (mut value, mut call) = (Value { value: 0 }, Call);
+        {
+            let values: &mut [(&str, &str, &mut Value)] = &mut [("AT+VAL", "+VAL: ", &mut value)];
+            let mut parser: AtParser<Value, SIZE> = AtParser::new();
+            parser.set_commands(values);
+            parser.set_observer(&mut codes);
+
+            assert!(parser.execute("AT+VAL=7;+VAL?").is_ok());
+            assert!(parser.execute("AT+VAL=x;+VAL?").is_err());
+            assert!(parser.execute_line("AT+VAL?;+NOPE", |_, _| {}).is_err());
+            assert!(parser.feed_slice(b"AT\xff\r").1.is_some());
+        }
+        {
+            let calls: &mut [(&str, &str, &mut Call)] = &mut [("AT+CALL", "", &mut call)];
+            let mut parser: AtParser<Call, SIZE> = AtParser::new();
+            parser.set_commands(calls);
+            parser.set_observer(&mut codes);
+
+            assert!(parser.execute("AT+CALL").is_ok());
+            assert!(parser.execute("AT+CALL=1").is_ok());
+            parser.complete_call(CallProgress::Busy).unwrap();
+        }
+
+        assert_eq!(codes.0, [
+            "+VAL Ok(Ok)",
+            "+VAL Err(InvalidArgs)",
+            "+NOPE Err(UnknownCommand)",
+            " Err(InvalidArgs)",
+            "+CALL Ok(Connect(Some(9600)))",
+            " Err(Final(Busy))",
+        ]);
+    }
+}
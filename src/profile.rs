@@ -0,0 +1,468 @@
+/***************************************************************************
+ *
+ * AT Command Parser
+ * Copyright (C) 2026 Antonio Salsi <passy.linux@zresa.it>
+ *
+ * This library is free software; you can redistribute it and/or
+ * modify it under the terms of the GNU Lesser General Public
+ * License as published by the Free Software Foundation; either
+ * version 2.1 of the License, or (at your option) any later version.
+ *
+ * This library is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the GNU
+ * Lesser General Public License for more details.
+ *
+ * You should have received a copy of the GNU Lesser General Public
+ * License along with this library; if not, see <https://www.gnu.org/licenses/>.
+ *
+ ***************************************************************************/
+
+ 
+//! Stored profiles
+//!
+//! Modems keep their configuration across power cycles: `AT&W` saves the
+//! active settings as the stored profile, `ATZ` restores it and `AT&F`
+//! restores the factory defaults. With a [`SettingsStore`] installed (see
+//! [`AtParser::set_settings_store`](crate::parser::AtParser::set_settings_store))
+//! the parser serves these three commands itself, unless handlers are
+//! registered for them, and [`load_profile`](crate::parser::AtParser::load_profile)
+//! restores the stored profile at boot. Once `ATZ` has restored the profile,
+//! every registered handler is told through
+//! [`AtContext::on_reset`](crate::context::AtContext::on_reset), and `OK` is
+//! sent after the last one returns.
+//!
+//! A profile holds the settings of the parser: echo (`ATE`), verbose
+//! (`ATV`) and quiet (`ATQ`) result codes, `AT+CMEE` and, when enabled,
+//! the S-registers. The store appends settings of the application to it
+//! with [`save_user`](SettingsStore::save_user), up to [`PROFILE_SIZE`]
+//! bytes in all. The store only reads and writes an opaque blob, e.g. to a
+//! flash page:
+//!
+//! ```text
+//! AT&W  → SettingsStore::write(blob)       ATZ  → SettingsStore::read(blob)
+//! AT&F  → parser defaults, SettingsStore::factory_user()
+//! ```
+//!
+//! `AT&V` views the active settings, whether a store is installed or not.
+//! The parser lists its own, then every [`SettingsView`] installed with
+//! [`set_settings_views`](crate::parser::AtParser::set_settings_views) adds
+//! lines of its own:
+//!
+//! ```text
+//! AT&V
+//! ACTIVE PROFILE:
+//! E1 Q0 V1 +CMEE=0
+//! S00:000 S01:000 S02:043 S03:013 S04:010 S05:008 S06:002 S07:050
+//! S08:002 S09:000 S10:014 S11:000 S12:050 S13:000 S14:000 S15:000
+//! +IPR: 115200
+//! OK
+//! ```
+
+use core::fmt;
+
+use crate::AtError;
+use crate::cme::CmeMode;
+use crate::response::Framing;
+use crate::sreg::{S_REGISTER_COUNT, SRegisters};
+
+/// Size of the largest profile, settings of the application included
+pub const PROFILE_SIZE: usize = 128;
+
+/// First byte of a profile
+const MAGIC: u8 = 0xa7;
+/// Layout version of a profile
+const VERSION: u8 = 1;
+/// Flag bits of a profile
+const ECHO: u8 = 0x01;
+const VERBOSE: u8 = 0x02;
+const QUIET: u8 = 0x04;
+const REGISTERS: u8 = 0x08;
+/// Bytes of a profile before the settings of the application
+const HEADER: usize = 4 + S_REGISTER_COUNT;
+
+/// Non-volatile storage of a profile
+///
+/// # Example
+///
+/// ```rust
+/// use at_parser_rs::AtError;
+/// use at_parser_rs::profile::SettingsStore;
+///
+/// struct Flash {
+///     page: [u8; 128],
+///     len: usize,
+///     baud: u32,
+/// }
+///
+/// impl SettingsStore for Flash {
+///     fn read(&mut self, buf: &mut [u8]) -> Option<usize> {
+///         buf[..self.len].copy_from_slice(&self.page[..self.len]);
+///         (self.len > 0).then_some(self.len)
+///     }
+///
+///     fn write(&mut self, profile: &[u8]) -> Result<(), AtError<'static>> {
+///         self.page[..profile.len()].copy_from_slice(profile);
+///         self.len = profile.len();
+///         Ok(())
+///     }
+///
+///     fn save_user(&mut self, buf: &mut [u8]) -> usize {
+///         buf[..4].copy_from_slice(&self.baud.to_le_bytes());
+///         4
+///     }
+///
+///     fn load_user(&mut self, settings: &[u8]) {
+///         if let Ok(baud) = settings.try_into() {
+///             self.baud = u32::from_le_bytes(baud);
+///         }
+///     }
+///
+///     fn factory_user(&mut self) {
+///         self.baud = 115_200;
+///     }
+/// }
+/// ```
+pub trait SettingsStore {
+    /// Read the stored profile into `buf`, of [`PROFILE_SIZE`] bytes,
+    /// returning its length, or `None` when no profile is stored.
+    fn read(&mut self, buf: &mut [u8]) -> Option<usize>;
+
+    /// Store `profile`, replacing the stored one.
+    ///
+    /// The error is reported as the result of `AT&W`.
+    fn write(&mut self, profile: &[u8]) -> Result<(), AtError<'static>>;
+
+    /// Write the settings of the application to save with the profile into
+    /// `buf`, returning their length.
+    fn save_user(&mut self, _buf: &mut [u8]) -> usize {
+        0
+    }
+
+    /// Restore the settings of the application saved with the profile.
+    fn load_user(&mut self, _settings: &[u8]) {
+    }
+
+    /// Restore the factory settings of the application (`AT&F`).
+    fn factory_user(&mut self) {
+    }
+}
+
+/// Settings of the application listed by `AT&V`
+///
+/// # Example
+///
+/// ```rust
+/// use core::fmt;
+/// use at_parser_rs::profile::{SettingsView, View};
+///
+/// struct Uart {
+///     baud: u32,
+/// }
+///
+/// impl SettingsView for Uart {
+///     fn view(&mut self, view: &mut View<'_>) -> fmt::Result {
+///         view.line(format_args!("+IPR: {}", self.baud))
+///     }
+/// }
+/// ```
+pub trait SettingsView {
+    /// Add the lines listing the settings to `view`.
+    fn view(&mut self, view: &mut View<'_>) -> fmt::Result;
+}
+
+/// Lines of the `AT&V` response
+///
+/// Each line is sent right away when the response framing layer is enabled,
+/// or appended to the information text of the response otherwise.
+pub struct View<'v> {
+    /// Receiver of each line
+    line: &'v mut dyn FnMut(fmt::Arguments<'_>) -> fmt::Result,
+}
+
+impl<'v> View<'v> {
+    /// Hand each line to `line`.
+    pub(crate) fn new(line: &'v mut dyn FnMut(fmt::Arguments<'_>) -> fmt::Result) -> Self {
+        Self { line }
+    }
+
+    /// Add one line, without its line ending.
+    pub fn line(&mut self, text: impl fmt::Display) -> fmt::Result {
+        (self.line)(format_args!("{text}"))
+    }
+}
+
+/// S-registers listed on a line of `AT&V`
+const ROW: usize = 8;
+
+/// Line of `AT&V` listing the S-registers from `first`
+struct Row<'r> {
+    registers: &'r SRegisters,
+    first: usize,
+}
+
+impl fmt::Display for Row<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for index in self.first..(self.first + ROW).min(S_REGISTER_COUNT) {
+            if index > self.first {
+                f.write_str(" ")?;
+            }
+            write!(f, "S{index:02}:{:03}", self.registers.get(index).unwrap_or(0))?;
+        }
+        Ok(())
+    }
+}
+
+/// Settings of the parser a profile holds
+pub(crate) struct Settings<'s> {
+    /// Command echo (`ATE`)
+    pub(crate) echo: &'s mut bool,
+    /// Result code settings (`ATV`, `ATQ`, `AT+CMEE`)
+    pub(crate) framing: &'s mut Framing,
+    /// S-registers, when enabled
+    pub(crate) registers: Option<&'s mut SRegisters>,
+}
+
+impl Settings<'_> {
+    /// Save the active settings to `store` (`AT&W`).
+    pub(crate) fn save(&self, store: &mut dyn SettingsStore) -> Result<(), AtError<'static>> {
+        let mut profile = [0; PROFILE_SIZE];
+        let mut flags = 0;
+        for (set, flag) in [(*self.echo, ECHO), (self.framing.verbose(), VERBOSE), (self.framing.quiet(), QUIET)] {
+            if set {
+                flags |= flag;
+            }
+        }
+        if let Some(registers) = self.registers.as_deref() {
+            flags |= REGISTERS;
+            for (index, slot) in profile[4..HEADER].iter_mut().enumerate() {
+                *slot = registers.get(index).unwrap_or(0);
+            }
+        }
+        profile[..4].copy_from_slice(&[MAGIC, VERSION, flags, self.framing.cme_mode().value()]);
+
+        let user = store.save_user(&mut profile[HEADER..]).min(PROFILE_SIZE - HEADER);
+        store.write(&profile[..HEADER + user])
+    }
+
+    /// Restore the settings stored in `store` (`ATZ`).
+    ///
+    /// Returns `false`, changing nothing, when no valid profile is stored.
+    pub(crate) fn load(&mut self, store: &mut dyn SettingsStore) -> bool {
+        let mut profile = [0; PROFILE_SIZE];
+        let Some(len) = store.read(&mut profile).filter(|len| (HEADER..=PROFILE_SIZE).contains(len)) else {
+            return false;
+        };
+        let [magic, version, flags, cme] = [profile[0], profile[1], profile[2], profile[3]];
+        let Some(cme) = CmeMode::from_value(cme).filter(|_| magic == MAGIC && version == VERSION) else {
+            return false;
+        };
+
+        // Checked in full before anything is applied
+        let mut registers = SRegisters::new();
+        if flags & REGISTERS != 0 {
+            for (index, &value) in profile[4..HEADER].iter().enumerate() {
+                if registers.set(index, value).is_err() {
+                    return false;
+                }
+            }
+        }
+
+        *self.echo = flags & ECHO != 0;
+        self.framing.set_verbose(flags & VERBOSE != 0);
+        self.framing.set_quiet(flags & QUIET != 0);
+        self.framing.set_cme_mode(cme);
+        if let Some(active) = self.registers.as_deref_mut()
+            && flags & REGISTERS != 0 {
+            *active = registers;
+        }
+        store.load_user(&profile[HEADER..len]);
+        true
+    }
+
+    /// List the active settings (`AT&V`).
+    pub(crate) fn view(&self, view: &mut View<'_>) -> fmt::Result {
+        view.line("ACTIVE PROFILE:")?;
+        view.line(format_args!(
+            "E{} Q{} V{} +CMEE={}",
+            u8::from(*self.echo),
+            u8::from(self.framing.quiet()),
+            u8::from(self.framing.verbose()),
+            self.framing.cme_mode().value(),
+        ))?;
+
+        let Some(registers) = self.registers.as_deref() else {
+            return Ok(());
+        };
+        for first in (0..S_REGISTER_COUNT).step_by(ROW) {
+            view.line(Row { registers, first })?;
+        }
+        Ok(())
+    }
+
+    /// Restore the factory defaults (`AT&F`).
+    pub(crate) fn factory(&mut self, store: &mut dyn SettingsStore) {
+        let defaults = Framing::new();
+        *self.echo = false;
+        self.framing.set_verbose(defaults.verbose());
+        self.framing.set_quiet(defaults.quiet());
+        self.framing.set_cme_mode(defaults.cme_mode());
+        if let Some(registers) = self.registers.as_deref_mut() {
+            registers.reset();
+        }
+        store.factory_user();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::string::String;
+    use alloc::vec::Vec;
+    use core::cell::RefCell;
+    use core::fmt::{self, Write};
+
+    use super::{SettingsStore, SettingsView, View};
+    use crate::AtError;
+    use crate::context::AtContext;
+    use crate::parser::AtParser;
+    use crate::sreg::S7;
+
+    const SIZE: usize = 64;
+
+    /// Counts the resets it is told of
+    #[derive(Default)]
+    struct Modem {
+        resets: usize,
+    }
+
+    impl AtContext<SIZE> for Modem {
+        fn on_reset(&mut self) {
+            self.resets += 1;
+        }
+    }
+
+    /// Flash page holding the profile, with a baud rate of the application
+    struct Flash {
+        page: Vec<u8>,
+        baud: u8,
+    }
+
+    impl SettingsStore for Flash {
+        fn read(&mut self, buf: &mut [u8]) -> Option<usize> {
+            buf[..self.page.len()].copy_from_slice(&self.page);
+            (!self.page.is_empty()).then_some(self.page.len())
+        }
+
+        fn write(&mut self, profile: &[u8]) -> Result<(), AtError<'static>> {
+            self.page = profile.to_vec();
+            Ok(())
+        }
+
+        fn save_user(&mut self, buf: &mut [u8]) -> usize {
+            buf[0] = self.baud;
+            1
+        }
+
+        fn load_user(&mut self, settings: &[u8]) {
+            self.baud = settings[0];
+        }
+
+        fn factory_user(&mut self) {
+            self.baud = 0;
+        }
+    }
+
+    impl SettingsView for Flash {
+        fn view(&mut self, view: &mut View<'_>) -> fmt::Result {
+            view.line(format_args!("+IPR: {}", self.baud))
+        }
+    }
+
+    struct Uart<'l>(&'l RefCell<String>);
+
+    impl Write for Uart<'_> {
+        fn write_str(&mut self, s: &str) -> fmt::Result {
+            self.0.borrow_mut().push_str(s);
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn profile_is_saved_restored_and_reset() {
+        let mut flash = Flash { page: Vec::new(), baud: 9 };
+        {
+            let mut parser: AtParser<Modem, SIZE> = AtParser::new();
+            parser.enable_s_registers();
+            assert!(parser.execute("AT&W").is_err());
+
+            parser.set_settings_store(&mut flash);
+            assert!(!parser.load_profile());
+            assert!(parser.execute("ATE1").is_ok());
+            assert!(parser.execute("ATV0S7=60&W").is_ok());
+
+            assert!(parser.execute("AT&F").is_ok());
+            assert!(!parser.echo());
+            assert!(parser.framing().verbose());
+            assert_eq!(parser.s_registers().and_then(|registers| registers.get(S7)), Some(50));
+
+            assert!(parser.execute("ATZ0").is_ok());
+            assert!(parser.echo());
+            assert!(!parser.framing().verbose());
+            assert_eq!(parser.s_registers().and_then(|registers| registers.get(S7)), Some(60));
+            assert!(parser.execute("ATZ1").is_err());
+        }
+        assert_eq!(flash.baud, 9);
+
+        // A corrupt profile is ignored at boot
+        flash.page[0] = 0;
+        {
+            let mut parser: AtParser<Modem, SIZE> = AtParser::new();
+            parser.set_settings_store(&mut flash);
+            assert!(!parser.load_profile());
+            assert!(parser.execute("ATZ").is_ok());
+        }
+        assert_eq!(flash.baud, 0);
+    }
+
+    #[test]
+    fn view_streams_the_active_settings() {
+        let log = RefCell::new(String::new());
+        let mut uart = Uart(&log);
+        let mut flash = Flash { page: Vec::new(), baud: 96 };
+        let views: &mut [&mut dyn SettingsView] = &mut [&mut flash];
+        let mut parser: AtParser<Modem, SIZE> = AtParser::new();
+        parser.enable_s_registers();
+        parser.set_settings_views(views);
+        parser.set_output(&mut uart);
+        parser.set_response_framing(true);
+
+        parser.feed_slice(b"ATS0=2V1&V\r");
+        assert_eq!(
+            log.borrow().as_str(),
+            "\r\nACTIVE PROFILE:\r\nE0 Q0 V1 +CMEE=0\r\n\
+             S00:002 S01:000 S02:043 S03:013 S04:010 S05:008 S06:002 S07:050\r\n\
+             S08:002 S09:000 S10:014 S11:000 S12:050 S13:000 S14:000 S15:000\r\n\
+             +IPR: 96\r\n\r\nOK\r\n"
+        );
+        assert!(parser.execute("AT&V1").is_err());
+    }
+
+    #[test]
+    fn reset_tells_every_handler_once_restored() {
+        let mut flash = Flash { page: Vec::new(), baud: 0 };
+        let (mut sms, mut dial) = (Modem::default(), Modem::default());
+        {
+            let commands: &mut [(&str, &str, &mut Modem)] = &mut [("AT+CMGS", "", &mut sms)];
+            let prefixes: &mut [(&str, &str, &mut Modem)] = &mut [("ATD", "", &mut dial)];
+            let mut parser: AtParser<Modem, SIZE> = AtParser::new();
+            parser.set_commands(commands);
+            parser.set_prefix_commands(prefixes);
+            parser.set_settings_store(&mut flash);
+
+            assert!(parser.execute("ATZ").is_ok());
+            assert!(parser.execute("ATZ5").is_err());
+            assert!(parser.execute("AT&F").is_ok());
+        }
+        assert_eq!((sms.resets, dial.resets), (1, 1));
+    }
+}
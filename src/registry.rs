@@ -0,0 +1,285 @@
+/***************************************************************************
+ *
+ * AT Command Parser
+ * Copyright (C) 2026 Antonio Salsi <passy.linux@zresa.it>
+ *
+ * This library is free software; you can redistribute it and/or
+ * modify it under the terms of the GNU Lesser General Public
+ * License as published by the Free Software Foundation; either
+ * version 2.1 of the License, or (at your option) any later version.
+ *
+ * This library is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the GNU
+ * Lesser General Public License for more details.
+ *
+ * You should have received a copy of the GNU Lesser General Public
+ * License along with this library; if not, see <https://www.gnu.org/licenses/>.
+ *
+ ***************************************************************************/
+
+ 
+//! Distributed command registration
+//!
+//! Handlers defined across modules are registered where they are declared,
+//! with the `at_command` attribute (`derive` feature), and gathered at run
+//! time by [`collect_commands!`](crate::collect_commands) without a central
+//! hand-maintained table:
+//!
+//! ```text
+//! // gmr.rs
+//! #[at_command(SIZE, "+GMR")]
+//! #[derive(Default)]
+//! struct Revision;
+//!
+//! // main.rs
+//! let mut registry = collect_commands!(SIZE);
+//! let mut commands = registry.commands();
+//! parser.set_commands(&mut commands);
+//! ```
+//!
+//! Each registration is a [`Registration`] placed by the linker into a
+//! dedicated section, next to the other ones; the section bounds are read
+//! through the symbols the linker defines for it. This works with ELF targets
+//! (Linux, bare-metal `*-none-eabi`) and Apple targets. Every registered handler
+//! is created with [`Default`] and boxed, so the `alloc` crate must have a
+//! global allocator.
+//!
+//! With the `std` feature, host-side tools and simulators also build a
+//! registry at run time, from handlers they create themselves:
+//!
+//! ```text
+//! let mut registry = Registry::new();
+//! registry.register("AT+GMR", "+GMR: ", Revision);
+//! let mut commands = registry.commands();
+//! parser.set_commands(&mut commands);
+//! ```
+
+use alloc::boxed::Box;
+use alloc::vec::Vec;
+
+use crate::context::AtContext;
+
+/// Command registered where its handler is declared.
+///
+/// Built by the `at_command` attribute; see the [module documentation](self).
+/// Registrations of every response size share the section, so the type does
+/// not depend on the size: the maker is stored erased, and only cast back
+/// once [`Registry::from_section`] has checked the size.
+#[repr(C)]
+pub struct Registration {
+    /// Command matched by the parser (`"AT+GMR"`)
+    command: &'static str,
+    /// Response prefix handed to the handler (`"+GMR: "`)
+    response: &'static str,
+    /// Response size the handler is built for
+    size: usize,
+    /// Create the handler: a `fn() -> Box<dyn AtContext<SIZE>>` for `size`;
+    /// `None` for placeholders
+    make: Option<fn()>,
+}
+
+impl Registration {
+    /// Register a `T` handler, created with [`Default`], for `command`.
+    pub const fn of<const SIZE: usize, T>(command: &'static str, response: &'static str) -> Self
+    where
+        T: AtContext<SIZE> + Default + 'static {
+        let make: fn() -> Box<dyn AtContext<SIZE>> = make::<SIZE, T>;
+        // SAFETY: function pointers share one representation whatever their
+        // signature; `from_section` casts it back only for `SIZE`
+        let make = unsafe { core::mem::transmute::<fn() -> Box<dyn AtContext<SIZE>>, fn()>(make) };
+        Self { command, response, size: SIZE, make: Some(make) }
+    }
+
+    /// Entry keeping the section defined when nothing is registered.
+    #[doc(hidden)]
+    pub const fn placeholder() -> Self {
+        Self { command: "", response: "", size: 0, make: None }
+    }
+
+    /// Command matched by the parser.
+    pub fn command(&self) -> &'static str {
+        self.command
+    }
+
+    /// Response prefix handed to the handler.
+    pub fn response(&self) -> &'static str {
+        self.response
+    }
+}
+
+fn make<const SIZE: usize, T>() -> Box<dyn AtContext<SIZE>>
+where
+    T: AtContext<SIZE> + Default + 'static {
+    Box::new(T::default())
+}
+
+/// Handlers of every registered command, built by
+/// [`collect_commands!`](crate::collect_commands).
+pub struct Registry<const SIZE: usize> {
+    handlers: Vec<(&'static str, &'static str, Box<dyn AtContext<SIZE>>)>,
+}
+
+#[cfg(feature = "std")]
+impl<const SIZE: usize> Registry<SIZE> {
+    /// Create an empty registry, filled with [`register`](Registry::register).
+    pub fn new() -> Self {
+        Self { handlers: Vec::new() }
+    }
+
+    /// Register `handler` for `command`, answering with the `response`
+    /// prefix.
+    pub fn register<T>(&mut self, command: &'static str, response: &'static str, handler: T)
+    where
+        T: AtContext<SIZE> + 'static {
+        self.handlers.push((command, response, Box::new(handler)));
+    }
+}
+
+#[cfg(feature = "std")]
+impl<const SIZE: usize> Default for Registry<SIZE> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const SIZE: usize> Registry<SIZE> {
+    /// Create the handlers registered between `start` and `stop`.
+    ///
+    /// Registrations built for another response size are skipped.
+    ///
+    /// # Safety
+    ///
+    /// `start..stop` must be the bounds of the registration section, as
+    /// provided by the linker.
+    #[doc(hidden)]
+    pub unsafe fn from_section(start: *const Registration, stop: *const Registration) -> Self {
+        let len = (stop as usize - start as usize) / size_of::<Registration>();
+        // SAFETY: the linker lays the registrations out contiguously between
+        // the section bounds
+        let registrations = unsafe { core::slice::from_raw_parts(start, len) };
+        let handlers = registrations
+            .iter()
+            .filter(|registration| registration.size == SIZE)
+            .filter_map(|registration| {
+                // SAFETY: `of` erased a maker of this very size
+                let make = unsafe { core::mem::transmute::<fn(), fn() -> Box<dyn AtContext<SIZE>>>(registration.make?) };
+                Some((registration.command, registration.response, make()))
+            })
+            .collect();
+        Self { handlers }
+    }
+
+    /// Number of registered commands.
+    pub fn len(&self) -> usize {
+        self.handlers.len()
+    }
+
+    /// Whether no command is registered.
+    pub fn is_empty(&self) -> bool {
+        self.handlers.is_empty()
+    }
+
+    /// Command table borrowing the handlers, for
+    /// [`AtParser::set_commands`](crate::parser::AtParser::set_commands).
+    pub fn commands(&mut self) -> Vec<(&'static str, &'static str, &mut dyn AtContext<SIZE>)> {
+        self.handlers
+            .iter_mut()
+            .map(|(command, response, handler)| (*command, *response, &mut **handler as &mut dyn AtContext<SIZE>))
+            .collect()
+    }
+}
+
+/// Gather the commands registered with the `at_command` attribute into a
+/// [`Registry`](crate::registry::Registry).
+///
+/// # Example
+///
+/// ```rust,ignore
+/// let mut registry = collect_commands!(SIZE);
+/// let mut commands = registry.commands();
+/// let mut parser: DynAtParser<SIZE> = DynAtParser::new();
+/// parser.set_commands(&mut commands);
+/// ```
+#[macro_export]
+macro_rules! collect_commands {
+    ($size:expr) => {{
+        #[used]
+        #[cfg_attr(not(target_vendor = "apple"), unsafe(link_section = "at_parser_commands"))]
+        #[cfg_attr(target_vendor = "apple", unsafe(link_section = "__DATA,__atparsercmds,regular,no_dead_strip"))]
+        static PLACEHOLDER: $crate::registry::Registration = $crate::registry::Registration::placeholder();
+
+        unsafe extern "Rust" {
+            #[cfg_attr(not(target_vendor = "apple"), link_name = "__start_at_parser_commands")]
+            #[cfg_attr(target_vendor = "apple", link_name = "\x01section$start$__DATA$__atparsercmds")]
+            static START: $crate::registry::Registration;
+            #[cfg_attr(not(target_vendor = "apple"), link_name = "__stop_at_parser_commands")]
+            #[cfg_attr(target_vendor = "apple", link_name = "\x01section$end$__DATA$__atparsercmds")]
+            static STOP: $crate::registry::Registration;
+        }
+
+        // SAFETY: the symbols are the bounds the linker defines for the section
+        unsafe { $crate::registry::Registry::<{ $size }>::from_section(&raw const START, &raw const STOP) }
+    }};
+}
+
+#[cfg(all(test, feature = "derive"))]
+mod tests {
+    use alloc::format;
+
+    use super::*;
+    use crate::parser::DynAtParser;
+    use crate::{AtResponse, AtResult, at_command, at_response};
+
+    const SIZE: usize = 32;
+
+    #[at_command(SIZE, "+GMR")]
+    #[derive(Default)]
+    struct Revision;
+
+    impl AtContext<SIZE> for Revision {
+        fn exec(&mut self, at_response: &'static str) -> AtResult<'_, SIZE> {
+            Ok(at_response!(SIZE, at_response; "1.0"))
+        }
+    }
+
+    #[at_command(SIZE, "AT+GMI", "Maker: ")]
+    #[derive(Default)]
+    struct Manufacturer {
+        queries: u8,
+    }
+
+    impl AtContext<SIZE> for Manufacturer {
+        fn exec(&mut self, at_response: &'static str) -> AtResult<'_, SIZE> {
+            self.queries += 1;
+            Ok(at_response!(SIZE, at_response; self.queries))
+        }
+    }
+
+    // Shares the section with the handlers of `SIZE`, and is skipped by
+    // their registry
+    #[at_command(16, "+GSN")]
+    #[derive(Default)]
+    struct Serial;
+
+    impl AtContext<16> for Serial {}
+
+        #[test]
+    fn registered_commands_are_collected() {
+        let mut registry = collect_commands!(SIZE);
+        assert_eq!(registry.len(), 2);
+
+        let mut commands = registry.commands();
+        let mut parser: DynAtParser<SIZE> = DynAtParser::new();
+        parser.set_commands(&mut commands);
+
+        assert!(matches!(parser.execute("AT+GMR"), Ok(("+GMR: ", AtResponse::InfoText(text))) if format!("{text}") == "1.0"));
+        assert!(parser.execute("AT+GMI").is_ok());
+        assert!(matches!(parser.execute("AT+GMI"), Ok(("Maker: ", AtResponse::InfoText(text))) if format!("{text}") == "2"));
+    }
+
+    #[test]
+    fn registrations_for_another_size_are_skipped() {
+        assert!(collect_commands!(SIZE + 1).is_empty());
+    }
+}
@@ -0,0 +1,1237 @@
+/***************************************************************************
+ *
+ * AT Command Parser
+ * Copyright (C) 2026 Antonio Salsi <passy.linux@zresa.it>
+ *
+ * This library is free software; you can redistribute it and/or
+ * modify it under the terms of the GNU Lesser General Public
+ * License as published by the Free Software Foundation; either
+ * version 2.1 of the License, or (at your option) any later version.
+ *
+ * This library is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the GNU
+ * Lesser General Public License for more details.
+ *
+ * You should have received a copy of the GNU Lesser General Public
+ * License along with this library; if not, see <https://www.gnu.org/licenses/>.
+ *
+ ***************************************************************************/
+
+ 
+//! Response framing layer
+//!
+//! Turns the result of a command line into the V.250 response format:
+//! information text wrapped in response line endings, followed by the final
+//! result code (`OK` / `ERROR`).
+//!
+//! ```text
+//! <CR><LF>+VAL: 2<CR><LF>
+//! <CR><LF>OK<CR><LF>
+//! ```
+//!
+//! In quiet mode (`ATQ1`) result codes are suppressed while information text
+//! is still sent. In numeric mode (`ATV0`) information text is only
+//! terminated, and result codes are sent as their number followed by `S3`
+//! alone:
+//!
+//! ```text
+//! +VAL: 2<CR><LF>
+//! 0<CR>
+//! ```
+//!
+//! Unsolicited result codes (`+CREG: 1`, `RING`) use the same framing as
+//! information text and result codes, see
+//! [`AtParser::emit_urc`](crate::parser::AtParser::emit_urc).
+//!
+//! Handlers reach the output during execution through a [`Responder`], e.g. to
+//! send `CONNECT` before entering online data state, or to list many lines of
+//! information text:
+//!
+//! ```text
+//! <CR><LF>+CMGL: 1,"REC READ"<CR><LF>
+//! +CMGL: 2,"REC UNREAD"<CR><LF>
+//! <CR><LF>OK<CR><LF>
+//! ```
+//!
+//! Each kind of error has a built-in result code (`ERROR`, `+CME ERROR: <n>`,
+//! `ABORTED`); an [error map](Framing::set_error_map) renders chosen ones
+//! otherwise, without changes in the handlers that return them.
+
+use core::convert::Infallible;
+use core::fmt::{self, Write};
+
+use crate::{AtError, AtResponse, AtResult, UserError};
+use crate::abort::Abort;
+use crate::cme::{self, CmeMode};
+use crate::cms;
+use crate::context::Form;
+use crate::help::Help;
+use crate::line::LineEnding;
+
+/// V.250 result code.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum ResultCode {
+    /// Command line executed successfully (`OK`, `0`)
+    Ok,
+    /// Connection established, with the optional data rate (`CONNECT 115200`, `1`)
+    Connect(Option<u32>),
+    /// Incoming call (`RING`, `2`)
+    Ring,
+    /// Connection terminated or attempt failed (`NO CARRIER`, `3`)
+    NoCarrier,
+    /// Command line not recognized or failed (`ERROR`, `4`)
+    Error,
+    /// No dial tone detected (`NO DIALTONE`, `6`)
+    NoDialtone,
+    /// Engaged signal detected (`BUSY`, `7`)
+    Busy,
+    /// Remote end did not answer (`NO ANSWER`, `8`)
+    NoAnswer,
+}
+
+impl ResultCode {
+    /// Text sent in verbose mode (`ATV1`).
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use at_parser_rs::response::ResultCode;
+    ///
+    /// assert_eq!(ResultCode::Error.verbose(), "ERROR");
+    /// ```
+    pub const fn verbose(self) -> &'static str {
+        match self {
+            ResultCode::Ok => "OK",
+            ResultCode::Connect(_) => "CONNECT",
+            ResultCode::Ring => "RING",
+            ResultCode::NoCarrier => "NO CARRIER",
+            ResultCode::Error => "ERROR",
+            ResultCode::NoDialtone => "NO DIALTONE",
+            ResultCode::Busy => "BUSY",
+            ResultCode::NoAnswer => "NO ANSWER",
+        }
+    }
+
+    /// Number sent in numeric mode (`ATV0`).
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use at_parser_rs::response::ResultCode;
+    ///
+    /// assert_eq!(ResultCode::Error.numeric(), 4);
+    /// ```
+    pub const fn numeric(self) -> u8 {
+        match self {
+            ResultCode::Ok => 0,
+            ResultCode::Connect(_) => 1,
+            ResultCode::Ring => 2,
+            ResultCode::NoCarrier => 3,
+            ResultCode::Error => 4,
+            ResultCode::NoDialtone => 6,
+            ResultCode::Busy => 7,
+            ResultCode::NoAnswer => 8,
+        }
+    }
+
+    /// Parse a result code sent in verbose mode, with the data rate of
+    /// `CONNECT`, as received on the DTE side.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use at_parser_rs::response::ResultCode;
+    ///
+    /// assert_eq!(ResultCode::from_verbose("CONNECT 9600"), Some(ResultCode::Connect(Some(9600))));
+    /// assert_eq!(ResultCode::from_verbose("+CSQ: 23,0"), None);
+    /// ```
+    pub fn from_verbose(text: &str) -> Option<Self> {
+        let code = match text.trim() {
+            "OK" => ResultCode::Ok,
+            "RING" => ResultCode::Ring,
+            "NO CARRIER" => ResultCode::NoCarrier,
+            "ERROR" => ResultCode::Error,
+            "NO DIALTONE" => ResultCode::NoDialtone,
+            "BUSY" => ResultCode::Busy,
+            "NO ANSWER" => ResultCode::NoAnswer,
+            "CONNECT" => ResultCode::Connect(None),
+            text => ResultCode::Connect(Some(text.strip_prefix("CONNECT ")?.trim().parse().ok()?)),
+        };
+        Some(code)
+    }
+
+    /// Parse a result code sent in numeric mode (`ATV0`); `CONNECT` carries
+    /// no data rate.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use at_parser_rs::response::ResultCode;
+    ///
+    /// assert_eq!(ResultCode::from_numeric(7), Some(ResultCode::Busy));
+    /// assert_eq!(ResultCode::from_numeric(5), None);
+    /// ```
+    pub const fn from_numeric(code: u8) -> Option<Self> {
+        let code = match code {
+            0 => ResultCode::Ok,
+            1 => ResultCode::Connect(None),
+            2 => ResultCode::Ring,
+            3 => ResultCode::NoCarrier,
+            4 => ResultCode::Error,
+            6 => ResultCode::NoDialtone,
+            7 => ResultCode::Busy,
+            8 => ResultCode::NoAnswer,
+            _ => return None,
+        };
+        Some(code)
+    }
+
+    /// Outcome of a call attempt this code reports, `None` for `OK`, `RING`
+    /// and `ERROR`.
+    pub const fn call_progress(self) -> Option<CallProgress> {
+        match self {
+            ResultCode::Connect(rate) => Some(CallProgress::Connected(rate)),
+            ResultCode::NoCarrier => Some(CallProgress::NoCarrier),
+            ResultCode::NoDialtone => Some(CallProgress::NoDialtone),
+            ResultCode::Busy => Some(CallProgress::Busy),
+            ResultCode::NoAnswer => Some(CallProgress::NoAnswer),
+            ResultCode::Ok | ResultCode::Ring | ResultCode::Error => None,
+        }
+    }
+}
+
+/// Outcome of a dial (`ATD`) or answer (`ATA`) command.
+///
+/// A call-control handler returns [`AtResponse::Deferred`] while the call is
+/// set up and reports the outcome with
+/// [`AtParser::complete_call`](crate::parser::AtParser::complete_call):
+/// `CONNECT` succeeds and enters online data mode, every other outcome is
+/// the final result code of a failed command.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum CallProgress {
+    /// Call answered, with the optional data rate (`CONNECT 9600`)
+    Connected(Option<u32>),
+    /// No carrier detected or the connection was lost (`NO CARRIER`)
+    NoCarrier,
+    /// No dial tone on the line (`NO DIALTONE`)
+    NoDialtone,
+    /// Remote end engaged (`BUSY`)
+    Busy,
+    /// Remote end did not answer in time (`NO ANSWER`)
+    NoAnswer,
+}
+
+impl CallProgress {
+    /// Result code that reports this outcome.
+    pub const fn code(self) -> ResultCode {
+        match self {
+            CallProgress::Connected(rate) => ResultCode::Connect(rate),
+            CallProgress::NoCarrier => ResultCode::NoCarrier,
+            CallProgress::NoDialtone => ResultCode::NoDialtone,
+            CallProgress::Busy => ResultCode::Busy,
+            CallProgress::NoAnswer => ResultCode::NoAnswer,
+        }
+    }
+
+    /// Command result that reports this outcome: a
+    /// [`FinalCode`](AtResponse::FinalCode) success for `CONNECT`, an
+    /// [`AtError::Final`] failure otherwise.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use at_parser_rs::{AtError, AtResult};
+    /// use at_parser_rs::response::{CallProgress, ResultCode};
+    ///
+    /// let result: AtResult<'_, 64> = CallProgress::Busy.result("");
+    /// assert!(matches!(result, Err(("", AtError::Final(ResultCode::Busy)))));
+    /// ```
+    pub fn result<'a, const SIZE: usize, E>(self, at_response: &'static str) -> AtResult<'a, SIZE, E> {
+        match self {
+            CallProgress::Connected(rate) => Ok((at_response, AtResponse::FinalCode(ResultCode::Connect(rate)))),
+            progress => Err((at_response, AtError::Final(progress.code()))),
+        }
+    }
+}
+
+impl From<CallProgress> for ResultCode {
+    fn from(progress: CallProgress) -> Self {
+        progress.code()
+    }
+}
+
+/// Kind of error matched by an [error map](Framing::set_error_map)
+///
+/// [`AtError::Argument`] is matched as [`InvalidArgs`](ErrorKind::InvalidArgs),
+/// both [`Unhandled`](AtError::Unhandled) variants as
+/// [`Unhandled`](ErrorKind::Unhandled), and [`AtError::User`] as the error
+/// [`UserError::report`] returns.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ErrorKind {
+    /// [`AtError::UnknownCommand`]
+    UnknownCommand,
+    /// [`AtError::NotSupported`]
+    NotSupported,
+    /// [`AtError::InvalidArgs`] and [`AtError::Argument`]
+    InvalidArgs,
+    /// [`AtError::Unhandled`] and [`AtError::UnhandledOwned`]
+    Unhandled,
+    /// [`AtError::Cme`] with the given code
+    Cme(u16),
+    /// [`AtError::Cms`] with the given code
+    Cms(u16),
+    /// [`AtError::Vendor`] with the given code
+    Vendor(u16),
+    /// [`AtError::Final`] with the given result code
+    Final(ResultCode),
+    /// [`AtError::Aborted`]
+    Aborted,
+    /// [`AtError::LineTooLong`]
+    LineTooLong,
+}
+
+impl ErrorKind {
+    /// Kind of `error`, `None` for [`AtError::User`].
+    fn of<E>(error: &AtError<'_, E>) -> Option<Self> {
+        let kind = match error {
+            AtError::UnknownCommand => ErrorKind::UnknownCommand,
+            AtError::NotSupported => ErrorKind::NotSupported,
+            AtError::InvalidArgs | AtError::Argument(_) => ErrorKind::InvalidArgs,
+            AtError::Unhandled(_) | AtError::UnhandledOwned(_) => ErrorKind::Unhandled,
+            AtError::Cme(code) => ErrorKind::Cme(*code),
+            AtError::Cms(code) => ErrorKind::Cms(*code),
+            AtError::Vendor(code, _) => ErrorKind::Vendor(*code),
+            AtError::Final(code) => ErrorKind::Final(*code),
+            AtError::Aborted => ErrorKind::Aborted,
+            AtError::LineTooLong => ErrorKind::LineTooLong,
+            AtError::User(_) => return None,
+        };
+        Some(kind)
+    }
+
+    /// Kind of `error`, [`AtError::User`] taking the kind of the error it
+    /// reports.
+    pub(crate) fn reported<E: UserError>(error: &AtError<'_, E>) -> Self {
+        match error {
+            AtError::User(e) => Self::reported(&e.report()),
+            error => Self::of(error).unwrap_or(ErrorKind::Unhandled),
+        }
+    }
+}
+
+/// How an error matched by an [error map](Framing::set_error_map) is
+/// rendered
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Rendering {
+    /// The final result code, e.g. [`ResultCode::Error`]
+    Code(ResultCode),
+    /// `+CME ERROR: <code>`, following the `AT+CMEE` mode
+    Cme(u16),
+    /// `+CMS ERROR: <code>`
+    Cms(u16),
+    /// A line of vendor text, e.g. `#ERROR: NOT SUPPORTED`, suppressed in
+    /// quiet mode
+    Text(&'static str),
+}
+
+/// How [`AtError::Vendor`] errors are reported
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum VendorErrors {
+    /// As `+CME ERROR: <code>`, following the `AT+CMEE` mode like
+    /// [`AtError::Cme`]; the description replaces the code in verbose mode
+    #[default]
+    Cme,
+    /// As the given prefix followed by the code, e.g. `#ERROR: 1012`, whatever
+    /// the `AT+CMEE` mode; the description replaces the code in verbose mode
+    /// (`AT+CMEE=2`)
+    Prefixed(&'static str),
+}
+
+/// Error map of a deployment, see [`Framing::set_error_map`]
+pub type ErrorMap = &'static [(ErrorKind, Rendering)];
+
+/// V.250 response formatting settings.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// use at_parser_rs::response::Framing;
+/// use at_parser_rs::{AtResult, at_response};
+///
+/// const SIZE: usize = 64;
+///
+/// # fn send(out: &mut impl core::fmt::Write) -> core::fmt::Result {
+/// let result: AtResult<'_, SIZE> = Ok(at_response!(SIZE, "+VAL: "; 2));
+/// Framing::new().write_result(out, &result)?;   // "\r\n+VAL: 2\r\n\r\nOK\r\n"
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Framing {
+    /// Characters framing every response line (V.250 `S3` + `S4`)
+    ending: LineEnding,
+    /// Whether result codes are sent as text (`ATV1`) or numbers (`ATV0`)
+    verbose: bool,
+    /// Whether result codes are suppressed (`ATQ1`)
+    quiet: bool,
+    /// How [`AtError::Cme`] errors are reported (`AT+CMEE`)
+    cme: CmeMode,
+    /// Renderings overriding the built-in ones of errors
+    errors: ErrorMap,
+    /// How [`AtError::Vendor`] errors are reported
+    vendor: VendorErrors,
+}
+
+impl Framing {
+    /// Create the default framing: verbose result codes that are not
+    /// suppressed, response lines wrapped in `\r\n`.
+    pub const fn new() -> Self {
+        Self { ending: LineEnding::CrLf, verbose: true, quiet: false, cme: CmeMode::Disabled, errors: &[], vendor: VendorErrors::Cme }
+    }
+
+    /// Characters framing every response line.
+    pub fn ending(&self) -> LineEnding {
+        self.ending
+    }
+
+    /// Set the characters framing every response line.
+    pub fn set_ending(&mut self, ending: LineEnding) {
+        self.ending = ending;
+    }
+
+    /// Whether result codes are sent as text (`ATV1`, the default).
+    pub fn verbose(&self) -> bool {
+        self.verbose
+    }
+
+    /// Select verbose (`true`, `ATV1`) or numeric (`false`, `ATV0`) result
+    /// codes.
+    pub fn set_verbose(&mut self, verbose: bool) {
+        self.verbose = verbose;
+    }
+
+    /// Whether result codes are suppressed (`ATQ1`).
+    pub fn quiet(&self) -> bool {
+        self.quiet
+    }
+
+    /// Suppress (`true`, `ATQ1`) or send (`false`, `ATQ0`) result codes.
+    ///
+    /// Information text is sent in both cases.
+    pub fn set_quiet(&mut self, quiet: bool) {
+        self.quiet = quiet;
+    }
+
+    /// How [`AtError::Cme`] errors are reported (`AT+CMEE`).
+    pub fn cme_mode(&self) -> CmeMode {
+        self.cme
+    }
+
+    /// Select how [`AtError::Cme`] errors are reported (`AT+CMEE=<n>`).
+    ///
+    /// Defaults to [`CmeMode::Disabled`]: every error ends with `ERROR`.
+    pub fn set_cme_mode(&mut self, mode: CmeMode) {
+        self.cme = mode;
+    }
+
+    /// Renderings overriding the built-in ones of errors.
+    pub fn error_map(&self) -> ErrorMap {
+        self.errors
+    }
+
+    /// Render the errors of `map` as it says instead of with their built-in
+    /// result code; the first entry matching an error wins.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use at_parser_rs::response::{ErrorKind, ErrorMap, Framing, Rendering};
+    /// use at_parser_rs::{AtError, AtResult};
+    ///
+    /// const SIZE: usize = 64;
+    /// const ERRORS: ErrorMap = &[
+    ///     (ErrorKind::NotSupported, Rendering::Cme(4)),
+    ///     (ErrorKind::UnknownCommand, Rendering::Text("#ERROR: UNKNOWN")),
+    /// ];
+    ///
+    /// let mut framing = Framing::new();
+    /// framing.set_error_map(ERRORS);
+    ///
+    /// let mut out = String::new();
+    /// let result: AtResult<'_, SIZE> = Err(("", AtError::UnknownCommand));
+    /// framing.write_result(&mut out, &result).unwrap();
+    /// assert_eq!(out, "\r\n#ERROR: UNKNOWN\r\n");
+    /// ```
+    pub fn set_error_map(&mut self, map: ErrorMap) {
+        self.errors = map;
+    }
+
+    /// How [`AtError::Vendor`] errors are reported.
+    pub fn vendor_errors(&self) -> VendorErrors {
+        self.vendor
+    }
+
+    /// Select how [`AtError::Vendor`] errors are reported.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use at_parser_rs::response::{Framing, VendorErrors};
+    /// use at_parser_rs::{AtError, AtResult};
+    ///
+    /// const SIZE: usize = 64;
+    ///
+    /// let mut framing = Framing::new();
+    /// framing.set_vendor_errors(VendorErrors::Prefixed("#ERROR: "));
+    ///
+    /// let mut out = String::new();
+    /// let result: AtResult<'_, SIZE> = Err(("", AtError::Vendor(1012, Some("flash busy"))));
+    /// framing.write_result(&mut out, &result).unwrap();
+    /// # #[cfg(not(feature = "basic-errors"))]
+    /// assert_eq!(out, "\r\n#ERROR: 1012\r\n");
+    /// ```
+    pub fn set_vendor_errors(&mut self, vendor: VendorErrors) {
+        self.vendor = vendor;
+    }
+
+    /// Write one line of information text.
+    pub fn write_info<W>(&self, out: &mut W, text: impl fmt::Display) -> fmt::Result
+    where
+        W: Write + ?Sized {
+        let ending = self.ending.as_str();
+        if self.verbose {
+            write!(out, "{ending}{text}{ending}")
+        } else {
+            write!(out, "{text}{ending}")
+        }
+    }
+
+    /// Write a result code, as text or number depending on the mode.
+    ///
+    /// Nothing is written in quiet mode.
+    pub fn write_code<W>(&self, out: &mut W, code: ResultCode) -> fmt::Result
+    where
+        W: Write + ?Sized {
+        let ending = self.ending.as_str();
+        if self.quiet {
+            Ok(())
+        } else if self.verbose {
+            match code {
+                ResultCode::Connect(Some(rate)) => write!(out, "{ending}CONNECT {rate}{ending}"),
+                code => write!(out, "{ending}{}{ending}", code.verbose()),
+            }
+        } else {
+            // numeric codes are terminated by S3 alone
+            write!(out, "{}{}", code.numeric(), &ending[..1])
+        }
+    }
+
+    /// Write the complete response for `result`.
+    ///
+    /// [`AtResponse::Ok`] and an empty [`AtResponse::InfoText`] only produce
+    /// the final result code; nothing is written for
+    /// [`AtResponse::Deferred`].
+    pub fn write_result<W, const SIZE: usize, E>(&self, out: &mut W, result: &AtResult<'_, SIZE, E>) -> fmt::Result
+    where
+        W: Write + ?Sized,
+        E: UserError {
+        match result {
+            Ok((at_response, response)) => {
+                if let Some(text) = response.text() {
+                    self.write_info(out, format_args!("{at_response}{text}"))?;
+                }
+                match response {
+                    AtResponse::FinalCode(code) => self.write_code(out, *code),
+                    AtResponse::Deferred | AtResponse::Continue => Ok(()),
+                    _ => self.write_code(out, ResultCode::Ok),
+                }
+            }
+            Err((_, error)) => self.write_error(out, error),
+        }
+    }
+
+    /// Write the final result code of `error`, as the error map renders it
+    /// or the built-in one.
+    fn write_error<W, E>(&self, out: &mut W, error: &AtError<'_, E>) -> fmt::Result
+    where
+        W: Write + ?Sized,
+        E: UserError {
+        let kind = ErrorKind::of(error);
+        let rendering = self.errors.iter().find(|(matched, _)| Some(*matched) == kind).map(|(_, rendering)| *rendering);
+        match rendering {
+            Some(Rendering::Code(code)) => return self.write_code(out, code),
+            Some(Rendering::Cme(code)) => return self.write_cme(out, code),
+            Some(Rendering::Cms(code)) => return self.write_cms(out, code),
+            Some(Rendering::Text(_)) if self.quiet => return Ok(()),
+            Some(Rendering::Text(text)) => return self.write_info(out, text),
+            None => {}
+        }
+
+        match error {
+            AtError::Cme(code) => self.write_cme(out, *code),
+            AtError::Cms(code) => self.write_cms(out, *code),
+            AtError::Vendor(code, text) => self.write_vendor(out, *code, *text),
+            AtError::Final(code) => self.write_code(out, *code),
+            AtError::Aborted => self.write_aborted(out),
+            AtError::User(e) => self.write_error::<W, Infallible>(out, &e.report()),
+            _ => self.write_code(out, ResultCode::Error),
+        }
+    }
+
+    /// Write the final result code of an aborted command: `ABORTED`, or the
+    /// number of `ERROR` in numeric mode since V.250 assigns none.
+    fn write_aborted<W>(&self, out: &mut W) -> fmt::Result
+    where
+        W: Write + ?Sized {
+        if self.quiet {
+            Ok(())
+        } else if self.verbose {
+            self.write_info(out, "ABORTED")
+        } else {
+            self.write_code(out, ResultCode::Error)
+        }
+    }
+
+    /// Write the final result code for a mobile equipment error.
+    fn write_cme<W>(&self, out: &mut W, code: u16) -> fmt::Result
+    where
+        W: Write + ?Sized {
+        if self.quiet {
+            return Ok(());
+        }
+        if cfg!(feature = "basic-errors") {
+            return self.write_code(out, ResultCode::Error);
+        }
+
+        match (self.cme, error_text(cme::text, code)) {
+            (CmeMode::Disabled, _) => self.write_code(out, ResultCode::Error),
+            (CmeMode::Verbose, Some(text)) => self.write_info(out, format_args!("+CME ERROR: {text}")),
+            _ => self.write_info(out, format_args!("+CME ERROR: {code}")),
+        }
+    }
+
+    /// Write the final result code for an error of the product's own error
+    /// space.
+    fn write_vendor<W>(&self, out: &mut W, code: u16, text: Option<&str>) -> fmt::Result
+    where
+        W: Write + ?Sized {
+        let prefix = match self.vendor {
+            _ if self.quiet => return Ok(()),
+            _ if cfg!(feature = "basic-errors") => return self.write_code(out, ResultCode::Error),
+            VendorErrors::Cme if self.cme == CmeMode::Disabled => return self.write_code(out, ResultCode::Error),
+            VendorErrors::Cme => "+CME ERROR: ",
+            VendorErrors::Prefixed(prefix) => prefix,
+        };
+
+        match (self.cme, text.filter(|_| !cfg!(feature = "no-error-text"))) {
+            (CmeMode::Verbose, Some(text)) => self.write_info(out, format_args!("{prefix}{text}")),
+            _ => self.write_info(out, format_args!("{prefix}{code}")),
+        }
+    }
+
+    /// Write the final result code for a message service error.
+    fn write_cms<W>(&self, out: &mut W, code: u16) -> fmt::Result
+    where
+        W: Write + ?Sized {
+        if self.quiet {
+            return Ok(());
+        }
+        if cfg!(feature = "basic-errors") {
+            return self.write_code(out, ResultCode::Error);
+        }
+
+        match (self.cme, error_text(cms::text, code)) {
+            (CmeMode::Verbose, Some(text)) => self.write_info(out, format_args!("+CMS ERROR: {text}")),
+            _ => self.write_info(out, format_args!("+CMS ERROR: {code}")),
+        }
+    }
+}
+
+/// Verbose description of an error code, left out by the `no-error-text`
+/// feature so that the description tables are not linked in.
+fn error_text(text: fn(u16) -> Option<&'static str>, code: u16) -> Option<&'static str> {
+    if cfg!(feature = "no-error-text") { None } else { text(code) }
+}
+
+impl Default for Framing {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Writer handed to a handler while it executes.
+///
+/// Implements [`core::fmt::Write`] into a caller-owned buffer (see
+/// [`AtParser::set_response_buffer`](crate::parser::AtParser::set_response_buffer)),
+/// so handlers can format their information text with `write!` and no static
+/// storage. When the handler succeeds with an empty payload, the written text
+/// becomes its payload.
+///
+/// It also reaches the parser output directly, with the current framing
+/// settings, for what must go out while the handler executes: intermediate
+/// result codes and listings spanning many lines. When framing is disabled or
+/// no output is set these writes are discarded.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// use core::fmt::Write;
+/// use at_parser_rs::context::AtContext;
+/// use at_parser_rs::response::Responder;
+/// use at_parser_rs::{AtError, AtResponse, AtResult};
+///
+/// const SIZE: usize = 64;
+///
+/// struct Signal { rssi: u8, ber: u8 }
+///
+/// impl AtContext<SIZE> for Signal {
+///     fn exec_with(&mut self, at_response: &'static str, responder: &mut Responder<'_>) -> AtResult<'_, SIZE> {
+///         write!(responder, "{},{}", self.rssi, self.ber).map_err(|_| (at_response, AtError::InvalidArgs))?;
+///         Ok((at_response, AtResponse::Ok))
+///     }
+/// }
+/// // AT+CSQ  →  Ok(("+CSQ: ", "23,0"))
+/// ```
+///
+/// The type parameter `S` is the user state shared by every handler, see
+/// [`state`](Responder::state); it is `()` when no state is used.
+pub struct Responder<'r, S = ()> {
+    /// Caller-owned storage for the information text
+    buf: &'r mut [u8],
+    /// Number of valid bytes in `buf`
+    len: usize,
+    /// Writer towards the DTE, if connected
+    out: Option<&'r mut dyn Write>,
+    /// Framing applied to everything sent to `out`
+    framing: Framing,
+    /// Whether an information line has been sent
+    info: bool,
+    /// User state shared by the handlers, if any
+    state: Option<&'r mut S>,
+    /// Name of the command being executed, as registered
+    command: &'static str,
+    /// Form of the command being executed
+    form: Form,
+    /// Abort flag of the command being executed, if any
+    abort: Option<&'r Abort>,
+    /// Help table of the registered commands, if any
+    help: Option<&'r Help>,
+    /// Payload the parser is to collect, see
+    /// [`prompt_payload`](Responder::prompt_payload)
+    payload: Option<Payload>,
+}
+
+/// Payload collected by the parser for [`AtContext::payload`](crate::context::AtContext::payload)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Payload {
+    /// Text ended by Ctrl-Z, see [`Responder::prompt_payload`]
+    Terminated,
+    /// Exactly this many raw bytes, see [`Responder::expect_bytes`]
+    Length(usize),
+}
+
+impl<'r> Responder<'r> {
+    /// Create a responder writing into `buf`, not connected to any output.
+    pub fn new(buf: &'r mut [u8]) -> Self {
+        Self::from_parts(buf, None, Framing::new(), None)
+    }
+
+    /// Create a responder writing into `buf` and sending intermediate result
+    /// codes and information lines to `out` with `framing`.
+    pub fn with_output(buf: &'r mut [u8], out: &'r mut dyn Write, framing: Framing) -> Self {
+        Self::from_parts(buf, Some(out), framing, None)
+    }
+}
+
+impl<'r, S> Responder<'r, S> {
+    pub(crate) fn from_parts(buf: &'r mut [u8], out: Option<&'r mut dyn Write>, framing: Framing, state: Option<&'r mut S>) -> Self {
+        Self { buf, len: 0, out, framing, info: false, state, command: "", form: Form::Exec, abort: None, help: None, payload: None }
+    }
+
+    /// Set the command the handler is executing, see
+    /// [`command`](Responder::command) and [`form`](Responder::form).
+    pub fn with_command(mut self, command: &'static str, form: Form) -> Self {
+        self.command = command;
+        self.form = form;
+        self
+    }
+
+    /// Set the flag polled by [`is_aborted`](Responder::is_aborted).
+    pub fn with_abort(mut self, abort: Option<&'r Abort>) -> Self {
+        self.abort = abort;
+        self
+    }
+
+    /// Set the table returned by [`help`](Responder::help).
+    pub fn with_help(mut self, help: Option<&'r Help>) -> Self {
+        self.help = help;
+        self
+    }
+
+    /// Attach the user state handed to the handler.
+    pub fn with_state<U>(self, state: &'r mut U) -> Responder<'r, U> {
+        Responder {
+            buf: self.buf,
+            len: self.len,
+            out: self.out,
+            framing: self.framing,
+            info: self.info,
+            state: Some(state),
+            command: self.command,
+            form: self.form,
+            abort: self.abort,
+            help: self.help,
+            payload: self.payload,
+        }
+    }
+
+    /// User state shared by every handler, as passed to
+    /// [`AtParser::execute_with_state`](crate::parser::AtParser::execute_with_state)
+    /// or installed with [`AtParser::set_state`](crate::parser::AtParser::set_state).
+    ///
+    /// Returns `None` when the parser has no state.
+    pub fn state(&mut self) -> Option<&mut S> {
+        self.state.as_deref_mut()
+    }
+
+    /// Name of the command being executed, as registered in the parser
+    /// (`"AT+CGACT"`), so that one handler can serve several related commands.
+    ///
+    /// Empty when the responder was not created by the parser.
+    pub fn command(&self) -> &'static str {
+        self.command
+    }
+
+    /// Form of the command being executed.
+    pub fn form(&self) -> Form {
+        self.form
+    }
+
+    /// Help table of the registered commands, as installed with
+    /// [`AtParser::set_help`](crate::parser::AtParser::set_help).
+    pub fn help(&self) -> Option<&'r Help> {
+        self.help
+    }
+
+    /// Whether the DTE sent a character since the command started, asking
+    /// an abortable command to stop (see [`abort`](crate::abort)).
+    ///
+    /// Always `false` when the parser has no abort flag, see
+    /// [`AtParser::set_abort`](crate::parser::AtParser::set_abort).
+    pub fn is_aborted(&self) -> bool {
+        self.abort.is_some_and(Abort::is_requested)
+    }
+
+    /// Text written so far.
+    pub fn as_str(&self) -> &str {
+        core::str::from_utf8(&self.buf[..self.len]).unwrap_or("")
+    }
+
+    /// Number of bytes written so far.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Whether nothing has been written.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Capacity of the buffer, in bytes.
+    pub fn capacity(&self) -> usize {
+        self.buf.len()
+    }
+
+    /// Discard the text written so far.
+    pub fn clear(&mut self) {
+        self.len = 0;
+    }
+
+    /// Whether intermediate result codes and information lines reach an
+    /// output.
+    pub fn is_connected(&self) -> bool {
+        self.out.is_some()
+    }
+
+    /// Framing applied to everything sent to the output.
+    pub fn framing(&self) -> Framing {
+        self.framing
+    }
+
+    /// Send an intermediate result code (e.g. `CONNECT 115200`) before the
+    /// final one.
+    pub fn intermediate(&mut self, code: ResultCode) -> fmt::Result {
+        self.info = false;
+        match self.out.as_deref_mut() {
+            Some(out) => self.framing.write_code(out, code),
+            None => Ok(()),
+        }
+    }
+
+    /// Send a prompt right away (the `> ` of `+CMGS`), preceded by the line
+    /// ending and not terminated, before returning
+    /// [`AtResponse::Continue`].
+    pub fn prompt(&mut self, text: impl fmt::Display) -> fmt::Result {
+        self.info = false;
+        match self.out.as_deref_mut() {
+            Some(out) => write!(out, "{}{text}", self.framing.ending().as_str()),
+            None => Ok(()),
+        }
+    }
+
+    /// Send the prompt of a command taking a payload (the `> ` of `+CMGS`)
+    /// like [`prompt`](Responder::prompt), and have the parser collect the
+    /// payload itself once the handler returns [`AtResponse::Continue`].
+    ///
+    /// The bytes received are buffered, in the line buffer or the buffer
+    /// installed with [`set_payload_buffer`](crate::parser::AtParser::set_payload_buffer),
+    /// until Ctrl-Z (`0x1A`) hands them to [`AtContext::payload`](crate::context::AtContext::payload);
+    /// ESC (`0x1B`) cancels the command with `OK` instead, and a longer
+    /// payload ends it with [`AtError::InvalidArgs`](crate::AtError::InvalidArgs).
+    /// Command line parsing resumes either way.
+    pub fn prompt_payload(&mut self, text: impl fmt::Display) -> fmt::Result {
+        self.payload = Some(Payload::Terminated);
+        self.prompt(text)
+    }
+
+    /// Have the parser collect exactly `len` raw bytes once the handler
+    /// returns [`AtResponse::Continue`](crate::AtResponse::Continue), the
+    /// data of `AT+CIPSEND=<len>` or of a file write, and hand them to
+    /// [`AtContext::payload`](crate::context::AtContext::payload).
+    ///
+    /// The bytes are 8-bit clean: no terminator, editing or cancel character
+    /// is interpreted and none is echoed. They are buffered like the payload
+    /// of [`prompt_payload`](Responder::prompt_payload); a longer one is still
+    /// received in full, then ends the command with
+    /// [`AtError::InvalidArgs`](crate::AtError::InvalidArgs). Send the prompt,
+    /// if any, with [`prompt`](Responder::prompt). `len` must not be zero.
+    pub fn expect_bytes(&mut self, len: usize) {
+        debug_assert!(len > 0, "a counted payload holds at least one byte");
+        self.payload = Some(Payload::Length(len));
+    }
+
+    /// Payload the handler asked for, see
+    /// [`prompt_payload`](Responder::prompt_payload) and
+    /// [`expect_bytes`](Responder::expect_bytes).
+    pub(crate) fn collects_payload(&self) -> Option<Payload> {
+        self.payload
+    }
+
+    /// Send one line of information text right away.
+    ///
+    /// Consecutive lines form a single information response: in verbose mode
+    /// only the first one is preceded by the line ending. The lines precede
+    /// the response returned by the handler, which usually carries an empty
+    /// payload once they have been sent.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// # use at_parser_rs::response::Responder;
+    /// # fn list(responder: &mut Responder<'_>, at_response: &str) -> core::fmt::Result {
+    /// for index in 1..=3 {
+    ///     responder.info(format_args!("{at_response}{index},\"REC READ\""))?;
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn info(&mut self, text: impl fmt::Display) -> fmt::Result {
+        let Some(out) = self.out.as_deref_mut() else {
+            return Ok(());
+        };
+
+        let ending = self.framing.ending().as_str();
+        if self.framing.verbose() && !self.info {
+            out.write_str(ending)?;
+        }
+        self.info = true;
+        write!(out, "{text}{ending}")
+    }
+}
+
+impl<S> Write for Responder<'_, S> {
+    /// Append `s` to the buffer, failing without writing anything when it
+    /// does not fit.
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        let end = self.len + s.len();
+        if end > self.buf.len() {
+            return Err(fmt::Error);
+        }
+        self.buf[self.len..end].copy_from_slice(s.as_bytes());
+        self.len = end;
+        Ok(())
+    }
+}
+
+/// Whether `value` renders to a non-empty string.
+pub(crate) fn has_text(value: &impl fmt::Display) -> bool {
+    /// Writer recording whether anything was written.
+    struct Probe(bool);
+
+    impl Write for Probe {
+        fn write_str(&mut self, s: &str) -> fmt::Result {
+            self.0 |= !s.is_empty();
+            Ok(())
+        }
+    }
+
+    let mut probe = Probe(false);
+    let _ = write!(probe, "{value}");
+    probe.0
+}
+
+/// Fixed-capacity storage for framed output that cannot be sent yet.
+///
+/// Used to hold unsolicited result codes back while a command line is being
+/// received. Each write is all-or-nothing, so the content stays valid UTF-8.
+pub(crate) struct Pending<const SIZE: usize> {
+    /// Written bytes
+    buf: [u8; SIZE],
+    /// Number of valid bytes in `buf`
+    len: usize,
+}
+
+impl<const SIZE: usize> Pending<SIZE> {
+    /// Create an empty buffer.
+    pub(crate) const fn new() -> Self {
+        Self { buf: [0; SIZE], len: 0 }
+    }
+
+    /// Write the whole of `args`, or nothing if it does not fit.
+    pub(crate) fn push(&mut self, args: fmt::Arguments<'_>) -> fmt::Result {
+        let len = self.len;
+        let result = self.write_fmt(args);
+        if result.is_err() {
+            self.len = len;
+        }
+        result
+    }
+
+    /// Buffered output.
+    pub(crate) fn as_str(&self) -> &str {
+        core::str::from_utf8(&self.buf[..self.len]).unwrap_or("")
+    }
+
+    /// Discard the buffered output.
+    pub(crate) fn clear(&mut self) {
+        self.len = 0;
+    }
+}
+
+impl<const SIZE: usize> Write for Pending<SIZE> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        let end = self.len + s.len();
+        if end > SIZE {
+            return Err(fmt::Error);
+        }
+        self.buf[self.len..end].copy_from_slice(s.as_bytes());
+        self.len = end;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+// Some fixtures serve only the tests left out of the reduced builds
+#[cfg_attr(any(feature = "basic-errors", feature = "no-error-text"), allow(unused_imports, dead_code))]
+mod tests {
+    use alloc::string::String;
+
+    use core::fmt::Write;
+
+    use super::{ErrorKind, ErrorMap, Framing, Pending, Rendering, Responder, ResultCode, VendorErrors};
+    use crate::cme::{self, CmeMode};
+    use crate::cms;
+    use crate::line::LineEnding;
+    use crate::{ArgError, AtError, AtResponse, AtResult, at_response};
+
+    const SIZE: usize = 16;
+
+    fn framed(framing: Framing, result: AtResult<'_, SIZE>) -> String {
+        let mut out = String::new();
+        framing.write_result(&mut out, &result).unwrap();
+        out
+    }
+
+    #[test]
+    fn wraps_information_text_and_final_result_code() {
+        let framing = Framing::new();
+
+        assert_eq!(framed(framing, Ok(at_response!(SIZE, "+VAL: "; 2))), "\r\n+VAL: 2\r\n\r\nOK\r\n");
+        assert_eq!(framed(framing, Ok(("", AtResponse::Ok))), "\r\nOK\r\n");
+        assert_eq!(framed(framing, Err(("+VAL: ", AtError::InvalidArgs))), "\r\nERROR\r\n");
+    }
+
+    #[test]
+    fn uses_configured_line_ending() {
+        let mut framing = Framing::new();
+        framing.set_ending(LineEnding::Lf);
+
+        assert_eq!(framed(framing, Ok(at_response!(SIZE, ""; "1.0"))), "\n1.0\n\nOK\n");
+    }
+
+    #[test]
+    fn numeric_mode_sends_result_code_numbers() {
+        let mut framing = Framing::new();
+        framing.set_verbose(false);
+
+        assert_eq!(framed(framing, Ok(at_response!(SIZE, "+VAL: "; 2))), "+VAL: 2\r\n0\r");
+        assert_eq!(framed(framing, Err(("", AtError::UnknownCommand))), "4\r");
+
+        let mut out = String::new();
+        framing.write_code(&mut out, ResultCode::Ok).unwrap();
+        assert_eq!(out, "0\r");
+    }
+
+    #[cfg(not(any(feature = "basic-errors", feature = "no-error-text")))]
+    #[test]
+    fn cme_errors_follow_the_cmee_mode() {
+        let mut framing = Framing::new();
+        let error = || Err(("", AtError::Cme(cme::SIM_NOT_INSERTED)));
+
+        assert_eq!(framed(framing, error()), "\r\nERROR\r\n");
+        framing.set_cme_mode(CmeMode::Numeric);
+        assert_eq!(framed(framing, error()), "\r\n+CME ERROR: 10\r\n");
+        framing.set_cme_mode(CmeMode::Verbose);
+        assert_eq!(framed(framing, error()), "\r\n+CME ERROR: SIM not inserted\r\n");
+        assert_eq!(framed(framing, Err(("", AtError::Cme(999)))), "\r\n+CME ERROR: 999\r\n");
+        assert_eq!(framed(framing, Err(("", AtError::InvalidArgs))), "\r\nERROR\r\n");
+    }
+
+    #[cfg(feature = "basic-errors")]
+    #[test]
+    fn extended_errors_are_reported_as_plain_errors() {
+        let mut framing = Framing::new();
+        framing.set_cme_mode(CmeMode::Verbose);
+
+        assert_eq!(framed(framing, Err(("", AtError::Cme(cme::SIM_NOT_INSERTED)))), "\r\nERROR\r\n");
+        assert_eq!(framed(framing, Err(("", AtError::Cms(cms::MEMORY_FULL)))), "\r\nERROR\r\n");
+        assert_eq!(framed(framing, Err(("", AtError::Vendor(7, Some("flash busy"))))), "\r\nERROR\r\n");
+    }
+
+    #[cfg(not(any(feature = "basic-errors", feature = "no-error-text")))]
+    #[test]
+    fn cms_errors_are_reported_whatever_the_cmee_mode() {
+        let mut framing = Framing::new();
+        let error = || Err(("+CMGS: ", AtError::Cms(cms::MEMORY_FULL)));
+
+        assert_eq!(framed(framing, error()), "\r\n+CMS ERROR: 322\r\n");
+        framing.set_cme_mode(CmeMode::Verbose);
+        assert_eq!(framed(framing, error()), "\r\n+CMS ERROR: memory full\r\n");
+        framing.set_quiet(true);
+        assert_eq!(framed(framing, error()), "");
+    }
+
+    #[cfg(not(any(feature = "basic-errors", feature = "no-error-text")))]
+    #[test]
+    fn vendor_errors_use_the_selected_format() {
+        let mut framing = Framing::new();
+        let error = || Err(("", AtError::Vendor(1012, Some("flash busy"))));
+
+        assert_eq!(framed(framing, error()), "\r\nERROR\r\n");
+        framing.set_cme_mode(CmeMode::Numeric);
+        assert_eq!(framed(framing, error()), "\r\n+CME ERROR: 1012\r\n");
+        framing.set_cme_mode(CmeMode::Verbose);
+        assert_eq!(framed(framing, error()), "\r\n+CME ERROR: flash busy\r\n");
+        assert_eq!(framed(framing, Err(("", AtError::Vendor(7, None)))), "\r\n+CME ERROR: 7\r\n");
+
+        framing.set_vendor_errors(VendorErrors::Prefixed("#ERROR: "));
+        framing.set_cme_mode(CmeMode::Disabled);
+        assert_eq!(framed(framing, error()), "\r\n#ERROR: 1012\r\n");
+        framing.set_quiet(true);
+        assert_eq!(framed(framing, error()), "");
+    }
+
+    #[cfg(not(feature = "basic-errors"))]
+    #[test]
+    fn error_map_overrides_the_built_in_result_codes() {
+        const ERRORS: ErrorMap = &[
+            (ErrorKind::NotSupported, Rendering::Cme(cme::OPERATION_NOT_SUPPORTED)),
+            (ErrorKind::InvalidArgs, Rendering::Text("#ERROR: BAD PARAM")),
+            (ErrorKind::Aborted, Rendering::Code(ResultCode::NoCarrier)),
+        ];
+        let mut framing = Framing::new();
+        framing.set_error_map(ERRORS);
+        framing.set_cme_mode(CmeMode::Numeric);
+
+        assert_eq!(framed(framing, Err(("", AtError::NotSupported))), "\r\n+CME ERROR: 4\r\n");
+        assert_eq!(framed(framing, Err(("", AtError::Argument(ArgError::Invalid(1))))), "\r\n#ERROR: BAD PARAM\r\n");
+        assert_eq!(framed(framing, Err(("", AtError::Aborted))), "\r\nNO CARRIER\r\n");
+        // Errors left out of the map keep their result code
+        assert_eq!(framed(framing, Err(("", AtError::UnknownCommand))), "\r\nERROR\r\n");
+
+        framing.set_quiet(true);
+        assert_eq!(framed(framing, Err(("", AtError::InvalidArgs))), "");
+    }
+
+    #[test]
+    fn intermediate_result_codes() {
+        let mut out = String::new();
+        let mut numeric = Framing::new();
+        numeric.set_verbose(false);
+
+        let mut responder = Responder::with_output(&mut [], &mut out, Framing::new());
+        assert!(responder.is_connected());
+        responder.intermediate(ResultCode::Connect(Some(115200))).unwrap();
+        responder.intermediate(ResultCode::Ring).unwrap();
+        let mut responder = Responder::with_output(&mut [], &mut out, numeric);
+        responder.intermediate(ResultCode::Connect(None)).unwrap();
+        assert!(Responder::new(&mut []).intermediate(ResultCode::Ring).is_ok());
+
+        assert_eq!(out, "\r\nCONNECT 115200\r\n\r\nRING\r\n1\r");
+        assert_eq!(framed(Framing::new(), Err(("", AtError::Final(ResultCode::Busy)))), "\r\nBUSY\r\n");
+        assert_eq!(framed(numeric, Err(("", AtError::Final(ResultCode::NoAnswer)))), "8\r");
+        assert_eq!(framed(Framing::new(), Err(("", AtError::Aborted))), "\r\nABORTED\r\n");
+        assert_eq!(framed(numeric, Err(("", AtError::Aborted))), "4\r");
+    }
+
+    #[test]
+    fn information_lines_share_one_header() {
+        let mut out = String::new();
+
+        let mut responder = Responder::with_output(&mut [], &mut out, Framing::new());
+        responder.info("+CMGL: 1").unwrap();
+        responder.info("+CMGL: 2").unwrap();
+        responder.intermediate(ResultCode::Ring).unwrap();
+        responder.info("+CLIP: 5551234").unwrap();
+
+        assert_eq!(out, "\r\n+CMGL: 1\r\n+CMGL: 2\r\n\r\nRING\r\n\r\n+CLIP: 5551234\r\n");
+    }
+
+    #[test]
+    fn responder_writes_into_the_caller_buffer() {
+        let mut buf = [0u8; 8];
+        let mut responder = Responder::new(&mut buf);
+
+        write!(responder, "{},{}", 23, 0).unwrap();
+        assert_eq!(responder.as_str(), "23,0");
+        assert!(responder.write_str("overflow").is_err());
+        assert_eq!(responder.len(), 4);
+        assert_eq!(responder.capacity(), 8);
+        responder.clear();
+        assert!(responder.is_empty());
+    }
+
+    #[test]
+    fn pending_output_is_all_or_nothing() {
+        let mut pending = Pending::<8>::new();
+
+        assert!(pending.push(format_args!("{}", "RING")).is_ok());
+        assert!(pending.push(format_args!("{}{}", "+CR", "EG: 1")).is_err());
+        assert_eq!(pending.as_str(), "RING");
+        pending.clear();
+        assert_eq!(pending.as_str(), "");
+    }
+
+    #[test]
+    fn quiet_mode_suppresses_result_codes_only() {
+        let mut framing = Framing::new();
+        framing.set_quiet(true);
+
+        assert_eq!(framed(framing, Ok(at_response!(SIZE, "+VAL: "; 2))), "\r\n+VAL: 2\r\n");
+        assert_eq!(framed(framing, Ok(("", AtResponse::Ok))), "");
+        assert_eq!(framed(framing, Err(("", AtError::UnknownCommand))), "");
+    }
+}
@@ -0,0 +1,288 @@
+/***************************************************************************
+ *
+ * AT Command Parser
+ * Copyright (C) 2026 Antonio Salsi <passy.linux@zresa.it>
+ *
+ * This library is free software; you can redistribute it and/or
+ * modify it under the terms of the GNU Lesser General Public
+ * License as published by the Free Software Foundation; either
+ * version 2.1 of the License, or (at your option) any later version.
+ *
+ * This library is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the GNU
+ * Lesser General Public License for more details.
+ *
+ * You should have received a copy of the GNU Lesser General Public
+ * License along with this library; if not, see <https://www.gnu.org/licenses/>.
+ *
+ ***************************************************************************/
+
+ 
+//! Receive ring buffer
+//!
+//! A UART interrupt cannot run the parser: handlers take too long and may
+//! block. An [`RxRing`] hands the received bytes over to the task or main
+//! loop running the parser instead. It is split once into its two ends: the
+//! [`RxProducer`] pushes bytes from the interrupt handler and the
+//! [`RxConsumer`] drains them into the parser, which dispatches every line
+//! completed on the way.
+//!
+//! ```text
+//! UART interrupt                    main loop / task
+//! producer.push_from_isr(byte)      consumer.drain(&mut parser, |result| …)
+//!   → false when full (dropped)       → one call per completed line
+//! ```
+//!
+//! There is a single producer and a single consumer, so no lock is needed:
+//! both ends only use atomic loads and stores, available on every target.
+//!
+//! Split with [`split_with_flow`](RxRing::split_with_flow), the ring also
+//! runs the software flow control of the link: XON and XOFF are taken out
+//! of the stream at the producer, and the fill level of the ring throttles
+//! the DTE, see [`flow`](crate::flow).
+
+use core::cell::UnsafeCell;
+use core::sync::atomic::{AtomicUsize, Ordering};
+
+use crate::context::AtContext;
+use crate::flow::SoftwareFlow;
+use crate::parser::AtParser;
+use crate::{AtResult, UserError};
+
+/// Lock-free single-producer, single-consumer ring of `N` slots
+///
+/// Holds up to `N - 1` bytes. Obtain its two ends with
+/// [`split`](RxRing::split).
+///
+/// # Example
+///
+/// ```rust
+/// # use at_parser_rs::parser::AtParser;
+/// # use at_parser_rs::context::AtContext;
+/// use at_parser_rs::ring::RxRing;
+/// # const SIZE: usize = 64;
+/// # struct MyHandler; impl AtContext<SIZE> for MyHandler {}
+///
+/// let mut ring: RxRing<128> = RxRing::new();
+/// let (mut producer, mut consumer) = ring.split();
+/// let mut parser: AtParser<MyHandler, SIZE> = AtParser::new();
+///
+/// // UART interrupt
+/// for &byte in b"ATE0\r" {
+///     producer.push_from_isr(byte);
+/// }
+///
+/// // main loop
+/// consumer.drain(&mut parser, |result| {
+///     // send the response of the line back to the host
+/// #   assert!(result.is_ok());
+/// });
+/// ```
+pub struct RxRing<const N: usize> {
+    buf: UnsafeCell<[u8; N]>,
+    /// Next slot written by the producer
+    head: AtomicUsize,
+    /// Next slot read by the consumer
+    tail: AtomicUsize,
+}
+
+// SAFETY: a slot is written by the producer only while it lies outside
+// `tail..head`, and read by the consumer only once the release store of
+// `head` has published it; `split` hands out a single end of each kind
+unsafe impl<const N: usize> Sync for RxRing<N> {}
+
+impl<const N: usize> RxRing<N> {
+    /// Create an empty ring.
+    ///
+    /// # Panics
+    ///
+    /// When `N` is smaller than 2 (at compile time in a `static`).
+    pub const fn new() -> Self {
+        assert!(N > 1, "an RxRing needs at least 2 slots");
+        Self { buf: UnsafeCell::new([0; N]), head: AtomicUsize::new(0), tail: AtomicUsize::new(0) }
+    }
+
+    /// Split the ring into its producer and consumer ends.
+    pub fn split(&mut self) -> (RxProducer<'_, N>, RxConsumer<'_, N>) {
+        let ring = &*self;
+        (RxProducer { ring, flow: None }, RxConsumer { ring, flow: None })
+    }
+
+    /// Split the ring like [`split`](RxRing::split), with the software flow
+    /// control of `flow`: the producer removes the XON and XOFF that control
+    /// the output from the stream, and both ends report the fill level of
+    /// the ring to `flow`.
+    pub fn split_with_flow<'r>(&'r mut self, flow: &'r SoftwareFlow) -> (RxProducer<'r, N>, RxConsumer<'r, N>) {
+        let ring = &*self;
+        (RxProducer { ring, flow: Some(flow) }, RxConsumer { ring, flow: Some(flow) })
+    }
+
+    /// Number of bytes waiting.
+    pub fn len(&self) -> usize {
+        let head = self.head.load(Ordering::Acquire);
+        let tail = self.tail.load(Ordering::Acquire);
+        (head + N - tail) % N
+    }
+
+    /// Whether no byte is waiting.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl<const N: usize> Default for RxRing<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Interrupt side of an [`RxRing`]
+pub struct RxProducer<'r, const N: usize> {
+    ring: &'r RxRing<N>,
+    /// Software flow control of the link, if any
+    flow: Option<&'r SoftwareFlow>,
+}
+
+impl<const N: usize> RxProducer<'_, N> {
+    /// Push a received byte, from the interrupt handler.
+    ///
+    /// Returns `false` when the ring is full: the byte is dropped. XON and
+    /// XOFF taken by the flow control are not pushed, and count as taken.
+    pub fn push_from_isr(&mut self, byte: u8) -> bool {
+        if let Some(flow) = self.flow
+            && !flow.receive(byte) {
+            return true;
+        }
+
+        let head = self.ring.head.load(Ordering::Relaxed);
+        let next = (head + 1) % N;
+        if next == self.ring.tail.load(Ordering::Acquire) {
+            return false;
+        }
+        // SAFETY: `head` is outside `tail..head`, the consumer does not read it
+        unsafe { (*self.ring.buf.get())[head] = byte };
+        self.ring.head.store(next, Ordering::Release);
+        if let Some(flow) = self.flow {
+            flow.update(self.ring.len(), N - 1);
+        }
+        true
+    }
+
+    /// Push the bytes of a FIFO or DMA transfer, from the interrupt handler.
+    ///
+    /// Returns the number of bytes pushed; the rest did not fit and is
+    /// dropped.
+    pub fn push_slice_from_isr(&mut self, bytes: &[u8]) -> usize {
+        bytes.iter().take_while(|&&byte| self.push_from_isr(byte)).count()
+    }
+
+    /// Whether the next byte would be dropped.
+    pub fn is_full(&self) -> bool {
+        self.ring.len() == N - 1
+    }
+}
+
+/// Parser side of an [`RxRing`]
+pub struct RxConsumer<'r, const N: usize> {
+    ring: &'r RxRing<N>,
+    /// Software flow control of the link, if any
+    flow: Option<&'r SoftwareFlow>,
+}
+
+impl<const N: usize> RxConsumer<'_, N> {
+    /// Take the oldest byte received.
+    pub fn pop(&mut self) -> Option<u8> {
+        let tail = self.ring.tail.load(Ordering::Relaxed);
+        if tail == self.ring.head.load(Ordering::Acquire) {
+            return None;
+        }
+        // SAFETY: `tail` is inside `tail..head`, published by the producer
+        // and not written again until the store below
+        let byte = unsafe { (*self.ring.buf.get())[tail] };
+        self.ring.tail.store((tail + 1) % N, Ordering::Release);
+        if let Some(flow) = self.flow {
+            flow.update(self.ring.len(), N - 1);
+        }
+        Some(byte)
+    }
+
+    /// Whether no byte is waiting.
+    pub fn is_empty(&self) -> bool {
+        self.ring.is_empty()
+    }
+
+    /// Feed every waiting byte to `parser`, calling `on_result` with the
+    /// response of each line completed, as [`AtParser::feed`] does.
+    pub fn drain<T, const SIZE: usize, S, E, F>(&mut self, parser: &mut AtParser<'_, T, SIZE, S, E>, mut on_result: F)
+    where
+        T: AtContext<SIZE, S, E> + ?Sized,
+        E: UserError,
+        F: FnMut(AtResult<'_, SIZE, E>) {
+        while let Some(byte) = self.pop() {
+            if let Some(result) = parser.feed(byte) {
+                on_result(result);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+// Some fixtures serve only the tests left out of the reduced builds
+#[cfg_attr(feature = "exec-set-only", allow(unused_imports, dead_code))]
+mod tests {
+    use alloc::format;
+    use alloc::string::String;
+    use alloc::vec::Vec;
+
+    use super::RxRing;
+    use crate::context::AtContext;
+    use crate::parser::AtParser;
+    use crate::{Args, AtError, AtResponse, AtResult, at_response};
+
+    const SIZE: usize = 64;
+
+    struct Value(u32);
+
+    impl AtContext<SIZE> for Value {
+        fn query(&mut self, at_response: &'static str) -> AtResult<'_, SIZE> {
+            Ok(at_response!(SIZE, at_response; self.0))
+        }
+
+        fn set(&mut self, at_response: &'static str, args: Args) -> AtResult<'_, SIZE> {
+            self.0 = args.get(0).and_then(|v| v.parse().ok()).ok_or((at_response, AtError::InvalidArgs))?;
+            Ok((at_response, AtResponse::Ok))
+        }
+    }
+
+    #[cfg(not(feature = "exec-set-only"))]
+    #[test]
+    fn bytes_wrap_around_and_lines_reach_the_parser() {
+        let mut ring: RxRing<8> = RxRing::new();
+        let (mut producer, mut consumer) = ring.split();
+
+        assert_eq!(producer.push_slice_from_isr(b"0123456789"), 7);
+        assert!(producer.is_full());
+        assert_eq!(consumer.pop(), Some(b'0'));
+        assert!(producer.push_from_isr(b'7'));
+        assert!(!producer.push_from_isr(b'8'));
+        let bytes: Vec<u8> = core::iter::from_fn(|| consumer.pop()).collect();
+        assert_eq!(bytes, b"1234567");
+        assert!(consumer.is_empty());
+
+        let mut value = Value(0);
+        let commands: &mut [(&str, &str, &mut Value)] = &mut [("AT+VAL", "+VAL: ", &mut value)];
+        let mut parser: AtParser<Value, SIZE> = AtParser::new();
+        parser.set_commands(commands);
+
+        let mut responses: Vec<String> = Vec::new();
+        for chunk in [&b"AT+VA"[..], b"L=5\rAT", b"+VAL?\r"] {
+            producer.push_slice_from_isr(chunk);
+            consumer.drain(&mut parser, |result| match result {
+                Ok((at_response, response)) => responses.push(format!("{at_response}{response}")),
+                Err((_, e)) => panic!("unexpected error: {:?}", e),
+            });
+        }
+        assert_eq!(responses, ["+VAL: ", "+VAL: 5"]);
+    }
+}
@@ -0,0 +1,292 @@
+/***************************************************************************
+ *
+ * AT Command Parser
+ * Copyright (C) 2026 Antonio Salsi <passy.linux@zresa.it>
+ *
+ * This library is free software; you can redistribute it and/or
+ * modify it under the terms of the GNU Lesser General Public
+ * License as published by the Free Software Foundation; either
+ * version 2.1 of the License, or (at your option) any later version.
+ *
+ * This library is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the GNU
+ * Lesser General Public License for more details.
+ *
+ * You should have received a copy of the GNU Lesser General Public
+ * License along with this library; if not, see <https://www.gnu.org/licenses/>.
+ *
+ ***************************************************************************/
+
+ 
+//! Parameter schemas
+//!
+//! A [`Schema`] declares the parameters of a set command once, with
+//! [`at_params!`](crate::at_params), and serves both sides of the command:
+//! [`Schema::validate`] checks the arguments of `AT+CMD=<args>` and
+//! [`Schema::test`] answers `AT+CMD=?` with the matching supported values, so
+//! the two cannot drift apart.
+//!
+//! # Example
+//!
+//! ```rust
+//! use at_parser_rs::context::AtContext;
+//! use at_parser_rs::schema::Schema;
+//! use at_parser_rs::{Args, AtResponse, AtResult, at_params};
+//!
+//! const SIZE: usize = 64;
+//!
+//! // AT+CWJAP=<ssid>,<password>[,<mode>]
+//! const CWJAP: Schema = at_params![string max 32, string max 64, optional int 0..=1];
+//!
+//! struct Wifi;
+//!
+//! impl AtContext<SIZE> for Wifi {
+//!     fn test(&mut self, at_response: &'static str) -> AtResult<'_, SIZE> {
+//!         CWJAP.test(at_response)
+//!     }
+//!
+//!     fn set(&mut self, at_response: &'static str, args: Args) -> AtResult<'_, SIZE> {
+//!         CWJAP.validate(&args).map_err(|e| (at_response, e.into()))?;
+//!         // every argument is known to be well formed from here on
+//!         Ok((at_response, AtResponse::Ok))
+//!     }
+//! }
+//!
+//! assert!(CWJAP.validate(&Args::new(r#""home","secret",1"#)).is_ok());
+//! assert!(CWJAP.validate(&Args::new(r#""home","secret",2"#)).is_err());
+//! ```
+
+use core::fmt;
+
+use osal_rs::utils::Bytes;
+
+use crate::context::Supported;
+use crate::{ArgError, Args, AtResult, Split};
+
+/// Values accepted by a parameter
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Values {
+    /// Integer in an inclusive range: `int 0..=4`, shown as `(0-4)`
+    Range(i64, i64),
+    /// One of the listed integers: `int [0, 1, 5]`, shown as `(0,1,5)`
+    Numbers(&'static [i64]),
+    /// Quoted string of at most the given length in bytes: `string max 32`,
+    /// left empty in the test response
+    Text(usize),
+    /// One of the listed quoted strings: `string ["GSM", "UCS2"]`, shown as
+    /// `("GSM","UCS2")`
+    Strings(&'static [&'static str]),
+}
+
+/// One parameter of a [`Schema`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Param {
+    /// Accepted values
+    pub values: Values,
+    /// Whether the parameter may be omitted (`optional int 0..=1`)
+    pub optional: bool,
+}
+
+impl Param {
+    /// Check the argument at `index`, given as split from the command line.
+    fn check(&self, index: usize, arg: &str, quoted: bool) -> Result<(), ArgError> {
+        if !quoted && arg.trim().is_empty() {
+            return if self.optional { Ok(()) } else { Err(ArgError::Missing(index)) };
+        }
+
+        let valid = if quoted {
+            self.accepts_str(&Args::decode_quoted(arg))
+        } else {
+            arg.trim().parse::<i64>().is_ok_and(|value| self.accepts_int(value))
+        };
+        if valid { Ok(()) } else { Err(ArgError::Invalid(index)) }
+    }
+
+    /// Whether the parameter accepts the number `value`.
+    pub fn accepts_int(&self, value: i64) -> bool {
+        match self.values {
+            Values::Range(min, max) => (min..=max).contains(&value),
+            Values::Numbers(numbers) => numbers.contains(&value),
+            Values::Text(_) | Values::Strings(_) => false,
+        }
+    }
+
+    /// Whether the parameter accepts the (unquoted) string `value`.
+    pub fn accepts_str(&self, value: &str) -> bool {
+        match self.values {
+            Values::Text(max) => value.len() <= max,
+            Values::Strings(strings) => strings.contains(&value),
+            Values::Range(..) | Values::Numbers(_) => false,
+        }
+    }
+}
+
+/// Parameters of a set command, in order, as declared with
+/// [`at_params!`](crate::at_params)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Schema {
+    params: &'static [Param],
+}
+
+impl Schema {
+    /// Schema of the given parameters.
+    pub const fn new(params: &'static [Param]) -> Self {
+        Self { params }
+    }
+
+    /// Declared parameters, in order.
+    pub fn params(&self) -> &'static [Param] {
+        self.params
+    }
+
+    /// Check the arguments of a set command against the schema.
+    ///
+    /// Trailing optional parameters may be left out, and any optional one
+    /// left empty (`1,,3`). Numbers must be unquoted and strings quoted.
+    ///
+    /// # Errors
+    ///
+    /// * [`ArgError::Missing`] — a required parameter is absent or empty
+    /// * [`ArgError::Unexpected`] — more arguments than parameters
+    /// * [`ArgError::Invalid`] — an argument is outside its accepted values
+    pub fn validate(&self, args: &Args) -> Result<(), ArgError> {
+        let required = self.params.iter().rposition(|param| !param.optional).map_or(0, |last| last + 1);
+        let count = args.expect(required, self.params.len())?;
+
+        for (index, (param, (arg, quoted))) in self.params.iter().zip(Split::new(args.raw())).take(count).enumerate() {
+            param.check(index, arg, quoted)?;
+        }
+        Ok(())
+    }
+
+    /// Answer the test form (`AT+CMD=?`) with the supported values of every
+    /// parameter, e.g. `,,(0-1)` for `string max 32, string max 64, int 0..=1`.
+    pub fn test<const SIZE: usize, E>(&self, at_response: &'static str) -> AtResult<'static, SIZE, E> {
+        let mut response = Bytes::<SIZE>::new();
+        response.format(format_args!("{self}"));
+        Ok((at_response, response.into()))
+    }
+}
+
+impl fmt::Display for Schema {
+    /// Supported values of every parameter, comma-separated, as listed by
+    /// the test form.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (index, param) in self.params.iter().enumerate() {
+            if index > 0 {
+                f.write_str(",")?;
+            }
+            match param.values {
+                Values::Range(min, max) => write!(f, "{}", Supported::Range(min, max))?,
+                Values::Numbers(numbers) => write!(f, "{}", Supported::Values(numbers))?,
+                Values::Text(_) => {}
+                Values::Strings(strings) => write!(f, "{}", Supported::Strings(strings))?,
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Declare the parameters of a set command as a [`Schema`](crate::schema::Schema).
+///
+/// Parameters are comma-separated, each one of:
+///
+/// - `int <min>..=<max>` — integer in an inclusive range
+/// - `int [<n>, ...]` — one of the listed integers
+/// - `string max <len>` — quoted string of at most `len` bytes
+/// - `string ["<s>", ...]` — one of the listed quoted strings
+///
+/// optionally preceded by `optional` when the parameter may be omitted.
+///
+/// # Example
+///
+/// ```rust
+/// use at_parser_rs::at_params;
+/// use at_parser_rs::schema::Schema;
+///
+/// // AT+CSCS=<chset>,<mode>
+/// const CSCS: Schema = at_params![string ["GSM", "UCS2"], int [0, 2]];
+/// assert_eq!(format!("{CSCS}"), r#"("GSM","UCS2"),(0,2)"#);
+/// ```
+#[macro_export]
+macro_rules! at_params {
+    (@split [$($done:tt)*] [$($current:tt)+] , $($rest:tt)*) => {
+        $crate::at_params!(@split [$($done)* ($($current)+)] [] $($rest)*)
+    };
+    (@split [$($done:tt)*] [$($current:tt)*] $next:tt $($rest:tt)*) => {
+        $crate::at_params!(@split [$($done)*] [$($current)* $next] $($rest)*)
+    };
+    (@split [$($done:tt)*] [$($current:tt)+]) => {
+        $crate::at_params!(@split [$($done)* ($($current)+)] [])
+    };
+    (@split [$(($($param:tt)+))*] []) => {
+        $crate::schema::Schema::new(&[$($crate::at_params!(@param $($param)+)),*])
+    };
+    (@param optional $($values:tt)+) => {
+        $crate::schema::Param { values: $crate::at_params!(@values $($values)+), optional: true }
+    };
+    (@param $($values:tt)+) => {
+        $crate::schema::Param { values: $crate::at_params!(@values $($values)+), optional: false }
+    };
+    (@values int $min:literal ..= $max:literal) => {
+        $crate::schema::Values::Range($min, $max)
+    };
+    (@values int [$($number:literal),* $(,)?]) => {
+        $crate::schema::Values::Numbers(&[$($number),*])
+    };
+    (@values string max $max:literal) => {
+        $crate::schema::Values::Text($max)
+    };
+    (@values string [$($string:literal),* $(,)?]) => {
+        $crate::schema::Values::Strings(&[$($string),*])
+    };
+    ($($params:tt)*) => {
+        $crate::at_params!(@split [] [] $($params)*)
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::format;
+
+    use crate::{ArgError, Args, AtResponse, AtResult};
+
+    use super::Schema;
+
+    const CWJAP: Schema = at_params![string max 8, string max 16, optional int 0..=1];
+
+    #[test]
+    fn arguments_are_checked_against_the_schema() {
+        let check = |raw| CWJAP.validate(&Args::new(raw));
+
+        assert_eq!(check(r#""home","secret",1"#), Ok(()));
+        assert_eq!(check(r#""home","secret""#), Ok(()));
+        assert_eq!(check(r#""home","secret","#), Ok(()));
+        assert_eq!(check(r#""home",,1"#), Err(ArgError::Missing(1)));
+        assert_eq!(check(r#""home""#), Err(ArgError::Missing(1)));
+        assert_eq!(check(r#""home","secret",2"#), Err(ArgError::Invalid(2)));
+        assert_eq!(check(r#""home","secret","1""#), Err(ArgError::Invalid(2)));
+        assert_eq!(check(r#"home,"secret""#), Err(ArgError::Invalid(0)));
+        assert_eq!(check(r#""too long ssid","secret""#), Err(ArgError::Invalid(0)));
+        assert_eq!(check(r#""home","secret",1,0"#), Err(ArgError::Unexpected(3)));
+    }
+
+    #[test]
+    fn listed_values_are_enforced() {
+        let schema = at_params![int [0, 1, 5], string ["GSM", "UCS2"]];
+
+        assert_eq!(schema.validate(&Args::new(r#"5,"UCS2""#)), Ok(()));
+        assert_eq!(schema.validate(&Args::new(r#"2,"UCS2""#)), Err(ArgError::Invalid(0)));
+        assert_eq!(schema.validate(&Args::new(r#"1,"IRA""#)), Err(ArgError::Invalid(1)));
+    }
+
+    #[test]
+    fn test_form_lists_the_supported_values() {
+        let schema = at_params![int -5..=5, optional int [0, 2], string ["GSM"], string max 4];
+        assert_eq!(format!("{schema}"), r#"(-5-5),(0,2),("GSM"),"#);
+
+        let result: AtResult<'_, 32> = CWJAP.test("+CWJAP: ");
+        assert!(matches!(result, Ok(("+CWJAP: ", AtResponse::InfoText(text))) if format!("{text}") == ",,(0-1)"));
+    }
+}
@@ -0,0 +1,141 @@
+/***************************************************************************
+ *
+ * AT Command Parser
+ * Copyright (C) 2026 Antonio Salsi <passy.linux@zresa.it>
+ *
+ * This library is free software; you can redistribute it and/or
+ * modify it under the terms of the GNU Lesser General Public
+ * License as published by the Free Software Foundation; either
+ * version 2.1 of the License, or (at your option) any later version.
+ *
+ * This library is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the GNU
+ * Lesser General Public License for more details.
+ *
+ * You should have received a copy of the GNU Lesser General Public
+ * License along with this library; if not, see <https://www.gnu.org/licenses/>.
+ *
+ ***************************************************************************/
+
+ 
+//! Serve loops over byte streams
+//!
+//! With the `embedded-io` feature, [`AtParser::serve`] glues the parser to
+//! any transport implementing [`embedded_io::Read`] and
+//! [`embedded_io::Write`], such as the UART drivers of most HALs: received
+//! bytes are fed to the parser and the echo and framed responses are written
+//! back. With `embedded-io-async`, [`AtParser::serve_async`] does the same
+//! over [`embedded_io_async`] drivers, e.g. from an Embassy task.
+//!
+//! HALs exposing only `nb` serial ports go through an [`NbSerial`] with the
+//! `embedded-hal-nb` feature, polled without ever blocking, and with `std`
+//! [`AtParser::serve_io`] runs the command table over `std::io` streams on
+//! the host. Devices exposing their AT interface over USB serve it through
+//! a [`UsbCdc`] with the `usbd-serial` feature.
+
+#[cfg(any(feature = "embedded-io", feature = "std"))]
+use alloc::string::String;
+use core::fmt;
+
+#[cfg(any(feature = "embedded-io", feature = "std"))]
+use crate::context::AtContext;
+#[cfg(any(feature = "embedded-io", feature = "std"))]
+use crate::parser::AtParser;
+#[cfg(any(feature = "embedded-io", feature = "std"))]
+use crate::UserError;
+
+#[cfg(feature = "std")]
+mod io;
+#[cfg(feature = "embedded-hal-nb")]
+mod serial;
+#[cfg(feature = "embedded-io")]
+mod stream;
+#[cfg(feature = "usbd-serial")]
+mod usb;
+
+#[cfg(feature = "embedded-hal-nb")]
+pub use serial::NbSerial;
+#[cfg(feature = "usbd-serial")]
+pub use usb::{CdcEndpoints, UsbCdc};
+
+/// Failure of a serve loop
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ServeError<R, W> {
+    /// Reading from the transport failed
+    Read(R),
+    /// Writing to the transport failed
+    Write(W),
+    /// A response did not fit in the transmit buffer of an [`NbSerial`] or
+    /// a [`UsbCdc`]
+    Overflow,
+}
+
+impl<R: fmt::Debug, W: fmt::Debug> fmt::Display for ServeError<R, W> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ServeError::Read(e) => write!(f, "read failed: {e:?}"),
+            ServeError::Write(e) => write!(f, "write failed: {e:?}"),
+            ServeError::Overflow => f.write_str("response too long for the transmit buffer"),
+        }
+    }
+}
+
+#[cfg(any(feature = "embedded-io", feature = "std"))]
+impl<'a, T, const SIZE: usize, S, E> AtParser<'a, T, SIZE, S, E>
+where
+    T: AtContext<SIZE, S, E> + ?Sized,
+    E: UserError {
+
+    /// Feed `chunk` up to the first completed line, framing its response
+    /// into `response`, and return the number of bytes consumed and whether
+    /// they are to be echoed.
+    fn serve_feed(&mut self, chunk: &[u8], response: &mut String) -> (usize, bool) {
+        let echo = self.echo();
+        response.clear();
+        // writing to a `String` cannot fail
+        let (consumed, _) = self.feed_slice_to(chunk, Some(response));
+        (consumed, echo)
+    }
+}
+
+/// Transmit buffer of the polled adapters
+#[cfg(any(feature = "embedded-hal-nb", feature = "usbd-serial"))]
+struct Output<const TX: usize> {
+    buf: [u8; TX],
+    /// Number of bytes of `buf` already written
+    sent: usize,
+    /// Number of valid bytes in `buf`
+    len: usize,
+    /// Whether a response did not fit in `buf`
+    overflow: bool,
+}
+
+#[cfg(any(feature = "embedded-hal-nb", feature = "usbd-serial"))]
+impl<const TX: usize> Output<TX> {
+    const fn new() -> Self {
+        Self { buf: [0; TX], sent: 0, len: 0, overflow: false }
+    }
+
+    /// Append `bytes`, as far as they fit.
+    fn queue(&mut self, bytes: &[u8]) {
+        let len = bytes.len().min(TX - self.len);
+        self.buf[self.len..self.len + len].copy_from_slice(&bytes[..len]);
+        self.len += len;
+        self.overflow |= len < bytes.len();
+    }
+}
+
+#[cfg(any(feature = "embedded-hal-nb", feature = "usbd-serial"))]
+impl<const TX: usize> fmt::Write for Output<TX> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        self.queue(s.as_bytes());
+        if self.overflow { Err(fmt::Error) } else { Ok(()) }
+    }
+}
+
+/// Runs of ASCII characters of `bytes`, the ones echoed.
+#[cfg(any(feature = "embedded-io", feature = "std"))]
+fn ascii_runs(bytes: &[u8]) -> impl Iterator<Item = &[u8]> {
+    bytes.split(|byte| !byte.is_ascii()).filter(|run| !run.is_empty())
+}
@@ -0,0 +1,117 @@
+/***************************************************************************
+ *
+ * AT Command Parser
+ * Copyright (C) 2026 Antonio Salsi <passy.linux@zresa.it>
+ *
+ * This library is free software; you can redistribute it and/or
+ * modify it under the terms of the GNU Lesser General Public
+ * License as published by the Free Software Foundation; either
+ * version 2.1 of the License, or (at your option) any later version.
+ *
+ * This library is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the GNU
+ * Lesser General Public License for more details.
+ *
+ * You should have received a copy of the GNU Lesser General Public
+ * License along with this library; if not, see <https://www.gnu.org/licenses/>.
+ *
+ ***************************************************************************/
+
+ 
+//! Serve loop over `std::io` streams
+
+use std::io::{self, ErrorKind, Read, Write};
+use std::string::String;
+
+use super::{ServeError, ascii_runs};
+use crate::context::AtContext;
+use crate::parser::AtParser;
+use crate::UserError;
+
+impl<'a, T, const SIZE: usize, S, E> AtParser<'a, T, SIZE, S, E>
+where
+    T: AtContext<SIZE, S, E> + ?Sized,
+    E: UserError {
+
+    /// Serve the commands received from `reader` until the end of its
+    /// stream, writing the echo and the framed responses to `writer`, like
+    /// [`serve`](AtParser::serve) over `std::io` streams.
+    ///
+    /// Runs the command table against stdin/stdout or a TCP stream on the
+    /// host, to exercise handlers before flashing them. Interrupted reads are
+    /// retried.
+    ///
+    /// # Errors
+    ///
+    /// The first failure of `reader` or `writer`.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// # use at_parser_rs::parser::AtParser;
+    /// # use at_parser_rs::context::AtContext;
+    /// # const SIZE: usize = 64;
+    /// # struct MyHandler; impl AtContext<SIZE> for MyHandler {}
+    /// let mut parser: AtParser<MyHandler, SIZE> = AtParser::new();
+    /// parser.set_line_ending(at_parser_rs::line::LineEnding::Lf);   // terminal lines
+    /// parser.serve_io(std::io::stdin(), std::io::stdout()).ok();
+    /// ```
+    pub fn serve_io<R: Read, W: Write>(&mut self, mut reader: R, mut writer: W) -> Result<(), ServeError<io::Error, io::Error>> {
+        let mut buf = [0u8; SIZE];
+        let mut response = String::new();
+        loop {
+            let len = match reader.read(&mut buf) {
+                Ok(0) => return Ok(()),
+                Ok(len) => len,
+                Err(e) if e.kind() == ErrorKind::Interrupted => continue,
+                Err(e) => return Err(ServeError::Read(e)),
+            };
+            let mut chunk = &buf[..len];
+            while !chunk.is_empty() {
+                let (consumed, echo) = self.serve_feed(chunk, &mut response);
+                if echo {
+                    for run in ascii_runs(&chunk[..consumed]) {
+                        writer.write_all(run).map_err(ServeError::Write)?;
+                    }
+                }
+                writer.write_all(response.as_bytes()).map_err(ServeError::Write)?;
+                chunk = &chunk[consumed..];
+            }
+            writer.flush().map_err(ServeError::Write)?;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::vec::Vec;
+
+    use crate::context::AtContext;
+    use crate::line::LineEnding;
+    use crate::parser::AtParser;
+    use crate::{AtResult, at_response};
+
+    const SIZE: usize = 64;
+
+    struct Version;
+
+    impl AtContext<SIZE> for Version {
+        fn exec(&mut self, at_response: &'static str) -> AtResult<'_, SIZE> {
+            Ok(at_response!(SIZE, at_response; "1.0"))
+        }
+    }
+
+    #[test]
+    fn serve_io_answers_a_terminal_session() {
+        let mut version = Version;
+        let commands: &mut [(&str, &str, &mut Version)] = &mut [("AT+GMR", "+GMR: ", &mut version)];
+        let mut parser: AtParser<Version, SIZE> = AtParser::new();
+        parser.set_commands(commands);
+        parser.set_line_ending(LineEnding::Lf);
+
+        let mut output = Vec::new();
+        parser.serve_io(&b"AT+GMR\nAT+NONE\n"[..], &mut output).unwrap();
+        assert_eq!(output, b"\r\n+GMR: 1.0\r\n\r\nOK\r\n\r\nERROR\r\n");
+    }
+}
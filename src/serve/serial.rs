@@ -0,0 +1,266 @@
+/***************************************************************************
+ *
+ * AT Command Parser
+ * Copyright (C) 2026 Antonio Salsi <passy.linux@zresa.it>
+ *
+ * This library is free software; you can redistribute it and/or
+ * modify it under the terms of the GNU Lesser General Public
+ * License as published by the Free Software Foundation; either
+ * version 2.1 of the License, or (at your option) any later version.
+ *
+ * This library is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the GNU
+ * Lesser General Public License for more details.
+ *
+ * You should have received a copy of the GNU Lesser General Public
+ * License along with this library; if not, see <https://www.gnu.org/licenses/>.
+ *
+ ***************************************************************************/
+
+ 
+//! Non-blocking serial adapter
+
+use embedded_hal_nb::nb;
+use embedded_hal_nb::serial::{ErrorType, Read, Write};
+
+use super::{Output, ServeError};
+use crate::context::AtContext;
+use crate::flow::SoftwareFlow;
+use crate::parser::AtParser;
+use crate::UserError;
+
+/// Serves a parser over an `embedded-hal-nb` serial port
+///
+/// [`poll`](NbSerial::poll) never blocks: it feeds the parser with the bytes
+/// received until the port has no more, and writes the echo and the framed
+/// responses until the port cannot take more. Output waiting to be written is
+/// kept in a transmit buffer of `TX` bytes, which must hold the longest
+/// framed response; no byte is read meanwhile, so a slow link holds the
+/// following commands back in the receive FIFO of the port.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// # use at_parser_rs::parser::AtParser;
+/// # use at_parser_rs::context::AtContext;
+/// # use at_parser_rs::serve::NbSerial;
+/// # use embedded_hal_nb::{nb, serial};
+/// # const SIZE: usize = 64;
+/// # struct MyHandler; impl AtContext<SIZE> for MyHandler {}
+/// # struct Uart;
+/// # impl serial::ErrorType for Uart { type Error = core::convert::Infallible; }
+/// # impl serial::Read for Uart { fn read(&mut self) -> nb::Result<u8, Self::Error> { Err(nb::Error::WouldBlock) } }
+/// # impl serial::Write for Uart {
+/// #     fn write(&mut self, _: u8) -> nb::Result<(), Self::Error> { Ok(()) }
+/// #     fn flush(&mut self) -> nb::Result<(), Self::Error> { Ok(()) }
+/// # }
+/// # let uart = Uart;
+/// let mut parser: AtParser<MyHandler, SIZE> = AtParser::new();
+/// let mut serial: NbSerial<_, 128> = NbSerial::new(uart);
+/// loop {
+///     serial.poll(&mut parser).ok();   // from the main loop or a UART interrupt
+/// }
+/// ```
+pub struct NbSerial<U, const TX: usize> {
+    /// Serial port
+    serial: U,
+    /// Output waiting to be written
+    tx: Output<TX>,
+}
+
+impl<U, const TX: usize> NbSerial<U, TX>
+where
+    U: Read<u8> + Write<u8> {
+
+    /// Serve over `serial`.
+    pub fn new(serial: U) -> Self {
+        Self { serial, tx: Output::new() }
+    }
+
+    /// Give the serial port back.
+    pub fn release(self) -> U {
+        self.serial
+    }
+
+    /// Whether all the output has been handed to the port.
+    pub fn is_idle(&self) -> bool {
+        self.tx.sent == self.tx.len
+    }
+
+    /// Write the pending output, then feed `parser` with the bytes received,
+    /// until the port would block.
+    ///
+    /// # Errors
+    ///
+    /// A failure of the port, or [`ServeError::Overflow`] when a response
+    /// was longer than the transmit buffer; it is then sent truncated.
+    pub fn poll<T, const SIZE: usize, S, E>(
+        &mut self,
+        parser: &mut AtParser<'_, T, SIZE, S, E>,
+    ) -> Result<(), ServeError<<U as ErrorType>::Error, <U as ErrorType>::Error>>
+    where
+        T: AtContext<SIZE, S, E> + ?Sized,
+        E: UserError {
+        self.serve(parser, None)
+    }
+
+    /// Poll like [`poll`](NbSerial::poll), with the software flow control of
+    /// `flow`, see [`flow`](crate::flow).
+    ///
+    /// The XON or XOFF queued by `flow` is written first. While the DTE
+    /// paused the output, the port is still read, so that its XON is seen:
+    /// the responses of the commands received meanwhile wait in the
+    /// transmit buffer.
+    ///
+    /// # Errors
+    ///
+    /// As [`poll`](NbSerial::poll).
+    pub fn poll_with_flow<T, const SIZE: usize, S, E>(
+        &mut self,
+        parser: &mut AtParser<'_, T, SIZE, S, E>,
+        flow: &SoftwareFlow,
+    ) -> Result<(), ServeError<<U as ErrorType>::Error, <U as ErrorType>::Error>>
+    where
+        T: AtContext<SIZE, S, E> + ?Sized,
+        E: UserError {
+        self.serve(parser, Some(flow))
+    }
+
+    /// Body of [`poll`](NbSerial::poll) and
+    /// [`poll_with_flow`](NbSerial::poll_with_flow).
+    fn serve<T, const SIZE: usize, S, E>(
+        &mut self,
+        parser: &mut AtParser<'_, T, SIZE, S, E>,
+        flow: Option<&SoftwareFlow>,
+    ) -> Result<(), ServeError<<U as ErrorType>::Error, <U as ErrorType>::Error>>
+    where
+        T: AtContext<SIZE, S, E> + ?Sized,
+        E: UserError {
+        loop {
+            if let Some(flow) = flow
+                && let Some(control) = flow.take_control() {
+                match self.serial.write(control) {
+                    Ok(()) => {}
+                    Err(nb::Error::WouldBlock) => {
+                        // Sent at the next poll
+                        flow.restore_control(control);
+                        return Ok(());
+                    }
+                    Err(nb::Error::Other(e)) => return Err(ServeError::Write(e)),
+                }
+            }
+            let paused = flow.is_some_and(SoftwareFlow::is_paused);
+            while !paused && self.tx.sent < self.tx.len {
+                match self.serial.write(self.tx.buf[self.tx.sent]) {
+                    Ok(()) => self.tx.sent += 1,
+                    Err(nb::Error::WouldBlock) => return Ok(()),
+                    Err(nb::Error::Other(e)) => return Err(ServeError::Write(e)),
+                }
+            }
+            if self.tx.sent == self.tx.len {
+                self.tx.sent = 0;
+                self.tx.len = 0;
+            }
+
+            let byte = match self.serial.read() {
+                Ok(byte) => byte,
+                Err(nb::Error::WouldBlock) => return Ok(()),
+                Err(nb::Error::Other(e)) => return Err(ServeError::Read(e)),
+            };
+            if flow.is_some_and(|flow| !flow.receive(byte)) {
+                continue;
+            }
+            if parser.echo() && byte.is_ascii() {
+                self.tx.queue(&[byte]);
+            }
+            // a response that does not fit is reported below
+            let _ = parser.feed_to(byte, Some(&mut self.tx));
+            if core::mem::take(&mut self.tx.overflow) {
+                return Err(ServeError::Overflow);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec::Vec;
+
+    use embedded_hal_nb::nb;
+    use embedded_hal_nb::serial::{ErrorKind, ErrorType, Read, Write};
+
+    use super::NbSerial;
+    use crate::context::AtContext;
+    use crate::parser::AtParser;
+    use crate::serve::ServeError;
+    use crate::{AtResult, at_response};
+
+    const SIZE: usize = 64;
+
+    struct Version;
+
+    impl AtContext<SIZE> for Version {
+        fn exec(&mut self, at_response: &'static str) -> AtResult<'_, SIZE> {
+            Ok(at_response!(SIZE, at_response; "EC25EFAR06A06M4G"))
+        }
+    }
+
+    /// Port receiving `rx` and taking a byte to transmit every other call
+    struct Port<'r> {
+        rx: &'r [u8],
+        tx: Vec<u8>,
+        busy: bool,
+    }
+
+    impl ErrorType for Port<'_> {
+        type Error = ErrorKind;
+    }
+
+    impl Read<u8> for Port<'_> {
+        fn read(&mut self) -> nb::Result<u8, ErrorKind> {
+            let (&byte, rest) = self.rx.split_first().ok_or(nb::Error::WouldBlock)?;
+            self.rx = rest;
+            Ok(byte)
+        }
+    }
+
+    impl Write<u8> for Port<'_> {
+        fn write(&mut self, byte: u8) -> nb::Result<(), ErrorKind> {
+            self.busy = !self.busy;
+            if self.busy {
+                return Err(nb::Error::WouldBlock);
+            }
+            self.tx.push(byte);
+            Ok(())
+        }
+
+        fn flush(&mut self) -> nb::Result<(), ErrorKind> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn responses_wait_for_the_port_to_take_them() {
+        let mut version = Version;
+        let commands: &mut [(&str, &str, &mut Version)] = &mut [("AT+CGMR", "", &mut version)];
+        let mut parser: AtParser<Version, SIZE> = AtParser::new();
+        parser.set_commands(commands);
+        parser.set_echo(true);
+
+        let mut serial: NbSerial<_, 32> = NbSerial::new(Port { rx: b"AT+CGMR\rATE0\r", tx: Vec::new(), busy: false });
+        let mut polls = 0;
+        while polls == 0 || !serial.is_idle() {
+            assert_eq!(serial.poll(&mut parser), Ok(()));
+            polls += 1;
+        }
+        assert!(polls > 20);
+        let port = serial.release();
+        assert!(port.rx.is_empty());
+        assert_eq!(core::str::from_utf8(&port.tx).unwrap(), "AT+CGMR\r\r\nEC25EFAR06A06M4G\r\n\r\nOK\r\nATE0\r\r\nOK\r\n");
+
+        let mut serial: NbSerial<_, 16> = NbSerial::new(Port { rx: b"AT+CGMR\r", tx: Vec::new(), busy: false });
+        let result = (0..64).map(|_| serial.poll(&mut parser)).find(Result::is_err);
+        assert_eq!(result, Some(Err(ServeError::Overflow)));
+    }
+}
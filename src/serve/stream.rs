@@ -0,0 +1,262 @@
+/***************************************************************************
+ *
+ * AT Command Parser
+ * Copyright (C) 2026 Antonio Salsi <passy.linux@zresa.it>
+ *
+ * This library is free software; you can redistribute it and/or
+ * modify it under the terms of the GNU Lesser General Public
+ * License as published by the Free Software Foundation; either
+ * version 2.1 of the License, or (at your option) any later version.
+ *
+ * This library is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the GNU
+ * Lesser General Public License for more details.
+ *
+ * You should have received a copy of the GNU Lesser General Public
+ * License along with this library; if not, see <https://www.gnu.org/licenses/>.
+ *
+ ***************************************************************************/
+
+ 
+//! Serve loops over `embedded-io` transports
+
+use alloc::string::String;
+
+use super::{ServeError, ascii_runs};
+use crate::context::AtContext;
+use crate::parser::AtParser;
+use crate::UserError;
+
+impl<'a, T, const SIZE: usize, S, E> AtParser<'a, T, SIZE, S, E>
+where
+    T: AtContext<SIZE, S, E> + ?Sized,
+    E: UserError {
+
+    /// Serve the commands received from `reader` until it reaches the end of
+    /// its stream, writing the echo and the framed responses to `writer`.
+    ///
+    /// Every response is framed (see [`Framing`](crate::response::Framing)),
+    /// whatever [`set_response_framing`](AtParser::set_response_framing) says;
+    /// characters are echoed while echo is enabled (`ATE1`). The loop writes
+    /// itself, so leave the [`output`](AtParser::set_output) of the parser
+    /// unset, or set to another sink dedicated to URCs.
+    ///
+    /// # Errors
+    ///
+    /// The first failure of `reader` or `writer`.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// # use at_parser_rs::parser::AtParser;
+    /// # use at_parser_rs::context::AtContext;
+    /// # const SIZE: usize = 64;
+    /// # struct MyHandler; impl AtContext<SIZE> for MyHandler {}
+    /// # struct Uart;
+    /// # impl embedded_io::ErrorType for Uart { type Error = core::convert::Infallible; }
+    /// # impl embedded_io::Read for Uart { fn read(&mut self, _: &mut [u8]) -> Result<usize, Self::Error> { Ok(0) } }
+    /// # impl embedded_io::Write for Uart {
+    /// #     fn write(&mut self, buf: &[u8]) -> Result<usize, Self::Error> { Ok(buf.len()) }
+    /// #     fn flush(&mut self) -> Result<(), Self::Error> { Ok(()) }
+    /// # }
+    /// # let (rx, tx) = (Uart, Uart);
+    /// let mut parser: AtParser<MyHandler, SIZE> = AtParser::new();
+    /// parser.set_echo(true);
+    /// parser.serve(rx, tx).ok();   // split halves of the UART driver
+    /// ```
+    pub fn serve<R, W>(&mut self, mut reader: R, mut writer: W) -> Result<(), ServeError<R::Error, W::Error>>
+    where
+        R: embedded_io::Read,
+        W: embedded_io::Write {
+        let mut buf = [0u8; SIZE];
+        let mut response = String::new();
+        loop {
+            let len = reader.read(&mut buf).map_err(ServeError::Read)?;
+            if len == 0 {
+                return Ok(());
+            }
+            let mut chunk = &buf[..len];
+            while !chunk.is_empty() {
+                let (consumed, echo) = self.serve_feed(chunk, &mut response);
+                if echo {
+                    for run in ascii_runs(&chunk[..consumed]) {
+                        writer.write_all(run).map_err(ServeError::Write)?;
+                    }
+                }
+                writer.write_all(response.as_bytes()).map_err(ServeError::Write)?;
+                chunk = &chunk[consumed..];
+            }
+            writer.flush().map_err(ServeError::Write)?;
+        }
+    }
+
+    /// Serve the commands received from `reader` like
+    /// [`serve`](AtParser::serve), awaiting the transfers of asynchronous
+    /// drivers such as the Embassy UARTs.
+    ///
+    /// Handlers are dispatched as by [`feed_slice`](AtParser::feed_slice);
+    /// each response is framed into a `String` before being written.
+    ///
+    /// # Errors
+    ///
+    /// The first failure of `reader` or `writer`.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// # use at_parser_rs::parser::AtParser;
+    /// # use at_parser_rs::context::AtContext;
+    /// # const SIZE: usize = 64;
+    /// # struct MyHandler; impl AtContext<SIZE> for MyHandler {}
+    /// # struct Uart;
+    /// # impl embedded_io_async::ErrorType for Uart { type Error = core::convert::Infallible; }
+    /// # impl embedded_io_async::Read for Uart { async fn read(&mut self, _: &mut [u8]) -> Result<usize, Self::Error> { Ok(0) } }
+    /// # impl embedded_io_async::Write for Uart { async fn write(&mut self, buf: &[u8]) -> Result<usize, Self::Error> { Ok(buf.len()) } }
+    /// async fn at_task(mut parser: AtParser<'static, MyHandler, SIZE>, rx: Uart, tx: Uart) {
+    ///     parser.set_echo(true);
+    ///     parser.serve_async(rx, tx).await.ok();
+    /// }
+    /// ```
+    #[cfg(feature = "embedded-io-async")]
+    pub async fn serve_async<R, W>(&mut self, mut reader: R, mut writer: W) -> Result<(), ServeError<R::Error, W::Error>>
+    where
+        R: embedded_io_async::Read,
+        W: embedded_io_async::Write {
+        let mut buf = [0u8; SIZE];
+        let mut response = String::new();
+        loop {
+            let len = reader.read(&mut buf).await.map_err(ServeError::Read)?;
+            if len == 0 {
+                return Ok(());
+            }
+            let mut chunk = &buf[..len];
+            while !chunk.is_empty() {
+                let (consumed, echo) = self.serve_feed(chunk, &mut response);
+                if echo {
+                    for run in ascii_runs(&chunk[..consumed]) {
+                        writer.write_all(run).await.map_err(ServeError::Write)?;
+                    }
+                }
+                writer.write_all(response.as_bytes()).await.map_err(ServeError::Write)?;
+                chunk = &chunk[consumed..];
+            }
+            writer.flush().await.map_err(ServeError::Write)?;
+        }
+    }
+
+}
+
+#[cfg(test)]
+// Some fixtures serve only the tests left out of the reduced builds
+#[cfg_attr(feature = "exec-set-only", allow(unused_imports, dead_code))]
+mod tests {
+    use alloc::vec::Vec;
+    use core::convert::Infallible;
+
+    use crate::context::AtContext;
+    use crate::parser::AtParser;
+    use crate::{Args, AtError, AtResult, at_response};
+
+    const SIZE: usize = 64;
+
+    struct Value {
+        value: u32,
+    }
+
+    impl AtContext<SIZE> for Value {
+        fn query(&mut self, at_response: &'static str) -> AtResult<'_, SIZE> {
+            Ok(at_response!(SIZE, at_response; self.value))
+        }
+
+        fn set(&mut self, at_response: &'static str, args: Args) -> AtResult<'_, SIZE> {
+            self.value = args.get_u32(0).map_err(|_| (at_response, AtError::InvalidArgs))?;
+            Ok(at_response!(SIZE, at_response; "OK"))
+        }
+    }
+
+    /// Receiver of the bytes of `rx` in chunks of `chunk`
+    struct Rx<'r> {
+        rx: &'r [u8],
+        chunk: usize,
+    }
+
+    /// Transmitter storing the bytes written
+    struct Tx(Vec<u8>);
+
+    impl embedded_io::ErrorType for Rx<'_> {
+        type Error = Infallible;
+    }
+
+    impl embedded_io::ErrorType for Tx {
+        type Error = Infallible;
+    }
+
+    impl embedded_io::Read for Rx<'_> {
+        fn read(&mut self, buf: &mut [u8]) -> Result<usize, Infallible> {
+            let len = self.rx.len().min(self.chunk).min(buf.len());
+            buf[..len].copy_from_slice(&self.rx[..len]);
+            self.rx = &self.rx[len..];
+            Ok(len)
+        }
+    }
+
+    impl embedded_io::Write for Tx {
+        fn write(&mut self, buf: &[u8]) -> Result<usize, Infallible> {
+            self.0.extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> Result<(), Infallible> {
+            Ok(())
+        }
+    }
+
+    #[cfg(feature = "embedded-io-async")]
+    impl embedded_io_async::Read for Rx<'_> {
+        async fn read(&mut self, buf: &mut [u8]) -> Result<usize, Infallible> {
+            embedded_io::Read::read(self, buf)
+        }
+    }
+
+    #[cfg(feature = "embedded-io-async")]
+    impl embedded_io_async::Write for Tx {
+        async fn write(&mut self, buf: &[u8]) -> Result<usize, Infallible> {
+            embedded_io::Write::write(self, buf)
+        }
+    }
+
+    #[cfg(not(feature = "exec-set-only"))]
+    #[test]
+    fn serve_writes_echo_and_framed_responses() {
+        let mut value = Value { value: 7 };
+        let commands: &mut [(&str, &str, &mut Value)] = &mut [("AT+VAL", "+VAL: ", &mut value)];
+        let mut parser: AtParser<Value, SIZE> = AtParser::new();
+        parser.set_commands(commands);
+        parser.set_echo(true);
+
+        let rx = Rx { rx: b"AT+VAL?\rATE0\rAT+VAL=9;+VAL?\rAT+NONE\r", chunk: 5 };
+        let mut tx = Tx(Vec::new());
+        assert_eq!(parser.serve(rx, &mut tx), Ok(()));
+        assert_eq!(
+            core::str::from_utf8(&tx.0).unwrap(),
+            "AT+VAL?\r\r\n+VAL: 7\r\n\r\nOK\r\nATE0\r\r\nOK\r\n\r\n+VAL: 9\r\n\r\nOK\r\n\r\nERROR\r\n"
+        );
+    }
+
+    #[cfg(all(feature = "embedded-io-async", not(feature = "exec-set-only")))]
+    #[test]
+    fn serve_async_awaits_the_transfers() {
+        let mut value = Value { value: 7 };
+        let commands: &mut [(&str, &str, &mut Value)] = &mut [("AT+VAL", "+VAL: ", &mut value)];
+        let mut parser: AtParser<Value, SIZE> = AtParser::new();
+        parser.set_commands(commands);
+
+        let rx = Rx { rx: b"AT+VAL=3\rAT+VAL?\r", chunk: 4 };
+        let mut tx = Tx(Vec::new());
+        let mut cx = core::task::Context::from_waker(core::task::Waker::noop());
+        let served = core::pin::pin!(parser.serve_async(rx, &mut tx)).poll(&mut cx);
+        assert_eq!(served, core::task::Poll::Ready(Ok(())));
+        assert_eq!(core::str::from_utf8(&tx.0).unwrap(), "\r\n+VAL: OK\r\n\r\nOK\r\n\r\n+VAL: 3\r\n\r\nOK\r\n");
+    }
+}
@@ -0,0 +1,263 @@
+/***************************************************************************
+ *
+ * AT Command Parser
+ * Copyright (C) 2026 Antonio Salsi <passy.linux@zresa.it>
+ *
+ * This library is free software; you can redistribute it and/or
+ * modify it under the terms of the GNU Lesser General Public
+ * License as published by the Free Software Foundation; either
+ * version 2.1 of the License, or (at your option) any later version.
+ *
+ * This library is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the GNU
+ * Lesser General Public License for more details.
+ *
+ * You should have received a copy of the GNU Lesser General Public
+ * License along with this library; if not, see <https://www.gnu.org/licenses/>.
+ *
+ ***************************************************************************/
+
+ 
+//! USB CDC-ACM adapter
+
+use usb_device::UsbError;
+use usb_device::bus::UsbBus;
+use usbd_serial::CdcAcmClass;
+
+use super::{Output, ServeError};
+use crate::context::AtContext;
+use crate::parser::AtParser;
+use crate::UserError;
+
+/// Largest packet of a full-speed bulk endpoint
+const PACKET: usize = 64;
+
+/// Bulk endpoints of a USB CDC-ACM interface
+///
+/// Implemented by [`CdcAcmClass`]; implement it for another class driver
+/// exposing the same packet interface.
+pub trait CdcEndpoints {
+    /// Maximum packet size of the endpoints, at most 64 bytes.
+    fn max_packet_size(&self) -> usize;
+
+    /// Read a packet received from the host, [`UsbError::WouldBlock`] when
+    /// there is none.
+    fn read_packet(&mut self, data: &mut [u8]) -> Result<usize, UsbError>;
+
+    /// Write a packet of at most [`max_packet_size`](CdcEndpoints::max_packet_size)
+    /// bytes, [`UsbError::WouldBlock`] while the previous one is in flight.
+    fn write_packet(&mut self, data: &[u8]) -> Result<usize, UsbError>;
+}
+
+impl<B: UsbBus> CdcEndpoints for CdcAcmClass<'_, B> {
+    fn max_packet_size(&self) -> usize {
+        CdcAcmClass::max_packet_size(self).into()
+    }
+
+    fn read_packet(&mut self, data: &mut [u8]) -> Result<usize, UsbError> {
+        CdcAcmClass::read_packet(self, data)
+    }
+
+    fn write_packet(&mut self, data: &[u8]) -> Result<usize, UsbError> {
+        CdcAcmClass::write_packet(self, data)
+    }
+}
+
+/// Serves a parser over a USB CDC-ACM class
+///
+/// [`poll`](UsbCdc::poll) goes after each `UsbDevice::poll` and never
+/// blocks. Responses are sent in packets of the endpoint size; a response
+/// ending on a full packet is followed by a zero-length packet, without
+/// which the host keeps waiting for the end of the transfer. Output waiting
+/// to be sent is kept in a transmit buffer of `TX` bytes, which must hold
+/// the longest framed response plus the echo of a packet; received packets
+/// are held back meanwhile.
+///
+/// # Example
+///
+/// ```rust,ignore
+/// use at_parser_rs::serve::UsbCdc;
+/// use usbd_serial::CdcAcmClass;
+///
+/// let class = CdcAcmClass::new(&usb_bus, 64);
+/// let mut usb_dev = UsbDeviceBuilder::new(&usb_bus, UsbVidPid(0x16c0, 0x27dd)).build();
+/// let mut cdc: UsbCdc<_, 256> = UsbCdc::new(class);
+///
+/// loop {
+///     usb_dev.poll(&mut [cdc.class()]);
+///     cdc.poll(&mut parser).ok();
+/// }
+/// ```
+pub struct UsbCdc<C, const TX: usize> {
+    /// Class driver
+    class: C,
+    /// Output waiting to be sent
+    tx: Output<TX>,
+    /// Whether the transfer ended on a full packet, still to be terminated
+    zlp: bool,
+    /// Packet received from the host
+    rx: [u8; PACKET],
+    /// Number of bytes of `rx` already fed to the parser
+    fed: usize,
+    /// Number of valid bytes in `rx`
+    received: usize,
+}
+
+impl<C: CdcEndpoints, const TX: usize> UsbCdc<C, TX> {
+    /// Serve over `class`.
+    pub fn new(class: C) -> Self {
+        Self { class, tx: Output::new(), zlp: false, rx: [0; PACKET], fed: 0, received: 0 }
+    }
+
+    /// The class driver, e.g. to pass it to `UsbDevice::poll`.
+    pub fn class(&mut self) -> &mut C {
+        &mut self.class
+    }
+
+    /// Give the class driver back.
+    pub fn release(self) -> C {
+        self.class
+    }
+
+    /// Whether all the output has been sent, transfer terminated.
+    pub fn is_idle(&self) -> bool {
+        self.tx.sent == self.tx.len && !self.zlp
+    }
+
+    /// Send the pending output, then feed `parser` with the packets
+    /// received, until the endpoints would block.
+    ///
+    /// # Errors
+    ///
+    /// A failure of the endpoints, or [`ServeError::Overflow`] when a
+    /// response was longer than the transmit buffer; it is then sent
+    /// truncated.
+    pub fn poll<T, const SIZE: usize, S, E>(&mut self, parser: &mut AtParser<'_, T, SIZE, S, E>) -> Result<(), ServeError<UsbError, UsbError>>
+    where
+        T: AtContext<SIZE, S, E> + ?Sized,
+        E: UserError {
+        let max = self.class.max_packet_size().min(PACKET);
+        loop {
+            while self.tx.sent < self.tx.len {
+                let len = (self.tx.len - self.tx.sent).min(max);
+                match self.class.write_packet(&self.tx.buf[self.tx.sent..self.tx.sent + len]) {
+                    Ok(written) => {
+                        self.tx.sent += written;
+                        self.zlp = written == max;
+                    }
+                    Err(UsbError::WouldBlock) => return Ok(()),
+                    Err(e) => return Err(ServeError::Write(e)),
+                }
+            }
+            self.tx.sent = 0;
+            self.tx.len = 0;
+            if self.zlp {
+                match self.class.write_packet(&[]) {
+                    Ok(_) => self.zlp = false,
+                    Err(UsbError::WouldBlock) => return Ok(()),
+                    Err(e) => return Err(ServeError::Write(e)),
+                }
+            }
+
+            if self.fed == self.received {
+                self.fed = 0;
+                self.received = match self.class.read_packet(&mut self.rx[..max]) {
+                    Ok(received) => received,
+                    Err(UsbError::WouldBlock) => return Ok(()),
+                    Err(e) => return Err(ServeError::Read(e)),
+                };
+            }
+            // Up to the end of the packet or of the first line answered
+            while self.fed < self.received {
+                let byte = self.rx[self.fed];
+                self.fed += 1;
+                if parser.echo() && byte.is_ascii() {
+                    self.tx.queue(&[byte]);
+                }
+                if parser.feed_to(byte, Some(&mut self.tx)).is_some() {
+                    break;
+                }
+            }
+            if core::mem::take(&mut self.tx.overflow) {
+                return Err(ServeError::Overflow);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec::Vec;
+
+    use usb_device::UsbError;
+
+    use super::{CdcEndpoints, UsbCdc};
+    use crate::context::AtContext;
+    use crate::parser::AtParser;
+    use crate::{AtResult, at_response};
+
+    const SIZE: usize = 64;
+
+    struct Version;
+
+    impl AtContext<SIZE> for Version {
+        fn exec(&mut self, at_response: &'static str) -> AtResult<'_, SIZE> {
+            Ok(at_response!(SIZE, at_response; "EC25EFAR06A06M"))
+        }
+    }
+
+    /// Class of 8-byte packets receiving `rx`, busy every other write
+    struct Class<'r> {
+        rx: Vec<&'r [u8]>,
+        tx: Vec<Vec<u8>>,
+        busy: bool,
+    }
+
+    impl CdcEndpoints for Class<'_> {
+        fn max_packet_size(&self) -> usize {
+            8
+        }
+
+        fn read_packet(&mut self, data: &mut [u8]) -> Result<usize, UsbError> {
+            if self.rx.is_empty() {
+                return Err(UsbError::WouldBlock);
+            }
+            let packet = self.rx.remove(0);
+            data[..packet.len()].copy_from_slice(packet);
+            Ok(packet.len())
+        }
+
+        fn write_packet(&mut self, data: &[u8]) -> Result<usize, UsbError> {
+            assert!(data.len() <= 8);
+            self.busy = !self.busy;
+            if self.busy {
+                return Err(UsbError::WouldBlock);
+            }
+            self.tx.push(data.to_vec());
+            Ok(data.len())
+        }
+    }
+
+    #[test]
+    fn responses_go_in_packets_ended_by_a_zero_length_one() {
+        let mut version = Version;
+        let commands: &mut [(&str, &str, &mut Version)] = &mut [("AT+CGMR", "", &mut version)];
+        let mut parser: AtParser<Version, SIZE> = AtParser::new();
+        parser.set_commands(commands);
+
+        let rx: Vec<&[u8]> = Vec::from([&b"AT+CGMR\r"[..], b"AT+CGMR\r", b"ATE0\r"]);
+        let mut cdc: UsbCdc<_, 64> = UsbCdc::new(Class { rx, tx: Vec::new(), busy: false });
+        let mut polls = 0;
+        while polls == 0 || !cdc.is_idle() || !cdc.class().rx.is_empty() {
+            assert_eq!(cdc.poll(&mut parser), Ok(()));
+            polls += 1;
+        }
+
+        let packets = cdc.release().tx;
+        let lens: Vec<usize> = packets.iter().map(Vec::len).collect();
+        // 24 bytes: three full packets, then the zero-length one
+        assert_eq!(lens, [8, 8, 8, 0, 8, 8, 8, 0, 6]);
+        assert_eq!(packets.concat(), b"\r\nEC25EFAR06A06M\r\n\r\nOK\r\n\r\nEC25EFAR06A06M\r\n\r\nOK\r\n\r\nOK\r\n");
+    }
+}
@@ -0,0 +1,153 @@
+/***************************************************************************
+ *
+ * AT Command Parser
+ * Copyright (C) 2026 Antonio Salsi <passy.linux@zresa.it>
+ *
+ * This library is free software; you can redistribute it and/or
+ * modify it under the terms of the GNU Lesser General Public
+ * License as published by the Free Software Foundation; either
+ * version 2.1 of the License, or (at your option) any later version.
+ *
+ * This library is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the GNU
+ * Lesser General Public License for more details.
+ *
+ * You should have received a copy of the GNU Lesser General Public
+ * License along with this library; if not, see <https://www.gnu.org/licenses/>.
+ *
+ ***************************************************************************/
+
+//! S-register subsystem
+//!
+//! A fixed array of numeric registers addressed with `ATS<n>=<value>` and
+//! `ATS<n>?`. Once enabled on the [`AtParser`](crate::parser::AtParser) with
+//! [`enable_s_registers`](crate::parser::AtParser::enable_s_registers), the
+//! registers that V.250 assigns to the command line are honoured:
+//!
+//! | Register | Meaning | Default |
+//! |---|---|---|
+//! | `S3` | Command line termination character (`13` or `10`) | `13` (`\r`) |
+//! | `S4` | Response formatting character | `10` (`\n`) |
+//! | `S5` | Command line editing (backspace) character | `8` |
+//!
+//! The remaining registers are plain storage for the application.
+
+use crate::AtError;
+use crate::line::LineEnding;
+
+/// Number of S-registers (`S0` to `S15`).
+pub const S_REGISTER_COUNT: usize = 16;
+
+/// Automatic answer ring count
+pub const S0: usize = 0;
+/// Escape character
+pub const S2: usize = 2;
+/// Command line termination character
+pub const S3: usize = 3;
+/// Response formatting character
+pub const S4: usize = 4;
+/// Command line editing character
+pub const S5: usize = 5;
+/// Pause before blind dialling
+pub const S6: usize = 6;
+/// Connection completion timeout
+pub const S7: usize = 7;
+/// Comma dial modifier time
+pub const S8: usize = 8;
+/// Automatic disconnect delay
+pub const S10: usize = 10;
+/// Escape prompt delay (guard time, in 1/50 s)
+pub const S12: usize = 12;
+
+/// Fixed array of S-registers initialized with the V.250 defaults.
+///
+/// # Example
+///
+/// ```rust
+/// use at_parser_rs::sreg::{SRegisters, S3};
+///
+/// let mut regs = SRegisters::new();
+/// assert_eq!(regs.get(S3), Some(13));
+/// assert!(regs.set(7, 60).is_ok());
+/// assert!(regs.set(S3, 42).is_err());   // not a supported terminator
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SRegisters {
+    values: [u8; S_REGISTER_COUNT],
+}
+
+impl SRegisters {
+
+    /// V.250 default values.
+    pub const DEFAULTS: [u8; S_REGISTER_COUNT] = [0, 0, 43, 13, 10, 8, 2, 50, 2, 0, 14, 0, 50, 0, 0, 0];
+
+    /// Create a register file holding the V.250 default values.
+    pub const fn new() -> Self {
+        Self { values: Self::DEFAULTS }
+    }
+
+    /// Read register `index`, or `None` when out of range.
+    pub fn get(&self, index: usize) -> Option<u8> {
+        self.values.get(index).copied()
+    }
+
+    /// Write `value` into register `index`.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(())` — the register has been updated
+    /// * `Err(AtError::InvalidArgs)` — `index` is out of range, or `S3` is
+    ///   written with a character other than `13` (`\r`) or `10` (`\n`)
+    pub fn set(&mut self, index: usize, value: u8) -> Result<(), AtError<'static>> {
+        if index == S3 && value != b'\r' && value != b'\n' {
+            return Err(AtError::InvalidArgs);
+        }
+
+        let slot = self.values.get_mut(index).ok_or(AtError::InvalidArgs)?;
+        *slot = value;
+        Ok(())
+    }
+
+    /// Restore the V.250 default values.
+    pub fn reset(&mut self) {
+        self.values = Self::DEFAULTS;
+    }
+
+    /// Command line termination convention selected by `S3`.
+    pub fn line_ending(&self) -> LineEnding {
+        match self.values[S3] {
+            b'\n' => LineEnding::Lf,
+            _ => LineEnding::Cr,
+        }
+    }
+
+    /// Response line ending formed by `S3` followed by `S4`.
+    pub fn response_ending(&self) -> LineEnding {
+        match (self.values[S3], self.values[S4]) {
+            (b'\n', _) => LineEnding::Lf,
+            (_, b'\n') => LineEnding::CrLf,
+            _ => LineEnding::Cr,
+        }
+    }
+
+    /// Command line editing character selected by `S5`.
+    pub fn backspace(&self) -> u8 {
+        self.values[S5]
+    }
+}
+
+impl Default for SRegisters {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Register index addressed by a basic command name such as `S3`.
+pub(crate) fn index(name: &str) -> Option<usize> {
+    let digits = name.strip_prefix(['S', 's'])?;
+    if digits.is_empty() || !digits.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    digits.parse().ok()
+}
@@ -0,0 +1,296 @@
+/***************************************************************************
+ *
+ * AT Command Parser
+ * Copyright (C) 2026 Antonio Salsi <passy.linux@zresa.it>
+ *
+ * This library is free software; you can redistribute it and/or
+ * modify it under the terms of the GNU Lesser General Public
+ * License as published by the Free Software Foundation; either
+ * version 2.1 of the License, or (at your option) any later version.
+ *
+ * This library is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the GNU
+ * Lesser General Public License for more details.
+ *
+ * You should have received a copy of the GNU Lesser General Public
+ * License along with this library; if not, see <https://www.gnu.org/licenses/>.
+ *
+ ***************************************************************************/
+
+ 
+//! RTOS task serving the parser over osal-rs queues
+//!
+//! With an RTOS (the `freertos` or `posix` feature), an [`AtTask`] owns the
+//! parser and runs it from a task of its own. Other tasks (the UART receive
+//! task, a BLE service, a test console) post whole command lines to the line
+//! queue; the task executes them one at a time and posts the framed response
+//! of each to the response queue, so the handlers never run concurrently and
+//! need no locking.
+//!
+//! Both queues carry [`Message`] slots of `MSG` bytes and must be created
+//! with that message size:
+//!
+//! ```text
+//! UART task                    AT task                      UART task
+//! "AT+CGMR" → lines.post()     lines.fetch() → execute()
+//!                              responses.post()  →  "\r\nEC25\r\n\r\nOK\r\n"
+//! ```
+
+use core::convert::Infallible;
+use core::fmt;
+
+use osal_rs::os::types::TickType;
+use osal_rs::os::{Queue, QueueFn};
+
+use crate::context::AtContext;
+use crate::parser::AtParser;
+use crate::UserError;
+
+/// Fixed-size text slot exchanged over the queues of an [`AtTask`]
+///
+/// Text shorter than `MSG` bytes is padded with NUL bytes; longer text is
+/// truncated. Post [`as_bytes`](Message::as_bytes) and fetch through
+/// [`fill`](Message::fill).
+///
+/// # Example
+///
+/// ```rust
+/// # use at_parser_rs::task::Message;
+/// let line = Message::<32>::from("AT+CGMR");
+/// assert_eq!(line.as_str(), "AT+CGMR");
+/// assert_eq!(line.as_bytes().len(), 32);
+/// ```
+#[derive(Clone)]
+pub struct Message<const MSG: usize> {
+    buf: [u8; MSG],
+    /// Number of text bytes in `buf`
+    len: usize,
+}
+
+impl<const MSG: usize> Message<MSG> {
+    /// Empty message.
+    pub const fn new() -> Self {
+        Self { buf: [0; MSG], len: 0 }
+    }
+
+    /// Text of the message, up to the first NUL byte or invalid UTF-8
+    /// sequence.
+    pub fn as_str(&self) -> &str {
+        let text = &self.buf[..self.len];
+        match core::str::from_utf8(text) {
+            Ok(text) => text,
+            Err(e) => core::str::from_utf8(&text[..e.valid_up_to()]).unwrap_or_default(),
+        }
+    }
+
+    /// Whether the message holds no text.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Whole slot, as posted to a queue.
+    pub fn as_bytes(&self) -> &[u8; MSG] {
+        &self.buf
+    }
+
+    /// Run `fetch` over the whole slot, then take its text up to the first
+    /// NUL byte.
+    ///
+    /// Returns the result of `fetch`, e.g. `queue.fetch(buffer, time)`.
+    pub fn fill<R>(&mut self, fetch: impl FnOnce(&mut [u8]) -> R) -> R {
+        self.buf = [0; MSG];
+        let result = fetch(&mut self.buf);
+        self.len = self.buf.iter().position(|&byte| byte == 0).unwrap_or(MSG);
+        result
+    }
+
+    /// Remove the text.
+    pub fn clear(&mut self) {
+        self.buf = [0; MSG];
+        self.len = 0;
+    }
+}
+
+impl<const MSG: usize> Default for Message<MSG> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const MSG: usize> From<&str> for Message<MSG> {
+    /// Message holding `text`, truncated to `MSG` bytes on a character
+    /// boundary.
+    fn from(text: &str) -> Self {
+        let mut message = Self::new();
+        let _ = fmt::Write::write_str(&mut message, text);
+        message
+    }
+}
+
+impl<const MSG: usize> fmt::Write for Message<MSG> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        let mut len = s.len().min(MSG - self.len);
+        while !s.is_char_boundary(len) {
+            len -= 1;
+        }
+        self.buf[self.len..self.len + len].copy_from_slice(&s.as_bytes()[..len]);
+        self.len += len;
+        if len < s.len() { Err(fmt::Error) } else { Ok(()) }
+    }
+}
+
+impl<const MSG: usize> fmt::Debug for Message<MSG> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(self.as_str(), f)
+    }
+}
+
+/// Task owning a parser fed by an osal-rs queue
+///
+/// Command lines are fetched from `lines` and their framed responses posted
+/// to `responses`, both queues of [`Message`] slots of `MSG` bytes. Bare
+/// [`AtResponse::Deferred`](crate::AtResponse::Deferred) results post
+/// nothing; complete them with [`parser`](AtTask::parser) and an output sink
+/// (see [`AtParser::set_output`]).
+///
+/// # Example
+///
+/// ```rust,no_run
+/// # use at_parser_rs::parser::AtParser;
+/// # use at_parser_rs::context::AtContext;
+/// # use at_parser_rs::task::{AtTask, Message};
+/// # use osal_rs::os::{Queue, QueueFn};
+/// # const SIZE: usize = 64;
+/// # struct MyHandler; impl AtContext<SIZE> for MyHandler {}
+/// const MSG: usize = 128;
+/// let lines = Queue::new(4, MSG as _).unwrap();
+/// let responses = Queue::new(4, MSG as _).unwrap();
+///
+/// // AT task
+/// let parser: AtParser<MyHandler, SIZE> = AtParser::new();
+/// let mut task: AtTask<_, SIZE, MSG> = AtTask::new(parser, &lines, &responses);
+/// # if false {
+/// task.run();
+/// # }
+///
+/// // UART task, once a line is received
+/// lines.post(Message::<MSG>::from("AT+CGMR").as_bytes(), 100).ok();
+/// ```
+pub struct AtTask<'a, 'q, T, const SIZE: usize, const MSG: usize, S = (), E = Infallible>
+where
+    T: AtContext<SIZE, S, E> + ?Sized {
+    parser: AtParser<'a, T, SIZE, S, E>,
+    /// Queue of the command lines to execute
+    lines: &'q Queue,
+    /// Queue taking the framed responses
+    responses: &'q Queue,
+}
+
+impl<'a, 'q, T, const SIZE: usize, const MSG: usize, S, E> AtTask<'a, 'q, T, SIZE, MSG, S, E>
+where
+    T: AtContext<SIZE, S, E> + ?Sized,
+    E: UserError {
+
+    /// Serve `parser` over `lines` and `responses`.
+    pub fn new(parser: AtParser<'a, T, SIZE, S, E>, lines: &'q Queue, responses: &'q Queue) -> Self {
+        Self { parser, lines, responses }
+    }
+
+    /// The parser, e.g. to reconfigure it between two lines.
+    pub fn parser(&mut self) -> &mut AtParser<'a, T, SIZE, S, E> {
+        &mut self.parser
+    }
+
+    /// Give the parser back.
+    pub fn into_parser(self) -> AtParser<'a, T, SIZE, S, E> {
+        self.parser
+    }
+
+    /// Wait up to `time` ticks for a command line and execute it.
+    ///
+    /// The response is posted waiting up to `time` ticks as well; when the
+    /// response queue stays full it is dropped.
+    ///
+    /// # Returns
+    ///
+    /// Whether a line was received and executed.
+    pub fn step(&mut self, time: TickType) -> bool {
+        let mut line = Message::<MSG>::new();
+        if line.fill(|buffer| self.lines.fetch(buffer, time)).is_err() {
+            return false;
+        }
+
+        let mut response = Message::<MSG>::new();
+        respond(&mut self.parser, line.as_str(), &mut response);
+        if !response.is_empty() {
+            let _ = self.responses.post(response.as_bytes(), time);
+        }
+        true
+    }
+
+    /// Execute the command lines as they arrive, forever.
+    ///
+    /// Call it as the body of the task owning the parser.
+    pub fn run(&mut self) -> ! {
+        loop {
+            self.step(TickType::MAX);
+        }
+    }
+}
+
+/// Execute `line` and write its framed response to `response`, truncated to
+/// the size of the message.
+fn respond<T, const SIZE: usize, const MSG: usize, S, E>(parser: &mut AtParser<'_, T, SIZE, S, E>, line: &str, response: &mut Message<MSG>)
+where
+    T: AtContext<SIZE, S, E> + ?Sized,
+    E: UserError {
+    // truncated to the message when longer
+    let _ = parser.respond(Some(line.as_bytes()), Some(response));
+}
+
+#[cfg(test)]
+mod tests {
+    use core::fmt::Write;
+
+    use super::{Message, respond};
+    use crate::context::AtContext;
+    use crate::parser::AtParser;
+    use crate::{AtResult, at_response};
+
+    const SIZE: usize = 64;
+
+    struct Version;
+
+    impl AtContext<SIZE> for Version {
+        fn exec(&mut self, at_response: &'static str) -> AtResult<'_, SIZE> {
+            Ok(at_response!(SIZE, at_response; "EC25EFAR06A06M4G"))
+        }
+    }
+
+    #[test]
+    fn lines_are_answered_with_framed_messages() {
+        let mut version = Version;
+        let commands: &mut [(&str, &str, &mut Version)] = &mut [("AT+CGMR", "", &mut version)];
+        let mut parser: AtParser<Version, SIZE> = AtParser::new();
+        parser.set_commands(commands);
+
+        // A line as fetched from the queue: NUL-padded slot
+        let mut line = Message::<32>::new();
+        line.fill(|buffer| buffer[..7].copy_from_slice(b"AT+CGMR"));
+        assert_eq!(line.as_str(), "AT+CGMR");
+
+        let mut response = Message::<32>::new();
+        respond(&mut parser, line.as_str(), &mut response);
+        assert_eq!(response.as_str(), "\r\nEC25EFAR06A06M4G\r\n\r\nOK\r\n");
+        assert_eq!(&response.as_bytes()[response.as_str().len()..], &[0; 6]);
+
+        let mut response = Message::<8>::new();
+        respond(&mut parser, "AT+CGMR", &mut response);
+        assert_eq!(response.as_str(), "\r\nEC25EF");
+
+        let mut message = Message::<4>::from("AT");
+        assert!(message.write_str("+é").is_err());
+        assert_eq!(message.as_str(), "AT+");
+    }
+}
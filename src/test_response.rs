@@ -0,0 +1,207 @@
+/***************************************************************************
+ *
+ * AT Command Parser
+ * Copyright (C) 2026 Antonio Salsi <passy.linux@zresa.it>
+ *
+ * This library is free software; you can redistribute it and/or
+ * modify it under the terms of the GNU Lesser General Public
+ * License as published by the Free Software Foundation; either
+ * version 2.1 of the License, or (at your option) any later version.
+ *
+ * This library is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the GNU
+ * Lesser General Public License for more details.
+ *
+ * You should have received a copy of the GNU Lesser General Public
+ * License along with this library; if not, see <https://www.gnu.org/licenses/>.
+ *
+ ***************************************************************************/
+
+ 
+//! Client-side parsing of `=?` test responses
+//!
+//! When the crate is used on the DTE side, a `AT+CMD=?` query returns the
+//! supported values of each parameter as parenthesized lists, e.g.
+//! `+COPS: (0-4),(0,1),("A","B")`. [`TestResponse`] splits such a line
+//! into one [`ParamSpec`] per parameter, whose [`Value`]s are single
+//! numbers, numeric ranges or strings.
+//!
+//! # Example
+//!
+//! ```rust
+//! use at_parser_rs::test_response::{TestResponse, Value};
+//!
+//! let response = TestResponse::new(r#"+COPS: (0-4),(0,1),( "A","B")"#);
+//! let mode = response.param(0).unwrap();
+//! assert!(mode.contains(3));
+//! assert_eq!(mode.values().next(), Some(Value::Range(0, 4)));
+//! assert!(response.param(2).unwrap().contains_str("B"));
+//! assert_eq!(response.len(), 3);
+//! ```
+
+use crate::Split;
+
+/// A parsed `=?` test response line
+#[derive(Debug, Clone, Copy)]
+pub struct TestResponse<'a> {
+    body: &'a str,
+}
+
+impl<'a> TestResponse<'a> {
+    /// Wrap a test response line.
+    ///
+    /// A leading `+NAME:` information prefix is skipped, so both
+    /// `+COPS: (0-4)` and `(0-4)` are accepted.
+    pub fn new(line: &'a str) -> Self {
+        let line = line.trim();
+        let body = match line.split_once(':') {
+            Some((prefix, rest)) if prefix.starts_with('+') && !prefix.contains('(') => rest,
+            _ => line,
+        };
+
+        Self { body: body.trim() }
+    }
+
+    /// Number of parameters described by the response.
+    pub fn len(&self) -> usize {
+        if self.is_empty() { 0 } else { self.params().count() }
+    }
+
+    /// Whether the response describes no parameter at all.
+    pub fn is_empty(&self) -> bool {
+        self.body.is_empty()
+    }
+
+    /// Supported values of the parameter at `index` (0-based).
+    pub fn param(&self, index: usize) -> Option<ParamSpec<'a>> {
+        self.params().nth(index)
+    }
+
+    /// Iterate over the supported values of every parameter in order.
+    pub fn params(&self) -> impl Iterator<Item = ParamSpec<'a>> + use<'a> {
+        Split::new(self.body).map(|(spec, quoted)| {
+            let spec = spec.trim();
+            let list = if quoted {
+                None
+            } else {
+                spec.strip_prefix('(').and_then(|inner| inner.strip_suffix(')'))
+            };
+
+            ParamSpec { raw: list.unwrap_or(spec) }
+        })
+    }
+}
+
+/// Supported values of one parameter, i.e. the content of one `( ... )`
+/// group
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ParamSpec<'a> {
+    raw: &'a str,
+}
+
+impl<'a> ParamSpec<'a> {
+    /// The group content without the surrounding parentheses.
+    pub fn as_str(&self) -> &'a str {
+        self.raw
+    }
+
+    /// Iterate over the values and ranges listed in the group.
+    ///
+    /// An empty group (`()`) yields nothing.
+    pub fn values(&self) -> impl Iterator<Item = Value<'a>> + use<'a> {
+        let raw = self.raw;
+        Split::new(raw)
+            .filter(move |_| !raw.trim().is_empty())
+            .map(|(value, _)| Value::parse(value))
+    }
+
+    /// Whether `value` is one of the listed numbers or inside a listed
+    /// range.
+    pub fn contains(&self, value: i64) -> bool {
+        self.values().any(|item| match item {
+            Value::Int(n) => n == value,
+            Value::Range(low, high) => (low..=high).contains(&value),
+            Value::Str(_) => false,
+        })
+    }
+
+    /// Whether `value` is one of the listed strings.
+    pub fn contains_str(&self, value: &str) -> bool {
+        self.values().any(|item| item == Value::Str(value))
+    }
+}
+
+/// One entry of a [`ParamSpec`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Value<'a> {
+    /// A single number, e.g. `1`
+    Int(i64),
+    /// An inclusive range, e.g. `0-4`
+    Range(i64, i64),
+    /// A string, e.g. `"A"`, with the quotes removed; unquoted entries
+    /// that are not numbers are reported as strings too
+    Str(&'a str),
+}
+
+impl<'a> Value<'a> {
+    fn parse(value: &'a str) -> Self {
+        let value = value.trim();
+
+        if let Some(inner) = value.strip_prefix('"').and_then(|rest| rest.strip_suffix('"')) {
+            return Value::Str(inner);
+        }
+        if let Ok(number) = value.parse() {
+            return Value::Int(number);
+        }
+
+        // skip the first character so that a leading minus sign is not
+        // taken as the range separator
+        let range = value
+            .char_indices()
+            .skip(1)
+            .find(|&(_, ch)| ch == '-')
+            .and_then(|(offset, _)| {
+                Some((value[..offset].trim().parse().ok()?, value[offset + 1..].trim().parse().ok()?))
+            });
+
+        match range {
+            Some((low, high)) => Value::Range(low, high),
+            None => Value::Str(value),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{TestResponse, Value};
+    use alloc::vec::Vec;
+
+    #[test]
+    fn parses_ranges_lists_and_strings() {
+        let response = TestResponse::new(r#"+COPS: (0-4),(0,1),( "A","B"),(-5--1,7)"#);
+
+        assert_eq!(response.len(), 4);
+        let values: Vec<_> = response.param(0).unwrap().values().collect();
+        assert_eq!(values, [Value::Range(0, 4)]);
+        let values: Vec<_> = response.param(1).unwrap().values().collect();
+        assert_eq!(values, [Value::Int(0), Value::Int(1)]);
+        let values: Vec<_> = response.param(2).unwrap().values().collect();
+        assert_eq!(values, [Value::Str("A"), Value::Str("B")]);
+        let values: Vec<_> = response.param(3).unwrap().values().collect();
+        assert_eq!(values, [Value::Range(-5, -1), Value::Int(7)]);
+        assert!(response.param(4).is_none());
+    }
+
+    #[test]
+    fn contains_checks_membership() {
+        let response = TestResponse::new("(1-3,8),(),\"x\"");
+        let first = response.param(0).unwrap();
+
+        assert!(first.contains(2) && first.contains(8));
+        assert!(!first.contains(5));
+        assert_eq!(response.param(1).unwrap().values().count(), 0);
+        assert!(response.param(2).unwrap().contains_str("x"));
+        assert!(TestResponse::new("+CMD: ").is_empty());
+    }
+}